@@ -1,13 +1,16 @@
 //! `agent-of-empires add` command implementation
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use clap::Args;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::containers::{self, ContainerRuntimeInterface};
 use crate::session::builder;
+use crate::session::ports;
 use crate::session::repo_config;
-use crate::session::{civilizations, GroupTree, Instance, SandboxInfo, Storage};
+use crate::session::{
+    civilizations, fanout, templates, Config, Instance, SandboxInfo, SessionTemplate, Storage,
+};
 
 #[derive(Args)]
 pub struct AddArgs {
@@ -70,6 +73,36 @@ pub struct AddArgs {
     /// Override the agent binary command
     #[arg(long)]
     cmd_override: Option<String>,
+
+    /// Initial prompt, sent once the agent is ready ('-' reads stdin)
+    #[arg(long, conflicts_with = "prompt_file")]
+    prompt: Option<String>,
+
+    /// Read the initial prompt from a file ('-' reads stdin)
+    #[arg(long, value_name = "PATH")]
+    prompt_file: Option<PathBuf>,
+
+    /// Start from a session template ([templates.<name>] in config); flags override it
+    #[arg(long, value_name = "NAME")]
+    template: Option<String>,
+
+    /// Hand the task to several agents, one worktree session each (e.g. claude,codex)
+    #[arg(
+        long,
+        value_name = "AGENTS",
+        value_delimiter = ',',
+        conflicts_with_all = ["command", "parent", "extra_repos"]
+    )]
+    fanout: Vec<String>,
+
+    /// Create N worktree sessions per agent (replicas of the same task)
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u16).range(1..=32),
+        conflicts_with_all = ["parent", "extra_repos"]
+    )]
+    count: Option<u16>,
 }
 
 pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
@@ -87,7 +120,20 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
         bail!("--repo requires --worktree to specify a branch\nTip: aoe add /path --repo /other -w branch-name");
     }
 
-    let config = repo_config::resolve_config_with_repo(profile, &path).unwrap_or_default();
+    // Read the prompt before any worktree is created, so a bad file leaves
+    // nothing behind
+    let initial_prompt = read_prompt(&args)?;
+
+    let mut config = repo_config::resolve_config_with_repo(profile, &path).unwrap_or_default();
+    let template = match &args.template {
+        Some(name) => templates::find(&config, name)?.clone(),
+        None => SessionTemplate::default(),
+    };
+    template.apply_to_config(&mut config);
+
+    if !args.fanout.is_empty() || args.count.is_some() {
+        return run_fanout(profile, &args, &path, &config, &template, initial_prompt);
+    }
 
     // Preserve the original project path for hook trust checking.
     // `path` gets reassigned to the worktree/workspace directory below,
@@ -170,10 +216,10 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     }
 
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
+    let (instances, _) = storage.load_with_groups()?;
 
     // Resolve parent session if specified
-    let mut group_path = args.group.clone();
+    let mut group_path = args.group.clone().or_else(|| template.group.clone());
     let parent_id = if let Some(parent_ref) = &args.parent {
         let parent = super::resolve_session(parent_ref, &instances)?;
         if parent.is_sub_session() {
@@ -212,6 +258,8 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     };
 
     let mut instance = Instance::new(&final_title, path.to_str().unwrap_or(""));
+    instance.source_profile = storage.profile().to_string();
+    instance.initial_prompt = initial_prompt;
 
     if let Some(group) = &group_path {
         instance.group_path = group.trim().to_string();
@@ -230,26 +278,10 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
         if cmd.trim().contains(' ') {
             instance.command = cmd.clone();
         }
+    } else if let Some(agent) = &template.agent {
+        instance.tool = template_tool(&config, agent)?;
     } else {
-        // Use default_tool from resolved config, then first available tool, then "claude".
-        // Check custom_agents first (exact match) before resolve_tool_name (substring match),
-        // so names like "lenovo-claude" resolve as the custom agent, not built-in "claude".
-        let available_tools = crate::tmux::AvailableTools::detect();
-        let tools_list = available_tools.available_list();
-        instance.tool = config
-            .session
-            .default_tool
-            .as_deref()
-            .and_then(|name| {
-                if config.session.custom_agents.contains_key(name) {
-                    Some(name)
-                } else {
-                    crate::agents::resolve_tool_name(name)
-                }
-            })
-            .or_else(|| tools_list.first().map(|s| s.as_str()))
-            .unwrap_or("claude")
-            .to_string();
+        instance.tool = default_tool(&config);
     }
 
     // Set detect_as for status detection (resolved once, avoids config load in poll loop)
@@ -276,11 +308,14 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
         instance.workspace_info = Some(workspace_info);
     }
 
-    instance.yolo_mode = args.yolo || config.session.yolo_mode_default;
+    instance.yolo_mode = args.yolo || template.yolo.unwrap_or(config.session.yolo_mode_default);
 
-    // Apply extra_args and command override: CLI flags take priority, then config defaults
+    // Apply extra_args and command override: CLI flags take priority, then the
+    // template, then config defaults
     if let Some(ref extra) = args.extra_args {
         instance.extra_args = extra.clone();
+    } else if let Some(ref extra) = template.extra_args {
+        instance.extra_args = extra.clone();
     } else if let Some(extra) = config.session.agent_extra_args.get(&instance.tool) {
         if !extra.is_empty() {
             instance.extra_args = extra.clone();
@@ -297,10 +332,14 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     }
 
     // Handle sandbox setup
-    let use_sandbox = args.sandbox || args.sandbox_image.is_some();
+    let use_sandbox =
+        args.sandbox || args.sandbox_image.is_some() || template.sandbox == Some(true);
+    let sandbox_by_default = template
+        .sandbox
+        .unwrap_or(config.sandbox.enabled_by_default);
 
     let runtime = containers::get_container_runtime();
-    if use_sandbox || config.sandbox.enabled_by_default {
+    if use_sandbox || sandbox_by_default {
         if !runtime.is_available() {
             if use_sandbox {
                 bail!(
@@ -315,6 +354,7 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
             let image = args
                 .sandbox_image
                 .as_ref()
+                .or(template.sandbox_image.as_ref())
                 .map(|s| s.trim().to_string())
                 .unwrap_or_else(|| runtime.effective_default_image());
             instance.sandbox_info = Some(SandboxInfo {
@@ -323,68 +363,27 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
                 image,
                 container_name,
                 created_at: None,
-                extra_env: None,
+                extra_env: template.sandbox_env(&config.sandbox.environment),
                 custom_instruction: config.sandbox.custom_instruction.clone(),
+                remote_user: None,
+                egress_proxy: None,
+                snapshots: Vec::new(),
+                compose_project: None,
             });
         }
     }
 
-    // Check for repository hooks.
+    // Reserve the session's ports, then check for repository hooks.
     // Use the original project path for trust checking (not the worktree/workspace
     // path, which won't contain `.agent-of-empires/config.toml`).
     let hook_result: Result<()> = (|| {
-        let resolved_hooks: Option<crate::session::HooksConfig> =
-            match repo_config::check_hook_trust(&original_project_path) {
-                Ok(repo_config::HookTrustStatus::NeedsTrust { hooks, hooks_hash }) => {
-                    let should_trust = if args.trust_hooks {
-                        true
-                    } else {
-                        println!("\nRepository hooks detected in .agent-of-empires/config.toml:");
-                        if !hooks.on_create.is_empty() {
-                            println!("  on_create:");
-                            for cmd in &hooks.on_create {
-                                println!("    {}", cmd);
-                            }
-                        }
-                        if !hooks.on_launch.is_empty() {
-                            println!("  on_launch:");
-                            for cmd in &hooks.on_launch {
-                                println!("    {}", cmd);
-                            }
-                        }
-                        print!("\nTrust and run these hooks? [y/N] ");
-                        use std::io::Write;
-                        std::io::stdout().flush()?;
-                        let mut input = String::new();
-                        std::io::stdin().read_line(&mut input)?;
-                        input.trim().eq_ignore_ascii_case("y")
-                    };
-
-                    if should_trust {
-                        repo_config::trust_repo(&original_project_path, &hooks_hash)?;
-                        println!("✓ Repository hooks trusted");
-                        repo_config::merge_hooks_with_config(profile, hooks)
-                    } else {
-                        println!("Hooks skipped (session created without running hooks)");
-                        None
-                    }
-                }
-                Ok(repo_config::HookTrustStatus::Trusted(repo_hooks)) => {
-                    repo_config::merge_hooks_with_config(profile, repo_hooks)
-                }
-                Ok(repo_config::HookTrustStatus::NoHooks) => {
-                    repo_config::resolve_global_profile_hooks(profile)
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to check repo hooks: {}", e);
-                    repo_config::resolve_global_profile_hooks(profile)
-                }
-            };
+        instance.ports = ports::allocate(&instance.id, &config.session.ports.names()?)?;
+        let resolved_hooks = resolve_hooks(profile, &original_project_path, args.trust_hooks)?;
 
         if let Some(hooks) = resolved_hooks {
             if !hooks.on_create.is_empty() {
                 println!("Running on_create hooks...");
-                repo_config::execute_hooks(&hooks.on_create, &path)?;
+                repo_config::execute_hooks(&hooks.on_create, &path, &ports::env(&instance.ports))?;
                 println!("✓ on_create hooks completed");
             }
         }
@@ -392,6 +391,7 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     })();
 
     if let Err(e) = hook_result {
+        let _ = ports::release(&instance.id);
         // Clean up worktree if we created one
         if let Some(ref wt_info) = instance.worktree_info {
             if wt_info.managed_by_aoe {
@@ -418,21 +418,22 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
         return Err(e);
     }
 
-    instances.push(instance.clone());
-
-    // Rebuild group tree
-    let mut group_tree = GroupTree::new_with_groups(&instances, &groups);
-    if !instance.group_path.is_empty() {
-        group_tree.create_group(&instance.group_path);
-    }
-
-    storage.save_with_groups(&instances, &group_tree)?;
+    storage.update_with_groups(|instances, group_tree| {
+        instances.push(instance.clone());
+        if !instance.group_path.is_empty() {
+            group_tree.create_group(&instance.group_path);
+        }
+        Ok(())
+    })?;
 
     println!("✓ Added session: {}", final_title);
     println!("  Profile: {}", storage.profile());
     println!("  Path:    {}", path.display());
     println!("  Group:   {}", instance.group_path);
     println!("  ID:      {}", instance.id);
+    if let Some(name) = &args.template {
+        println!("  Template: {}", name);
+    }
     if let Some(cmd) = &args.command {
         println!("  Cmd:     {}", cmd);
     }
@@ -445,6 +446,22 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     if instance.yolo_mode {
         println!("  YOLO:    enabled");
     }
+    if !instance.ports.is_empty() {
+        println!("  Ports:   {}", ports::summary(&instance.ports));
+        if instance.sandbox_info.is_some()
+            && !crate::session::egress::publishes_ports(Path::new(&instance.project_path))
+        {
+            println!(
+                "           (not reachable from the host: sandbox.network isolates the container)"
+            );
+        }
+    }
+    if let Some(prompt) = &instance.initial_prompt {
+        println!(
+            "  Prompt:  {} line(s), sent when the agent is ready",
+            prompt.lines().count()
+        );
+    }
     if let Some(ws) = &instance.workspace_info {
         println!("  Workspace: {} repos", ws.repos.len());
         for repo in &ws.repos {
@@ -453,12 +470,8 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     }
 
     if args.launch {
-        let idx = instances
-            .iter()
-            .position(|i| i.id == instance.id)
-            .expect("just added instance");
-        instances[idx].start_with_size(crate::terminal::get_size())?;
-        storage.save_with_groups(&instances, &group_tree)?;
+        instance.start_with_size(crate::terminal::get_size())?;
+        storage.replace_instance(&instance)?;
 
         let tmux_session = crate::tmux::Session::new(&instance.id, &instance.title)?;
         tmux_session.attach()?;
@@ -475,6 +488,316 @@ pub async fn run(profile: &str, args: AddArgs) -> Result<()> {
     Ok(())
 }
 
+/// Create one worktree session per agent and replica for `--fanout` /
+/// `--count`, all in a generated group and given the same prompt.
+fn run_fanout(
+    profile: &str,
+    args: &AddArgs,
+    path: &Path,
+    config: &Config,
+    template: &SessionTemplate,
+    initial_prompt: Option<String>,
+) -> Result<()> {
+    use crate::git::GitWorktree;
+
+    if !GitWorktree::is_git_repo(path) {
+        bail!("Path is not in a git repository\nTip: --fanout and --count create a worktree for each session");
+    }
+
+    let agents = if args.fanout.is_empty() {
+        vec![match (&args.command, &template.agent) {
+            (Some(cmd), _) => detect_tool(cmd)?,
+            (None, Some(agent)) => template_tool(config, agent)?,
+            (None, None) => default_tool(config),
+        }]
+    } else {
+        args.fanout
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(detect_tool)
+            .collect::<Result<Vec<_>>>()?
+    };
+    if agents.is_empty() {
+        bail!("--fanout needs at least one agent\nTip: aoe add --fanout claude,codex");
+    }
+
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+
+    // Base name for the branches and the group: --worktree, then --title,
+    // then a random civilization
+    let base_branch = args
+        .worktree_branch
+        .as_deref()
+        .or(args.title.as_deref())
+        .map(|name| name.trim().replace(char::is_whitespace, "-"))
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| {
+            let existing_titles: Vec<&str> = instances.iter().map(|i| i.title.as_str()).collect();
+            civilizations::generate_random_title(&existing_titles)
+                .to_lowercase()
+                .replace(char::is_whitespace, "-")
+        });
+
+    let main_repo_path = GitWorktree::find_main_repo(path)?;
+    let taken = crate::git::diff::list_branches(&main_repo_path).unwrap_or_default();
+    let members = fanout::plan(
+        &base_branch,
+        &agents,
+        args.count.map_or(1, usize::from),
+        &taken,
+    );
+    let group = fanout::group_path(
+        args.group.as_deref().or(template.group.as_deref()),
+        &base_branch,
+    );
+
+    let runtime = containers::get_container_runtime();
+    let sandbox = args.sandbox
+        || args.sandbox_image.is_some()
+        || match template.sandbox {
+            Some(sandbox) => sandbox,
+            None => config.sandbox.enabled_by_default && runtime.is_available(),
+        };
+    let sandbox_image = if sandbox {
+        args.sandbox_image
+            .as_ref()
+            .or(template.sandbox_image.as_ref())
+            .map(|s| s.trim().to_string())
+            .unwrap_or_else(|| runtime.effective_default_image())
+    } else {
+        String::new()
+    };
+
+    // Same precedence as a single session: --cmd-override, then a --cmd
+    // with arguments
+    let command_override = args
+        .cmd_override
+        .clone()
+        .or_else(|| args.command.clone().filter(|cmd| cmd.trim().contains(' ')))
+        .unwrap_or_default();
+
+    let params = builder::InstanceParams {
+        title: String::new(),
+        path: path.to_string_lossy().to_string(),
+        group: group.clone(),
+        tool: String::new(),
+        worktree_branch: None,
+        create_new_branch: true,
+        sandbox,
+        sandbox_image,
+        yolo_mode: args.yolo || template.yolo.unwrap_or(config.session.yolo_mode_default),
+        extra_env: template
+            .sandbox_env(&config.sandbox.environment)
+            .unwrap_or_default(),
+        extra_args: args
+            .extra_args
+            .clone()
+            .or_else(|| template.extra_args.clone())
+            .unwrap_or_default(),
+        command_override,
+        extra_repo_paths: Vec::new(),
+        prompt: initial_prompt,
+        template: args.template.clone(),
+    };
+
+    for member in &members {
+        println!("Creating worktree for {} ({})", member.branch, member.tool);
+    }
+    let built = fanout::build_all(&params, &members, profile)?;
+
+    let hook_result: Result<()> = (|| {
+        if let Some(hooks) = resolve_hooks(profile, path, args.trust_hooks)? {
+            if !hooks.on_create.is_empty() {
+                for result in &built {
+                    println!("Running on_create hooks for {}...", result.instance.title);
+                    repo_config::execute_hooks(
+                        &hooks.on_create,
+                        Path::new(&result.instance.project_path),
+                        &ports::env(&result.instance.ports),
+                    )?;
+                }
+                println!("✓ on_create hooks completed");
+            }
+        }
+        Ok(())
+    })();
+    if let Err(e) = hook_result {
+        fanout::rollback(&built);
+        return Err(e);
+    }
+
+    let mut new_instances: Vec<Instance> = built
+        .into_iter()
+        .map(|result| {
+            let mut instance = result.instance;
+            instance.source_profile = storage.profile().to_string();
+            instance
+        })
+        .collect();
+
+    storage.update_with_groups(|instances, group_tree| {
+        instances.extend(new_instances.iter().cloned());
+        group_tree.create_group(&group);
+        Ok(())
+    })?;
+
+    println!(
+        "✓ Fanned out to {} sessions in group: {}",
+        new_instances.len(),
+        group
+    );
+    println!("  Profile: {}", storage.profile());
+    for instance in &new_instances {
+        println!(
+            "  {:<24} {:<10} {}",
+            instance.title, instance.tool, instance.project_path
+        );
+    }
+    if let Some(prompt) = &params.prompt {
+        println!(
+            "  Prompt:  {} line(s), sent when each agent is ready",
+            prompt.lines().count()
+        );
+    }
+
+    if args.launch {
+        let size = crate::terminal::get_size();
+        for instance in &mut new_instances {
+            if let Err(e) = instance.start_with_size(size) {
+                eprintln!("Warning: failed to start {}: {}", instance.title, e);
+                continue;
+            }
+            storage.replace_instance(instance)?;
+        }
+        println!();
+        println!("Started all sessions. Wait for them to finish with:");
+        println!("  agent-of-empires wait --group {}", group);
+    } else {
+        println!();
+        println!("Next steps:");
+        for instance in &new_instances {
+            println!("  agent-of-empires session start {}", instance.title);
+        }
+    }
+    println!("  agent-of-empires   # Open TUI, select the group and press C to compare");
+
+    Ok(())
+}
+
+/// Use default_tool from resolved config, then first available tool, then "claude".
+/// Check custom_agents first (exact match) before resolve_tool_name (substring match),
+/// so names like "lenovo-claude" resolve as the custom agent, not built-in "claude".
+fn default_tool(config: &Config) -> String {
+    let available_tools = crate::tmux::AvailableTools::detect();
+    let tools_list = available_tools.available_list();
+    config
+        .session
+        .default_tool
+        .as_deref()
+        .and_then(|name| {
+            if config.session.custom_agents.contains_key(name) {
+                Some(name)
+            } else {
+                crate::agents::resolve_tool_name(name)
+            }
+        })
+        .or_else(|| tools_list.first().map(|s| s.as_str()))
+        .unwrap_or("claude")
+        .to_string()
+}
+
+/// Resolve a template's agent, which may also name a custom agent.
+fn template_tool(config: &Config, agent: &str) -> Result<String> {
+    if config.session.custom_agents.contains_key(agent) {
+        return Ok(agent.to_string());
+    }
+    detect_tool(agent)
+}
+
+/// Resolve the hooks to run for a new session, asking the user to trust
+/// repository hooks that haven't been trusted yet.
+fn resolve_hooks(
+    profile: &str,
+    project_path: &Path,
+    trust_hooks: bool,
+) -> Result<Option<crate::session::HooksConfig>> {
+    Ok(match repo_config::check_hook_trust(project_path) {
+        Ok(repo_config::HookTrustStatus::NeedsTrust { hooks, hooks_hash }) => {
+            let should_trust = if trust_hooks {
+                true
+            } else {
+                println!("\nRepository hooks detected in .agent-of-empires/config.toml:");
+                for (name, cmds) in hooks.named_commands() {
+                    println!("  {}:", name);
+                    for cmd in cmds {
+                        println!("    {}", cmd);
+                    }
+                }
+                if hooks.status_hooks_in_container == Some(false) && hooks.has_status_hooks() {
+                    println!("  (status hooks run on the host, even for sandboxed sessions)");
+                }
+                print!("\nTrust and run these hooks? [y/N] ");
+                use std::io::Write;
+                std::io::stdout().flush()?;
+                let mut input = String::new();
+                std::io::stdin().read_line(&mut input)?;
+                input.trim().eq_ignore_ascii_case("y")
+            };
+
+            if should_trust {
+                repo_config::trust_repo(project_path, &hooks_hash)?;
+                println!("✓ Repository hooks trusted");
+                repo_config::merge_hooks_with_config(profile, hooks)
+            } else {
+                println!("Hooks skipped (session created without running hooks)");
+                None
+            }
+        }
+        Ok(repo_config::HookTrustStatus::Trusted(repo_hooks)) => {
+            repo_config::merge_hooks_with_config(profile, repo_hooks)
+        }
+        Ok(repo_config::HookTrustStatus::NoHooks) => {
+            repo_config::resolve_global_profile_hooks(profile)
+        }
+        Err(e) => {
+            tracing::warn!("Failed to check repo hooks: {}", e);
+            repo_config::resolve_global_profile_hooks(profile)
+        }
+    })
+}
+
+/// Resolve `--prompt` / `--prompt-file`, where `-` means stdin.
+fn read_prompt(args: &AddArgs) -> Result<Option<String>> {
+    let raw = match (&args.prompt, &args.prompt_file) {
+        (Some(text), _) if text == "-" => read_stdin()?,
+        (Some(text), _) => text.clone(),
+        (None, Some(path)) if path.as_os_str() == "-" => read_stdin()?,
+        (None, Some(path)) => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read prompt file: {}", path.display()))?,
+        (None, None) => return Ok(None),
+    };
+    normalize_prompt(&raw).map(Some)
+}
+
+fn read_stdin() -> Result<String> {
+    use std::io::Read;
+    let mut text = String::new();
+    std::io::stdin()
+        .read_to_string(&mut text)
+        .context("Failed to read prompt from stdin")?;
+    Ok(text)
+}
+
+fn normalize_prompt(raw: &str) -> Result<String> {
+    let prompt = raw.trim_end().trim_start_matches(['\n', '\r']);
+    if prompt.trim().is_empty() {
+        bail!("Prompt is empty");
+    }
+    Ok(prompt.to_string())
+}
+
 pub fn is_duplicate_session(instances: &[Instance], title: &str, path: &str) -> bool {
     let normalized_path = path.trim_end_matches('/');
     instances.iter().any(|inst| {
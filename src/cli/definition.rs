@@ -12,6 +12,7 @@ use super::init::InitArgs;
 use super::list::ListArgs;
 use super::profile::ProfileCommands;
 use super::remove::RemoveArgs;
+use super::sandbox::SandboxCommands;
 use super::send::SendArgs;
 #[cfg(feature = "serve")]
 use super::serve::ServeArgs;
@@ -21,6 +22,7 @@ use super::status::StatusArgs;
 use super::theme::ThemeCommands;
 use super::tmux::TmuxCommands;
 use super::uninstall::UninstallArgs;
+use super::wait::WaitArgs;
 use super::worktree::WorktreeCommands;
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -46,7 +48,7 @@ pub struct Cli {
 #[derive(Subcommand)]
 pub enum Commands {
     /// Add a new session
-    Add(AddArgs),
+    Add(Box<AddArgs>),
 
     /// Initialize .agent-of-empires/config.toml in a repository
     Init(InitArgs),
@@ -65,6 +67,12 @@ pub enum Commands {
     /// Show session status summary
     Status(StatusArgs),
 
+    /// Wait until sessions reach a status
+    ///
+    /// Exits 0 once every session has reached the status, 1 if a session
+    /// errors or is removed first, and 124 if the timeout expires.
+    Wait(WaitArgs),
+
     /// Manage session lifecycle (start, stop, attach, etc.)
     Session {
         #[command(subcommand)]
@@ -89,6 +97,12 @@ pub enum Commands {
         command: WorktreeCommands,
     },
 
+    /// Manage sandbox images and container snapshots
+    Sandbox {
+        #[command(subcommand)]
+        command: SandboxCommands,
+    },
+
     /// tmux integration utilities
     Tmux {
         #[command(subcommand)]
@@ -126,7 +126,6 @@ async fn list_groups(profile: &str, args: GroupListArgs) -> Result<()> {
 
 async fn create_group(profile: &str, args: GroupCreateArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (instances, groups) = storage.load_with_groups()?;
 
     let name = args.name.trim();
     let group_path = if let Some(parent) = &args.parent {
@@ -135,14 +134,13 @@ async fn create_group(profile: &str, args: GroupCreateArgs) -> Result<()> {
         name.to_string()
     };
 
-    let mut group_tree = GroupTree::new_with_groups(&instances, &groups);
-
-    if group_tree.group_exists(&group_path) {
-        bail!("Group already exists: {}", group_path);
-    }
-
-    group_tree.create_group(&group_path);
-    storage.save_with_groups(&instances, &group_tree)?;
+    storage.update_with_groups(|_, group_tree| {
+        if group_tree.group_exists(&group_path) {
+            bail!("Group already exists: {}", group_path);
+        }
+        group_tree.create_group(&group_path);
+        Ok(())
+    })?;
 
     println!("✓ Created group: {}", group_path);
 
@@ -151,40 +149,39 @@ async fn create_group(profile: &str, args: GroupCreateArgs) -> Result<()> {
 
 async fn delete_group(profile: &str, args: GroupDeleteArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
-
-    let mut group_tree = GroupTree::new_with_groups(&instances, &groups);
-
     let name = args.name.trim();
-    if !group_tree.group_exists(name) {
-        bail!("Group not found: {}", name);
-    }
 
-    // Check for sessions in this group
-    let session_count = instances
-        .iter()
-        .filter(|i| i.group_path == name || i.group_path.starts_with(&format!("{}/", name)))
-        .count();
-
-    if session_count > 0 {
-        if !args.force {
-            bail!(
-                "Group '{}' contains {} sessions. Use --force to move them to default group.",
-                name,
-                session_count
-            );
+    let session_count = storage.update_with_groups(|instances, group_tree| {
+        if !group_tree.group_exists(name) {
+            bail!("Group not found: {}", name);
         }
 
-        // Move sessions to default group
-        for inst in &mut instances {
-            if inst.group_path == name || inst.group_path.starts_with(&format!("{}/", name)) {
-                inst.group_path = String::new();
+        // Check for sessions in this group
+        let session_count = instances
+            .iter()
+            .filter(|i| i.group_path == name || i.group_path.starts_with(&format!("{}/", name)))
+            .count();
+
+        if session_count > 0 {
+            if !args.force {
+                bail!(
+                    "Group '{}' contains {} sessions. Use --force to move them to default group.",
+                    name,
+                    session_count
+                );
+            }
+
+            // Move sessions to default group
+            for inst in instances.iter_mut() {
+                if inst.group_path == name || inst.group_path.starts_with(&format!("{}/", name)) {
+                    inst.group_path = String::new();
+                }
             }
         }
-    }
 
-    group_tree.delete_group(name);
-    storage.save_with_groups(&instances, &group_tree)?;
+        group_tree.delete_group(name);
+        Ok(session_count)
+    })?;
 
     println!("✓ Deleted group: {}", name);
     if args.force && session_count > 0 {
@@ -196,24 +193,21 @@ async fn delete_group(profile: &str, args: GroupDeleteArgs) -> Result<()> {
 
 async fn move_session(profile: &str, args: GroupMoveArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
 
     let identifier = args.identifier.trim();
-    let inst = instances
-        .iter_mut()
-        .find(|i| i.id == identifier || i.id.starts_with(identifier) || i.title == identifier)
-        .ok_or_else(|| anyhow::anyhow!("Session not found: {}", identifier))?;
-
     let group = args.group.trim();
-    let old_group = inst.group_path.clone();
-    inst.group_path = group.to_string();
-
-    let mut group_tree = GroupTree::new_with_groups(&instances, &groups);
-    if !group.is_empty() {
-        group_tree.create_group(group);
-    }
-
-    storage.save_with_groups(&instances, &group_tree)?;
+    let old_group = storage.update_with_groups(|instances, group_tree| {
+        let inst = instances
+            .iter_mut()
+            .find(|i| i.id == identifier || i.id.starts_with(identifier) || i.title == identifier)
+            .ok_or_else(|| anyhow::anyhow!("Session not found: {}", identifier))?;
+
+        let old_group = std::mem::replace(&mut inst.group_path, group.to_string());
+        if !group.is_empty() {
+            group_tree.create_group(group);
+        }
+        Ok(old_group)
+    })?;
 
     if old_group.is_empty() {
         println!("✓ Moved session to group: {}", group);
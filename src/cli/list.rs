@@ -5,6 +5,7 @@ use clap::Args;
 use serde::Serialize;
 
 use crate::session::{Instance, Storage};
+use crate::usage::{SessionUsage, Sources, UsageTracker};
 
 const TABLE_COL_TITLE: usize = 20;
 const TABLE_COL_GROUP: usize = 15;
@@ -33,6 +34,8 @@ struct SessionJson {
     command: String,
     profile: String,
     created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<SessionUsage>,
 }
 
 fn print_table_header() {
@@ -82,6 +85,8 @@ pub async fn run(profile: &str, args: ListArgs) -> Result<()> {
     }
 
     if args.json {
+        let config = crate::session::resolve_config(storage.profile()).unwrap_or_default();
+        let mut tracker = UsageTracker::new(Sources::detect());
         let sessions: Vec<SessionJson> = instances
             .iter()
             .map(|inst| SessionJson {
@@ -93,6 +98,7 @@ pub async fn run(profile: &str, args: ListArgs) -> Result<()> {
                 command: inst.command.clone(),
                 profile: storage.profile().to_string(),
                 created_at: inst.created_at,
+                usage: tracker.session_usage(inst, &instances, &config.usage),
             })
             .collect();
         println!("{}", serde_json::to_string_pretty(&sessions)?);
@@ -120,23 +126,37 @@ async fn run_all_profiles(json: bool) -> Result<()> {
     }
 
     if json {
+        let loaded: Vec<(&String, Vec<Instance>)> = profiles
+            .iter()
+            .filter_map(|profile_name| {
+                let storage = Storage::new(profile_name).ok()?;
+                let (instances, _) = storage.load_with_groups().ok()?;
+                Some((profile_name, instances))
+            })
+            .collect();
+        // Sessions in any profile may share a directory and its transcripts
+        let every_instance: Vec<Instance> = loaded
+            .iter()
+            .flat_map(|(_, instances)| instances.iter().cloned())
+            .collect();
+
         let mut all_sessions: Vec<SessionJson> = Vec::new();
-        for profile_name in &profiles {
-            if let Ok(storage) = Storage::new(profile_name) {
-                if let Ok((instances, _)) = storage.load_with_groups() {
-                    for inst in instances {
-                        all_sessions.push(SessionJson {
-                            id: inst.id,
-                            title: inst.title,
-                            path: inst.project_path,
-                            group: inst.group_path,
-                            tool: inst.tool,
-                            command: inst.command,
-                            profile: profile_name.clone(),
-                            created_at: inst.created_at,
-                        });
-                    }
-                }
+        let mut tracker = UsageTracker::new(Sources::detect());
+        for (profile_name, instances) in loaded {
+            let config = crate::session::resolve_config(profile_name).unwrap_or_default();
+            for inst in instances {
+                let usage = tracker.session_usage(&inst, &every_instance, &config.usage);
+                all_sessions.push(SessionJson {
+                    id: inst.id,
+                    title: inst.title,
+                    path: inst.project_path,
+                    group: inst.group_path,
+                    tool: inst.tool,
+                    command: inst.command,
+                    profile: profile_name.clone(),
+                    created_at: inst.created_at,
+                    usage,
+                });
             }
         }
         println!("{}", serde_json::to_string_pretty(&all_sessions)?);
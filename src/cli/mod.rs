@@ -7,6 +7,7 @@ pub mod init;
 pub mod list;
 pub mod profile;
 pub mod remove;
+pub mod sandbox;
 pub mod send;
 #[cfg(feature = "serve")]
 pub mod serve;
@@ -16,6 +17,7 @@ pub mod status;
 pub mod theme;
 pub mod tmux;
 pub mod uninstall;
+pub mod wait;
 pub mod worktree;
 
 pub use definition::{Cli, Commands};
@@ -6,7 +6,7 @@ use clap::Args;
 use crate::containers;
 use crate::git::cleanup::remove_managed_worktree;
 use crate::git::GitWorktree;
-use crate::session::{GroupTree, Instance, Storage};
+use crate::session::{Instance, Storage};
 use std::path::PathBuf;
 
 #[derive(Args)]
@@ -39,7 +39,7 @@ fn needs_worktree_cleanup(inst: &Instance, args: &RemoveArgs) -> bool {
 
 pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (instances, groups) = storage.load_with_groups()?;
+    let (instances, _) = storage.load_with_groups()?;
     // Config is resolved per-session inside the loop (see below) so that
     // repo-level overrides (e.g. delete_branch_on_cleanup) are respected.
     // We keep a fallback for the case where no session matches.
@@ -47,7 +47,7 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
 
     let mut found = false;
     let mut removed_title = String::new();
-    let mut new_instances = Vec::with_capacity(instances.len());
+    let mut removed_ids = Vec::new();
 
     for inst in instances {
         if inst.id == args.identifier
@@ -56,6 +56,7 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
         {
             found = true;
             removed_title = inst.title.clone();
+            removed_ids.push(inst.id.clone());
 
             let config = crate::session::repo_config::resolve_config_with_repo(
                 profile,
@@ -104,6 +105,7 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
                         crate::session::repo_config::execute_hooks_best_effort(
                             &on_destroy,
                             project_path,
+                            &crate::session::ports::env(&inst.ports),
                         )
                     };
 
@@ -214,6 +216,11 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
                                 println!("  Container removed");
                             }
                         }
+                        if sandbox.egress_proxy.is_some() {
+                            if let Err(e) = crate::session::egress::remove_network(&inst.id) {
+                                eprintln!("Warning: failed to remove sandbox network: {:#}", e);
+                            }
+                        }
                     } else {
                         println!(
                             "Container preserved: {} (auto_cleanup disabled in config)",
@@ -224,8 +231,30 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
                     println!("Container preserved: {}", sandbox.container_name);
                 }
             }
-        } else {
-            new_instances.push(inst);
+
+            crate::session::egress::cleanup(&inst.id);
+            if let Some(project) = inst
+                .sandbox_info
+                .as_ref()
+                .and_then(|s| s.compose_project.as_deref())
+            {
+                if let Err(e) = crate::session::services::down(&inst.id, project) {
+                    eprintln!("Warning: failed to remove session services: {:#}", e);
+                }
+            }
+
+            if let Err(e) = crate::session::recording::remove(storage.profile(), &inst.id) {
+                eprintln!("Warning: failed to remove session recording: {}", e);
+            }
+            if let Err(e) = crate::session::message_queue::clear(storage.profile(), &inst.id) {
+                eprintln!("Warning: failed to remove message queue: {}", e);
+            }
+            if let Err(e) = crate::session::auto_respond::clear(storage.profile(), &inst.id) {
+                eprintln!("Warning: failed to remove auto-responder lock: {}", e);
+            }
+            if let Err(e) = crate::session::ports::release(&inst.id) {
+                eprintln!("Warning: failed to release session ports: {}", e);
+            }
         }
     }
 
@@ -237,9 +266,10 @@ pub async fn run(profile: &str, args: RemoveArgs) -> Result<()> {
         );
     }
 
-    // Rebuild group tree and save
-    let group_tree = GroupTree::new_with_groups(&new_instances, &groups);
-    storage.save_with_groups(&new_instances, &group_tree)?;
+    storage.update_with_groups(|instances, _| {
+        instances.retain(|i| !removed_ids.contains(&i.id));
+        Ok(())
+    })?;
 
     println!(
         "  Removed session: {} (from profile '{}')",
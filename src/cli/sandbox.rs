@@ -0,0 +1,202 @@
+//! `agent-of-empires sandbox` command implementation
+
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use crate::containers::{self, ContainerRuntimeInterface, ImageBuild};
+use crate::session::container_config;
+use crate::session::devcontainer::ImageSource;
+use crate::session::egress;
+use crate::session::image_build;
+use crate::session::snapshot;
+use crate::session::Storage;
+
+#[derive(Subcommand)]
+pub enum SandboxCommands {
+    /// Build the repository's sandbox image from `sandbox.build` or its
+    /// devcontainer.json
+    Build {
+        /// Repository path (defaults to current directory)
+        path: Option<PathBuf>,
+
+        /// Rebuild even if an image for the current inputs exists
+        #[arg(long)]
+        rebuild: bool,
+    },
+
+    /// Save a session's sandbox container as a snapshot image
+    Snapshot {
+        /// Session ID or title
+        identifier: String,
+    },
+
+    /// Recreate a session's sandbox container from one of its snapshots
+    Restore {
+        /// Session ID or title
+        identifier: String,
+
+        /// Snapshot image or tag, as printed by `aoe sandbox snapshot`
+        snapshot: String,
+    },
+
+    /// Remove built sandbox images and snapshots that no session uses
+    Prune {
+        /// Actually remove images (default is dry-run)
+        #[arg(short = 'f', long = "force")]
+        force: bool,
+    },
+
+    /// Run a session's egress filtering proxy
+    #[command(hide = true)]
+    Proxy {
+        /// Address to listen on
+        #[arg(long)]
+        listen: SocketAddr,
+
+        /// Allowed host, or `*.domain` for its subdomains
+        #[arg(long)]
+        allow: Vec<String>,
+    },
+}
+
+pub async fn run(profile: &str, command: SandboxCommands) -> Result<()> {
+    match command {
+        SandboxCommands::Build { path, rebuild } => build(path, rebuild).await,
+        SandboxCommands::Snapshot { identifier } => snapshot(profile, &identifier).await,
+        SandboxCommands::Restore {
+            identifier,
+            snapshot,
+        } => restore(profile, &identifier, &snapshot).await,
+        SandboxCommands::Prune { force } => prune(force).await,
+        SandboxCommands::Proxy { listen, allow } => egress::run_proxy(listen, allow).await,
+    }
+}
+
+async fn build(path: Option<PathBuf>, rebuild: bool) -> Result<()> {
+    let path = match path {
+        Some(path) => path,
+        None => std::env::current_dir()?,
+    };
+    let project_path = path.canonicalize()?;
+    let build = project_build(&project_path)?;
+
+    let runtime = containers::get_container_runtime();
+    if !rebuild && runtime.image_exists_locally(&build.tag) {
+        println!("✓ {} is up to date", build.tag);
+        return Ok(());
+    }
+
+    println!("Building {} from {}", build.tag, build.dockerfile.display());
+    runtime.build_image(&build, &mut |line| println!("{}", line))?;
+    println!("✓ Built {}", build.tag);
+    Ok(())
+}
+
+/// The image build the project's sandbox would use: its devcontainer's
+/// Dockerfile when `use_devcontainer` is on, otherwise `sandbox.build`.
+fn project_build(project_path: &Path) -> Result<ImageBuild> {
+    if let Some(devcontainer) = container_config::load_devcontainer(project_path)? {
+        return match devcontainer.image_source()? {
+            ImageSource::Build(build) => Ok(build),
+            ImageSource::Pull(image) => bail!(
+                "devcontainer.json uses the prebuilt image '{}'; there is nothing to build",
+                image
+            ),
+        };
+    }
+    match image_build::for_project(project_path)? {
+        Some(build) => Ok(build),
+        None => bail!(
+            "No sandbox image build configured for {}\n\
+             Tip: Add [sandbox.build] to the repo's .agent-of-empires/config.toml",
+            project_path.display()
+        ),
+    }
+}
+
+async fn snapshot(profile: &str, identifier: &str) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let mut inst = super::resolve_session(identifier, &instances)?.clone();
+
+    println!("Snapshotting {}...", inst.title);
+    let snapshot = inst.snapshot()?;
+    storage.replace_instance(&inst)?;
+
+    println!("✓ Saved snapshot {}", snapshot.image);
+    println!(
+        "  Restore it with: aoe sandbox restore {} {}",
+        inst.id,
+        snapshot.tag()
+    );
+    Ok(())
+}
+
+async fn restore(profile: &str, identifier: &str, query: &str) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let mut inst = super::resolve_session(identifier, &instances)?.clone();
+    inst.source_profile = storage.profile().to_string();
+
+    let snapshots = inst
+        .sandbox_info
+        .as_ref()
+        .map(|s| s.snapshots.as_slice())
+        .unwrap_or_default();
+    if snapshot::find(snapshots, query).is_none() {
+        if snapshots.is_empty() {
+            bail!("Session {} has no snapshots", inst.title);
+        }
+        let available: Vec<String> = snapshots
+            .iter()
+            .map(|s| format!("  • {}", s.tag()))
+            .collect();
+        bail!(
+            "No snapshot '{}' for session {}. Available snapshots:\n{}",
+            query,
+            inst.title,
+            available.join("\n")
+        );
+    }
+
+    let snapshot = inst.restore_snapshot(query)?;
+    storage.replace_instance(&inst)?;
+
+    println!("✓ Restored {} from {}", inst.title, snapshot.image);
+    Ok(())
+}
+
+async fn prune(force: bool) -> Result<()> {
+    let unused = image_build::unused_images()?;
+    if unused.is_empty() {
+        println!("✓ No unused sandbox images found");
+        return Ok(());
+    }
+
+    println!("Unused sandbox images:\n");
+    for image in &unused {
+        println!("  • {}", image);
+    }
+    println!();
+
+    if !force {
+        println!("This is a dry-run. Use --force to actually remove unused images.");
+        return Ok(());
+    }
+
+    let runtime = containers::get_container_runtime();
+    let mut removed = 0;
+    for image in &unused {
+        match runtime.remove_image(image) {
+            Ok(()) => {
+                println!("✓ Removed {}", image);
+                removed += 1;
+            }
+            Err(e) => eprintln!("✗ {}", e),
+        }
+    }
+    println!("\n✓ Prune complete: {} images removed", removed);
+    Ok(())
+}
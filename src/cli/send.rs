@@ -3,7 +3,7 @@
 use anyhow::{bail, Result};
 use clap::Args;
 
-use crate::session::Storage;
+use crate::session::{message_queue, Storage};
 
 #[derive(Args)]
 pub struct SendArgs {
@@ -12,6 +12,11 @@ pub struct SendArgs {
 
     /// Message to send to the agent
     message: String,
+
+    /// Add the message to the session's queue instead of typing it now. Queued
+    /// messages are sent one at a time, each when the agent next goes idle.
+    #[arg(short, long)]
+    queue: bool,
 }
 
 pub async fn run(profile: &str, args: SendArgs) -> Result<()> {
@@ -23,6 +28,14 @@ pub async fn run(profile: &str, args: SendArgs) -> Result<()> {
     }
 
     let inst = super::resolve_session(&args.identifier, &instances)?;
+
+    if args.queue {
+        message_queue::enqueue(storage.profile(), inst, &args.message)?;
+        let queued = message_queue::load(storage.profile(), &inst.id)?.len();
+        println!("Queued message for '{}' ({} in queue)", inst.title, queued);
+        return Ok(());
+    }
+
     let tmux_session = crate::tmux::Session::new(&inst.id, &inst.title)?;
 
     if !tmux_session.exists() {
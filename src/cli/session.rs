@@ -1,10 +1,16 @@
 //! `agent-of-empires session` subcommands implementation
 
+use std::path::{Path, PathBuf};
+
 use anyhow::{bail, Result};
 use clap::{Args, Subcommand};
 use serde::Serialize;
 
-use crate::session::{GroupTree, Storage};
+use crate::session::bundle;
+use crate::session::history::{self, StatusEvent, StatusSource};
+use crate::session::ports::{self, PortAllocation};
+use crate::session::{Status, Storage};
+use crate::usage::{format_cost, format_tokens, SessionUsage};
 
 #[derive(Subcommand)]
 pub enum SessionCommands {
@@ -31,6 +37,22 @@ pub enum SessionCommands {
 
     /// Auto-detect current session
     Current(CurrentArgs),
+
+    /// Show a session's status history timeline
+    History(HistoryArgs),
+
+    /// Show prompts the auto-responder answered for a session
+    AutoResponses(AutoResponsesArgs),
+
+    /// Play back a session's recording in the terminal
+    Replay(ReplayArgs),
+
+    /// Package a session into a bundle: metadata, branch commits,
+    /// uncommitted changes and recording
+    Export(ExportArgs),
+
+    /// Recreate a session from a bundle made by `session export`
+    Import(ImportArgs),
 }
 
 #[derive(Args)]
@@ -92,6 +114,81 @@ pub struct CurrentArgs {
     json: bool,
 }
 
+#[derive(Args)]
+pub struct HistoryArgs {
+    /// Session ID or title
+    identifier: String,
+
+    /// Only show the most recent N transitions
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct AutoResponsesArgs {
+    /// Session ID or title
+    identifier: String,
+
+    /// Only show the most recent N decisions
+    #[arg(short = 'n', long)]
+    limit: Option<usize>,
+
+    /// Output as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+pub struct ReplayArgs {
+    /// Session ID or title
+    identifier: String,
+
+    /// Playback speed multiplier
+    #[arg(short, long, default_value_t = 1.0)]
+    speed: f64,
+
+    /// Cap pauses between output at this many seconds (0 to keep real timing)
+    #[arg(short = 'i', long, default_value_t = 2.0)]
+    idle_limit: f64,
+
+    /// Print the recording file path instead of playing it
+    #[arg(long)]
+    path: bool,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Session ID or title
+    identifier: String,
+
+    /// Bundle file to write [default: <title>.tar.gz]
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// Bundle file made by `aoe session export`
+    bundle: PathBuf,
+
+    /// Local clone of the session's repository [default: the original
+    /// repository path, if it exists on this machine]
+    #[arg(long)]
+    path: Option<PathBuf>,
+
+    /// Import the branch under a different name
+    #[arg(short, long)]
+    branch: Option<String>,
+
+    /// Title for the imported session
+    #[arg(short, long)]
+    title: Option<String>,
+}
+
 #[derive(Serialize)]
 struct CaptureOutput {
     id: String,
@@ -114,6 +211,10 @@ struct SessionDetails {
     #[serde(skip_serializing_if = "Option::is_none")]
     parent_session_id: Option<String>,
     profile: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<PortAllocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    usage: Option<SessionUsage>,
 }
 
 pub async fn run(profile: &str, command: SessionCommands) -> Result<()> {
@@ -126,12 +227,17 @@ pub async fn run(profile: &str, command: SessionCommands) -> Result<()> {
         SessionCommands::Capture(args) => capture_session(profile, args).await,
         SessionCommands::Rename(args) => rename_session(profile, args).await,
         SessionCommands::Current(args) => current_session(args).await,
+        SessionCommands::History(args) => show_history(profile, args).await,
+        SessionCommands::AutoResponses(args) => show_auto_responses(profile, args).await,
+        SessionCommands::Replay(args) => replay_session(profile, args).await,
+        SessionCommands::Export(args) => export_session(profile, args).await,
+        SessionCommands::Import(args) => import_session(profile, args).await,
     }
 }
 
 async fn start_session(profile: &str, args: SessionIdArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
+    let (mut instances, _) = storage.load_with_groups()?;
 
     let idx = instances
         .iter()
@@ -142,11 +248,11 @@ async fn start_session(profile: &str, args: SessionIdArgs) -> Result<()> {
         })
         .ok_or_else(|| anyhow::anyhow!("Session not found: {}", args.identifier))?;
 
+    instances[idx].source_profile = storage.profile().to_string();
     instances[idx].start_with_size(crate::terminal::get_size())?;
     let title = instances[idx].title.clone();
 
-    let group_tree = GroupTree::new_with_groups(&instances, &groups);
-    storage.save_with_groups(&instances, &group_tree)?;
+    storage.replace_instance(&instances[idx])?;
 
     println!("✓ Started session: {}", title);
     Ok(())
@@ -154,9 +260,11 @@ async fn start_session(profile: &str, args: SessionIdArgs) -> Result<()> {
 
 async fn stop_session(profile: &str, args: SessionIdArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
+    let (instances, _) = storage.load_with_groups()?;
 
-    let inst = super::resolve_session(&args.identifier, &instances)?;
+    let mut inst = super::resolve_session(&args.identifier, &instances)?.clone();
+    // Lets `stop` find the profile's on_stop hooks
+    inst.source_profile = storage.profile().to_string();
     let session_id = inst.id.clone();
     let title = inst.title.clone();
     let tmux_session = crate::tmux::Session::new(&inst.id, &inst.title)?;
@@ -172,13 +280,23 @@ async fn stop_session(profile: &str, args: SessionIdArgs) -> Result<()> {
     }
 
     inst.stop()?;
+    history::record_best_effort(
+        storage.profile(),
+        &StatusEvent::new(
+            &session_id,
+            inst.status,
+            Status::Stopped,
+            StatusSource::User,
+        ),
+    );
 
     // Persist Stopped status to disk so it survives TUI restarts
-    if let Some(stored) = instances.iter_mut().find(|i| i.id == session_id) {
-        stored.status = crate::session::Status::Stopped;
-    }
-    let group_tree = crate::session::GroupTree::new_with_groups(&instances, &groups);
-    storage.save_with_groups(&instances, &group_tree)?;
+    storage.update(|instances| {
+        if let Some(stored) = instances.iter_mut().find(|i| i.id == session_id) {
+            stored.status = Status::Stopped;
+        }
+        Ok(())
+    })?;
 
     if had_container {
         println!("✓ Stopped session and container: {}", title);
@@ -191,7 +309,7 @@ async fn stop_session(profile: &str, args: SessionIdArgs) -> Result<()> {
 
 async fn restart_session(profile: &str, args: SessionIdArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
+    let (mut instances, _) = storage.load_with_groups()?;
 
     let idx = instances
         .iter()
@@ -202,11 +320,11 @@ async fn restart_session(profile: &str, args: SessionIdArgs) -> Result<()> {
         })
         .ok_or_else(|| anyhow::anyhow!("Session not found: {}", args.identifier))?;
 
+    instances[idx].source_profile = storage.profile().to_string();
     instances[idx].restart_with_size(crate::terminal::get_size())?;
     let title = instances[idx].title.clone();
 
-    let group_tree = GroupTree::new_with_groups(&instances, &groups);
-    storage.save_with_groups(&instances, &group_tree)?;
+    storage.replace_instance(&instances[idx])?;
 
     println!("✓ Restarted session: {}", title);
     Ok(())
@@ -263,6 +381,9 @@ async fn show_session(profile: &str, args: ShowArgs) -> Result<()> {
     crate::tmux::refresh_session_cache();
     inst.update_status();
 
+    let config = crate::session::resolve_config(storage.profile()).unwrap_or_default();
+    let usage = crate::usage::session_usage(&inst, &instances, &config.usage);
+
     if args.json {
         let details = SessionDetails {
             id: inst.id.clone(),
@@ -274,6 +395,8 @@ async fn show_session(profile: &str, args: ShowArgs) -> Result<()> {
             status: format!("{:?}", inst.status).to_lowercase(),
             parent_session_id: inst.parent_session_id.clone(),
             profile: storage.profile().to_string(),
+            ports: inst.ports.clone(),
+            usage,
         };
         println!("{}", serde_json::to_string_pretty(&details)?);
     } else {
@@ -288,11 +411,231 @@ async fn show_session(profile: &str, args: ShowArgs) -> Result<()> {
         if let Some(parent_id) = &inst.parent_session_id {
             println!("  Parent:  {}", parent_id);
         }
+        if !inst.ports.is_empty() {
+            println!("  Ports:   {}", ports::summary(&inst.ports));
+        }
+        if let Some(usage) = &usage {
+            let tokens = &usage.tokens;
+            println!(
+                "  Tokens:  {} ({} in, {} out, {} cache read, {} cache write)",
+                format_tokens(tokens.total()),
+                format_tokens(tokens.input_tokens),
+                format_tokens(tokens.output_tokens),
+                format_tokens(tokens.cache_read_tokens),
+                format_tokens(tokens.cache_write_tokens),
+            );
+            if let Some(cost) = usage.cost_usd {
+                println!("  Cost:    ~{} (estimated)", format_cost(cost));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn show_history(profile: &str, args: HistoryArgs) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let inst = super::resolve_session(&args.identifier, &instances)?;
+
+    let mut events = history::load(storage.profile(), &inst.id)?;
+    if let Some(limit) = args.limit {
+        let skip = events.len().saturating_sub(limit);
+        events.drain(..skip);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&events)?);
+        return Ok(());
+    }
+
+    if events.is_empty() {
+        println!("No status history recorded for '{}' yet.", inst.title);
+        return Ok(());
+    }
+
+    let now = chrono::Utc::now();
+    println!("Status history: {}", inst.title);
+    println!();
+    println!(
+        "{:<20} {:<20} {:<10} DURATION",
+        "TIME", "TRANSITION", "SOURCE"
+    );
+    for (i, event) in events.iter().enumerate() {
+        let end = events.get(i + 1).map(|e| e.at).unwrap_or(now);
+        let transition = format!("{:?} → {:?}", event.from, event.to);
+        println!(
+            "{:<20} {:<20} {:<10} {}",
+            event
+                .at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            transition,
+            event.source.label(),
+            history::format_duration(end - event.at),
+        );
+    }
+
+    println!();
+    println!("Time in status:");
+    for (status, spent) in history::time_in_status(&events, now) {
+        println!(
+            "  {:<10} {}",
+            format!("{:?}", status),
+            history::format_duration(spent)
+        );
     }
 
     Ok(())
 }
 
+async fn show_auto_responses(profile: &str, args: AutoResponsesArgs) -> Result<()> {
+    use crate::session::auto_respond;
+
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let inst = super::resolve_session(&args.identifier, &instances)?;
+
+    let mut decisions = auto_respond::load(storage.profile(), &inst.id)?;
+    if let Some(limit) = args.limit {
+        let skip = decisions.len().saturating_sub(limit);
+        decisions.drain(..skip);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&decisions)?);
+        return Ok(());
+    }
+
+    if decisions.is_empty() {
+        println!("No auto-responses recorded for '{}'.", inst.title);
+        return Ok(());
+    }
+
+    println!("Auto-responses: {}", inst.title);
+    println!();
+    println!(
+        "{:<20} {:<20} {:<8} {:<12} MATCHED",
+        "TIME", "RULE", "ACTION", "KEYS"
+    );
+    for decision in &decisions {
+        println!(
+            "{:<20} {:<20} {:<8} {:<12} {}",
+            decision
+                .at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S"),
+            decision.rule,
+            decision.action.label(),
+            decision.keys.join(" "),
+            decision.matched.replace('\n', " "),
+        );
+    }
+
+    Ok(())
+}
+
+async fn replay_session(profile: &str, args: ReplayArgs) -> Result<()> {
+    use crate::session::recording;
+
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let inst = super::resolve_session(&args.identifier, &instances)?;
+
+    let path = recording::recording_path(storage.profile(), &inst.id)?;
+    if !path.exists() {
+        bail!(
+            "No recording for '{}'. Enable `session.record` in your config to record new session starts.",
+            inst.title
+        );
+    }
+
+    if args.path {
+        println!("{}", path.display());
+        return Ok(());
+    }
+
+    let idle_limit = (args.idle_limit > 0.0).then_some(args.idle_limit);
+    let mut stdout = std::io::stdout().lock();
+    recording::replay(&path, &mut stdout, args.speed, idle_limit)?;
+    // Leave the terminal usable if playback ended mid-style or with a hidden cursor
+    println!("\x1b[0m\x1b[?25h");
+    Ok(())
+}
+
+async fn export_session(profile: &str, args: ExportArgs) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let (instances, _) = storage.load_with_groups()?;
+    let inst = super::resolve_session(&args.identifier, &instances)?;
+
+    let output = args.output.unwrap_or_else(|| {
+        let name = crate::git::template::sanitize_branch_name(&inst.title);
+        PathBuf::from(format!("{}.tar.gz", name.replace(char::is_whitespace, "-")))
+    });
+    let config = crate::session::repo_config::resolve_config_with_repo(
+        storage.profile(),
+        Path::new(&inst.project_path),
+    )?;
+    let manifest = bundle::export(inst, storage.profile(), &config, &output)?;
+
+    println!("✓ Exported session: {}", inst.title);
+    println!("  Bundle:  {}", output.display());
+    if let Some(git) = &manifest.git {
+        let changes = if git.has_patch {
+            ", uncommitted changes"
+        } else {
+            ""
+        };
+        println!(
+            "  Branch:  {} ({} commit{}{})",
+            git.branch,
+            git.commits,
+            if git.commits == 1 { "" } else { "s" },
+            changes
+        );
+    }
+    if manifest.has_recording {
+        println!("  Recording included");
+    }
+    Ok(())
+}
+
+async fn import_session(profile: &str, args: ImportArgs) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let opts = bundle::ImportOptions {
+        repo_path: args.path,
+        branch: args.branch,
+        title: args.title,
+    };
+    let instance = bundle::import(&args.bundle, storage.profile(), &opts)?;
+
+    storage.update_with_groups(|instances, group_tree| {
+        instances.push(instance.clone());
+        if !instance.group_path.is_empty() {
+            group_tree.create_group(&instance.group_path);
+        }
+        Ok(())
+    })?;
+
+    println!("✓ Imported session: {}", instance.title);
+    println!("  Profile: {}", storage.profile());
+    println!("  Path:    {}", instance.project_path);
+    if let Some(wt) = &instance.worktree_info {
+        println!("  Branch:  {}", wt.branch);
+        if !wt.managed_by_aoe {
+            println!("  (reusing the existing worktree for this branch)");
+        }
+    }
+    println!("  ID:      {}", instance.id);
+    println!();
+    println!("Next steps:");
+    println!(
+        "  agent-of-empires session start {}   # Start the session",
+        instance.id
+    );
+    Ok(())
+}
+
 async fn capture_session(profile: &str, args: CaptureArgs) -> Result<()> {
     let storage = Storage::new(profile)?;
     let (instances, _) = storage.load_with_groups()?;
@@ -358,7 +701,7 @@ async fn rename_session(profile: &str, args: RenameArgs) -> Result<()> {
     }
 
     let storage = Storage::new(profile)?;
-    let (mut instances, groups) = storage.load_with_groups()?;
+    let (instances, _) = storage.load_with_groups()?;
 
     let inst = if let Some(id) = &args.identifier {
         super::resolve_session(id, &instances)?
@@ -407,17 +750,21 @@ async fn rename_session(profile: &str, args: RenameArgs) -> Result<()> {
         }
     }
 
-    instances[idx].title = effective_title.clone();
-
-    if let Some(group) = args.group {
-        instances[idx].group_path = group.trim().to_string();
-    }
-
-    let mut group_tree = GroupTree::new_with_groups(&instances, &groups);
-    if !instances[idx].group_path.is_empty() {
-        group_tree.create_group(&instances[idx].group_path);
-    }
-    storage.save_with_groups(&instances, &group_tree)?;
+    let new_group = args.group.map(|g| g.trim().to_string());
+    storage.update_with_groups(|instances, group_tree| {
+        let inst = instances
+            .iter_mut()
+            .find(|i| i.id == id)
+            .ok_or_else(|| anyhow::anyhow!("Session not found"))?;
+        inst.title = effective_title.clone();
+        if let Some(group) = new_group {
+            inst.group_path = group;
+        }
+        if !inst.group_path.is_empty() {
+            group_tree.create_group(&inst.group_path);
+        }
+        Ok(())
+    })?;
 
     if old_title != effective_title {
         println!("✓ Renamed session: {} → {}", old_title, effective_title);
@@ -28,6 +28,7 @@ struct StatusCounts {
     idle: usize,
     stopped: usize,
     error: usize,
+    stalled: usize,
     total: usize,
 }
 
@@ -38,6 +39,7 @@ struct StatusJson {
     idle: usize,
     stopped: usize,
     error: usize,
+    stalled: usize,
     total: usize,
 }
 
@@ -48,7 +50,7 @@ pub async fn run(profile: &str, args: StatusArgs) -> Result<()> {
     if instances.is_empty() {
         if args.json {
             println!(
-                r#"{{"waiting": 0, "running": 0, "idle": 0, "stopped": 0, "error": 0, "total": 0}}"#
+                r#"{{"waiting": 0, "running": 0, "idle": 0, "stopped": 0, "error": 0, "stalled": 0, "total": 0}}"#
             );
         } else if args.quiet {
             println!("0");
@@ -75,6 +77,7 @@ pub async fn run(profile: &str, args: StatusArgs) -> Result<()> {
             idle: counts.idle,
             stopped: counts.stopped,
             error: counts.error,
+            stalled: counts.stalled,
             total: counts.total,
         };
         println!("{}", serde_json::to_string(&status_json)?);
@@ -82,6 +85,7 @@ pub async fn run(profile: &str, args: StatusArgs) -> Result<()> {
         println!("{}", counts.waiting);
     } else if args.verbose {
         print_status_group("WAITING", "⠃", Status::Waiting, &instances);
+        print_status_group("STALLED", "⠿", Status::Stalled, &instances);
         print_status_group("RUNNING", "⠋", Status::Running, &instances);
         print_status_group("IDLE", "⠒", Status::Idle, &instances);
         print_status_group("STOPPED", "⠒", Status::Stopped, &instances);
@@ -91,16 +95,18 @@ pub async fn run(profile: &str, args: StatusArgs) -> Result<()> {
             counts.total,
             storage.profile()
         );
-    } else if counts.stopped > 0 {
-        println!(
-            "{} waiting • {} running • {} idle • {} stopped",
-            counts.waiting, counts.running, counts.idle, counts.stopped
-        );
     } else {
-        println!(
+        let mut summary = format!(
             "{} waiting • {} running • {} idle",
             counts.waiting, counts.running, counts.idle
         );
+        if counts.stalled > 0 {
+            summary.push_str(&format!(" • {} stalled", counts.stalled));
+        }
+        if counts.stopped > 0 {
+            summary.push_str(&format!(" • {} stopped", counts.stopped));
+        }
+        println!("{}", summary);
     }
 
     // Show update notice if available (skip for JSON/quiet output)
@@ -121,6 +127,7 @@ fn count_by_status(instances: &[crate::session::Instance]) -> StatusCounts {
             Status::Unknown => counts.idle += 1,
             Status::Stopped => counts.stopped += 1,
             Status::Error => counts.error += 1,
+            Status::Stalled => counts.stalled += 1,
             Status::Starting => counts.idle += 1,
             Status::Deleting => {}
             Status::Creating => {}
@@ -10,6 +10,22 @@ pub enum TmuxCommands {
     /// Add this to your ~/.tmux.conf:
     ///   set -g status-right "#(aoe tmux status)"
     Status(TmuxStatusArgs),
+
+    /// Write piped pane output to an asciicast file (used by `session.record`)
+    #[command(hide = true)]
+    Record(TmuxRecordArgs),
+
+    /// Send a session's initial prompt once its agent is ready
+    #[command(hide = true)]
+    DeliverPrompt(TmuxDeliverPromptArgs),
+
+    /// Send a session's queued messages, one each time its agent goes idle
+    #[command(hide = true)]
+    DispatchQueue(TmuxDeliverPromptArgs),
+
+    /// Answer a session's permission prompts by `auto_respond` rules
+    #[command(hide = true)]
+    AutoRespond(TmuxDeliverPromptArgs),
 }
 
 #[derive(Args)]
@@ -19,6 +35,61 @@ pub struct TmuxStatusArgs {
     format: String,
 }
 
+#[derive(Args)]
+pub struct TmuxRecordArgs {
+    /// Asciicast file to create or append to
+    #[arg(long)]
+    output: std::path::PathBuf,
+
+    /// Terminal width recorded in a new file's header
+    #[arg(long, default_value_t = 120)]
+    width: u16,
+
+    /// Terminal height recorded in a new file's header
+    #[arg(long, default_value_t = 40)]
+    height: u16,
+
+    /// Title recorded in a new file's header
+    #[arg(long)]
+    title: Option<String>,
+}
+
+#[derive(Args)]
+pub struct TmuxDeliverPromptArgs {
+    /// Profile the session belongs to
+    #[arg(long, default_value = "")]
+    profile: String,
+
+    /// Session ID
+    #[arg(long)]
+    session: String,
+}
+
+pub fn run_deliver_prompt(args: TmuxDeliverPromptArgs) -> Result<()> {
+    crate::session::initial_prompt::deliver_when_ready(&args.profile, &args.session)?;
+    Ok(())
+}
+
+pub fn run_dispatch_queue(args: TmuxDeliverPromptArgs) -> Result<()> {
+    crate::session::message_queue::dispatch(&args.profile, &args.session)?;
+    Ok(())
+}
+
+pub fn run_auto_respond(args: TmuxDeliverPromptArgs) -> Result<()> {
+    crate::session::auto_respond::watch(&args.profile, &args.session)?;
+    Ok(())
+}
+
+pub fn run_record(args: TmuxRecordArgs) -> Result<()> {
+    crate::session::recording::write_stream(
+        std::io::stdin().lock(),
+        &args.output,
+        args.width,
+        args.height,
+        args.title.as_deref(),
+    )
+}
+
 pub fn run_status(args: TmuxStatusArgs) -> Result<()> {
     use crate::tmux::status_bar::get_session_info_for_current;
 
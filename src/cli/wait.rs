@@ -0,0 +1,440 @@
+//! `agent-of-empires wait` command implementation
+
+use anyhow::{bail, Result};
+use clap::{ArgGroup, Args, ValueEnum};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::session::message_queue::{self, Sent};
+use crate::session::{Instance, Status, Storage};
+
+/// Exit code when every session reached the target status.
+pub const EXIT_REACHED: i32 = 0;
+/// Exit code when a session errored or was removed while waiting. Failures
+/// such as an unknown session exit with this code too.
+pub const EXIT_ERROR: i32 = 1;
+/// Exit code when the timeout expired first (same as coreutils `timeout`).
+pub const EXIT_TIMEOUT: i32 = 124;
+
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("selection")
+        .required(true)
+        .args(["identifiers", "group", "all"])
+))]
+pub struct WaitArgs {
+    /// Session IDs or titles to wait for
+    identifiers: Vec<String>,
+
+    /// Wait for every session in this group (including subgroups)
+    #[arg(short = 'g', long)]
+    group: Option<String>,
+
+    /// Wait for every session in the profile
+    #[arg(short = 'a', long)]
+    all: bool,
+
+    /// Status to wait for; separate several with commas (e.g. idle,waiting)
+    #[arg(
+        long = "for",
+        value_enum,
+        value_delimiter = ',',
+        default_value = "idle"
+    )]
+    target: Vec<WaitTarget>,
+
+    /// Give up after this long (e.g. 90s, 30m, 1h30m); waits forever if unset
+    #[arg(short = 't', long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+
+    /// How often to check session status
+    #[arg(long, value_parser = parse_duration, default_value = "2s")]
+    interval: Duration,
+
+    /// Only count a status reached after the session changed: it was seen in
+    /// another status, or its pane or agent hooks changed. Useful right after
+    /// `aoe send`, while the agent may not have picked the message up
+    #[arg(long)]
+    after_change: bool,
+
+    /// Output the final status of each session as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum WaitTarget {
+    Idle,
+    Waiting,
+    Stopped,
+}
+
+impl WaitTarget {
+    fn matches(self, status: Status) -> bool {
+        match self {
+            WaitTarget::Idle => status == Status::Idle,
+            WaitTarget::Waiting => status == Status::Waiting,
+            WaitTarget::Stopped => status == Status::Stopped,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            WaitTarget::Idle => "idle",
+            WaitTarget::Waiting => "waiting",
+            WaitTarget::Stopped => "stopped",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Reached,
+    Timeout,
+    Error,
+}
+
+impl Outcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Reached => EXIT_REACHED,
+            Outcome::Timeout => EXIT_TIMEOUT,
+            Outcome::Error => EXIT_ERROR,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SessionState {
+    id: String,
+    title: String,
+    /// `None` once the session has been removed
+    status: Option<String>,
+    reached: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct WaitJson<'a> {
+    outcome: Outcome,
+    elapsed_secs: u64,
+    sessions: &'a [SessionState],
+}
+
+pub async fn run(profile: &str, args: WaitArgs) -> Result<()> {
+    let storage = Storage::new(profile)?;
+    let instances = storage.load()?;
+
+    let ids = select_sessions(&args, &instances)?;
+    let started = Instant::now();
+    // Kept across polls so status detection sees a continuous history
+    // instead of whatever was last saved.
+    let mut tracked: HashMap<String, Instance> = HashMap::new();
+    // With --after-change, each session as first seen
+    let mut changes: Option<HashMap<String, Sent>> = args.after_change.then(HashMap::new);
+
+    let (outcome, states) = loop {
+        crate::tmux::refresh_session_cache();
+        let instances = storage.load()?;
+        let states: Vec<SessionState> = ids
+            .iter()
+            .map(|id| {
+                let mut state = poll_session(id, &instances, &mut tracked, &args.target);
+                if let (Some(changes), Some(inst)) = (changes.as_mut(), tracked.get(id)) {
+                    state.reached = changed_since_start(changes, inst, state.reached);
+                }
+                state
+            })
+            .collect();
+
+        if states.iter().all(|s| s.reached) {
+            break (Outcome::Reached, states);
+        }
+        if states.iter().any(|s| !s.reached && s.error.is_some()) {
+            break (Outcome::Error, states);
+        }
+        let remaining = args
+            .timeout
+            .map(|timeout| timeout.saturating_sub(started.elapsed()));
+        if remaining == Some(Duration::ZERO) {
+            break (Outcome::Timeout, states);
+        }
+
+        let sleep = remaining.map_or(args.interval, |r| r.min(args.interval));
+        tokio::time::sleep(sleep).await;
+    };
+
+    if args.json {
+        let output = WaitJson {
+            outcome,
+            elapsed_secs: started.elapsed().as_secs(),
+            sessions: &states,
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    } else {
+        print_summary(outcome, &states, &args.target);
+    }
+
+    if outcome != Outcome::Reached {
+        std::process::exit(outcome.exit_code());
+    }
+    Ok(())
+}
+
+/// Resolve the selection to session IDs up front, so sessions added to the
+/// group later don't extend the wait and removed ones are noticed.
+fn select_sessions(args: &WaitArgs, instances: &[Instance]) -> Result<Vec<String>> {
+    let mut ids: Vec<String> = Vec::new();
+    if args.all {
+        ids.extend(instances.iter().map(|i| i.id.clone()));
+        if ids.is_empty() {
+            bail!("No sessions to wait for");
+        }
+    } else if let Some(group) = &args.group {
+        let prefix = format!("{}/", group);
+        ids.extend(
+            instances
+                .iter()
+                .filter(|i| i.group_path == *group || i.group_path.starts_with(&prefix))
+                .map(|i| i.id.clone()),
+        );
+        if ids.is_empty() {
+            bail!("No sessions in group: {}", group);
+        }
+    } else {
+        for identifier in &args.identifiers {
+            let id = super::resolve_session(identifier, instances)?.id.clone();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+fn poll_session(
+    id: &str,
+    instances: &[Instance],
+    tracked: &mut HashMap<String, Instance>,
+    targets: &[WaitTarget],
+) -> SessionState {
+    let Some(stored) = instances.iter().find(|i| i.id == id) else {
+        let title = tracked.get(id).map(|i| i.title.clone()).unwrap_or_default();
+        return SessionState {
+            id: id.to_string(),
+            title,
+            status: None,
+            reached: false,
+            error: Some("Session was removed".to_string()),
+        };
+    };
+
+    let inst = tracked.entry(id.to_string()).or_insert_with(|| {
+        let mut inst = stored.clone();
+        // A session that was just started gets the usual grace period, so
+        // an empty pane isn't mistaken for an idle agent.
+        if inst.status == Status::Starting {
+            inst.last_start_time = Some(Instant::now());
+        }
+        inst
+    });
+    // Stopping, starting and deleting are only visible in storage, so take
+    // the saved state whenever either side is in one of those phases.
+    let lifecycle = |s: Status| matches!(s, Status::Stopped | Status::Deleting | Status::Creating);
+    if lifecycle(stored.status) || lifecycle(inst.status) {
+        *inst = stored.clone();
+    }
+    inst.initial_prompt = stored.initial_prompt.clone();
+    inst.update_status();
+
+    let reached = is_reached(inst, targets);
+    let error = (inst.status == Status::Error).then(|| {
+        inst.last_error
+            .clone()
+            .unwrap_or_else(|| "Session is in an error state".to_string())
+    });
+    SessionState {
+        id: inst.id.clone(),
+        title: inst.title.clone(),
+        status: Some(format!("{:?}", inst.status).to_lowercase()),
+        reached,
+        error,
+    }
+}
+
+/// With `--after-change`, whether a session that is (or isn't) at its target
+/// has changed since the first poll, and so counts as having reached it.
+fn changed_since_start(
+    changes: &mut HashMap<String, Sent>,
+    inst: &Instance,
+    reached: bool,
+) -> bool {
+    let pane = inst
+        .tmux_session()
+        .ok()
+        .and_then(|s| s.capture_pane(message_queue::PANE_LINES).ok());
+    let hook_updated = message_queue::hook_updated(&inst.id);
+    let seen = changes
+        .entry(inst.id.clone())
+        .or_insert_with(|| Sent::new(hook_updated));
+    seen.observe(reached, hook_updated, pane);
+    reached && seen.taken
+}
+
+/// A session with an initial prompt still pending isn't done starting, so
+/// it doesn't count as idle or waiting until the prompt has been sent.
+fn is_reached(inst: &Instance, targets: &[WaitTarget]) -> bool {
+    targets.iter().any(|t| {
+        t.matches(inst.status) && (*t == WaitTarget::Stopped || inst.initial_prompt.is_none())
+    })
+}
+
+fn print_summary(outcome: Outcome, states: &[SessionState], targets: &[WaitTarget]) {
+    let target = targets
+        .iter()
+        .map(|t| t.label())
+        .collect::<Vec<_>>()
+        .join(" or ");
+    let reached = states.iter().filter(|s| s.reached).count();
+    match outcome {
+        Outcome::Reached => println!("✓ {} session(s) {}", states.len(), target),
+        Outcome::Timeout => println!(
+            "✗ Timed out: {}/{} session(s) {}",
+            reached,
+            states.len(),
+            target
+        ),
+        Outcome::Error => println!("✗ A session failed before becoming {}", target),
+    }
+    for state in states {
+        let symbol = if state.reached { "✓" } else { "✗" };
+        let status = state.status.as_deref().unwrap_or("removed");
+        match &state.error {
+            Some(error) if !state.reached => {
+                println!("  {} {:<20} {:<10} {}", symbol, state.title, status, error)
+            }
+            _ => println!("  {} {:<20} {}", symbol, state.title, status),
+        }
+    }
+}
+
+/// Parse a duration like `90`, `90s`, `30m`, `2h` or `1h30m`. A bare number
+/// is taken as seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("duration is empty".to_string());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            _ => return Err(format!("invalid duration '{}': unknown unit '{}'", s, c)),
+        };
+        let value: u64 = digits
+            .parse()
+            .map_err(|_| format!("invalid duration '{}': expected a number before '{}'", s, c))?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(|| format!("invalid duration '{}': too large", s))?;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        return Err(format!(
+            "invalid duration '{}': missing unit after '{}'",
+            s, digits
+        ));
+    }
+    Ok(Duration::from_secs(total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("45"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(90 * 60)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(24 * 60 * 60)));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_malformed_input() {
+        for input in ["", "m", "10x", "1h30", "-5s", "1.5h"] {
+            assert!(parse_duration(input).is_err(), "{input} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_target_matches() {
+        assert!(WaitTarget::Idle.matches(Status::Idle));
+        assert!(!WaitTarget::Idle.matches(Status::Unknown));
+        assert!(!WaitTarget::Idle.matches(Status::Running));
+        assert!(!WaitTarget::Idle.matches(Status::Starting));
+        assert!(WaitTarget::Waiting.matches(Status::Waiting));
+        assert!(WaitTarget::Stopped.matches(Status::Stopped));
+        assert!(!WaitTarget::Stopped.matches(Status::Error));
+    }
+
+    #[test]
+    fn test_pending_prompt_is_not_idle() {
+        let mut inst = Instance::new("test", "/tmp/test");
+        inst.status = Status::Idle;
+        inst.initial_prompt = Some("fix the build".to_string());
+        assert!(!is_reached(&inst, &[WaitTarget::Idle, WaitTarget::Waiting]));
+
+        inst.initial_prompt = None;
+        assert!(is_reached(&inst, &[WaitTarget::Idle]));
+        assert!(!is_reached(&inst, &[WaitTarget::Stopped]));
+    }
+
+    #[test]
+    fn test_pending_prompt_still_counts_as_stopped() {
+        let mut inst = Instance::new("test", "/tmp/test");
+        inst.status = Status::Stopped;
+        inst.initial_prompt = Some("fix the build".to_string());
+        assert!(is_reached(&inst, &[WaitTarget::Stopped]));
+    }
+
+    #[test]
+    fn test_after_change_ignores_a_session_that_starts_idle() {
+        let mut inst = Instance::new("test", "/tmp/test");
+        inst.id = "aoe-wait-after-change-test".to_string();
+        let mut changes = HashMap::new();
+
+        // Already idle before the agent picked up what was sent
+        assert!(!changed_since_start(&mut changes, &inst, true));
+        assert!(!changed_since_start(&mut changes, &inst, true));
+
+        // Busy, then idle again
+        assert!(!changed_since_start(&mut changes, &inst, false));
+        assert!(changed_since_start(&mut changes, &inst, true));
+    }
+
+    #[test]
+    fn test_outcome_exit_codes_are_distinct() {
+        assert_eq!(Outcome::Reached.exit_code(), 0);
+        assert_ne!(Outcome::Timeout.exit_code(), Outcome::Error.exit_code());
+        assert_ne!(Outcome::Timeout.exit_code(), 0);
+        assert_ne!(Outcome::Error.exit_code(), 0);
+    }
+}
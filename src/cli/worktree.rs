@@ -153,7 +153,7 @@ async fn show_info(profile: &str, identifier: &str) -> Result<()> {
 
 async fn cleanup_orphaned(profile: &str, force: bool) -> Result<()> {
     let storage = Storage::new(profile)?;
-    let (instances, groups) = storage.load_with_groups()?;
+    let (instances, _) = storage.load_with_groups()?;
 
     let mut orphaned_sessions = Vec::new();
     let mut orphaned_worktrees = Vec::new();
@@ -258,11 +258,10 @@ async fn cleanup_orphaned(profile: &str, force: bool) -> Result<()> {
 
     // Remove orphaned sessions
     if !orphaned_sessions.is_empty() {
-        let mut new_instances = instances.clone();
-        new_instances.retain(|inst| !orphaned_sessions.iter().any(|orphan| orphan.id == inst.id));
-
-        let group_tree = crate::session::GroupTree::new_with_groups(&new_instances, &groups);
-        storage.save_with_groups(&new_instances, &group_tree)?;
+        storage.update_with_groups(|instances, _| {
+            instances.retain(|inst| !orphaned_sessions.iter().any(|orphan| orphan.id == inst.id));
+            Ok(())
+        })?;
 
         removed_count += orphaned_sessions.len();
         println!("✓ Removed {} orphaned sessions", orphaned_sessions.len());
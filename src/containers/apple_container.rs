@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use super::container_interface::{ContainerConfig, ContainerRuntimeInterface};
+use super::container_interface::{
+    ContainerConfig, ContainerRuntimeInterface, ContainerUsage, ImageBuild,
+};
 use super::error::{DockerError, Result};
 use super::runtime_base::RuntimeBase;
 use serde_json::Value;
@@ -38,6 +40,42 @@ impl ContainerRuntimeInterface for AppleContainer {
         self.base.ensure_image(image)
     }
 
+    fn build_image(&self, build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()> {
+        self.base.build_image(build, on_output)
+    }
+
+    fn list_images(&self, prefix: &str) -> Vec<String> {
+        self.base.list_images(prefix)
+    }
+
+    fn remove_image(&self, image: &str) -> Result<()> {
+        self.base.remove_image(image)
+    }
+
+    fn commit_container(&self, name: &str, image: &str) -> Result<()> {
+        self.base.commit_container(name, image)
+    }
+
+    fn connect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.connect_network(network, name)
+    }
+
+    fn disconnect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.disconnect_network(network, name)
+    }
+
+    fn compose_command(&self, project: &str) -> Result<std::process::Command> {
+        self.base.compose_command(project)
+    }
+
+    fn ensure_internal_network(&self, name: &str) -> Result<String> {
+        self.base.ensure_internal_network(name)
+    }
+
+    fn remove_network(&self, name: &str) -> Result<()> {
+        self.base.remove_network(name)
+    }
+
     fn default_sandbox_image(&self) -> &'static str {
         self.base.default_sandbox_image()
     }
@@ -134,6 +172,10 @@ impl ContainerRuntimeInterface for AppleContainer {
     fn batch_running_states(&self, _prefix: &str) -> HashMap<String, bool> {
         HashMap::new()
     }
+
+    fn batch_usage(&self, _prefix: &str) -> HashMap<String, ContainerUsage> {
+        HashMap::new()
+    }
 }
 
 #[cfg(test)]
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 use super::error::Result;
 use enum_dispatch::enum_dispatch;
@@ -48,6 +49,28 @@ pub struct ContainerConfig {
     pub cpu_limit: Option<String>,
     pub memory_limit: Option<String>,
     pub port_mappings: Vec<String>,
+    /// `--network` to attach the container to, instead of the default
+    pub network: Option<String>,
+}
+
+/// An image to build from a Dockerfile
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageBuild {
+    pub tag: String,
+    pub dockerfile: PathBuf,
+    pub context: PathBuf,
+    /// `--build-arg` values
+    pub args: Vec<(String, String)>,
+    /// Stage to build in a multi-stage Dockerfile
+    pub target: Option<String>,
+}
+
+/// CPU and memory use of a running container
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ContainerUsage {
+    /// Percent of one core, so a busy container can exceed 100
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
 }
 
 #[enum_dispatch]
@@ -71,6 +94,33 @@ pub trait ContainerRuntimeInterface {
 
     fn image_exists_locally(&self, image: &str) -> bool;
 
+    /// Build an image, passing each line of build output to `on_output`
+    fn build_image(&self, build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()>;
+
+    /// Local images whose name starts with `prefix`, as `name:tag`
+    fn list_images(&self, prefix: &str) -> Vec<String>;
+
+    fn remove_image(&self, image: &str) -> Result<()>;
+
+    /// Save a container's filesystem as the image `image`
+    fn commit_container(&self, name: &str, image: &str) -> Result<()>;
+
+    /// Create the internal network `name` unless it exists, returning the
+    /// gateway address containers on it use to reach the host
+    fn ensure_internal_network(&self, name: &str) -> Result<String>;
+
+    /// Remove the network `name`; a network that doesn't exist is fine
+    fn remove_network(&self, name: &str) -> Result<()>;
+
+    /// Attach the container `name` to the network `network`
+    fn connect_network(&self, network: &str, name: &str) -> Result<()>;
+
+    /// Detach the container `name` from the network `network`
+    fn disconnect_network(&self, network: &str, name: &str) -> Result<()>;
+
+    /// A `compose` command for the project `project`, to add a subcommand to
+    fn compose_command(&self, project: &str) -> Result<std::process::Command>;
+
     // container management
     fn does_container_exist(&self, name: &str) -> Result<bool>;
 
@@ -96,4 +146,8 @@ pub trait ContainerRuntimeInterface {
     /// Check running state of all containers matching a name prefix in a single call.
     /// Returns a map of container name -> is_running.
     fn batch_running_states(&self, prefix: &str) -> HashMap<String, bool>;
+
+    /// CPU and memory use of all running containers matching a name prefix,
+    /// in a single call. Returns a map of container name -> usage.
+    fn batch_usage(&self, prefix: &str) -> HashMap<String, ContainerUsage>;
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
-use super::container_interface::{ContainerConfig, ContainerRuntimeInterface};
+use super::container_interface::{
+    ContainerConfig, ContainerRuntimeInterface, ContainerUsage, ImageBuild,
+};
 use super::error::{DockerError, Result};
 use super::runtime_base::RuntimeBase;
 
@@ -41,6 +43,42 @@ impl ContainerRuntimeInterface for Docker {
         self.base.ensure_image(image)
     }
 
+    fn build_image(&self, build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()> {
+        self.base.build_image(build, on_output)
+    }
+
+    fn list_images(&self, prefix: &str) -> Vec<String> {
+        self.base.list_images(prefix)
+    }
+
+    fn remove_image(&self, image: &str) -> Result<()> {
+        self.base.remove_image(image)
+    }
+
+    fn commit_container(&self, name: &str, image: &str) -> Result<()> {
+        self.base.commit_container(name, image)
+    }
+
+    fn connect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.connect_network(network, name)
+    }
+
+    fn disconnect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.disconnect_network(network, name)
+    }
+
+    fn compose_command(&self, project: &str) -> Result<std::process::Command> {
+        self.base.compose_command(project)
+    }
+
+    fn ensure_internal_network(&self, name: &str) -> Result<String> {
+        self.base.ensure_internal_network(name)
+    }
+
+    fn remove_network(&self, name: &str) -> Result<()> {
+        self.base.remove_network(name)
+    }
+
     fn default_sandbox_image(&self) -> &'static str {
         self.base.default_sandbox_image()
     }
@@ -146,6 +184,10 @@ impl ContainerRuntimeInterface for Docker {
             })
             .collect()
     }
+
+    fn batch_usage(&self, prefix: &str) -> HashMap<String, ContainerUsage> {
+        self.base.batch_usage(prefix)
+    }
 }
 
 #[cfg(test)]
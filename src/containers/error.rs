@@ -31,6 +31,9 @@ pub enum DockerError {
     #[error("Docker image not found: {0}")]
     ImageNotFound(String),
 
+    #[error("Failed to build image: {0}")]
+    BuildFailed(String),
+
     #[error("Failed to create container: {0}")]
     CreateFailed(String),
 
@@ -43,6 +46,9 @@ pub enum DockerError {
     #[error("Failed to remove container: {0}")]
     RemoveFailed(String),
 
+    #[error("Not supported: {0}")]
+    Unsupported(String),
+
     #[error("Docker command failed: {0}")]
     CommandFailed(String),
 
@@ -2,22 +2,30 @@ mod apple_container;
 pub mod container_interface;
 mod docker;
 pub mod error;
+mod podman;
 pub(crate) mod runtime_base;
 
 use std::collections::HashMap;
+use std::process::Command;
+use std::sync::OnceLock;
 
 use crate::cli::truncate_id;
 use crate::session::{Config, ContainerRuntimeName};
 use apple_container::AppleContainer;
-pub use container_interface::{ContainerConfig, ContainerRuntimeInterface, EnvEntry, VolumeMount};
+pub use container_interface::{
+    ContainerConfig, ContainerRuntimeInterface, ContainerUsage, EnvEntry, ImageBuild, VolumeMount,
+};
 use docker::Docker;
 use enum_dispatch::enum_dispatch;
 use error::Result;
+use podman::Podman;
+use runtime_base::RuntimeBase;
 
 #[enum_dispatch(ContainerRuntimeInterface)]
 pub enum ContainerRuntime {
     AppleContainer,
     Docker,
+    Podman,
 }
 
 impl Default for ContainerRuntime {
@@ -29,9 +37,10 @@ impl Default for ContainerRuntime {
 /// Returns the CLI binary name for the configured container runtime.
 pub fn runtime_binary() -> &'static str {
     if let Ok(cfg) = Config::load() {
-        match cfg.sandbox.container_runtime {
+        match effective_runtime(cfg.sandbox.container_runtime) {
             ContainerRuntimeName::AppleContainer => "container",
             ContainerRuntimeName::Docker => "docker",
+            ContainerRuntimeName::Podman => "podman",
         }
     } else {
         "docker"
@@ -40,21 +49,55 @@ pub fn runtime_binary() -> &'static str {
 
 pub fn get_container_runtime() -> ContainerRuntime {
     if let Ok(cfg) = Config::load() {
-        match cfg.sandbox.container_runtime {
+        match effective_runtime(cfg.sandbox.container_runtime) {
             ContainerRuntimeName::AppleContainer => AppleContainer::default().into(),
             ContainerRuntimeName::Docker => Docker::default().into(),
+            ContainerRuntimeName::Podman => Podman::default().into(),
         }
     } else {
         ContainerRuntime::default()
     }
 }
 
+/// The runtime to drive for the configured one. Docker falls back to
+/// Podman when `docker` is Podman's docker-compatible wrapper
+/// (podman-docker), or when only Podman is installed, so Podman's user
+/// namespace and volume label handling still apply.
+fn effective_runtime(configured: ContainerRuntimeName) -> ContainerRuntimeName {
+    match configured {
+        ContainerRuntimeName::Docker if docker_is_podman() => ContainerRuntimeName::Podman,
+        other => other,
+    }
+}
+
+fn docker_is_podman() -> bool {
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+    *DETECTED.get_or_init(|| {
+        let detected = match Command::new("docker").arg("--version").output() {
+            Ok(o) if o.status.success() => {
+                podman::is_podman_version(&String::from_utf8_lossy(&o.stdout))
+            }
+            _ => RuntimeBase::PODMAN.is_available(),
+        };
+        if detected {
+            tracing::info!("Using Podman for the docker container runtime");
+        }
+        detected
+    })
+}
+
 /// Check running state of all aoe sandbox containers in a single subprocess call.
 /// Returns a map of container name -> is_running.
 pub fn batch_container_health() -> HashMap<String, bool> {
     get_container_runtime().batch_running_states("aoe-sandbox-")
 }
 
+/// CPU and memory use of all running aoe sandbox containers in a single
+/// subprocess call. Returns a map of container name -> usage.
+pub fn batch_container_usage() -> HashMap<String, ContainerUsage> {
+    get_container_runtime().batch_usage("aoe-sandbox-")
+}
+
 pub struct DockerContainer {
     pub name: String,
     pub image: String,
@@ -112,6 +155,11 @@ impl DockerContainer {
         self.runtime.remove(&self.name, force)
     }
 
+    /// Save the container's filesystem as the image `image`
+    pub fn commit(&self, image: &str) -> Result<()> {
+        self.runtime.commit_container(&self.name, image)
+    }
+
     pub fn exec_command(&self, options: Option<&str>, cmd: &str) -> String {
         self.runtime.exec_command(&self.name, options, cmd)
     }
@@ -157,6 +205,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = container.build_create_args(&config);
@@ -186,6 +235,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = container.build_create_args(&config);
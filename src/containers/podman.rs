@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::container_interface::{
+    ContainerConfig, ContainerRuntimeInterface, ContainerUsage, ImageBuild,
+};
+use super::error::{DockerError, Result};
+use super::runtime_base::RuntimeBase;
+use serde_json::Value;
+
+/// Maps the host user to root inside the container. The sandbox image runs
+/// agents as root, so files they write in the project keep the host user's
+/// ownership instead of landing on a subordinate UID.
+const KEEP_ID_AS_ROOT: &str = "--userns=keep-id:uid=0,gid=0";
+
+/// Shared SELinux label for bind mounts. Project repos and agent config
+/// dirs are mounted into every session's container, so the private `Z`
+/// label would lock each one to a single container.
+const SELINUX_SHARED_LABEL: &str = "z";
+
+pub struct Podman {
+    base: RuntimeBase,
+    /// Running without root, in a user namespace
+    rootless: bool,
+    /// SELinux is enabled on the host, so bind mounts need relabeling
+    selinux: bool,
+}
+
+impl Default for Podman {
+    fn default() -> Self {
+        Self {
+            base: RuntimeBase::PODMAN,
+            rootless: !nix::unistd::geteuid().is_root(),
+            selinux: Path::new("/sys/fs/selinux/enforce").exists(),
+        }
+    }
+}
+
+impl Podman {
+    fn run_flags(&self) -> Vec<&'static str> {
+        // keep-id only exists in rootless mode; rootful root is already root
+        if self.rootless {
+            vec![KEEP_ID_AS_ROOT]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn volume_label(&self) -> Option<&'static str> {
+        self.selinux.then_some(SELINUX_SHARED_LABEL)
+    }
+}
+
+impl ContainerRuntimeInterface for Podman {
+    fn is_available(&self) -> bool {
+        self.base.is_available()
+    }
+
+    fn is_daemon_running(&self) -> bool {
+        // Podman has no daemon; `info` fails when it can't set up storage
+        // or the user namespace
+        self.base.is_daemon_running()
+    }
+
+    fn get_version(&self) -> Result<String> {
+        self.base.get_version()
+    }
+
+    fn image_exists_locally(&self, image: &str) -> bool {
+        self.base.image_exists_locally(image)
+    }
+
+    fn pull_image(&self, image: &str) -> Result<()> {
+        self.base.pull_image(image)
+    }
+
+    fn ensure_image(&self, image: &str) -> Result<()> {
+        self.base.ensure_image(image)
+    }
+
+    fn build_image(&self, build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()> {
+        self.base.build_image(build, on_output)
+    }
+
+    fn list_images(&self, prefix: &str) -> Vec<String> {
+        self.base.list_images(prefix)
+    }
+
+    fn remove_image(&self, image: &str) -> Result<()> {
+        self.base.remove_image(image)
+    }
+
+    fn commit_container(&self, name: &str, image: &str) -> Result<()> {
+        self.base.commit_container(name, image)
+    }
+
+    fn connect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.connect_network(network, name)
+    }
+
+    fn disconnect_network(&self, network: &str, name: &str) -> Result<()> {
+        self.base.disconnect_network(network, name)
+    }
+
+    fn compose_command(&self, project: &str) -> Result<std::process::Command> {
+        self.base.compose_command(project)
+    }
+
+    fn ensure_internal_network(&self, name: &str) -> Result<String> {
+        // Rootless networks live in a separate namespace, so the gateway
+        // isn't an address of the host
+        if self.rootless {
+            return Err(DockerError::Unsupported(
+                "rootless Podman containers can't reach the host from an internal network"
+                    .to_string(),
+            ));
+        }
+        self.base.ensure_internal_network(name)
+    }
+
+    fn remove_network(&self, name: &str) -> Result<()> {
+        self.base.remove_network(name)
+    }
+
+    fn default_sandbox_image(&self) -> &'static str {
+        self.base.default_sandbox_image()
+    }
+
+    fn effective_default_image(&self) -> String {
+        self.base.effective_default_image()
+    }
+
+    fn does_container_exist(&self, name: &str) -> Result<bool> {
+        // Exits 0 when the container exists, 1 when it doesn't
+        let output = self
+            .base
+            .command()
+            .args(["container", "exists", name])
+            .output()?;
+        Ok(output.status.success())
+    }
+
+    fn is_container_running(&self, name: &str) -> Result<bool> {
+        let output = self
+            .base
+            .command()
+            .args(["container", "inspect", "-f", "{{.State.Running}}", name])
+            .output()?;
+
+        if !output.status.success() {
+            return Ok(false);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.trim() == "true")
+    }
+
+    fn build_create_args(&self, name: &str, image: &str, config: &ContainerConfig) -> Vec<String> {
+        self.base.build_create_args_with(
+            name,
+            image,
+            config,
+            &self.run_flags(),
+            self.volume_label(),
+        )
+    }
+
+    fn create_container(
+        &self,
+        name: &str,
+        image: &str,
+        config: &ContainerConfig,
+    ) -> Result<String> {
+        if self.does_container_exist(name)? {
+            return Err(DockerError::ContainerAlreadyExists(name.to_string()));
+        }
+        let args = self.build_create_args(name, image, config);
+        self.base.run_create_args(args, image, config)
+    }
+
+    fn start_container(&self, name: &str) -> Result<()> {
+        self.base.start_container(name)
+    }
+
+    fn stop_container(&self, name: &str) -> Result<()> {
+        self.base.stop_container(name)
+    }
+
+    fn remove(&self, name: &str, force: bool) -> Result<()> {
+        self.base.remove(name, force)
+    }
+
+    fn exec_command(&self, name: &str, options: Option<&str>, cmd: &str) -> String {
+        self.base.exec_command(name, options, cmd)
+    }
+
+    fn exec(&self, name: &str, cmd: &[&str]) -> Result<std::process::Output> {
+        self.base.exec(name, cmd)
+    }
+
+    fn batch_running_states(&self, prefix: &str) -> HashMap<String, bool> {
+        // Podman's name filter is a regex, and its JSON output is stable
+        // across versions, unlike the Go template fields of `ps`
+        let output = self
+            .base
+            .command()
+            .args([
+                "ps",
+                "-a",
+                "--filter",
+                &format!("name=^{}", prefix),
+                "--format",
+                "json",
+            ])
+            .output();
+
+        match output {
+            Ok(o) if o.status.success() => parse_ps_json(&o.stdout, prefix),
+            _ => HashMap::new(),
+        }
+    }
+
+    fn batch_usage(&self, prefix: &str) -> HashMap<String, ContainerUsage> {
+        self.base.batch_usage(prefix)
+    }
+}
+
+/// Parse `podman ps --format json` into container name -> is_running.
+fn parse_ps_json(stdout: &[u8], prefix: &str) -> HashMap<String, bool> {
+    let Ok(Value::Array(containers)) = serde_json::from_slice::<Value>(stdout) else {
+        return HashMap::new();
+    };
+    containers
+        .iter()
+        .flat_map(|c| {
+            let running = c.get("State").and_then(Value::as_str) == Some("running");
+            c.get("Names")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .filter(|name| name.starts_with(prefix))
+                .map(move |name| (name.to_string(), running))
+        })
+        .collect()
+}
+
+/// Whether `--version` output comes from Podman, as with the `docker`
+/// command installed by the podman-docker package.
+pub(super) fn is_podman_version(version: &str) -> bool {
+    version.to_lowercase().contains("podman")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::containers::container_interface::VolumeMount;
+
+    fn config() -> ContainerConfig {
+        ContainerConfig {
+            working_dir: "/workspace/project".to_string(),
+            volumes: vec![
+                VolumeMount {
+                    host_path: "/src/project".to_string(),
+                    container_path: "/workspace/project".to_string(),
+                    read_only: false,
+                },
+                VolumeMount {
+                    host_path: "/home/me/.ssh".to_string(),
+                    container_path: "/root/.ssh".to_string(),
+                    read_only: true,
+                },
+            ],
+            anonymous_volumes: vec!["/workspace/project/target".to_string()],
+            environment: vec![],
+            cpu_limit: None,
+            memory_limit: None,
+            port_mappings: vec![],
+            network: None,
+        }
+    }
+
+    #[test]
+    fn test_rootless_selinux_create_args() {
+        let podman = Podman {
+            base: RuntimeBase::PODMAN,
+            rootless: true,
+            selinux: true,
+        };
+        let args = podman.build_create_args("aoe-sandbox-abc", "alpine:latest", &config());
+
+        assert_eq!(
+            args[..5],
+            ["run", "-d", "--name", "aoe-sandbox-abc", KEEP_ID_AS_ROOT]
+        );
+        assert!(args.contains(&"/src/project:/workspace/project:z".to_string()));
+        // Read-only mounts such as ~/.ssh keep their host label
+        assert!(args.contains(&"/home/me/.ssh:/root/.ssh:ro".to_string()));
+        // Anonymous volumes are not bind mounts
+        assert!(args.contains(&"/workspace/project/target".to_string()));
+    }
+
+    #[test]
+    fn test_rootful_create_args() {
+        let podman = Podman {
+            base: RuntimeBase::PODMAN,
+            rootless: false,
+            selinux: false,
+        };
+        let args = podman.build_create_args("aoe-sandbox-abc", "alpine:latest", &config());
+
+        assert!(!args.iter().any(|a| a.starts_with("--userns")));
+        assert!(args.contains(&"/src/project:/workspace/project".to_string()));
+    }
+
+    #[test]
+    fn test_parse_ps_json() {
+        let stdout = br#"[
+            {"Names": ["aoe-sandbox-abc"], "State": "running"},
+            {"Names": ["aoe-sandbox-def"], "State": "exited"},
+            {"Names": ["other"], "State": "running"}
+        ]"#;
+        let states = parse_ps_json(stdout, "aoe-sandbox-");
+        assert_eq!(states.len(), 2);
+        assert!(states["aoe-sandbox-abc"]);
+        assert!(!states["aoe-sandbox-def"]);
+
+        assert!(parse_ps_json(b"", "aoe-sandbox-").is_empty());
+    }
+
+    #[test]
+    fn test_is_podman_version() {
+        assert!(is_podman_version("podman version 5.2.2"));
+        assert!(!is_podman_version("Docker version 28.2.2, build e6534b4"));
+    }
+}
@@ -1,6 +1,12 @@
-use super::container_interface::{ContainerConfig, EnvEntry};
+use super::container_interface::{ContainerConfig, ContainerUsage, EnvEntry, ImageBuild};
 use super::error::{DockerError, Result};
-use std::process::Command;
+use std::collections::{HashMap, VecDeque};
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+/// Lines of output kept for the error when a streamed command fails
+const ERROR_TAIL_LINES: usize = 20;
 
 /// Shared implementation for container runtimes.
 ///
@@ -23,6 +29,16 @@ pub(crate) struct RuntimeBase {
     pub supports_read_only_volumes: bool,
     /// Whether this runtime supports `-v` on remove to clean up anonymous volumes
     pub supports_remove_volumes: bool,
+    /// Args listing local images one `name:tag` per line
+    pub image_list_args: &'static [&'static str],
+    /// Args preceding the image name when removing one
+    pub image_remove_prefix: &'static [&'static str],
+    /// Whether this runtime can create networks without outside access
+    pub supports_internal_networks: bool,
+    /// Whether this runtime can save a container's filesystem as an image
+    pub supports_commit: bool,
+    /// Whether this runtime has a `compose` subcommand
+    pub supports_compose: bool,
 }
 
 impl RuntimeBase {
@@ -34,6 +50,11 @@ impl RuntimeBase {
         remove_subcommand: "rm",
         supports_read_only_volumes: true,
         supports_remove_volumes: true,
+        image_list_args: &["images", "--format", "{{.Repository}}:{{.Tag}}"],
+        image_remove_prefix: &["image", "rm"],
+        supports_internal_networks: true,
+        supports_commit: true,
+        supports_compose: true,
     };
 
     pub const APPLE_CONTAINER: Self = Self {
@@ -44,6 +65,26 @@ impl RuntimeBase {
         remove_subcommand: "delete",
         supports_read_only_volumes: false,
         supports_remove_volumes: false,
+        image_list_args: &["image", "list", "--quiet"],
+        image_remove_prefix: &["image", "delete"],
+        supports_internal_networks: false,
+        supports_commit: false,
+        supports_compose: false,
+    };
+
+    pub const PODMAN: Self = Self {
+        binary: "podman",
+        name: "Podman",
+        daemon_check_args: &["info"],
+        pull_prefix: &["pull"],
+        remove_subcommand: "rm",
+        supports_read_only_volumes: true,
+        supports_remove_volumes: true,
+        image_list_args: &["images", "--format", "{{.Repository}}:{{.Tag}}"],
+        image_remove_prefix: &["image", "rm"],
+        supports_internal_networks: true,
+        supports_commit: true,
+        supports_compose: true,
     };
 
     pub fn command(&self) -> Command {
@@ -102,6 +143,148 @@ impl RuntimeBase {
         Ok(())
     }
 
+    /// Build `build`, passing each line of build output to `on_output` as
+    /// it's printed.
+    pub fn build_image(&self, build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()> {
+        let mut cmd = self.command();
+        cmd.args(["build", "-t", &build.tag, "-f"])
+            .arg(&build.dockerfile);
+        for (key, value) in &build.args {
+            cmd.arg("--build-arg").arg(format!("{}={}", key, value));
+        }
+        if let Some(target) = &build.target {
+            cmd.args(["--target", target]);
+        }
+        cmd.arg(&build.context);
+
+        tracing::info!(
+            "Building {} image '{}' from {}",
+            self.name,
+            build.tag,
+            build.dockerfile.display()
+        );
+        // BuildKit reports progress on stderr and Podman on stdout
+        let (success, tail) = run_streamed(&mut cmd, on_output)?;
+        if !success {
+            return Err(DockerError::BuildFailed(format!(
+                "{}: {}",
+                build.tag,
+                tail.join("\n")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// A `compose` command for the project `project`
+    pub fn compose_command(&self, project: &str) -> Result<Command> {
+        if !self.supports_compose {
+            return Err(DockerError::Unsupported(format!(
+                "{} has no compose command",
+                self.name
+            )));
+        }
+        let mut cmd = self.command();
+        cmd.args(["compose", "--project-name", project]);
+        Ok(cmd)
+    }
+
+    /// Attach the container `name` to the network `network`
+    pub fn connect_network(&self, network: &str, name: &str) -> Result<()> {
+        let output = self
+            .command()
+            .args(["network", "connect", network, name])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(DockerError::CommandFailed(format!(
+                "connecting {} to network {}: {}",
+                name,
+                network,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Detach the container `name` from the network `network`
+    pub fn disconnect_network(&self, network: &str, name: &str) -> Result<()> {
+        let output = self
+            .command()
+            .args(["network", "disconnect", "--force", network, name])
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(DockerError::CommandFailed(format!(
+                "disconnecting {} from network {}: {}",
+                name,
+                network,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Local images whose name starts with `prefix`, as `name:tag`. Podman
+    /// names local images `localhost/<name>`; that prefix is dropped.
+    pub fn list_images(&self, prefix: &str) -> Vec<String> {
+        let output = match self.command().args(self.image_list_args).output() {
+            Ok(o) if o.status.success() => o,
+            _ => return Vec::new(),
+        };
+        let mut images: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| line.trim().trim_start_matches("localhost/"))
+            .filter(|image| image.starts_with(prefix))
+            .map(String::from)
+            .collect();
+        images.sort();
+        images.dedup();
+        images
+    }
+
+    pub fn remove_image(&self, image: &str) -> Result<()> {
+        let output = self
+            .command()
+            .args(self.image_remove_prefix)
+            .arg(image)
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(DockerError::CommandFailed(format!(
+                "removing image {}: {}",
+                image,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Save the container's filesystem as the image `image`
+    pub fn commit_container(&self, name: &str, image: &str) -> Result<()> {
+        if !self.supports_commit {
+            return Err(DockerError::Unsupported(format!(
+                "{} can't commit containers to images",
+                self.name
+            )));
+        }
+
+        let output = self.command().args(["commit", name, image]).output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("No such container") {
+                return Err(DockerError::ContainerNotFound(name.to_string()));
+            }
+            return Err(DockerError::CommandFailed(format!(
+                "committing container {}: {}",
+                name,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
     pub fn ensure_image(&self, image: &str) -> Result<()> {
         if self.image_exists_locally(image) {
             tracing::info!("Using local {} image '{}'", self.name, image);
@@ -112,6 +295,58 @@ impl RuntimeBase {
         self.pull_image(image)
     }
 
+    /// Create the internal network `name` unless it exists, returning its
+    /// gateway address. Containers on it can reach the host at the gateway
+    /// and nothing else.
+    pub fn ensure_internal_network(&self, name: &str) -> Result<String> {
+        if !self.supports_internal_networks {
+            return Err(DockerError::Unsupported(format!(
+                "{} has no internal networks",
+                self.name
+            )));
+        }
+
+        let inspect = || self.command().args(["network", "inspect", name]).output();
+        let mut output = inspect()?;
+        if !output.status.success() {
+            // Another session may create it first; inspect again either way
+            let created = self
+                .command()
+                .args(["network", "create", "--internal", name])
+                .output()?;
+            output = inspect()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&created.stderr);
+                return Err(DockerError::CommandFailed(format!(
+                    "creating network {}: {}",
+                    name,
+                    stderr.trim()
+                )));
+            }
+        }
+
+        parse_network_gateway(&output.stdout).ok_or_else(|| {
+            DockerError::CommandFailed(format!("network {} has no IPv4 gateway", name))
+        })
+    }
+
+    /// Remove the network `name`, unless it doesn't exist
+    pub fn remove_network(&self, name: &str) -> Result<()> {
+        let output = self.command().args(["network", "rm", name]).output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if stderr.contains("not found") || stderr.contains("No such network") {
+                return Ok(());
+            }
+            return Err(DockerError::CommandFailed(format!(
+                "removing network {}: {}",
+                name,
+                stderr.trim()
+            )));
+        }
+        Ok(())
+    }
+
     pub fn default_sandbox_image(&self) -> &'static str {
         "ghcr.io/njbrake/aoe-sandbox:latest"
     }
@@ -128,15 +363,30 @@ impl RuntimeBase {
         name: &str,
         image: &str,
         config: &ContainerConfig,
+    ) -> Vec<String> {
+        self.build_create_args_with(name, image, config, &[], None)
+    }
+
+    /// Like `build_create_args`, with runtime-specific `run_flags` placed
+    /// after the container name and `volume_label` (e.g. an SELinux relabel
+    /// option) added to every read-write bind mount.
+    pub fn build_create_args_with(
+        &self,
+        name: &str,
+        image: &str,
+        config: &ContainerConfig,
+        run_flags: &[&str],
+        volume_label: Option<&str>,
     ) -> Vec<String> {
         let mut args = vec![
             "run".to_string(),
             "-d".to_string(),
             "--name".to_string(),
             name.to_string(),
-            "-w".to_string(),
-            config.working_dir.clone(),
         ];
+        args.extend(run_flags.iter().map(|f| f.to_string()));
+        args.push("-w".to_string());
+        args.push(config.working_dir.clone());
 
         for vol in &config.volumes {
             if !self.supports_read_only_volumes && vol.read_only {
@@ -148,6 +398,8 @@ impl RuntimeBase {
             }
             let mount = if vol.read_only && self.supports_read_only_volumes {
                 format!("{}:{}:ro", vol.host_path, vol.container_path)
+            } else if let Some(label) = volume_label.filter(|_| !vol.read_only) {
+                format!("{}:{}:{}", vol.host_path, vol.container_path, label)
             } else {
                 format!("{}:{}", vol.host_path, vol.container_path)
             };
@@ -178,6 +430,11 @@ impl RuntimeBase {
             args.push(port.clone());
         }
 
+        if let Some(network) = &config.network {
+            args.push("--network".to_string());
+            args.push(network.clone());
+        }
+
         if let Some(cpu) = &config.cpu_limit {
             args.push("--cpus".to_string());
             args.push(cpu.clone());
@@ -197,7 +454,16 @@ impl RuntimeBase {
 
     /// Run the container creation command (after existence has already been checked by the caller).
     pub fn run_create(&self, name: &str, image: &str, config: &ContainerConfig) -> Result<String> {
-        let args = self.build_create_args(name, image, config);
+        self.run_create_args(self.build_create_args(name, image, config), image, config)
+    }
+
+    /// Run the container creation command with prebuilt `args`.
+    pub fn run_create_args(
+        &self,
+        args: Vec<String>,
+        image: &str,
+        config: &ContainerConfig,
+    ) -> Result<String> {
         tracing::debug!("{} create args: {}", self.name, args.join(" "));
 
         let mut cmd = self.command();
@@ -217,7 +483,9 @@ impl RuntimeBase {
             if stderr.contains("permission denied") {
                 return Err(DockerError::PermissionDenied);
             }
-            if stderr.contains("Cannot connect to the Docker daemon") {
+            if stderr.contains("Cannot connect to the Docker daemon")
+                || stderr.contains("Cannot connect to Podman")
+            {
                 return Err(DockerError::DaemonNotRunning);
             }
             if stderr.contains("No such image") || stderr.contains("Unable to find image") {
@@ -296,6 +564,140 @@ impl RuntimeBase {
 
         Ok(output)
     }
+
+    /// CPU and memory use of running containers whose name starts with
+    /// `prefix`, from `stats`. Docker and Podman print the same columns.
+    pub fn batch_usage(&self, prefix: &str) -> HashMap<String, ContainerUsage> {
+        // `stats` has no --filter; it lists every running container
+        let output = self
+            .command()
+            .args([
+                "stats",
+                "--no-stream",
+                "--format",
+                "{{.Name}}\t{{.CPUPerc}}\t{{.MemUsage}}",
+            ])
+            .output();
+
+        let output = match output {
+            Ok(o) if o.status.success() => o,
+            _ => return HashMap::new(),
+        };
+
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let name = parts.next()?.trim();
+                if !name.starts_with(prefix) {
+                    return None;
+                }
+                let cpu_percent = parts.next()?.trim().trim_end_matches('%').parse().ok()?;
+                // "12.5MiB / 7.66GiB": usage / limit
+                let memory = parts.next()?.split('/').next()?;
+                Some((
+                    name.to_string(),
+                    ContainerUsage {
+                        cpu_percent,
+                        memory_bytes: parse_size(memory)?,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+/// The IPv4 gateway in `network inspect` output. Docker lists subnets under
+/// `IPAM.Config`, Podman under `subnets`.
+/// Run `cmd`, passing each line it prints to `on_output` as it arrives.
+/// Returns whether it succeeded and its last lines of output.
+pub(crate) fn run_streamed(
+    cmd: &mut Command,
+    on_output: &mut dyn FnMut(&str),
+) -> std::io::Result<(bool, Vec<String>)> {
+    let mut child = cmd.stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+    // Read both pipes and hand lines over as they arrive
+    let (line_tx, line_rx) = mpsc::channel::<String>();
+    let readers: Vec<_> = [
+        child
+            .stdout
+            .take()
+            .map(|o| Box::new(o) as Box<dyn Read + Send>),
+        child
+            .stderr
+            .take()
+            .map(|e| Box::new(e) as Box<dyn Read + Send>),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|pipe| {
+        let line_tx = line_tx.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(pipe).lines().map_while(std::io::Result::ok) {
+                let _ = line_tx.send(line);
+            }
+        })
+    })
+    .collect();
+    drop(line_tx);
+
+    // Output is long; on failure the cause is at the end
+    let mut tail: VecDeque<String> = VecDeque::with_capacity(ERROR_TAIL_LINES);
+    for line in line_rx {
+        on_output(&line);
+        if tail.len() == ERROR_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    }
+    for reader in readers {
+        let _ = reader.join();
+    }
+
+    let success = child.wait()?.success();
+    Ok((success, tail.into()))
+}
+
+fn parse_network_gateway(stdout: &[u8]) -> Option<String> {
+    let networks: serde_json::Value = serde_json::from_slice(stdout).ok()?;
+    let network = networks.as_array()?.first()?;
+    let subnets = network
+        .pointer("/IPAM/Config")
+        .or_else(|| network.get("subnets"))?
+        .as_array()?;
+    subnets
+        .iter()
+        .filter_map(|subnet| {
+            subnet
+                .get("Gateway")
+                .or_else(|| subnet.get("gateway"))?
+                .as_str()
+        })
+        .find(|gateway| gateway.parse::<std::net::Ipv4Addr>().is_ok())
+        .map(String::from)
+}
+
+/// Parse a size as printed by `docker stats`, e.g. `512KiB`, `1.2GB` or `0B`
+fn parse_size(s: &str) -> Option<u64> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let multiplier: f64 = match unit.trim() {
+        "" | "B" => 1.0,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return None,
+    };
+    Some((number.parse::<f64>().ok()? * multiplier) as u64)
 }
 
 #[cfg(test)]
@@ -303,6 +705,15 @@ mod tests {
     use super::*;
     use crate::containers::container_interface::{EnvEntry, VolumeMount};
 
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("512KiB"), Some(512 * 1024));
+        assert_eq!(parse_size("1.5MiB "), Some(1_572_864));
+        assert_eq!(parse_size("2GB"), Some(2_000_000_000));
+        assert_eq!(parse_size("12 parsecs"), None);
+    }
+
     #[test]
     fn test_build_create_args_read_only_supported() {
         let base = RuntimeBase::DOCKER;
@@ -318,6 +729,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = base.build_create_args("test-container", "alpine:latest", &config);
@@ -341,6 +753,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = base.build_create_args("test-container", "alpine:latest", &config);
@@ -389,6 +802,7 @@ mod tests {
             cpu_limit: Some("2".to_string()),
             memory_limit: Some("4g".to_string()),
             port_mappings: vec!["3000:3000".to_string()],
+            network: None,
         };
 
         let args = base.build_create_args("test", "ubuntu:latest", &config);
@@ -427,6 +841,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = base.build_create_args("test", "alpine:latest", &config);
@@ -456,6 +871,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec![],
+            network: None,
         };
 
         let args = base.build_create_args("test", "alpine:latest", &config);
@@ -478,6 +894,7 @@ mod tests {
             cpu_limit: None,
             memory_limit: None,
             port_mappings: vec!["3000:3000".to_string(), "5432:5432".to_string()],
+            network: None,
         };
 
         let args = base.build_create_args("test", "alpine:latest", &config);
@@ -493,4 +910,41 @@ mod tests {
         assert_eq!(args[p_indices[0] + 1], "3000:3000");
         assert_eq!(args[p_indices[1] + 1], "5432:5432");
     }
+
+    #[test]
+    fn test_build_create_args_network() {
+        let base = RuntimeBase::DOCKER;
+        let config = ContainerConfig {
+            working_dir: "/workspace".to_string(),
+            volumes: vec![],
+            anonymous_volumes: vec![],
+            environment: vec![],
+            cpu_limit: None,
+            memory_limit: None,
+            port_mappings: vec![],
+            network: Some("aoe-egress".to_string()),
+        };
+
+        let args = base.build_create_args("test", "alpine:latest", &config);
+        let idx = args.iter().position(|a| a == "--network").unwrap();
+        assert_eq!(args[idx + 1], "aoe-egress");
+    }
+
+    #[test]
+    fn test_parse_network_gateway() {
+        let docker = br#"[{"Name": "aoe-egress", "Internal": true,
+            "IPAM": {"Config": [{"Subnet": "fd00::/64", "Gateway": "fd00::1"},
+                                {"Subnet": "172.19.0.0/16", "Gateway": "172.19.0.1"}]}}]"#;
+        assert_eq!(parse_network_gateway(docker).as_deref(), Some("172.19.0.1"));
+
+        let podman = br#"[{"name": "aoe-egress", "internal": true,
+            "subnets": [{"subnet": "10.89.1.0/24", "gateway": "10.89.1.1"}]}]"#;
+        assert_eq!(parse_network_gateway(podman).as_deref(), Some("10.89.1.1"));
+
+        assert_eq!(
+            parse_network_gateway(br#"[{"IPAM": {"Config": []}}]"#),
+            None
+        );
+        assert_eq!(parse_network_gateway(b""), None);
+    }
 }
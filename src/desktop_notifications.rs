@@ -0,0 +1,349 @@
+//! Desktop notifications from the TUI
+//!
+//! When enabled, the TUI sends a freedesktop notification
+//! (`org.freedesktop.Notifications` on the D-Bus session bus) as a session
+//! starts waiting, finishes or hits an error, without needing `aoe serve`.
+//! Sessions' `notify_on_*` overrides apply as they do for Web Push. The
+//! auto-responder also sends one when a `notify` rule leaves a prompt to the
+//! user.
+//!
+//! Clicking a notification (or its "Open session" action) focuses the
+//! session: a tmux client that is already attached is switched to it,
+//! otherwise the TUI attaches on its next tick. Other platforms have no
+//! session bus, so notifications are only sent on Linux.
+
+use serde::{Deserialize, Serialize};
+
+use crate::session::{Instance, Status};
+
+/// Desktop notification configuration (`[desktop_notifications]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DesktopNotificationsConfig {
+    /// Send desktop notifications from the TUI. Off by default.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Notify when a session starts waiting for input
+    #[serde(default = "default_true")]
+    pub on_waiting: bool,
+
+    /// Notify when a session finishes and goes idle. Off by default since
+    /// it fires on every completed turn.
+    #[serde(default)]
+    pub on_idle: bool,
+
+    /// Notify when a session errors or stalls
+    #[serde(default = "default_true")]
+    pub on_error: bool,
+}
+
+impl Default for DesktopNotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            on_waiting: true,
+            on_idle: false,
+            on_error: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Event {
+    Waiting,
+    Idle,
+    Error,
+    Stalled,
+}
+
+impl Event {
+    /// The event a session entering `status` would notify about, if the
+    /// config and the session's overrides want it.
+    fn wanted(
+        config: &DesktopNotificationsConfig,
+        inst: &Instance,
+        status: Status,
+    ) -> Option<Self> {
+        let (event, default, overridden) = match status {
+            Status::Waiting => (Self::Waiting, config.on_waiting, inst.notify_on_waiting),
+            Status::Idle => (Self::Idle, config.on_idle, inst.notify_on_idle),
+            Status::Error => (Self::Error, config.on_error, inst.notify_on_error),
+            Status::Stalled => (Self::Stalled, config.on_error, inst.notify_on_error),
+            _ => return None,
+        };
+        overridden.unwrap_or(default).then_some(event)
+    }
+
+    fn summary(self, title: &str) -> String {
+        match self {
+            Self::Waiting => format!("{} is waiting for input", title),
+            Self::Idle => format!("{} finished", title),
+            Self::Error => format!("{} hit an error", title),
+            Self::Stalled => format!("{} stalled with no output", title),
+        }
+    }
+
+    fn critical(self) -> bool {
+        matches!(self, Self::Error | Self::Stalled)
+    }
+}
+
+/// Report a status transition the TUI applied.
+pub fn status_changed(
+    inst: &Instance,
+    old: Status,
+    new: Status,
+    config: &DesktopNotificationsConfig,
+) {
+    if !config.enabled || old == new {
+        return;
+    }
+    if let Some(event) = Event::wanted(config, inst, new) {
+        platform::show(Notification {
+            session_id: inst.id.clone(),
+            tmux_name: crate::tmux::Session::generate_name(&inst.id, &inst.title),
+            summary: event.summary(&inst.title),
+            body: inst.project_path.clone(),
+            critical: event.critical(),
+        });
+    }
+}
+
+/// Report a prompt an auto-respond `notify` rule left for the user. The rule
+/// asked for it, so only `enabled` is checked.
+pub fn prompt_held(inst: &Instance, rule: &str, config: &DesktopNotificationsConfig) {
+    if !config.enabled {
+        return;
+    }
+    platform::show(Notification {
+        session_id: inst.id.clone(),
+        tmux_name: crate::tmux::Session::generate_name(&inst.id, &inst.title),
+        summary: format!("{} needs you ({})", inst.title, rule),
+        body: inst.project_path.clone(),
+        critical: true,
+    });
+}
+
+/// A session the user asked to focus from a notification, for the TUI to
+/// attach to. Taken at most once.
+pub fn take_focus_request() -> Option<String> {
+    platform::take_focus_request()
+}
+
+struct Notification {
+    session_id: String,
+    tmux_name: String,
+    summary: String,
+    body: String,
+    critical: bool,
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::collections::HashMap;
+    use std::process::Command;
+    use std::sync::{Arc, Mutex, OnceLock};
+
+    use anyhow::Result;
+    use zbus::blocking::{Connection, Proxy};
+    use zbus::zvariant::Value;
+
+    use super::Notification;
+
+    const DESTINATION: &str = "org.freedesktop.Notifications";
+    const PATH: &str = "/org/freedesktop/Notifications";
+    const INTERFACE: &str = "org.freedesktop.Notifications";
+    const APP_NAME: &str = "Agent of Empires";
+    /// Action key for the button; `default` is a click on the notification
+    const FOCUS_ACTION: &str = "focus";
+
+    struct Notifier {
+        proxy: Proxy<'static>,
+        /// Latest notification id per session, with the session's tmux name.
+        /// A new notification for a session replaces the previous one.
+        shown: Mutex<HashMap<String, (u32, String)>>,
+    }
+
+    static NOTIFIER: OnceLock<Option<Arc<Notifier>>> = OnceLock::new();
+    static FOCUS_REQUEST: Mutex<Option<String>> = Mutex::new(None);
+
+    pub(super) fn show(notification: Notification) {
+        let notifier = NOTIFIER.get_or_init(|| match connect() {
+            Ok(notifier) => Some(notifier),
+            Err(e) => {
+                tracing::warn!("desktop notifications: no session bus: {}", e);
+                None
+            }
+        });
+        let Some(notifier) = notifier.clone() else {
+            return;
+        };
+        // D-Bus calls can stall; keep them off the TUI thread
+        std::thread::spawn(move || {
+            if let Err(e) = notifier.notify(notification) {
+                tracing::warn!("desktop notifications: {}", e);
+            }
+        });
+    }
+
+    pub(super) fn take_focus_request() -> Option<String> {
+        FOCUS_REQUEST.lock().ok()?.take()
+    }
+
+    fn connect() -> Result<Arc<Notifier>> {
+        let conn = Connection::session()?;
+        let proxy = Proxy::new(&conn, DESTINATION, PATH, INTERFACE)?;
+        let actions = proxy.receive_signal("ActionInvoked")?;
+        let notifier = Arc::new(Notifier {
+            proxy,
+            shown: Mutex::new(HashMap::new()),
+        });
+
+        let listener = notifier.clone();
+        std::thread::spawn(move || {
+            for message in actions {
+                let Ok((id, action)) = message.body().deserialize::<(u32, String)>() else {
+                    continue;
+                };
+                if action == FOCUS_ACTION || action == "default" {
+                    listener.focus(id);
+                }
+            }
+        });
+        Ok(notifier)
+    }
+
+    impl Notifier {
+        fn notify(&self, notification: Notification) -> Result<()> {
+            let replaces = self
+                .shown
+                .lock()
+                .ok()
+                .and_then(|shown| shown.get(&notification.session_id).map(|(id, _)| *id))
+                .unwrap_or(0);
+            let actions = ["default", "Open session", FOCUS_ACTION, "Open session"];
+            let mut hints: HashMap<&str, Value> = HashMap::new();
+            // 1 = normal, 2 = critical (stays until dismissed)
+            hints.insert(
+                "urgency",
+                Value::U8(if notification.critical { 2 } else { 1 }),
+            );
+
+            let id: u32 = self.proxy.call(
+                "Notify",
+                &(
+                    APP_NAME,
+                    replaces,
+                    "",
+                    notification.summary.as_str(),
+                    notification.body.as_str(),
+                    &actions[..],
+                    hints,
+                    -1i32,
+                ),
+            )?;
+            if let Ok(mut shown) = self.shown.lock() {
+                shown.insert(notification.session_id, (id, notification.tmux_name));
+            }
+            Ok(())
+        }
+
+        /// Switch an attached tmux client to the session, or, when no client
+        /// is attached, leave it for the TUI to attach.
+        fn focus(&self, notification_id: u32) {
+            let Some((session_id, tmux_name)) = self.shown.lock().ok().and_then(|shown| {
+                shown
+                    .iter()
+                    .find(|(_, (id, _))| *id == notification_id)
+                    .map(|(session, (_, name))| (session.clone(), name.clone()))
+            }) else {
+                return;
+            };
+
+            let has_client = Command::new("tmux")
+                .args(["list-clients", "-F", "#{client_name}"])
+                .output()
+                .is_ok_and(|out| out.status.success() && !out.stdout.trim_ascii().is_empty());
+            if has_client {
+                let switched = Command::new("tmux")
+                    .args(["switch-client", "-t", &tmux_name])
+                    .status()
+                    .is_ok_and(|s| s.success());
+                if switched {
+                    return;
+                }
+            }
+            if let Ok(mut request) = FOCUS_REQUEST.lock() {
+                *request = Some(session_id);
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod platform {
+    use super::Notification;
+
+    pub(super) fn show(_notification: Notification) {}
+
+    pub(super) fn take_focus_request() -> Option<String> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() {
+        let config: DesktopNotificationsConfig = toml::from_str("enabled = true").unwrap();
+        assert!(config.enabled);
+        assert!(config.on_waiting);
+        assert!(!config.on_idle);
+        assert!(config.on_error);
+        assert!(!DesktopNotificationsConfig::default().enabled);
+    }
+
+    #[test]
+    fn test_session_overrides_replace_defaults() {
+        let config = DesktopNotificationsConfig::default();
+        let mut inst = Instance::new("fix-login", "/src/app");
+
+        assert_eq!(
+            Event::wanted(&config, &inst, Status::Waiting),
+            Some(Event::Waiting)
+        );
+        assert_eq!(Event::wanted(&config, &inst, Status::Idle), None);
+        assert_eq!(
+            Event::wanted(&config, &inst, Status::Stalled),
+            Some(Event::Stalled)
+        );
+        assert_eq!(Event::wanted(&config, &inst, Status::Running), None);
+
+        inst.notify_on_waiting = Some(false);
+        inst.notify_on_idle = Some(true);
+        inst.notify_on_error = Some(false);
+        assert_eq!(Event::wanted(&config, &inst, Status::Waiting), None);
+        assert_eq!(
+            Event::wanted(&config, &inst, Status::Idle),
+            Some(Event::Idle)
+        );
+        assert_eq!(Event::wanted(&config, &inst, Status::Error), None);
+        assert_eq!(Event::wanted(&config, &inst, Status::Stalled), None);
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            Event::Waiting.summary("fix-login"),
+            "fix-login is waiting for input"
+        );
+        assert!(Event::Error.critical());
+        assert!(!Event::Idle.critical());
+    }
+}
@@ -18,6 +18,20 @@ pub fn sanitize_branch_name(branch: &str) -> String {
     )
 }
 
+/// Branch name for one member of a fan-out: `<base>-<suffix>`, with `-2`,
+/// `-3`, ... appended until it doesn't collide with a name in `taken`.
+pub fn suffixed_branch_name(base: &str, suffix: &str, taken: &[String]) -> String {
+    let suffix = sanitize_branch_name(suffix).replace(char::is_whitespace, "-");
+    let candidate = format!("{}-{}", base, suffix);
+    if !taken.contains(&candidate) {
+        return candidate;
+    }
+    (2..)
+        .map(|n| format!("{}-{}", candidate, n))
+        .find(|name| !taken.contains(name))
+        .expect("unbounded range always yields a free name")
+}
+
 pub fn resolve_template(template: &str, vars: &TemplateVars) -> Result<PathBuf> {
     let sanitized_branch = sanitize_branch_name(&vars.branch);
 
@@ -66,6 +80,32 @@ mod tests {
         assert!(!sanitized.contains("#"));
     }
 
+    #[test]
+    fn test_suffixed_branch_name_appends_suffix() {
+        assert_eq!(
+            suffixed_branch_name("fix-login", "claude", &[]),
+            "fix-login-claude"
+        );
+        assert_eq!(suffixed_branch_name("feat/x", "2", &[]), "feat/x-2");
+    }
+
+    #[test]
+    fn test_suffixed_branch_name_sanitizes_suffix() {
+        assert_eq!(
+            suffixed_branch_name("task", "my agent/v2", &[]),
+            "task-my-agent-v2"
+        );
+    }
+
+    #[test]
+    fn test_suffixed_branch_name_avoids_taken_names() {
+        let taken = vec!["task-claude".to_string(), "task-claude-2".to_string()];
+        assert_eq!(
+            suffixed_branch_name("task", "claude", &taken),
+            "task-claude-3"
+        );
+    }
+
     #[test]
     fn test_resolve_template_with_all_variables() {
         let vars = TemplateVars {
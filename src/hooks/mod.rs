@@ -13,7 +13,9 @@ use std::path::Path;
 use anyhow::Result;
 use serde_json::Value;
 
-pub use status_file::{cleanup_hook_status_dir, hook_status_dir, read_hook_status};
+pub use status_file::{
+    claim_event, claim_marker_path, cleanup_hook_status_dir, hook_status_dir, read_hook_status,
+};
 
 /// Base directory for all AoE hook status files.
 pub(crate) const HOOK_STATUS_BASE: &str = "/tmp/aoe-hooks";
@@ -30,6 +32,15 @@ fn hook_command(status: &str) -> String {
     )
 }
 
+/// Like `hook_command`, but first saves the JSON the agent passes on stdin to
+/// `<event>.json` next to the status file, for auto-respond payload rules.
+fn hook_command_with_payload(event: &str, status: &str) -> String {
+    format!(
+        "sh -c '[ -n \"$AOE_INSTANCE_ID\" ] || exit 0; mkdir -p /tmp/aoe-hooks/$AOE_INSTANCE_ID || exit 0; cat > /tmp/aoe-hooks/$AOE_INSTANCE_ID/{}.json; printf {} > /tmp/aoe-hooks/$AOE_INSTANCE_ID/status'",
+        event, status
+    )
+}
+
 fn is_aoe_hook_command(cmd: &str) -> bool {
     cmd.contains(AOE_HOOK_MARKER)
 }
@@ -52,7 +63,7 @@ fn build_aoe_hooks(events: &[crate::agents::HookEvent]) -> Value {
             "hooks".to_string(),
             Value::Array(vec![serde_json::json!({
                 "type": "command",
-                "command": hook_command(status)
+                "command": hook_command_with_payload(event.name, status)
             })]),
         );
         hooks_obj.insert(
@@ -463,6 +474,19 @@ mod tests {
         assert!(cmd.contains("printf running"));
     }
 
+    #[test]
+    fn test_hook_command_with_payload_saves_stdin_before_status() {
+        let cmd = hook_command_with_payload("PreToolUse", "running");
+        assert!(cmd.contains(AOE_HOOK_MARKER));
+        let save = cmd.find("cat > /tmp/aoe-hooks/$AOE_INSTANCE_ID/PreToolUse.json");
+        let status = cmd.find("printf running");
+        assert!(
+            save.is_some() && status.is_some() && save < status,
+            "{}",
+            cmd
+        );
+    }
+
     #[test]
     fn test_hook_command_contains_instance_id_guard() {
         let cmd = hook_command("idle");
@@ -3,7 +3,12 @@
 //! Agent hooks write `running`, `waiting`, or `idle` to a well-known
 //! file path so AoE can detect agent status without parsing tmux pane content.
 
-use std::path::PathBuf;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use fs2::FileExt;
 
 use crate::session::Status;
 
@@ -35,6 +40,47 @@ pub fn read_hook_status(instance_id: &str) -> Option<Status> {
     }
 }
 
+/// Path of the claim marker `name` next to the instance's hook status file.
+pub fn claim_marker_path(instance_id: &str, name: &str) -> Result<PathBuf> {
+    let dir = hook_status_dir(instance_id);
+    fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+/// Claim an event in the marker file at `path`. The TUI and `aoe serve` may
+/// both see the same event, and only the first to claim it acts on it. The
+/// marker holds the last claim; `same_event` tells whether that was for
+/// this event. Returns `false` if it was, else records `claim`.
+pub fn claim_event(
+    path: &Path,
+    claim: &str,
+    same_event: impl FnOnce(&str) -> bool,
+) -> Result<bool> {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    FileExt::lock_exclusive(&file)?;
+
+    let result = (|| -> Result<bool> {
+        let mut last = String::new();
+        file.read_to_string(&mut last)?;
+        let last = last.trim();
+        if !last.is_empty() && same_event(last) {
+            return Ok(false);
+        }
+        file.seek(SeekFrom::Start(0))?;
+        file.set_len(0)?;
+        file.write_all(claim.as_bytes())?;
+        Ok(true)
+    })();
+
+    let _ = FileExt::unlock(&file);
+    result
+}
+
 /// Remove the hook status directory for a given instance (cleanup on stop/delete).
 pub fn cleanup_hook_status_dir(instance_id: &str) {
     let dir = hook_status_dir(instance_id);
@@ -115,6 +161,17 @@ mod tests {
         fs::remove_dir_all(dir).ok();
     }
 
+    #[test]
+    fn test_claim_event_replaces_other_claims() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("marker");
+        assert!(claim_event(&path, "a", |last| last == "a").unwrap());
+        assert!(!claim_event(&path, "a", |last| last == "a").unwrap());
+        assert!(claim_event(&path, "b", |last| last == "b").unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "b");
+        assert!(claim_event(&path, "a", |last| last == "a").unwrap());
+    }
+
     #[test]
     fn test_cleanup_existing_dir() {
         let id = "test_cleanup_existing";
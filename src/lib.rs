@@ -3,6 +3,7 @@
 pub mod agents;
 pub mod cli;
 pub mod containers;
+pub mod desktop_notifications;
 pub mod git;
 pub mod hooks;
 pub mod migrations;
@@ -15,3 +16,5 @@ pub mod terminal;
 pub mod tmux;
 pub mod tui;
 pub mod update;
+pub mod usage;
+pub mod webhooks;
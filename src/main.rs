@@ -45,6 +45,10 @@ async fn main() -> Result<()> {
             use cli::tmux::TmuxCommands;
             return match command {
                 TmuxCommands::Status(args) => cli::tmux::run_status(args),
+                TmuxCommands::Record(args) => cli::tmux::run_record(args),
+                TmuxCommands::DeliverPrompt(args) => cli::tmux::run_deliver_prompt(args),
+                TmuxCommands::DispatchQueue(args) => cli::tmux::run_dispatch_queue(args),
+                TmuxCommands::AutoRespond(args) => cli::tmux::run_auto_respond(args),
             };
         }
         Some(Commands::Sounds { command }) => return cli::sounds::run(command).await,
@@ -73,15 +77,17 @@ async fn main() -> Result<()> {
     }
 
     match cli.command {
-        Some(Commands::Add(args)) => cli::add::run(&profile, args).await,
+        Some(Commands::Add(args)) => cli::add::run(&profile, *args).await,
         Some(Commands::List(args)) => cli::list::run(&profile, args).await,
         Some(Commands::Remove(args)) => cli::remove::run(&profile, args).await,
         Some(Commands::Send(args)) => cli::send::run(&profile, args).await,
         Some(Commands::Status(args)) => cli::status::run(&profile, args).await,
+        Some(Commands::Wait(args)) => cli::wait::run(&profile, args).await,
         Some(Commands::Session { command }) => cli::session::run(&profile, command).await,
         Some(Commands::Group { command }) => cli::group::run(&profile, command).await,
         Some(Commands::Profile { command }) => cli::profile::run(command).await,
         Some(Commands::Worktree { command }) => cli::worktree::run(&profile, command).await,
+        Some(Commands::Sandbox { command }) => cli::sandbox::run(&profile, command).await,
         #[cfg(feature = "serve")]
         Some(Commands::Serve(args)) => cli::serve::run(&profile, args).await,
         None => tui::run(&profile, debug_log_warning).await,
@@ -1,12 +1,15 @@
 //! Linux-specific process utilities
 
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
 use nix::sys::signal::{kill, Signal};
-use nix::unistd::Pid;
+use nix::unistd::{sysconf, Pid, SysconfVar};
 use tracing::debug;
 
+use super::ProcessStat;
+
 /// Kill a process and all its descendants
 /// Uses SIGTERM first, then SIGKILL after a short delay for stragglers
 pub fn kill_process_tree(pid: u32) {
@@ -131,6 +134,50 @@ fn find_process_in_group(pgrp: u32) -> Option<u32> {
     None
 }
 
+/// Read the parent, CPU time and RSS of every process from /proc/[pid]/stat
+pub fn process_table() -> HashMap<u32, ProcessStat> {
+    let mut table = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return table;
+    };
+    let ticks_per_sec = sysconf(SysconfVar::CLK_TCK)
+        .ok()
+        .flatten()
+        .filter(|&t| t > 0)
+        .unwrap_or(100) as f64;
+    let page_size = sysconf(SysconfVar::PAGE_SIZE)
+        .ok()
+        .flatten()
+        .filter(|&p| p > 0)
+        .unwrap_or(4096) as u64;
+
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(entry.path().join("stat")) else {
+            continue;
+        };
+        if let Some(stat) = parse_process_stat(&content, ticks_per_sec, page_size) {
+            table.insert(pid, stat);
+        }
+    }
+    table
+}
+
+fn parse_process_stat(content: &str, ticks_per_sec: f64, page_size: u64) -> Option<ProcessStat> {
+    // ppid(3), utime(13), stime(14), rss in pages(23)
+    let ppid = parse_stat_field(content, 3)?;
+    let utime = parse_stat_field(content, 13)?;
+    let stime = parse_stat_field(content, 14)?;
+    let rss_pages = parse_stat_field(content, 23)?;
+    Some(ProcessStat {
+        ppid: ppid.max(0) as u32,
+        cpu_secs: (utime + stime).max(0) as f64 / ticks_per_sec,
+        rss_bytes: rss_pages.max(0) as u64 * page_size,
+    })
+}
+
 /// Parse a specific field from /proc/[pid]/stat
 /// Fields are space-separated but comm (field 2) can contain spaces and is in parens
 fn parse_stat_field(content: &str, field_idx: usize) -> Option<i64> {
@@ -159,4 +206,14 @@ mod tests {
         assert_eq!(parse_stat_field(stat, 4), Some(1234)); // pgrp
         assert_eq!(parse_stat_field(stat, 7), Some(1234)); // tpgid
     }
+
+    #[test]
+    fn test_parse_process_stat() {
+        let stat = "4242 (cargo build) R 4200 4242 4200 34816 4242 4194304 5000 0 0 0 \
+                    250 50 0 0 20 0 4 0 123456 104857600 2560 18446744073709551615";
+        let parsed = parse_process_stat(stat, 100.0, 4096).unwrap();
+        assert_eq!(parsed.ppid, 4200);
+        assert_eq!(parsed.cpu_secs, 3.0);
+        assert_eq!(parsed.rss_bytes, 2560 * 4096);
+    }
 }
@@ -8,6 +8,8 @@ use nix::sys::signal::{kill, Signal};
 use nix::unistd::Pid;
 use tracing::debug;
 
+use super::ProcessStat;
+
 /// Kill a process and all its descendants
 /// Uses SIGTERM first, then SIGKILL after a short delay for stragglers
 pub fn kill_process_tree(pid: u32) {
@@ -65,6 +67,60 @@ fn build_children_map() -> HashMap<u32, Vec<u32>> {
     children_map
 }
 
+/// Read the parent, CPU time and RSS of every process from `ps`
+pub fn process_table() -> HashMap<u32, ProcessStat> {
+    let mut table = HashMap::new();
+
+    let Ok(output) = Command::new("ps")
+        .args(["-o", "pid=,ppid=,time=,rss=", "-A"])
+        .output()
+    else {
+        return table;
+    };
+
+    if !output.status.success() {
+        return table;
+    }
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 4 {
+            continue;
+        }
+        let (Ok(pid), Ok(ppid), Some(cpu_secs), Ok(rss_kib)) = (
+            parts[0].parse::<u32>(),
+            parts[1].parse::<u32>(),
+            parse_cpu_time(parts[2]),
+            parts[3].parse::<u64>(),
+        ) else {
+            continue;
+        };
+        table.insert(
+            pid,
+            ProcessStat {
+                ppid,
+                cpu_secs,
+                rss_bytes: rss_kib * 1024,
+            },
+        );
+    }
+
+    table
+}
+
+/// Parse `ps` CPU time: `[[dd-]hh:]mm:ss.ss`
+fn parse_cpu_time(s: &str) -> Option<f64> {
+    let (days, rest) = match s.split_once('-') {
+        Some((days, rest)) => (days.parse::<f64>().ok()?, rest),
+        None => (0.0, s),
+    };
+    let mut secs = 0.0;
+    for part in rest.split(':') {
+        secs = secs * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(days * 86_400.0 + secs)
+}
+
 /// Recursively collect all descendant PIDs using the pre-built children map
 fn collect_descendants_from_map(
     pid: u32,
@@ -148,6 +204,15 @@ fn find_process_in_group(pgrp: u32) -> Option<u32> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_cpu_time() {
+        assert_eq!(parse_cpu_time("0:01.50"), Some(1.5));
+        assert_eq!(parse_cpu_time("12:00.00"), Some(720.0));
+        assert_eq!(parse_cpu_time("1:02:03.00"), Some(3723.0));
+        assert_eq!(parse_cpu_time("2-00:00:01.00"), Some(172_801.0));
+        assert_eq!(parse_cpu_time("n/a"), None);
+    }
+
     #[test]
     fn test_collect_descendants_from_map_empty() {
         let children_map = HashMap::new();
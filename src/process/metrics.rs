@@ -0,0 +1,258 @@
+//! CPU and memory use per session
+//!
+//! Host sessions are measured over the whole process tree under the agent's
+//! tmux pane, so builds and test runs the agent starts count toward its
+//! session. CPU% is the change in CPU time between two samples. Sandboxed
+//! sessions are measured per container with `docker stats`, since the pane
+//! only runs the `docker exec` client.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::ProcessStat;
+use crate::session::{Instance, Status};
+
+/// Samples kept per session for the sparklines
+pub const HISTORY_LEN: usize = 60;
+
+/// Process metrics configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Sample CPU and memory of running sessions
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Show CPU and memory columns in the TUI session list
+    #[serde(default)]
+    pub list_columns: bool,
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            list_columns: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// One CPU and memory reading
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSample {
+    /// Percent of one core, so a parallel build can exceed 100
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+}
+
+/// Latest reading and recent history of one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionMetrics {
+    #[serde(flatten)]
+    pub current: MetricsSample,
+    /// Up to `HISTORY_LEN` readings, oldest first, ending with `current`
+    pub history: Vec<MetricsSample>,
+}
+
+impl SessionMetrics {
+    /// Compact label for the session list, e.g. ` 12% 340M`
+    pub fn short_label(&self) -> String {
+        format!(
+            "{:>3.0}% {:>4}",
+            self.current.cpu_percent,
+            format_bytes(self.current.rss_bytes)
+        )
+    }
+}
+
+/// `512K`, `340M`, `1.2G`
+pub fn format_bytes(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b < KIB * KIB {
+        format!("{:.0}K", b / KIB)
+    } else if b < KIB * KIB * KIB {
+        format!("{:.0}M", b / (KIB * KIB))
+    } else {
+        format!("{:.1}G", b / (KIB * KIB * KIB))
+    }
+}
+
+/// Samples sessions and keeps their rolling history between calls.
+#[derive(Default)]
+pub struct MetricsSampler {
+    /// Previous total CPU time of each host session's process tree
+    cpu_times: HashMap<String, (Instant, f64)>,
+    history: HashMap<String, VecDeque<MetricsSample>>,
+}
+
+impl MetricsSampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take one sample of every live session. Sessions that were not
+    /// sampled this time (stopped, deleted) lose their history.
+    pub fn collect(&mut self, instances: &[Instance]) -> HashMap<String, SessionMetrics> {
+        let live: Vec<&Instance> = instances
+            .iter()
+            .filter(|i| {
+                !matches!(
+                    i.status,
+                    Status::Stopped | Status::Deleting | Status::Creating
+                )
+            })
+            .collect();
+
+        let containers = if live.iter().any(|i| i.is_sandboxed()) {
+            crate::containers::batch_container_usage()
+        } else {
+            HashMap::new()
+        };
+        let table = if live.iter().any(|i| !i.is_sandboxed()) {
+            super::process_table()
+        } else {
+            HashMap::new()
+        };
+
+        let now = Instant::now();
+        let mut seen = HashSet::new();
+        for inst in live {
+            let sample = if let Some(sandbox) = inst.sandbox_info.as_ref().filter(|s| s.enabled) {
+                containers
+                    .get(&sandbox.container_name)
+                    .map(|usage| MetricsSample {
+                        cpu_percent: usage.cpu_percent,
+                        rss_bytes: usage.memory_bytes,
+                    })
+            } else {
+                let session_name = crate::tmux::Session::generate_name(&inst.id, &inst.title);
+                super::get_pane_pid(&session_name).and_then(|pid| {
+                    let total = tree_total(&table, pid)?;
+                    let previous = self
+                        .cpu_times
+                        .insert(inst.id.clone(), (now, total.cpu_secs));
+                    // The first sample only sets the baseline for CPU%
+                    let (then, cpu_secs) = previous?;
+                    Some(MetricsSample {
+                        cpu_percent: cpu_percent(cpu_secs, total.cpu_secs, now - then),
+                        rss_bytes: total.rss_bytes,
+                    })
+                })
+            };
+
+            let Some(sample) = sample else {
+                continue;
+            };
+            seen.insert(inst.id.clone());
+            let history = self.history.entry(inst.id.clone()).or_default();
+            history.push_back(sample);
+            while history.len() > HISTORY_LEN {
+                history.pop_front();
+            }
+        }
+
+        self.history.retain(|id, _| seen.contains(id));
+        self.cpu_times
+            .retain(|id, _| instances.iter().any(|i| &i.id == id));
+
+        self.history
+            .iter()
+            .map(|(id, history)| {
+                let metrics = SessionMetrics {
+                    current: history.back().copied().unwrap_or_default(),
+                    history: history.iter().copied().collect(),
+                };
+                (id.clone(), metrics)
+            })
+            .collect()
+    }
+}
+
+/// CPU time and memory summed over `pid` and all its descendants
+fn tree_total(table: &HashMap<u32, ProcessStat>, pid: u32) -> Option<ProcessStat> {
+    let root = table.get(&pid)?;
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (&child, stat) in table {
+        children.entry(stat.ppid).or_default().push(child);
+    }
+
+    let mut total = ProcessStat {
+        ppid: root.ppid,
+        ..Default::default()
+    };
+    let mut stack = vec![pid];
+    let mut visited = HashSet::new();
+    while let Some(p) = stack.pop() {
+        if !visited.insert(p) {
+            continue;
+        }
+        if let Some(stat) = table.get(&p) {
+            total.cpu_secs += stat.cpu_secs;
+            total.rss_bytes += stat.rss_bytes;
+        }
+        if let Some(kids) = children.get(&p) {
+            stack.extend(kids);
+        }
+    }
+    Some(total)
+}
+
+/// CPU% between two readings of a tree's CPU time. Processes that exit in
+/// between take their CPU time with them, so the total can shrink; that
+/// reads as idle rather than negative.
+fn cpu_percent(before: f64, after: f64, elapsed: std::time::Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs <= 0.0 {
+        return 0.0;
+    }
+    ((after - before).max(0.0) / secs) * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn stat(ppid: u32, cpu_secs: f64, rss_bytes: u64) -> ProcessStat {
+        ProcessStat {
+            ppid,
+            cpu_secs,
+            rss_bytes,
+        }
+    }
+
+    #[test]
+    fn test_tree_total_sums_descendants_only() {
+        let table = HashMap::from([
+            (100, stat(1, 1.0, 10)),
+            (101, stat(100, 2.0, 20)),
+            (102, stat(101, 3.0, 30)),
+            (200, stat(1, 50.0, 500)),
+        ]);
+        let total = tree_total(&table, 100).unwrap();
+        assert_eq!(total.cpu_secs, 6.0);
+        assert_eq!(total.rss_bytes, 60);
+        assert!(tree_total(&table, 999).is_none());
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        assert_eq!(cpu_percent(10.0, 15.0, Duration::from_secs(5)), 100.0);
+        assert_eq!(cpu_percent(10.0, 30.0, Duration::from_secs(5)), 400.0);
+        assert_eq!(cpu_percent(10.0, 4.0, Duration::from_secs(5)), 0.0);
+        assert_eq!(cpu_percent(1.0, 2.0, Duration::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512 * 1024), "512K");
+        assert_eq!(format_bytes(340 * 1024 * 1024), "340M");
+        assert_eq!(format_bytes(1288490188), "1.2G");
+    }
+}
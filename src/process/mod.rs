@@ -1,5 +1,6 @@
 //! Process utilities for tmux session management
 
+use std::collections::HashMap;
 use std::process::Command;
 
 #[cfg(target_os = "linux")]
@@ -8,6 +9,17 @@ mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
 
+pub mod metrics;
+
+/// Parent, CPU time and memory of one process
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProcessStat {
+    pub ppid: u32,
+    /// User plus system CPU time used so far, in seconds
+    pub cpu_secs: f64,
+    pub rss_bytes: u64,
+}
+
 /// Get the PID of the shell process running in a tmux pane
 pub fn get_pane_pid(session_name: &str) -> Option<u32> {
     // Use `^.0` to target the first window's first pane regardless of
@@ -66,3 +78,21 @@ pub fn kill_process_tree(pid: u32) {
         // No-op on unsupported platforms, fall back to tmux kill-session only
     }
 }
+
+/// Snapshot of every process on the machine, by PID
+pub fn process_table() -> HashMap<u32, ProcessStat> {
+    #[cfg(target_os = "linux")]
+    {
+        linux::process_table()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        macos::process_table()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        HashMap::new()
+    }
+}
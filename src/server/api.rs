@@ -10,7 +10,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 
-use crate::session::{Instance, Status, Storage};
+use crate::session::{message_queue, Instance, SessionTemplate, Status, Storage};
 
 use super::AppState;
 
@@ -64,6 +64,21 @@ pub struct SessionResponse {
     pub notify_on_waiting: Option<bool>,
     pub notify_on_idle: Option<bool>,
     pub notify_on_error: Option<bool>,
+    /// Per-session webhook overrides. None means each webhook's `events`
+    /// filter decides; Some(v) replaces it for that event type.
+    pub webhook_on_waiting: Option<bool>,
+    pub webhook_on_idle: Option<bool>,
+    pub webhook_on_error: Option<bool>,
+    /// Host ports reserved for the session (`session.ports`)
+    pub ports: Vec<crate::session::ports::PortAllocation>,
+    /// Token usage and estimated cost, read from the agent's transcripts
+    /// by `usage_poll_loop`. None until the first poll or when the agent's
+    /// transcripts can't be found.
+    pub usage: Option<crate::usage::SessionUsage>,
+    /// CPU and memory with recent history, sampled by `metrics_poll_loop`.
+    /// None until the session has been sampled twice (the first sample
+    /// only sets the CPU baseline) or while it isn't running.
+    pub metrics: Option<crate::process::metrics::SessionMetrics>,
 }
 
 #[derive(Serialize, Clone)]
@@ -107,6 +122,12 @@ impl From<&Instance> for SessionResponse {
             notify_on_waiting: inst.notify_on_waiting,
             notify_on_idle: inst.notify_on_idle,
             notify_on_error: inst.notify_on_error,
+            webhook_on_waiting: inst.webhook_on_waiting,
+            webhook_on_idle: inst.webhook_on_idle,
+            webhook_on_error: inst.webhook_on_error,
+            ports: inst.ports.clone(),
+            usage: None,
+            metrics: None,
         }
     }
 }
@@ -203,6 +224,15 @@ pub async fn list_sessions(State(state): State<Arc<AppState>>) -> Json<Vec<Sessi
         }
     }
 
+    {
+        let usage = state.usage.read().await;
+        let metrics = state.metrics.read().await;
+        for session in &mut sessions {
+            session.usage = usage.get(&session.id).cloned();
+            session.metrics = metrics.get(&session.id).cloned();
+        }
+    }
+
     Json(sessions)
 }
 
@@ -251,12 +281,14 @@ pub async fn rename_session(
     let profile = inst.source_profile.clone();
 
     if let Ok(storage) = Storage::new(&profile) {
-        let profile_instances: Vec<_> = instances
-            .iter()
-            .filter(|i| i.source_profile == profile)
-            .cloned()
-            .collect();
-        if let Err(e) = storage.save(&profile_instances) {
+        let result = storage.update(|stored| {
+            if let Some(s) = stored.iter_mut().find(|i| i.id == id) {
+                s.title = inst.title.clone();
+                s.worktree_info = inst.worktree_info.clone();
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
             tracing::error!("Failed to save after rename: {e}");
         }
     }
@@ -280,6 +312,12 @@ pub struct UpdateNotificationsBody {
     pub notify_on_idle: Tristate,
     #[serde(default, deserialize_with = "deserialize_tristate")]
     pub notify_on_error: Tristate,
+    #[serde(default, deserialize_with = "deserialize_tristate")]
+    pub webhook_on_waiting: Tristate,
+    #[serde(default, deserialize_with = "deserialize_tristate")]
+    pub webhook_on_idle: Tristate,
+    #[serde(default, deserialize_with = "deserialize_tristate")]
+    pub webhook_on_error: Tristate,
 }
 
 /// Three-state field representing JSON `undefined | null | true | false`:
@@ -333,17 +371,26 @@ pub async fn update_session_notifications(
     apply(&mut inst.notify_on_waiting, body.notify_on_waiting);
     apply(&mut inst.notify_on_idle, body.notify_on_idle);
     apply(&mut inst.notify_on_error, body.notify_on_error);
+    apply(&mut inst.webhook_on_waiting, body.webhook_on_waiting);
+    apply(&mut inst.webhook_on_idle, body.webhook_on_idle);
+    apply(&mut inst.webhook_on_error, body.webhook_on_error);
 
     let response = SessionResponse::from(&*inst);
     let profile = inst.source_profile.clone();
 
     if let Ok(storage) = Storage::new(&profile) {
-        let profile_instances: Vec<_> = instances
-            .iter()
-            .filter(|i| i.source_profile == profile)
-            .cloned()
-            .collect();
-        if let Err(e) = storage.save(&profile_instances) {
+        let result = storage.update(|stored| {
+            if let Some(s) = stored.iter_mut().find(|i| i.id == id) {
+                s.notify_on_waiting = inst.notify_on_waiting;
+                s.notify_on_idle = inst.notify_on_idle;
+                s.notify_on_error = inst.notify_on_error;
+                s.webhook_on_waiting = inst.webhook_on_waiting;
+                s.webhook_on_idle = inst.webhook_on_idle;
+                s.webhook_on_error = inst.webhook_on_error;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
             tracing::error!("Failed to save after notification update: {e}");
         }
     }
@@ -429,12 +476,11 @@ pub async fn delete_session(
             instances.retain(|i| i.id != id);
 
             if let Ok(storage) = Storage::new(&profile) {
-                let profile_instances: Vec<_> = instances
-                    .iter()
-                    .filter(|i| i.source_profile == profile)
-                    .cloned()
-                    .collect();
-                if let Err(e) = storage.save(&profile_instances) {
+                let result = storage.update(|stored| {
+                    stored.retain(|i| i.id != id);
+                    Ok(())
+                });
+                if let Err(e) = result {
                     tracing::error!("Failed to save after deletion: {e}");
                 }
             }
@@ -481,16 +527,19 @@ pub async fn delete_session(
 pub struct CreateSessionBody {
     pub title: Option<String>,
     pub path: String,
+    /// Agent to run. When empty, the template's agent or the configured
+    /// default is used.
+    #[serde(default)]
     pub tool: String,
     #[serde(default)]
     pub group: String,
     #[serde(default)]
-    pub yolo_mode: bool,
+    pub yolo_mode: Option<bool>,
     pub worktree_branch: Option<String>,
     #[serde(default)]
     pub create_new_branch: bool,
     #[serde(default)]
-    pub sandbox: bool,
+    pub sandbox: Option<bool>,
     #[serde(default)]
     pub extra_args: String,
     #[serde(default)]
@@ -504,6 +553,12 @@ pub struct CreateSessionBody {
     #[serde(default)]
     pub custom_instruction: Option<String>,
     pub profile: Option<String>,
+    /// Initial prompt, sent once the agent is first ready
+    #[serde(default)]
+    pub prompt: Option<String>,
+    /// Session template to start from; fields set in the request override it
+    #[serde(default)]
+    pub template: Option<String>,
 }
 
 pub async fn create_session(
@@ -577,7 +632,39 @@ pub async fn create_session(
         }
     }
 
-    let profile = body.profile.unwrap_or_else(|| state.profile.clone());
+    let profile = body
+        .profile
+        .clone()
+        .unwrap_or_else(|| state.profile.clone());
+
+    // The template and, if it adds any, the container environment it implies
+    let (template, template_env) = match body.template.as_deref() {
+        Some(name) => {
+            let resolved = crate::session::resolve_config_with_repo(
+                &profile,
+                std::path::Path::new(&body.path),
+            )
+            .unwrap_or_default();
+            match crate::session::templates::find(&resolved, name) {
+                Ok(template) => (
+                    template.clone(),
+                    template.sandbox_env(&resolved.sandbox.environment),
+                ),
+                Err(e) => {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(serde_json::json!({
+                            "error": "template_not_found",
+                            "message": e.to_string()
+                        })),
+                    )
+                        .into_response();
+                }
+            }
+        }
+        None => (SessionTemplate::default(), None),
+    };
+
     let instances = state.instances.read().await;
     let existing_titles: Vec<String> = instances.iter().map(|i| i.title.clone()).collect();
     drop(instances);
@@ -587,13 +674,16 @@ pub async fn create_session(
         use crate::session::Config;
 
         let config = Config::load().unwrap_or_default();
-        let sandbox_image = body.sandbox_image.unwrap_or_else(|| {
-            if config.sandbox.default_image.is_empty() {
-                "ubuntu:latest".to_string()
-            } else {
-                config.sandbox.default_image.clone()
-            }
-        });
+        let sandbox_image = body
+            .sandbox_image
+            .or(template.sandbox_image)
+            .unwrap_or_else(|| {
+                if config.sandbox.default_image.is_empty() {
+                    "ubuntu:latest".to_string()
+                } else {
+                    config.sandbox.default_image.clone()
+                }
+            });
 
         let title_refs: Vec<&str> = existing_titles.iter().map(|s| s.as_str()).collect();
         let extra_repo_paths: Vec<String> = body
@@ -619,20 +709,45 @@ pub async fn create_session(
             title
         };
 
+        // Request fields take priority over the template, then config defaults
+        let tool = if !body.tool.is_empty() {
+            body.tool
+        } else {
+            template
+                .agent
+                .or(config.session.default_tool.clone())
+                .unwrap_or_else(|| "claude".to_string())
+        };
+        let extra_env = if body.extra_env.is_empty() {
+            template_env.unwrap_or_default()
+        } else {
+            body.extra_env
+        };
+
         let params = InstanceParams {
             title,
             path: body.path,
-            group: body.group,
-            tool: body.tool,
+            group: if body.group.is_empty() {
+                template.group.unwrap_or_default()
+            } else {
+                body.group
+            },
+            tool,
             worktree_branch,
             create_new_branch: body.create_new_branch,
-            sandbox: body.sandbox,
+            sandbox: body.sandbox.or(template.sandbox).unwrap_or(false),
             sandbox_image,
-            yolo_mode: body.yolo_mode,
-            extra_env: body.extra_env,
-            extra_args: body.extra_args,
+            yolo_mode: body.yolo_mode.or(template.yolo).unwrap_or(false),
+            extra_env,
+            extra_args: if body.extra_args.is_empty() {
+                template.extra_args.unwrap_or_default()
+            } else {
+                body.extra_args
+            },
             command_override: body.command_override,
             extra_repo_paths,
+            prompt: body.prompt,
+            template: body.template,
         };
 
         let build_result = builder::build_instance(params, &title_refs, &profile)?;
@@ -648,9 +763,10 @@ pub async fn create_session(
 
         // Save to disk
         let storage = Storage::new(&profile)?;
-        let mut all = storage.load().unwrap_or_default();
-        all.push(instance.clone());
-        storage.save(&all)?;
+        storage.update(|all| {
+            all.push(instance.clone());
+            Ok(())
+        })?;
 
         // Start the session
         instance.start()?;
@@ -1277,6 +1393,268 @@ fn validate_diff_path(
     Ok(final_path)
 }
 
+/// Status transitions recorded for a session, oldest first.
+pub async fn session_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let profile = match resolve_session_profile(&state, &id).await {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+
+    let result =
+        tokio::task::spawn_blocking(move || crate::session::history::load(&profile, &id)).await;
+
+    match result {
+        Ok(Ok(events)) => (StatusCode::OK, Json(events)).into_response(),
+        Ok(Err(e)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({"error": "internal", "message": e.to_string()})),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("History load panicked: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "internal", "message": "Internal server error"})),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RecordingQuery {
+    /// Keep the response open and stream new output as it is recorded
+    #[serde(default)]
+    pub follow: bool,
+}
+
+/// How often a followed recording is checked for new output.
+const RECORDING_FOLLOW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Stream a session's asciicast recording. With `?follow=true` the response
+/// stays open and tails the file until the recording is removed or the client
+/// disconnects.
+pub async fn session_recording(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    axum::extract::Query(query): axum::extract::Query<RecordingQuery>,
+) -> impl IntoResponse {
+    use tokio::io::AsyncReadExt;
+
+    let profile = match resolve_session_profile(&state, &id).await {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "not_found", "message": "No recording for this session"})),
+        )
+            .into_response()
+    };
+    let Ok(path) = crate::session::recording::recording_path(&profile, &id) else {
+        return not_found();
+    };
+    let Ok(file) = tokio::fs::File::open(&path).await else {
+        return not_found();
+    };
+
+    let follow = query.follow;
+    let stream = futures_util::stream::unfold((file, path), move |(mut file, path)| async move {
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            match file.read(&mut buf).await {
+                Ok(0) if follow && path.exists() => {
+                    tokio::time::sleep(RECORDING_FOLLOW_INTERVAL).await;
+                }
+                Ok(0) => return None,
+                Ok(n) => {
+                    buf.truncate(n);
+                    let chunk = Ok::<_, std::io::Error>(axum::body::Bytes::from(buf));
+                    return Some((chunk, (file, path)));
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to read recording {}: {}", path.display(), e);
+                    return None;
+                }
+            }
+        }
+    });
+
+    (
+        [(axum::http::header::CONTENT_TYPE, "application/x-asciicast")],
+        axum::body::Body::from_stream(stream),
+    )
+        .into_response()
+}
+
+// --- Message queue ---
+
+#[derive(Deserialize)]
+pub struct QueueMessageBody {
+    pub text: String,
+}
+
+fn queue_error(e: anyhow::Error) -> axum::response::Response {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(serde_json::json!({"error": "internal", "message": e.to_string()})),
+    )
+        .into_response()
+}
+
+fn queue_read_only() -> axum::response::Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(serde_json::json!({"error": "read_only", "message": "Server is in read-only mode"})),
+    )
+        .into_response()
+}
+
+fn message_not_found() -> axum::response::Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({
+            "error": "not_found",
+            "message": "Message not found; it may already have been sent",
+        })),
+    )
+        .into_response()
+}
+
+/// Messages waiting to be sent to a session, next first.
+pub async fn session_queue(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    let profile = match resolve_session_profile(&state, &id).await {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    match tokio::task::spawn_blocking(move || message_queue::load(&profile, &id)).await {
+        Ok(Ok(messages)) => (StatusCode::OK, Json(messages)).into_response(),
+        Ok(Err(e)) => queue_error(e),
+        Err(e) => queue_error(e.into()),
+    }
+}
+
+/// Queue a message to be typed into the session the next time its agent
+/// goes idle.
+pub async fn enqueue_message(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(body): Json<QueueMessageBody>,
+) -> impl IntoResponse {
+    if state.read_only {
+        return queue_read_only();
+    }
+    if body.text.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "bad_request", "message": "Message cannot be empty"})),
+        )
+            .into_response();
+    }
+    let inst =
+        {
+            let instances = state.instances.read().await;
+            match instances.iter().find(|i| i.id == id) {
+                Some(inst) => inst.clone(),
+                None => return (
+                    StatusCode::NOT_FOUND,
+                    Json(serde_json::json!({"error": "not_found", "message": "Session not found"})),
+                )
+                    .into_response(),
+            }
+        };
+    let result = tokio::task::spawn_blocking(move || {
+        message_queue::enqueue(&inst.source_profile, &inst, &body.text)
+    })
+    .await;
+    match result {
+        Ok(Ok(message)) => (StatusCode::CREATED, Json(message)).into_response(),
+        Ok(Err(e)) => queue_error(e),
+        Err(e) => queue_error(e.into()),
+    }
+}
+
+pub async fn update_queued_message(
+    State(state): State<Arc<AppState>>,
+    Path((id, message_id)): Path<(String, String)>,
+    Json(body): Json<QueueMessageBody>,
+) -> impl IntoResponse {
+    if state.read_only {
+        return queue_read_only();
+    }
+    if body.text.trim().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({"error": "bad_request", "message": "Message cannot be empty"})),
+        )
+            .into_response();
+    }
+    let profile = match resolve_session_profile(&state, &id).await {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    let result = tokio::task::spawn_blocking(move || {
+        if !message_queue::edit(&profile, &id, &message_id, &body.text)? {
+            return Ok(None);
+        }
+        Ok(message_queue::load(&profile, &id)?
+            .into_iter()
+            .find(|m| m.id == message_id))
+    })
+    .await;
+    match result {
+        Ok(Ok(Some(message))) => (StatusCode::OK, Json(message)).into_response(),
+        Ok(Ok(None)) => message_not_found(),
+        Ok(Err(e)) => queue_error(e),
+        Err(e) => queue_error(e.into()),
+    }
+}
+
+pub async fn delete_queued_message(
+    State(state): State<Arc<AppState>>,
+    Path((id, message_id)): Path<(String, String)>,
+) -> impl IntoResponse {
+    if state.read_only {
+        return queue_read_only();
+    }
+    let profile = match resolve_session_profile(&state, &id).await {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    let result =
+        tokio::task::spawn_blocking(move || message_queue::remove(&profile, &id, &message_id))
+            .await;
+    match result {
+        Ok(Ok(true)) => StatusCode::NO_CONTENT.into_response(),
+        Ok(Ok(false)) => message_not_found(),
+        Ok(Err(e)) => queue_error(e),
+        Err(e) => queue_error(e.into()),
+    }
+}
+
+async fn resolve_session_profile(
+    state: &AppState,
+    id: &str,
+) -> Result<String, axum::response::Response> {
+    let instances = state.instances.read().await;
+    match instances.iter().find(|i| i.id == id) {
+        Some(i) => Ok(i.source_profile.clone()),
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({"error": "not_found", "message": "Session not found"})),
+        )
+            .into_response()),
+    }
+}
+
 /// Helper: look up a session's project_path by ID.
 async fn resolve_session_path(
     state: &AppState,
@@ -1525,6 +1903,58 @@ pub async fn list_agents() -> Json<Vec<AgentInfo>> {
     Json(result)
 }
 
+// --- Templates ---
+
+#[derive(Deserialize)]
+pub struct TemplatesQuery {
+    pub profile: Option<String>,
+    /// Project path, to include the repo's own templates
+    pub path: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TemplateInfo {
+    pub name: String,
+    #[serde(flatten)]
+    pub template: SessionTemplate,
+}
+
+pub async fn list_templates(
+    State(state): State<Arc<AppState>>,
+    axum::extract::Query(query): axum::extract::Query<TemplatesQuery>,
+) -> impl IntoResponse {
+    let profile = query.profile.unwrap_or_else(|| state.profile.clone());
+    let config_result = match query.path {
+        Some(ref path) => {
+            crate::session::resolve_config_with_repo(&profile, std::path::Path::new(path))
+        }
+        None => crate::session::resolve_config(&profile),
+    };
+
+    match config_result {
+        Ok(config) => {
+            let templates: Vec<TemplateInfo> = config
+                .templates
+                .into_iter()
+                .map(|(name, template)| TemplateInfo { name, template })
+                .collect();
+            (
+                StatusCode::OK,
+                Json(serde_json::to_value(templates).unwrap()),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("Template config load failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({"error": "load_failed", "message": "Failed to load templates"})),
+            )
+                .into_response()
+        }
+    }
+}
+
 // --- Settings ---
 
 #[derive(Deserialize)]
@@ -1564,7 +1994,10 @@ pub async fn get_settings(
     }
 }
 
-pub async fn update_settings(Json(body): Json<serde_json::Value>) -> impl IntoResponse {
+pub async fn update_settings(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<serde_json::Value>,
+) -> impl IntoResponse {
     // Validate that only allowed sections are being updated
     if let Some(obj) = body.as_object() {
         for key in obj.keys() {
@@ -1581,6 +2014,11 @@ pub async fn update_settings(Json(body): Json<serde_json::Value>) -> impl IntoRe
         }
     }
 
+    let sections: Vec<String> = body
+        .as_object()
+        .map(|obj| obj.keys().cloned().collect())
+        .unwrap_or_default();
+
     let result = tokio::task::spawn_blocking(move || {
         let config = crate::session::Config::load().unwrap_or_default();
         let mut current = serde_json::to_value(&config)?;
@@ -1604,6 +2042,12 @@ pub async fn update_settings(Json(body): Json<serde_json::Value>) -> impl IntoRe
     })
     .await;
 
+    if matches!(result, Ok(Ok(_))) {
+        state
+            .events
+            .publish(super::events::SessionEvent::SettingsUpdated { sections });
+    }
+
     match result {
         Ok(Ok(config)) => match serde_json::to_value(&config) {
             Ok(val) => (StatusCode::OK, Json(val)).into_response(),
@@ -1759,7 +2203,7 @@ pub async fn browse_filesystem(
                 is_git_repo,
             });
         }
-        entries.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+        entries.sort_by_key(|a| a.name.to_lowercase());
         let has_more = entries.len() > limit;
         entries.truncate(limit);
         Ok(BrowseResponse { entries, has_more })
@@ -1821,7 +2265,7 @@ pub async fn list_branches(
             })
             .collect();
 
-        result.sort_by(|a, b| b.is_current.cmp(&a.is_current));
+        result.sort_by_key(|b| std::cmp::Reverse(b.is_current));
 
         Ok(result)
     })
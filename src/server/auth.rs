@@ -206,6 +206,12 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Response {
+    // The Prometheus route checks its own token or loopback; see
+    // `prometheus::metrics_handler`.
+    if request.uri().path() == "/metrics" {
+        return next.run(request).await;
+    }
+
     let client_ip = resolve_client_ip(addr, request.headers());
 
     // No-auth mode: pass everything through. Insert a zeroed
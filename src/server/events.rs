@@ -0,0 +1,347 @@
+//! Server-sent event stream at `/api/events`.
+//!
+//! Lets the dashboard (or a script) react to changes instead of polling
+//! `/api/sessions`. Events are numbered and the most recent ones are kept
+//! in a bounded ring, so a client that reconnects with `Last-Event-ID`
+//! gets what it missed. When that isn't possible (the events fell off the
+//! ring, or the server restarted since) the client is sent a `resync`
+//! event and should refetch whatever it shows.
+//!
+//! Event ids are `<server start millis>-<sequence>`, so ids from a
+//! previous server run are recognised rather than mistaken for current
+//! ones.
+
+use std::collections::{HashMap, VecDeque};
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures_util::stream::{self, Stream, StreamExt};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use super::api::SessionResponse;
+use super::push::StatusChange;
+use super::AppState;
+use crate::session::Instance;
+
+/// Events kept for `Last-Event-ID` resume.
+const RING_CAPACITY: usize = 512;
+
+/// A change pushed to `/api/events` subscribers. Sent as an SSE event
+/// named after `type`, with the whole value as JSON data.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SessionEvent {
+    SessionCreated {
+        session: Box<SessionResponse>,
+    },
+    SessionRenamed {
+        id: String,
+        title: String,
+    },
+    SessionDeleted {
+        id: String,
+    },
+    StatusChanged(StatusChange),
+    /// Changed files against the default branch, as shown in the diff view
+    DiffStatsChanged {
+        id: String,
+        files: usize,
+        additions: usize,
+        deletions: usize,
+    },
+    SettingsUpdated {
+        sections: Vec<String>,
+    },
+}
+
+impl SessionEvent {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::SessionCreated { .. } => "session_created",
+            Self::SessionRenamed { .. } => "session_renamed",
+            Self::SessionDeleted { .. } => "session_deleted",
+            Self::StatusChanged(_) => "status_changed",
+            Self::DiffStatsChanged { .. } => "diff_stats_changed",
+            Self::SettingsUpdated { .. } => "settings_updated",
+        }
+    }
+}
+
+pub struct Entry {
+    pub seq: u64,
+    pub event: SessionEvent,
+}
+
+struct Ring {
+    entries: VecDeque<Arc<Entry>>,
+    next_seq: u64,
+}
+
+/// Numbers events, keeps the recent ones and fans them out to streams.
+pub struct EventBus {
+    started: i64,
+    capacity: usize,
+    ring: Mutex<Ring>,
+    tx: broadcast::Sender<Arc<Entry>>,
+}
+
+/// What a new stream starts with before following live events.
+pub struct Subscription {
+    /// The client's `Last-Event-ID` can't be resumed from
+    pub resync: bool,
+    /// Events after the client's `Last-Event-ID`, oldest first
+    pub backlog: Vec<Arc<Entry>>,
+    pub rx: broadcast::Receiver<Arc<Entry>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::with_capacity(RING_CAPACITY)
+    }
+}
+
+impl EventBus {
+    fn with_capacity(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity);
+        Self {
+            started: chrono::Utc::now().timestamp_millis(),
+            capacity,
+            ring: Mutex::new(Ring {
+                entries: VecDeque::with_capacity(capacity),
+                next_seq: 1,
+            }),
+            tx,
+        }
+    }
+
+    pub fn publish(&self, event: SessionEvent) {
+        let Ok(mut ring) = self.ring.lock() else {
+            return;
+        };
+        let entry = Arc::new(Entry {
+            seq: ring.next_seq,
+            event,
+        });
+        ring.next_seq += 1;
+        ring.entries.push_back(entry.clone());
+        while ring.entries.len() > self.capacity {
+            ring.entries.pop_front();
+        }
+        // Sent under the lock so a concurrent `subscribe` sees each event
+        // either in its backlog or on its receiver, never both or neither.
+        let _ = self.tx.send(entry);
+    }
+
+    pub fn has_subscribers(&self) -> bool {
+        self.tx.receiver_count() > 0
+    }
+
+    /// Start a stream, resuming after `last_event_id` when given.
+    pub fn subscribe(&self, last_event_id: Option<&str>) -> Subscription {
+        let ring = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+        let rx = self.tx.subscribe();
+        let Some(last_event_id) = last_event_id else {
+            return Subscription {
+                resync: false,
+                backlog: Vec::new(),
+                rx,
+            };
+        };
+
+        let resumable = self.parse_seq(last_event_id).filter(|&seq| {
+            let oldest = ring.entries.front().map_or(ring.next_seq, |e| e.seq);
+            seq < ring.next_seq && seq + 1 >= oldest
+        });
+        match resumable {
+            Some(seq) => Subscription {
+                resync: false,
+                backlog: ring
+                    .entries
+                    .iter()
+                    .filter(|e| e.seq > seq)
+                    .cloned()
+                    .collect(),
+                rx,
+            },
+            None => Subscription {
+                resync: true,
+                backlog: Vec::new(),
+                rx,
+            },
+        }
+    }
+
+    fn event_id(&self, seq: u64) -> String {
+        format!("{}-{}", self.started, seq)
+    }
+
+    /// The sequence number of an id issued by this server run.
+    fn parse_seq(&self, id: &str) -> Option<u64> {
+        let (started, seq) = id.trim().split_once('-')?;
+        if started.parse::<i64>().ok()? != self.started {
+            return None;
+        }
+        seq.parse().ok()
+    }
+
+    fn to_sse(&self, entry: &Entry) -> Event {
+        Event::default()
+            .event(entry.event.name())
+            .id(self.event_id(entry.seq))
+            .json_data(&entry.event)
+            .unwrap_or_else(|_| resync_event())
+    }
+}
+
+fn resync_event() -> Event {
+    Event::default().event("resync").data("{}")
+}
+
+/// Compare the session list against the titles last seen, updating them,
+/// and return created, renamed and deleted events.
+pub fn session_changes(
+    known: &mut HashMap<String, String>,
+    instances: &[Instance],
+) -> Vec<SessionEvent> {
+    let mut events = Vec::new();
+    for inst in instances {
+        match known.get(&inst.id) {
+            None => events.push(SessionEvent::SessionCreated {
+                session: Box::new(SessionResponse::from(inst)),
+            }),
+            Some(title) if *title != inst.title => events.push(SessionEvent::SessionRenamed {
+                id: inst.id.clone(),
+                title: inst.title.clone(),
+            }),
+            Some(_) => continue,
+        }
+        known.insert(inst.id.clone(), inst.title.clone());
+    }
+    known.retain(|id, _| {
+        let present = instances.iter().any(|i| &i.id == id);
+        if !present {
+            events.push(SessionEvent::SessionDeleted { id: id.clone() });
+        }
+        present
+    });
+    events
+}
+
+pub async fn events_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let last_event_id = headers.get("last-event-id").and_then(|v| v.to_str().ok());
+    let Subscription {
+        resync,
+        backlog,
+        rx,
+    } = state.events.subscribe(last_event_id);
+
+    let mut initial: Vec<Event> = backlog.iter().map(|e| state.events.to_sse(e)).collect();
+    if resync {
+        initial.insert(0, resync_event());
+    }
+
+    let live = stream::unfold((rx, state), |(mut rx, state)| async move {
+        match rx.recv().await {
+            Ok(entry) => {
+                let event = state.events.to_sse(&entry);
+                Some((event, (rx, state)))
+            }
+            // Events were dropped; the client has to refetch
+            Err(broadcast::error::RecvError::Lagged(_)) => Some((resync_event(), (rx, state))),
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    });
+
+    Sse::new(stream::iter(initial).chain(live).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deleted(id: &str) -> SessionEvent {
+        SessionEvent::SessionDeleted { id: id.to_string() }
+    }
+
+    fn seqs(sub: &Subscription) -> Vec<u64> {
+        sub.backlog.iter().map(|e| e.seq).collect()
+    }
+
+    #[test]
+    fn resumes_from_last_event_id() {
+        let bus = EventBus::with_capacity(4);
+        for i in 0..3 {
+            bus.publish(deleted(&i.to_string()));
+        }
+
+        let sub = bus.subscribe(Some(&bus.event_id(1)));
+        assert!(!sub.resync);
+        assert_eq!(seqs(&sub), vec![2, 3]);
+
+        let sub = bus.subscribe(Some(&bus.event_id(3)));
+        assert!(!sub.resync);
+        assert!(sub.backlog.is_empty());
+
+        let sub = bus.subscribe(None);
+        assert!(!sub.resync);
+        assert!(sub.backlog.is_empty());
+    }
+
+    #[test]
+    fn resyncs_when_events_were_lost() {
+        let bus = EventBus::with_capacity(2);
+        for i in 0..5 {
+            bus.publish(deleted(&i.to_string()));
+        }
+        // Ring holds 4 and 5: resuming after 3 is fine, after 2 is not
+        assert_eq!(seqs(&bus.subscribe(Some(&bus.event_id(3)))), vec![4, 5]);
+        assert!(bus.subscribe(Some(&bus.event_id(2))).resync);
+        // An id from another server run, or from the future
+        assert!(bus.subscribe(Some("1-4")).resync);
+        assert!(bus.subscribe(Some(&bus.event_id(9))).resync);
+        assert!(bus.subscribe(Some("garbage")).resync);
+    }
+
+    #[test]
+    fn live_events_follow_backlog() {
+        let bus = EventBus::with_capacity(4);
+        bus.publish(deleted("a"));
+        let mut sub = bus.subscribe(Some(&bus.event_id(0)));
+        assert_eq!(seqs(&sub), vec![1]);
+        bus.publish(deleted("b"));
+        assert_eq!(sub.rx.try_recv().unwrap().seq, 2);
+        assert!(bus.has_subscribers());
+    }
+
+    #[test]
+    fn session_changes_diffs_titles() {
+        let mut known = HashMap::new();
+        let mut a = Instance::new("a", "/tmp/a");
+        let b = Instance::new("b", "/tmp/b");
+
+        let events = session_changes(&mut known, &[a.clone(), b.clone()]);
+        assert_eq!(events.len(), 2);
+        assert!(events
+            .iter()
+            .all(|e| matches!(e, SessionEvent::SessionCreated { .. })));
+        assert!(session_changes(&mut known, &[a.clone(), b.clone()]).is_empty());
+
+        a.title = "renamed".to_string();
+        let events = session_changes(&mut known, std::slice::from_ref(&a));
+        let names: Vec<&str> = events.iter().map(SessionEvent::name).collect();
+        assert_eq!(names, vec!["session_renamed", "session_deleted"]);
+        assert!(matches!(
+            &events[1],
+            SessionEvent::SessionDeleted { id } if *id == b.id
+        ));
+    }
+}
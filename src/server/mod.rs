@@ -5,7 +5,9 @@
 
 pub mod api;
 pub mod auth;
+pub mod events;
 pub mod login;
+pub mod prometheus;
 pub mod push;
 pub mod push_send;
 pub mod rate_limit;
@@ -211,6 +213,14 @@ pub struct AppState {
     /// Snapshot of the resolved WebConfig at startup. Consumed by the
     /// push consumer task to evaluate per-event-type defaults.
     pub web_config: crate::session::config::WebConfig,
+    /// Token usage per session id, refreshed by `usage_poll_loop`.
+    pub usage: RwLock<std::collections::HashMap<String, crate::usage::SessionUsage>>,
+    /// Counters and gauges for the Prometheus `/metrics` route.
+    pub server_metrics: Arc<prometheus::ServerMetrics>,
+    /// CPU and memory per session id, refreshed by `metrics_poll_loop`.
+    pub metrics: RwLock<std::collections::HashMap<String, crate::process::metrics::SessionMetrics>>,
+    /// Change events for `/api/events` subscribers.
+    pub events: events::EventBus,
 }
 
 impl AppState {
@@ -334,6 +344,10 @@ pub async fn start_server(config: ServerConfig<'_>) -> anyhow::Result<()> {
         push: push_state,
         push_enabled,
         web_config: config.web.clone(),
+        usage: RwLock::new(std::collections::HashMap::new()),
+        metrics: RwLock::new(std::collections::HashMap::new()),
+        server_metrics: Arc::new(prometheus::ServerMetrics::default()),
+        events: events::EventBus::default(),
     });
 
     let app = build_router(state.clone());
@@ -437,11 +451,27 @@ pub async fn start_server(config: ServerConfig<'_>) -> anyhow::Result<()> {
     tokio::spawn(async move {
         status_poll_loop(poll_state).await;
     });
+    let usage_state = state.clone();
+    tokio::spawn(async move {
+        usage_poll_loop(usage_state).await;
+    });
+    let metrics_state = state.clone();
+    tokio::spawn(async move {
+        metrics_poll_loop(metrics_state).await;
+    });
+    let diff_state = state.clone();
+    tokio::spawn(async move {
+        diff_stats_poll_loop(diff_state).await;
+    });
 
     // Push-notification consumer: subscribes to status_tx, applies
     // dwell + cooldown, sends pushes. No-op when push_state is None
     // (feature disabled via web.notifications_enabled=false).
     push::spawn_consumer(state.clone());
+    spawn_history_recorder(&state);
+    spawn_stall_recovery(&state);
+    spawn_event_forwarder(&state);
+    spawn_webhook_forwarder(&state);
 
     rate_limiter.spawn_cleanup_task();
     login_manager.spawn_cleanup_task();
@@ -575,6 +605,16 @@ fn build_router(state: Arc<AppState>) -> Router {
         )
         .route("/api/sessions/{id}/diff/file", get(api::session_diff_file))
         .route("/api/sessions/{id}/ensure", post(api::ensure_session))
+        .route("/api/sessions/{id}/history", get(api::session_history))
+        .route("/api/sessions/{id}/recording", get(api::session_recording))
+        .route(
+            "/api/sessions/{id}/queue",
+            get(api::session_queue).post(api::enqueue_message),
+        )
+        .route(
+            "/api/sessions/{id}/queue/{message_id}",
+            patch(api::update_queued_message).delete(api::delete_queued_message),
+        )
         .route(
             "/api/sessions/{id}/notifications",
             patch(api::update_session_notifications),
@@ -586,6 +626,7 @@ fn build_router(state: Arc<AppState>) -> Router {
         )
         // Agents
         .route("/api/agents", get(api::list_agents))
+        .route("/api/templates", get(api::list_templates))
         // Wizard support
         .route("/api/profiles", get(api::list_profiles))
         .route("/api/filesystem/browse", get(api::browse_filesystem))
@@ -617,6 +658,10 @@ fn build_router(state: Arc<AppState>) -> Router {
         .route("/api/devices", get(api::list_devices))
         // About (version, auth status, read-only state)
         .route("/api/about", get(api::get_about))
+        // Session change events (SSE)
+        .route("/api/events", get(events::events_handler))
+        // Prometheus scrape target (opt-in, own auth)
+        .route("/metrics", get(prometheus::metrics_handler))
         // Terminal WebSockets
         .route("/sessions/{id}/ws", get(ws::terminal_ws))
         .route("/sessions/{id}/terminal/ws", get(ws::paired_terminal_ws))
@@ -896,6 +941,16 @@ fn load_all_instances() -> anyhow::Result<Vec<Instance>> {
 /// and keeps TUI/CLI callers unchanged.
 async fn status_poll_loop(state: Arc<AppState>) {
     let mut interval = tokio::time::interval(std::time::Duration::from_secs(2));
+    // Session titles as of the last tick, to turn reloads into created,
+    // renamed and deleted events. Storage is the source of truth, so
+    // changes made from the TUI or CLI show up too.
+    let mut known: std::collections::HashMap<String, String> = state
+        .instances
+        .read()
+        .await
+        .iter()
+        .map(|i| (i.id.clone(), i.title.clone()))
+        .collect();
     loop {
         interval.tick().await;
 
@@ -931,23 +986,287 @@ async fn status_poll_loop(state: Arc<AppState>) {
             for inst in &instances {
                 if let Some(old) = prev.get(&inst.id) {
                     if *old != inst.status {
+                        state.server_metrics.record_transition(*old, inst.status);
                         // send() errors only when there are no receivers;
                         // that's fine, we emit best-effort.
                         let _ = state.status_tx.send(StatusChange {
                             instance_id: inst.id.clone(),
                             instance_title: inst.title.clone(),
+                            profile: inst.source_profile.clone(),
                             old: *old,
                             new: inst.status,
+                            source: inst.status_source,
                             at: now,
                         });
                     }
                 }
             }
+            let changes = events::session_changes(&mut known, &instances);
+            if !changes.is_empty() {
+                let previous = state.instances.read().await;
+                for event in &changes {
+                    match event {
+                        events::SessionEvent::SessionCreated { session } => {
+                            if let Some(inst) = instances.iter().find(|i| i.id == session.id) {
+                                crate::webhooks::session_created(inst);
+                            }
+                        }
+                        events::SessionEvent::SessionDeleted { id } => {
+                            if let Some(inst) = previous.iter().find(|i| &i.id == id) {
+                                crate::webhooks::session_deleted(inst);
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            for event in changes {
+                state.events.publish(event);
+            }
             *state.instances.write().await = instances;
         }
     }
 }
 
+/// Background task that refreshes per-session token usage. Much slower
+/// than the status poll: transcripts only grow while an agent is working
+/// and the numbers are informational. The tracker moves in and out of the
+/// blocking thread so it keeps its read offsets between ticks.
+async fn usage_poll_loop(state: Arc<AppState>) {
+    use crate::usage::{Sources, UsageTracker};
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(15));
+    let mut tracker = UsageTracker::new(Sources::detect());
+    loop {
+        interval.tick().await;
+
+        let instances = state.instances.read().await.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let config = crate::session::Config::load().unwrap_or_default();
+            let usage = tracker.collect(&instances, &config.usage);
+            (tracker, usage)
+        })
+        .await;
+
+        match result {
+            Ok((returned, usage)) => {
+                tracker = returned;
+                *state.usage.write().await = usage;
+            }
+            // The tracker was lost with the panicked task; start over
+            Err(_) => tracker = UsageTracker::new(Sources::detect()),
+        }
+    }
+}
+
+/// Background task that samples per-session CPU and memory. Like the usage
+/// loop, the sampler moves in and out of the blocking thread so it keeps
+/// each session's previous CPU time and rolling history between ticks.
+async fn metrics_poll_loop(state: Arc<AppState>) {
+    use crate::process::metrics::MetricsSampler;
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+    let mut sampler = MetricsSampler::new();
+    loop {
+        interval.tick().await;
+
+        let config = crate::session::Config::load().unwrap_or_default();
+        if !config.metrics.enabled {
+            state.metrics.write().await.clear();
+            continue;
+        }
+
+        let instances = state.instances.read().await.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let metrics = sampler.collect(&instances);
+            (sampler, metrics)
+        })
+        .await;
+
+        match result {
+            Ok((returned, metrics)) => {
+                sampler = returned;
+                *state.metrics.write().await = metrics;
+            }
+            // The sampler was lost with the panicked task; start over
+            Err(_) => sampler = MetricsSampler::new(),
+        }
+    }
+}
+
+/// Background task that publishes changes to each session's diff against
+/// its default branch. Runs git per session, so it only does so while
+/// someone is subscribed to `/api/events`.
+async fn diff_stats_poll_loop(state: Arc<AppState>) {
+    use crate::session::Status;
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(30));
+    let mut last: std::collections::HashMap<String, (usize, usize, usize)> =
+        std::collections::HashMap::new();
+    loop {
+        interval.tick().await;
+        if !state.events.has_subscribers() {
+            continue;
+        }
+
+        let sessions: Vec<(String, String)> = state
+            .instances
+            .read()
+            .await
+            .iter()
+            .filter(|i| !matches!(i.status, Status::Creating | Status::Deleting))
+            .map(|i| (i.id.clone(), i.project_path.clone()))
+            .collect();
+        let Ok(stats) = tokio::task::spawn_blocking(move || {
+            use crate::git::diff;
+            sessions
+                .into_iter()
+                .filter_map(|(id, project_path)| {
+                    let path = std::path::Path::new(&project_path);
+                    let base = diff::get_default_branch(path).ok()?;
+                    let files = diff::compute_changed_files(path, &base).ok()?;
+                    let additions = files.iter().map(|f| f.additions).sum();
+                    let deletions = files.iter().map(|f| f.deletions).sum();
+                    Some((id, (files.len(), additions, deletions)))
+                })
+                .collect::<std::collections::HashMap<_, _>>()
+        })
+        .await
+        else {
+            continue;
+        };
+
+        for (id, current) in &stats {
+            if last.get(id) != Some(current) {
+                let (files, additions, deletions) = *current;
+                state
+                    .events
+                    .publish(events::SessionEvent::DiffStatsChanged {
+                        id: id.clone(),
+                        files,
+                        additions,
+                        deletions,
+                    });
+            }
+        }
+        last = stats;
+    }
+}
+
+/// Append every status transition to the owning profile's history log.
+/// A separate subscriber from the push consumer so history is kept even
+/// when Web Push is disabled.
+fn spawn_history_recorder(state: &Arc<AppState>) {
+    use crate::session::history::{self, StatusEvent};
+
+    let mut rx = state.status_tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(change) => {
+                    let event = StatusEvent {
+                        session_id: change.instance_id,
+                        at: change.at,
+                        from: change.old,
+                        to: change.new,
+                        source: change.source,
+                    };
+                    let profile = change.profile;
+                    let _ = tokio::task::spawn_blocking(move || {
+                        history::record_best_effort(&profile, &event)
+                    })
+                    .await;
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(lagged = n, "history: recorder lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Run the watchdog's recovery action for sessions that turn Stalled.
+/// The TUI does the same; the watchdog's claim file keeps the two from
+/// both acting on one stall.
+fn spawn_stall_recovery(state: &Arc<AppState>) {
+    use crate::session::{watchdog, Status};
+
+    let mut rx = state.status_tx.subscribe();
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(change) if change.new == Status::Stalled => {
+                    let inst = state
+                        .instances
+                        .read()
+                        .await
+                        .iter()
+                        .find(|i| i.id == change.instance_id)
+                        .cloned();
+                    let Some(mut inst) = inst else { continue };
+                    let _ = tokio::task::spawn_blocking(move || {
+                        if let Err(e) = watchdog::recover(&mut inst) {
+                            tracing::warn!("watchdog: recovery for '{}' failed: {}", inst.title, e);
+                        }
+                    })
+                    .await;
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(lagged = n, "watchdog: recovery lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Publish status transitions to `/api/events` subscribers.
+fn spawn_event_forwarder(state: &Arc<AppState>) {
+    let mut rx = state.status_tx.subscribe();
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(change) => state
+                    .events
+                    .publish(events::SessionEvent::StatusChanged(change)),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(lagged = n, "events: forwarder lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
+/// Report status transitions to configured webhooks and status hooks. The
+/// TUI does the same; claim files in both modules keep the two from both
+/// acting on one transition.
+fn spawn_webhook_forwarder(state: &Arc<AppState>) {
+    let mut rx = state.status_tx.subscribe();
+    let state = state.clone();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(change) => {
+                    let instances = state.instances.read().await;
+                    if let Some(inst) = instances.iter().find(|i| i.id == change.instance_id) {
+                        crate::webhooks::status_changed(inst, change.old, change.new);
+                        crate::session::status_hooks::status_changed(inst, change.old, change.new);
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!(lagged = n, "webhooks: forwarder lagged, skipped events");
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            }
+        }
+    });
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
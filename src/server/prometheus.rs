@@ -0,0 +1,278 @@
+//! Prometheus `/metrics` endpoint.
+//!
+//! Off unless `web.metrics_enabled` is set. The route bypasses the
+//! dashboard's token and passphrase auth: scrapers can't do the cookie
+//! dance, and a dashboard token in a scrape config would grant full
+//! control of every agent. Instead it requires `web.metrics_token` as a
+//! bearer token, or, when no token is configured, a direct loopback
+//! connection (never one arriving through the tunnel).
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+use super::AppState;
+use crate::session::{Instance, Status};
+
+/// Counters and gauges the server keeps for `/metrics`. Session counts
+/// are read from `AppState.instances` at scrape time instead.
+#[derive(Default)]
+pub struct ServerMetrics {
+    /// Status transitions seen by `status_poll_loop`, by (from, to)
+    transitions: Mutex<BTreeMap<(String, String), u64>>,
+    active_terminals: AtomicI64,
+}
+
+impl ServerMetrics {
+    pub fn record_transition(&self, from: Status, to: Status) {
+        if let Ok(mut transitions) = self.transitions.lock() {
+            *transitions
+                .entry((status_label(from), status_label(to)))
+                .or_default() += 1;
+        }
+    }
+
+    /// Count a terminal WebSocket as open until the returned guard drops.
+    pub fn terminal_opened(self: &Arc<Self>) -> ActiveTerminal {
+        self.active_terminals.fetch_add(1, Ordering::Relaxed);
+        ActiveTerminal(Arc::clone(self))
+    }
+}
+
+pub struct ActiveTerminal(Arc<ServerMetrics>);
+
+impl Drop for ActiveTerminal {
+    fn drop(&mut self) {
+        self.0.active_terminals.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+fn status_label(status: Status) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+pub async fn metrics_handler(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+) -> Response {
+    let web = &state.web_config;
+    if !web.metrics_enabled {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let client_ip = super::auth::resolve_client_ip(addr, &headers);
+    if state.rate_limiter.check_locked(client_ip).await.is_some() {
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    }
+
+    match web.metrics_token.as_deref().filter(|t| !t.is_empty()) {
+        Some(expected) => {
+            let presented = headers
+                .get(header::AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .map(str::trim);
+            if !presented.is_some_and(|t| super::auth::constant_time_eq(t, expected)) {
+                state.rate_limiter.record_failure(client_ip).await;
+                return StatusCode::UNAUTHORIZED.into_response();
+            }
+        }
+        None => {
+            if state.behind_tunnel || !addr.ip().is_loopback() {
+                return StatusCode::FORBIDDEN.into_response();
+            }
+        }
+    }
+
+    let instances = state.instances.read().await.clone();
+    let body = render(&state, &instances);
+    (
+        [(
+            header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        body,
+    )
+        .into_response()
+}
+
+fn render(state: &AppState, instances: &[Instance]) -> String {
+    let mut out = String::new();
+
+    let mut sessions: BTreeMap<(String, String, String), u64> = BTreeMap::new();
+    for inst in instances {
+        let key = (
+            status_label(inst.status),
+            inst.tool.clone(),
+            inst.source_profile.clone(),
+        );
+        *sessions.entry(key).or_default() += 1;
+    }
+    family(
+        &mut out,
+        "aoe_sessions",
+        "gauge",
+        "Sessions by status, agent and profile.",
+    );
+    for ((status, agent, profile), count) in &sessions {
+        sample(
+            &mut out,
+            "aoe_sessions",
+            &[("status", status), ("agent", agent), ("profile", profile)],
+            *count,
+        );
+    }
+
+    family(
+        &mut out,
+        "aoe_status_transitions_total",
+        "counter",
+        "Session status transitions since the server started.",
+    );
+    if let Ok(transitions) = state.server_metrics.transitions.lock() {
+        for ((from, to), count) in transitions.iter() {
+            sample(
+                &mut out,
+                "aoe_status_transitions_total",
+                &[("from", from), ("to", to)],
+                *count,
+            );
+        }
+    }
+
+    family(
+        &mut out,
+        "aoe_push_sends_total",
+        "counter",
+        "Web Push sends by outcome (delivered, gone, failed).",
+    );
+    if let Some(push) = &state.push {
+        for (outcome, count) in push.sends.snapshot() {
+            sample(
+                &mut out,
+                "aoe_push_sends_total",
+                &[("outcome", outcome)],
+                count,
+            );
+        }
+    }
+
+    family(
+        &mut out,
+        "aoe_login_failures_total",
+        "counter",
+        "Failed token or passphrase attempts.",
+    );
+    sample(
+        &mut out,
+        "aoe_login_failures_total",
+        &[],
+        state.rate_limiter.failures_total(),
+    );
+    family(
+        &mut out,
+        "aoe_login_lockouts_total",
+        "counter",
+        "Client IPs locked out after repeated failures.",
+    );
+    sample(
+        &mut out,
+        "aoe_login_lockouts_total",
+        &[],
+        state.rate_limiter.lockouts_total(),
+    );
+
+    family(
+        &mut out,
+        "aoe_websocket_terminals",
+        "gauge",
+        "Open terminal WebSocket connections.",
+    );
+    sample(
+        &mut out,
+        "aoe_websocket_terminals",
+        &[],
+        state
+            .server_metrics
+            .active_terminals
+            .load(Ordering::Relaxed)
+            .max(0),
+    );
+
+    out
+}
+
+fn family(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+fn sample(out: &mut String, name: &str, labels: &[(&str, &str)], value: impl std::fmt::Display) {
+    out.push_str(name);
+    if !labels.is_empty() {
+        let labels: Vec<String> = labels
+            .iter()
+            .map(|(k, v)| format!("{}=\"{}\"", k, escape_label(v)))
+            .collect();
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", value);
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_formats_labels() {
+        let mut out = String::new();
+        sample(
+            &mut out,
+            "aoe_sessions",
+            &[("status", "running"), ("profile", "a\"b")],
+            3,
+        );
+        sample(&mut out, "aoe_websocket_terminals", &[], 0);
+        assert_eq!(
+            out,
+            "aoe_sessions{status=\"running\",profile=\"a\\\"b\"} 3\naoe_websocket_terminals 0\n"
+        );
+    }
+
+    #[test]
+    fn transitions_and_terminals_are_counted() {
+        let metrics = Arc::new(ServerMetrics::default());
+        metrics.record_transition(Status::Running, Status::Waiting);
+        metrics.record_transition(Status::Running, Status::Waiting);
+        metrics.record_transition(Status::Waiting, Status::Running);
+        let transitions = metrics.transitions.lock().unwrap();
+        assert_eq!(
+            transitions.get(&("running".to_string(), "waiting".to_string())),
+            Some(&2)
+        );
+        drop(transitions);
+
+        let first = metrics.terminal_opened();
+        let second = metrics.terminal_opened();
+        assert_eq!(metrics.active_terminals.load(Ordering::Relaxed), 2);
+        drop(first);
+        drop(second);
+        assert_eq!(metrics.active_terminals.load(Ordering::Relaxed), 0);
+    }
+}
@@ -11,6 +11,7 @@
 //! ownership, rotate-invalidation) are documented in
 //! `docs/plans/web-push-notifications.md`.
 
+use crate::session::history::StatusSource;
 use crate::session::Status;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -19,14 +20,18 @@ use std::path::{Path, PathBuf};
 use tokio::sync::RwLock;
 
 /// Emitted when an instance's status changes. The broadcast channel on
-/// `AppState.status_tx` carries these; `push.rs` is the only consumer in
-/// v1, but future features (UI realtime, webhooks) can subscribe too.
-#[derive(Clone, Debug)]
+/// `AppState.status_tx` carries these to the push consumer, the history
+/// recorder, the stall watchdog and the `/api/events` stream.
+#[derive(Clone, Debug, Serialize)]
 pub struct StatusChange {
     pub instance_id: String,
     pub instance_title: String,
+    /// Profile the instance was loaded from, so consumers can write
+    /// per-profile state (e.g. the status history log).
+    pub profile: String,
     pub old: Status,
     pub new: Status,
+    pub source: StatusSource,
     pub at: DateTime<Utc>,
 }
 
@@ -314,6 +319,8 @@ pub struct PushState {
     /// either `mailto:` or an `https://` URL per the spec. Not strongly
     /// validated by push endpoints in practice.
     pub subject: String,
+    /// Outcomes of every push sent, exposed on `/metrics`.
+    pub sends: super::push_send::SendCounters,
 }
 
 /// VAPID `sub` claim (RFC 8292). Spec requires a `mailto:` or `https://`
@@ -330,6 +337,7 @@ impl PushState {
             vapid,
             store,
             subject: VAPID_SUBJECT.to_string(),
+            sends: Default::default(),
         })
     }
 }
@@ -357,6 +365,8 @@ pub enum NotificationEvent {
     Waiting,
     Idle,
     Error,
+    /// The watchdog marked the session Stalled. Shares Error's settings.
+    Stalled,
 }
 
 impl NotificationEvent {
@@ -379,6 +389,8 @@ struct DwellState {
     idle_since: Option<std::time::Instant>,
     /// When the session most recently entered Error.
     error_since: Option<std::time::Instant>,
+    /// When the session most recently entered Stalled.
+    stalled_since: Option<std::time::Instant>,
     /// Last time a push fired for this session (any event type). Used
     /// for a shared per-session cooldown: rapid-fire events like
     /// Error → brief Running → Error don't double-buzz.
@@ -455,10 +467,12 @@ fn handle_status_change(dwell: &mut HashMap<String, DwellState>, change: StatusC
     entry.waiting_since = None;
     entry.idle_since = None;
     entry.error_since = None;
+    entry.stalled_since = None;
     match change.new {
         Status::Waiting => entry.waiting_since = Some(now),
         Status::Idle => entry.idle_since = Some(now),
         Status::Error => entry.error_since = Some(now),
+        Status::Stalled => entry.stalled_since = Some(now),
         _ => {}
     }
     // Drop entries for transitions into Stopped/Deleting so the map
@@ -482,7 +496,7 @@ fn should_fire(
             instance.and_then(|i| i.notify_on_waiting),
         ),
         NotificationEvent::Idle => (web.notify_on_idle, instance.and_then(|i| i.notify_on_idle)),
-        NotificationEvent::Error => (
+        NotificationEvent::Error | NotificationEvent::Stalled => (
             web.notify_on_error,
             instance.and_then(|i| i.notify_on_error),
         ),
@@ -522,6 +536,7 @@ async fn fire_due_pushes(
         let checks = [
             (NotificationEvent::Waiting, state.waiting_since),
             (NotificationEvent::Error, state.error_since),
+            (NotificationEvent::Stalled, state.stalled_since),
             (NotificationEvent::Idle, state.idle_since),
         ];
         for (event, since_opt) in checks {
@@ -533,6 +548,7 @@ async fn fire_due_pushes(
             state.waiting_since = None;
             state.idle_since = None;
             state.error_since = None;
+            state.stalled_since = None;
             to_fire.push((id.clone(), state.title.clone(), event));
             break;
         }
@@ -570,6 +586,7 @@ async fn fire_due_pushes(
             NotificationEvent::Waiting => ("Claude is waiting", "Waiting for input"),
             NotificationEvent::Idle => ("Session finished", "Agent is idle"),
             NotificationEvent::Error => ("Session error", "Agent errored"),
+            NotificationEvent::Stalled => ("Session stalled", "No output from agent"),
         };
         let body = if instance_title.is_empty() {
             body_prefix.to_string()
@@ -609,6 +626,26 @@ async fn fire_due_pushes(
     }
 }
 
+/// Push `payload` to every subscription from outside the server process,
+/// e.g. from the auto-responder's watcher. Blocks until the sends finish;
+/// subscriptions that are gone are left for the server to collect.
+pub fn send_blocking(
+    app_dir: &Path,
+    payload: &super::push_send::PushPayload,
+) -> anyhow::Result<()> {
+    let push = PushState::init(app_dir)?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        let client = super::push_send::build_client()?;
+        for sub in push.store.snapshot().await {
+            super::push_send::send_one(&client, &push, &sub, payload).await;
+        }
+        Ok(())
+    })
+}
+
 pub fn sha256_token(token: &str) -> [u8; 32] {
     use sha2::{Digest, Sha256};
     let mut h = Sha256::new();
@@ -966,8 +1003,10 @@ mod tests {
             StatusChange {
                 instance_id: id.clone(),
                 instance_title: "my session".to_string(),
+                profile: "default".to_string(),
                 old: Status::Running,
                 new: Status::Waiting,
+                source: StatusSource::Hook,
                 at: Utc::now(),
             },
         );
@@ -981,8 +1020,10 @@ mod tests {
             StatusChange {
                 instance_id: id.clone(),
                 instance_title: "my session".to_string(),
+                profile: "default".to_string(),
                 old: Status::Waiting,
                 new: Status::Running,
+                source: StatusSource::Hook,
                 at: Utc::now(),
             },
         );
@@ -996,8 +1037,10 @@ mod tests {
         let ev = |new: Status| StatusChange {
             instance_id: id.clone(),
             instance_title: "s".into(),
+            profile: "default".to_string(),
             old: Status::Running,
             new,
+            source: StatusSource::Hook,
             at: Utc::now(),
         };
 
@@ -1018,6 +1061,11 @@ mod tests {
         assert!(s.waiting_since.is_none());
         assert!(s.idle_since.is_some());
         assert!(s.error_since.is_none());
+
+        handle_status_change(&mut dwell, ev(Status::Stalled));
+        let s = dwell.get(&id).unwrap();
+        assert!(s.idle_since.is_none());
+        assert!(s.stalled_since.is_some());
     }
 
     #[test]
@@ -1030,6 +1078,7 @@ mod tests {
             notify_on_waiting: true,
             notify_on_idle: false, // globally off
             notify_on_error: true,
+            ..Default::default()
         };
 
         // No instance (session not in state): fall back to web defaults.
@@ -1055,6 +1104,10 @@ mod tests {
 
         // Error unaffected: per-event-type overrides don't cross-pollute.
         assert!(should_fire(NotificationEvent::Error, &web, Some(&inst)));
+
+        // Stalled follows the error setting.
+        inst.notify_on_error = Some(false);
+        assert!(!should_fire(NotificationEvent::Stalled, &web, Some(&inst)));
     }
 
     #[test]
@@ -1066,8 +1119,10 @@ mod tests {
             StatusChange {
                 instance_id: id.clone(),
                 instance_title: "s".to_string(),
+                profile: "default".to_string(),
                 old: Status::Running,
                 new: Status::Waiting,
+                source: StatusSource::Hook,
                 at: Utc::now(),
             },
         );
@@ -1077,8 +1132,10 @@ mod tests {
             StatusChange {
                 instance_id: id.clone(),
                 instance_title: "s".to_string(),
+                profile: "default".to_string(),
                 old: Status::Waiting,
                 new: Status::Stopped,
+                source: StatusSource::Hook,
                 at: Utc::now(),
             },
         );
@@ -30,6 +30,7 @@ use anyhow::{anyhow, Context, Result};
 use base64::Engine;
 use serde::Serialize;
 use sha2::Sha256;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
 use super::push::{base64_url_decode, PushState, Subscription};
@@ -58,6 +59,34 @@ pub enum SendOutcome {
     Failed,
 }
 
+/// Totals of `send_one` outcomes since the server started, for `/metrics`.
+#[derive(Default)]
+pub struct SendCounters {
+    delivered: AtomicU64,
+    gone: AtomicU64,
+    failed: AtomicU64,
+}
+
+impl SendCounters {
+    fn record(&self, outcome: SendOutcome) {
+        let counter = match outcome {
+            SendOutcome::Delivered => &self.delivered,
+            SendOutcome::Gone => &self.gone,
+            SendOutcome::Failed => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// (outcome label, count) pairs
+    pub fn snapshot(&self) -> [(&'static str, u64); 3] {
+        [
+            ("delivered", self.delivered.load(Ordering::Relaxed)),
+            ("gone", self.gone.load(Ordering::Relaxed)),
+            ("failed", self.failed.load(Ordering::Relaxed)),
+        ]
+    }
+}
+
 #[derive(Serialize)]
 struct VapidClaims {
     aud: String,
@@ -224,7 +253,7 @@ pub async fn send_one(
     subscription: &Subscription,
     payload: &PushPayload,
 ) -> SendOutcome {
-    match send_one_inner(client, state, subscription, payload).await {
+    let outcome = match send_one_inner(client, state, subscription, payload).await {
         Ok(outcome) => outcome,
         Err(e) => {
             tracing::warn!(
@@ -234,7 +263,9 @@ pub async fn send_one(
             );
             SendOutcome::Failed
         }
-    }
+    };
+    state.sends.record(outcome);
+    outcome
 }
 
 async fn send_one_inner(
@@ -6,6 +6,7 @@
 
 use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -38,6 +39,9 @@ struct FailureRecord {
 
 pub struct RateLimiter {
     failures: RwLock<HashMap<IpAddr, FailureRecord>>,
+    /// Every `record_failure` call since startup, coalesced or not
+    failures_total: AtomicU64,
+    lockouts_total: AtomicU64,
 }
 
 impl Default for RateLimiter {
@@ -50,6 +54,8 @@ impl RateLimiter {
     pub fn new() -> Self {
         Self {
             failures: RwLock::new(HashMap::new()),
+            failures_total: AtomicU64::new(0),
+            lockouts_total: AtomicU64::new(0),
         }
     }
 
@@ -70,6 +76,7 @@ impl RateLimiter {
 
     /// Record a failed auth attempt. Returns true if this failure triggered a lockout.
     pub async fn record_failure(&self, ip: IpAddr) -> bool {
+        self.failures_total.fetch_add(1, Ordering::Relaxed);
         let mut failures = self.failures.write().await;
         let now = Instant::now();
 
@@ -117,12 +124,23 @@ impl RateLimiter {
 
         if record.count >= MAX_FAILURES {
             record.locked_until = Some(now + LOCKOUT_DURATION);
+            self.lockouts_total.fetch_add(1, Ordering::Relaxed);
             return true;
         }
 
         false
     }
 
+    /// Failed auth attempts since startup, for `/metrics`.
+    pub fn failures_total(&self) -> u64 {
+        self.failures_total.load(Ordering::Relaxed)
+    }
+
+    /// Lockouts triggered since startup, for `/metrics`.
+    pub fn lockouts_total(&self) -> u64 {
+        self.lockouts_total.load(Ordering::Relaxed)
+    }
+
     /// Clear failure count for an IP after successful auth.
     pub async fn record_success(&self, ip: IpAddr) {
         let mut failures = self.failures.write().await;
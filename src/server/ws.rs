@@ -33,6 +33,7 @@ pub async fn paired_terminal_ws(
     drop(instances);
 
     let read_only = state.read_only;
+    let metrics = Arc::clone(&state.server_metrics);
 
     match session_info {
         // Accept the "aoe-auth" subprotocol so the browser's handshake
@@ -42,7 +43,7 @@ pub async fn paired_terminal_ws(
         // itself is not echoed, only the marker.
         Some(tmux_name) => ws
             .protocols(["aoe-auth"])
-            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only))
+            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only, metrics))
             .into_response(),
         None => (axum::http::StatusCode::NOT_FOUND, "Session not found").into_response(),
     }
@@ -62,6 +63,7 @@ pub async fn container_terminal_ws(
     drop(instances);
 
     let read_only = state.read_only;
+    let metrics = Arc::clone(&state.server_metrics);
 
     match session_info {
         // Accept the "aoe-auth" subprotocol so the browser's handshake
@@ -71,7 +73,7 @@ pub async fn container_terminal_ws(
         // itself is not echoed, only the marker.
         Some(tmux_name) => ws
             .protocols(["aoe-auth"])
-            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only))
+            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only, metrics))
             .into_response(),
         None => (axum::http::StatusCode::NOT_FOUND, "Session not found").into_response(),
     }
@@ -92,6 +94,7 @@ pub async fn terminal_ws(
     drop(instances);
 
     let read_only = state.read_only;
+    let metrics = Arc::clone(&state.server_metrics);
 
     match session_info {
         // Accept the "aoe-auth" subprotocol so the browser's handshake
@@ -101,15 +104,22 @@ pub async fn terminal_ws(
         // itself is not echoed, only the marker.
         Some(tmux_name) => ws
             .protocols(["aoe-auth"])
-            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only))
+            .on_upgrade(move |socket| handle_terminal_ws(socket, tmux_name, read_only, metrics))
             .into_response(),
         None => (axum::http::StatusCode::NOT_FOUND, "Session not found").into_response(),
     }
 }
 
-async fn handle_terminal_ws(socket: WebSocket, tmux_name: String, read_only: bool) {
+async fn handle_terminal_ws(
+    socket: WebSocket,
+    tmux_name: String,
+    read_only: bool,
+    metrics: Arc<super::prometheus::ServerMetrics>,
+) {
     use futures_util::{SinkExt, StreamExt};
 
+    let _active = metrics.terminal_opened();
+
     // Spawn tmux attach inside a PTY
     let pty_system = NativePtySystem::default();
     let pair = match pty_system.openpty(PtySize {
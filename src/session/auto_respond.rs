@@ -0,0 +1,790 @@
+//! Auto-responder - rule-based answers to agent permission prompts
+//!
+//! `[[auto_respond.rules]]` in the global, profile and repo config describe
+//! which Waiting prompts aoe may answer on the user's behalf. When a session
+//! starts and any rule applies to its agent, a hidden `aoe tmux auto-respond`
+//! process (launched through `tmux run-shell -b`, like message queue
+//! delivery) watches its status. Each time the agent turns Waiting it
+//! captures the visible pane, reads the payloads the agent passed to its
+//! status hooks, and checks the rules in order: the first match sends its
+//! keys or leaves the prompt to the user, who is notified by sound, desktop
+//! notification and Web Push as configured. Profile and repo rules are
+//! checked before the ones they override, except that inherited `deny` and
+//! `notify` rules always come first, so a cloned repo can't approve what the
+//! global config holds back. A repo's approvals need the same trust as its
+//! hooks. Every decision is appended to `auto_respond.jsonl` in the profile
+//! directory.
+//!
+//! The watcher holds a lock on `auto_respond/<session id>.lock` while it
+//! runs, so restarting a session never leaves two answering the same prompt.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use super::{get_profile_dir, Instance, Status, Storage};
+use crate::tmux;
+
+const LEASE_DIR: &str = "auto_respond";
+
+const LOG_FILE: &str = "auto_respond.jsonl";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest matched text kept in a decision log entry.
+const MAX_MATCHED_CHARS: usize = 200;
+
+/// Auto-responder configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoRespondConfig {
+    /// Answer Waiting prompts that match a rule
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Checked in order; the first matching rule decides
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AutoRespondRule>,
+}
+
+impl Default for AutoRespondConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Profile and repo override for the auto-responder. Rules are added to the
+/// ones they override rather than replacing them: they're checked after the
+/// inherited `deny` and `notify` rules and before the inherited approvals.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutoRespondConfigOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<AutoRespondRule>,
+}
+
+impl AutoRespondConfigOverride {
+    /// What the override does beyond holding prompts back, one line each:
+    /// turning the responder on and rules that answer prompts. Repos need
+    /// the user's trust for these.
+    pub fn trust_summary(&self) -> Vec<String> {
+        let enable = (self.enabled == Some(true)).then(|| "enabled = true".to_string());
+        enable
+            .into_iter()
+            .chain(
+                self.rules
+                    .iter()
+                    .filter(|r| !r.action.holds_back())
+                    .map(AutoRespondRule::describe),
+            )
+            .collect()
+    }
+
+    /// Drop everything `trust_summary` lists, keeping `deny` and `notify`
+    /// rules and `enabled = false`.
+    pub fn hold_back_only(&mut self) {
+        if self.enabled == Some(true) {
+            self.enabled = None;
+        }
+        self.rules.retain(|r| r.action.holds_back());
+    }
+}
+
+pub fn apply_auto_respond_overrides(
+    target: &mut AutoRespondConfig,
+    source: &AutoRespondConfigOverride,
+) {
+    if let Some(enabled) = source.enabled {
+        target.enabled = enabled;
+    }
+    if !source.rules.is_empty() {
+        let (held_back, answered): (Vec<_>, Vec<_>) = std::mem::take(&mut target.rules)
+            .into_iter()
+            .partition(|r| r.action.holds_back());
+        target.rules = held_back
+            .into_iter()
+            .chain(source.rules.iter().cloned())
+            .chain(answered)
+            .collect();
+    }
+}
+
+/// What to do with a prompt a rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Accept the prompt's default choice (Enter)
+    Approve,
+    /// Dismiss the prompt (Escape)
+    Deny,
+    /// Send the rule's `keys`
+    Keys,
+    /// Send nothing and leave the prompt to the user
+    Notify,
+}
+
+impl RuleAction {
+    /// Whether the action keeps the prompt from being accepted
+    pub fn holds_back(self) -> bool {
+        matches!(self, RuleAction::Deny | RuleAction::Notify)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RuleAction::Approve => "approve",
+            RuleAction::Deny => "deny",
+            RuleAction::Keys => "keys",
+            RuleAction::Notify => "notify",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AutoRespondRule {
+    /// Shown in the decision log instead of the rule's position
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Only apply to this agent (e.g. `claude`); all agents when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    /// Regex that must match the visible pane text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pane: Option<String>,
+
+    /// Regex that must match the JSON payload of the agent's latest hooks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+
+    pub action: RuleAction,
+
+    /// tmux key names to send, e.g. `["2"]` or `["Down", "Enter"]`. Required
+    /// for `keys`; replaces the default keys of `approve` and `deny`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+}
+
+impl AutoRespondRule {
+    fn applies_to(&self, agent: &str) -> bool {
+        self.agent.as_deref().is_none_or(|a| a == agent)
+    }
+
+    /// One line with everything that decides what the rule matches and
+    /// sends, e.g. `approve, pane = "cargo test"`.
+    fn describe(&self) -> String {
+        let mut parts = vec![self.action.label().to_string()];
+        if let Some(agent) = &self.agent {
+            parts.push(format!("agent = {}", agent));
+        }
+        if let Some(pane) = &self.pane {
+            parts.push(format!("pane = {:?}", pane));
+        }
+        if let Some(payload) = &self.payload {
+            parts.push(format!("payload = {:?}", payload));
+        }
+        if !self.keys.is_empty() {
+            parts.push(format!("keys = {:?}", self.keys));
+        }
+        parts.join(", ")
+    }
+
+    /// Keys sent when this rule matches; empty for `notify`.
+    pub fn key_sequence(&self) -> Vec<String> {
+        match self.action {
+            RuleAction::Notify => Vec::new(),
+            _ if !self.keys.is_empty() => self.keys.clone(),
+            RuleAction::Approve => vec!["Enter".to_string()],
+            RuleAction::Deny => vec!["Escape".to_string()],
+            RuleAction::Keys => Vec::new(),
+        }
+    }
+}
+
+/// The rules that apply to one agent, with their regexes compiled.
+pub struct RuleSet {
+    rules: Vec<CompiledRule>,
+}
+
+struct CompiledRule {
+    label: String,
+    rule: AutoRespondRule,
+    pane: Option<Regex>,
+    payload: Option<Regex>,
+}
+
+/// A rule that matched a prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub action: RuleAction,
+    pub keys: Vec<String>,
+    /// Text matched by the pane regex, or the payload regex without one
+    pub matched: String,
+}
+
+impl RuleSet {
+    /// Rules from `config` that apply to `agent`. Rules with an invalid
+    /// regex, or a `keys` action without keys, are skipped with a warning.
+    pub fn new(config: &AutoRespondConfig, agent: &str) -> Self {
+        if !config.enabled {
+            return Self { rules: Vec::new() };
+        }
+        let compile = |label: &str, pattern: &Option<String>| -> Option<Option<Regex>> {
+            match pattern.as_deref().map(Regex::new).transpose() {
+                Ok(regex) => Some(regex),
+                Err(e) => {
+                    tracing::warn!("Skipping auto-respond {}: invalid regex: {}", label, e);
+                    None
+                }
+            }
+        };
+
+        let rules = config
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.applies_to(agent))
+            .filter_map(|(i, rule)| {
+                let label = rule
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("rule {}", i + 1));
+                if rule.action == RuleAction::Keys && rule.keys.is_empty() {
+                    tracing::warn!(
+                        "Skipping auto-respond {}: action \"keys\" needs keys",
+                        label
+                    );
+                    return None;
+                }
+                Some(CompiledRule {
+                    pane: compile(&label, &rule.pane)?,
+                    payload: compile(&label, &rule.payload)?,
+                    label,
+                    rule: rule.clone(),
+                })
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The first rule whose regexes all match.
+    pub fn evaluate(&self, pane: &str, payload: &str) -> Option<RuleMatch> {
+        self.rules.iter().find_map(|compiled| {
+            let payload_match = match &compiled.payload {
+                Some(regex) => Some(regex.find(payload)?.as_str()),
+                None => None,
+            };
+            let pane_match = match &compiled.pane {
+                Some(regex) => Some(regex.find(pane)?.as_str()),
+                None => None,
+            };
+            let matched: String = pane_match
+                .or(payload_match)
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_MATCHED_CHARS)
+                .collect();
+            Some(RuleMatch {
+                rule: compiled.label.clone(),
+                action: compiled.rule.action,
+                keys: compiled.rule.key_sequence(),
+                matched,
+            })
+        })
+    }
+}
+
+/// Tells the user about a prompt a `notify` rule left for them.
+pub trait Notifier {
+    fn prompt_held(&self, inst: &Instance, hit: &RuleMatch);
+}
+
+/// The user's configured waiting sound and desktop notifications, and a Web
+/// Push when `aoe serve` is running.
+struct UserNotifier {
+    sound: crate::sound::SoundConfig,
+    desktop: crate::desktop_notifications::DesktopNotificationsConfig,
+}
+
+impl Notifier for UserNotifier {
+    fn prompt_held(&self, inst: &Instance, hit: &RuleMatch) {
+        crate::sound::play_for_transition(Status::Running, Status::Waiting, &self.sound);
+        crate::desktop_notifications::prompt_held(inst, &hit.rule, &self.desktop);
+
+        #[cfg(feature = "serve")]
+        if crate::cli::serve::daemon_pid().is_some() {
+            let payload = crate::server::push_send::PushPayload {
+                title: "Prompt needs you".to_string(),
+                body: format!("{}: {}", hit.rule, inst.title),
+                url: format!("/?session={}", inst.id),
+                tag: format!("session-{}", inst.id),
+                session_id: inst.id.clone(),
+            };
+            let sent = super::get_app_dir()
+                .and_then(|dir| crate::server::push::send_blocking(&dir, &payload));
+            if let Err(e) = sent {
+                tracing::warn!("Auto-respond push failed: {:#}", e);
+            }
+        }
+    }
+}
+
+/// The first rule matching a Waiting prompt, if any. The user is notified
+/// of `notify` matches; sending keys is left to the caller.
+fn decide(
+    rules: &RuleSet,
+    inst: &Instance,
+    pane: &str,
+    payload: &str,
+    notifier: &dyn Notifier,
+) -> Option<RuleMatch> {
+    let hit = rules.evaluate(pane, payload)?;
+    if hit.action == RuleAction::Notify {
+        notifier.prompt_held(inst, &hit);
+    }
+    Some(hit)
+}
+
+/// One automatic decision, as logged to `auto_respond.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    pub session_id: String,
+    pub at: DateTime<Utc>,
+    pub rule: String,
+    pub action: RuleAction,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub matched: String,
+}
+
+pub fn log_path(profile: &str) -> Result<PathBuf> {
+    Ok(get_profile_dir(profile)?.join(LOG_FILE))
+}
+
+/// All logged decisions for one session, oldest first.
+pub fn load(profile: &str, session_id: &str) -> Result<Vec<Decision>> {
+    load_from(&log_path(profile)?, session_id)
+}
+
+/// Whether any enabled rule applies to a session with this agent and
+/// project (global > profile > repo config).
+pub fn is_enabled(profile: &str, project_path: &Path, agent: &str) -> bool {
+    super::repo_config::resolve_config_with_repo(profile, project_path).is_ok_and(|c| {
+        c.auto_respond.enabled && c.auto_respond.rules.iter().any(|r| r.applies_to(agent))
+    })
+}
+
+/// Launch the background watcher for a session.
+pub fn start(session: &tmux::Session, profile: &str, session_id: &str) -> Result<()> {
+    super::initial_prompt::run_aoe_background(
+        session,
+        &[
+            "tmux",
+            "auto-respond",
+            "--profile",
+            profile,
+            "--session",
+            session_id,
+        ],
+    )
+}
+
+/// Delete a session's watcher lock file, for when the session is deleted.
+pub fn clear(profile: &str, session_id: &str) -> Result<()> {
+    let path = lease_path(profile, session_id)?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Answer the session's prompts until its tmux session is gone or it is
+/// deleted. Rules are read once, so config changes apply from the next
+/// start. Returns the number of decisions made; 0 right away if another
+/// watcher is running or no rule applies.
+pub fn watch(profile: &str, session_id: &str) -> Result<usize> {
+    let Some(_lease) = try_lease(&lease_path(profile, session_id)?)? else {
+        return Ok(0);
+    };
+    let storage = Storage::new(profile)?;
+    let Some(mut inst) = find_instance(&storage, session_id)? else {
+        return Ok(0);
+    };
+    let config =
+        super::repo_config::resolve_config_with_repo(profile, Path::new(&inst.project_path))?;
+    let rules = RuleSet::new(&config.auto_respond, &inst.tool);
+    if rules.is_empty() {
+        return Ok(0);
+    }
+    let notifier = UserNotifier {
+        sound: config.sound,
+        desktop: config.desktop_notifications,
+    };
+
+    let log = log_path(profile)?;
+    let mut was_waiting = false;
+    let mut decided = 0;
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+
+        if find_instance(&storage, session_id)?.is_none() {
+            return Ok(decided);
+        }
+        let session = inst.tmux_session()?;
+        if !session.exists() {
+            return Ok(decided);
+        }
+        inst.update_status_with_metadata(None);
+        let waiting = inst.status == Status::Waiting;
+        if waiting && !was_waiting {
+            let pane = tmux::utils::strip_ansi(&session.capture_pane(0)?);
+            let payload = read_hook_payloads(session_id);
+            if let Some(hit) = decide(&rules, &inst, &pane, &payload, &notifier) {
+                tracing::info!(
+                    "Auto-respond {} for '{}': {} {:?}",
+                    hit.rule,
+                    inst.title,
+                    hit.action.label(),
+                    hit.keys
+                );
+                if !hit.keys.is_empty() {
+                    session.send_key_names(&hit.keys)?;
+                }
+                append_to(
+                    &log,
+                    &Decision {
+                        session_id: session_id.to_string(),
+                        at: Utc::now(),
+                        rule: hit.rule,
+                        action: hit.action,
+                        keys: hit.keys,
+                        matched: hit.matched,
+                    },
+                )?;
+                decided += 1;
+            }
+        }
+        was_waiting = waiting;
+    }
+}
+
+fn find_instance(storage: &Storage, session_id: &str) -> Result<Option<Instance>> {
+    Ok(storage.load()?.into_iter().find(|i| i.id == session_id))
+}
+
+/// The JSON the agent passed to each of its status hooks, latest per event,
+/// one per line in event name order.
+fn read_hook_payloads(session_id: &str) -> String {
+    let Ok(entries) = fs::read_dir(crate::hooks::hook_status_dir(session_id)) else {
+        return String::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .map(|s| s.trim().to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn lease_path(profile: &str, session_id: &str) -> Result<PathBuf> {
+    Ok(get_profile_dir(profile)?
+        .join(LEASE_DIR)
+        .join(format!("{}.lock", session_id)))
+}
+
+/// Take the watcher lock at `path`, or `None` if another process holds it.
+/// Released when the returned file is dropped.
+fn try_lease(path: &Path) -> Result<Option<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    Ok(FileExt::try_lock_exclusive(&file).ok().map(|_| file))
+}
+
+fn append_to(path: &Path, decision: &Decision) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    FileExt::lock_exclusive(&file)?;
+    let result = writeln!(file, "{}", serde_json::to_string(decision)?);
+    let _ = FileExt::unlock(&file);
+    Ok(result?)
+}
+
+fn load_from(path: &Path, session_id: &str) -> Result<Vec<Decision>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(File::open(path)?);
+    Ok(reader
+        .lines()
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<Decision>(&line).ok())
+        .filter(|d| d.session_id == session_id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn config(toml_src: &str) -> AutoRespondConfig {
+        toml::from_str(toml_src).unwrap()
+    }
+
+    const PROMPT: &str =
+        "Bash command\n  cargo test --workspace\nDo you want to proceed?\n❯ 1. Yes\n  2. No";
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let config = config(
+            r#"
+            [[rules]]
+            name = "never rm -rf"
+            pane = "rm -rf"
+            action = "notify"
+
+            [[rules]]
+            name = "tests"
+            pane = "cargo test"
+            action = "approve"
+
+            [[rules]]
+            pane = "Do you want to proceed"
+            action = "deny"
+            "#,
+        );
+        let rules = RuleSet::new(&config, "claude");
+
+        let hit = rules.evaluate(PROMPT, "").unwrap();
+        assert_eq!(hit.rule, "tests");
+        assert_eq!(hit.action, RuleAction::Approve);
+        assert_eq!(hit.keys, vec!["Enter"]);
+        assert_eq!(hit.matched, "cargo test");
+
+        let hit = rules
+            .evaluate(
+                &PROMPT.replace("cargo test --workspace", "rm -rf target"),
+                "",
+            )
+            .unwrap();
+        assert_eq!(hit.rule, "never rm -rf");
+        assert!(hit.keys.is_empty());
+
+        let hit = rules
+            .evaluate(&PROMPT.replace("cargo test", "npm test"), "")
+            .unwrap();
+        assert_eq!(hit.rule, "rule 3");
+        assert_eq!(hit.keys, vec!["Escape"]);
+
+        assert!(rules.evaluate("$ ", "").is_none());
+    }
+
+    #[test]
+    fn test_agent_and_payload_filters() {
+        let config = config(
+            r#"
+            [[rules]]
+            agent = "codex"
+            action = "approve"
+
+            [[rules]]
+            payload = '"tool_name":"Bash".*"command":"cargo '
+            action = "keys"
+            keys = ["2"]
+            "#,
+        );
+        let rules = RuleSet::new(&config, "claude");
+        assert!(rules.evaluate(PROMPT, "").is_none());
+
+        let payload = r#"{"tool_name":"Bash","tool_input":{"command":"cargo build"}}"#;
+        let hit = rules.evaluate(PROMPT, payload).unwrap();
+        assert_eq!(hit.rule, "rule 2");
+        assert_eq!(hit.keys, vec!["2"]);
+        assert!(hit.matched.starts_with("\"tool_name\""));
+
+        let codex = RuleSet::new(&config, "codex");
+        assert_eq!(codex.evaluate("", "").unwrap().rule, "rule 1");
+    }
+
+    #[test]
+    fn test_invalid_rules_are_skipped() {
+        let config = config(
+            r#"
+            [[rules]]
+            pane = "("
+            action = "approve"
+
+            [[rules]]
+            action = "keys"
+            "#,
+        );
+        assert!(RuleSet::new(&config, "claude").is_empty());
+
+        let mut disabled = AutoRespondConfig::default();
+        disabled.rules.push(AutoRespondRule {
+            name: None,
+            agent: None,
+            pane: None,
+            payload: None,
+            action: RuleAction::Approve,
+            keys: Vec::new(),
+        });
+        assert!(!RuleSet::new(&disabled, "claude").is_empty());
+        disabled.enabled = false;
+        assert!(RuleSet::new(&disabled, "claude").is_empty());
+    }
+
+    #[test]
+    fn test_override_rules_come_after_inherited_holds() {
+        let mut target = config(
+            r#"
+            [[rules]]
+            name = "global approve"
+            action = "approve"
+
+            [[rules]]
+            name = "global notify"
+            action = "notify"
+            "#,
+        );
+        let source: AutoRespondConfigOverride = toml::from_str(
+            r#"
+            [[rules]]
+            name = "repo"
+            action = "keys"
+            keys = ["2"]
+            "#,
+        )
+        .unwrap();
+        apply_auto_respond_overrides(&mut target, &source);
+        let names: Vec<_> = target
+            .rules
+            .iter()
+            .map(|r| r.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(names, vec!["global notify", "repo", "global approve"]);
+        assert!(target.enabled);
+    }
+
+    #[test]
+    fn test_repo_approve_does_not_override_global_deny() {
+        let global = config(
+            r#"
+            [[rules]]
+            name = "no rm"
+            pane = "rm -rf"
+            action = "deny"
+            "#,
+        );
+        let repo: super::super::RepoConfig = toml::from_str(
+            r#"
+            [[auto_respond.rules]]
+            name = "everything"
+            pane = "."
+            action = "approve"
+            "#,
+        )
+        .unwrap();
+        let base = super::super::Config {
+            auto_respond: global,
+            ..Default::default()
+        };
+        let merged = super::super::merge_repo_config(base, &repo);
+        let rules = RuleSet::new(&merged.auto_respond, "claude");
+
+        let hit = rules.evaluate("Run rm -rf build/?", "").unwrap();
+        assert_eq!(hit.rule, "no rm");
+        assert_eq!(hit.action, RuleAction::Deny);
+        let hit = rules.evaluate("Run cargo test?", "").unwrap();
+        assert_eq!(hit.action, RuleAction::Approve);
+    }
+
+    #[derive(Default)]
+    struct RecordingNotifier(std::cell::RefCell<Vec<String>>);
+
+    impl Notifier for RecordingNotifier {
+        fn prompt_held(&self, inst: &Instance, hit: &RuleMatch) {
+            self.0
+                .borrow_mut()
+                .push(format!("{}: {}", inst.title, hit.rule));
+        }
+    }
+
+    #[test]
+    fn test_notify_hit_reaches_notifier() {
+        let config = config(
+            r#"
+            [[rules]]
+            name = "no rm"
+            pane = "rm -rf"
+            action = "notify"
+
+            [[rules]]
+            name = "tests"
+            pane = "cargo test"
+            action = "approve"
+            "#,
+        );
+        let rules = RuleSet::new(&config, "claude");
+        let inst = Instance::new("fix-login", "/src/app");
+        let notifier = RecordingNotifier::default();
+
+        let hit = decide(&rules, &inst, "Run cargo test?", "", &notifier).unwrap();
+        assert_eq!(hit.action, RuleAction::Approve);
+        assert!(notifier.0.borrow().is_empty());
+
+        let hit = decide(&rules, &inst, "Run rm -rf build/?", "", &notifier).unwrap();
+        assert_eq!(hit.action, RuleAction::Notify);
+        assert!(hit.keys.is_empty());
+        assert_eq!(*notifier.0.borrow(), vec!["fix-login: no rm"]);
+    }
+
+    #[test]
+    fn test_decision_log_round_trip() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join(LOG_FILE);
+        let decision = |session_id: &str| Decision {
+            session_id: session_id.to_string(),
+            at: Utc::now(),
+            rule: "tests".to_string(),
+            action: RuleAction::Approve,
+            keys: vec!["Enter".to_string()],
+            matched: "cargo test".to_string(),
+        };
+        let first = decision("abc");
+        append_to(&path, &first)?;
+        append_to(&path, &decision("other"))?;
+        assert_eq!(load_from(&path, "abc")?, vec![first]);
+        Ok(())
+    }
+}
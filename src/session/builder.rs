@@ -37,6 +37,11 @@ pub struct InstanceParams {
     pub command_override: String,
     /// Additional repository paths for multi-repo workspace mode
     pub extra_repo_paths: Vec<String>,
+    /// Initial prompt to send once the agent is first ready
+    pub prompt: Option<String>,
+    /// Session template whose worktree path template and custom instruction
+    /// apply. Its other fields are expected to be resolved by the caller.
+    pub template: Option<String>,
 }
 
 /// Result of building an instance, tracking what was created for cleanup purposes.
@@ -208,12 +213,17 @@ pub fn build_instance(
         }
     }
 
-    let config =
+    let mut config =
         super::repo_config::resolve_config_with_repo(profile, std::path::Path::new(&params.path))
             .unwrap_or_else(|e| {
                 tracing::warn!("Failed to load config, using defaults: {}", e);
                 Config::default()
             });
+    if let Some(ref name) = params.template {
+        super::templates::find(&config, name)?
+            .clone()
+            .apply_to_config(&mut config);
+    }
 
     let mut final_path = PathBuf::from(&params.path)
         .canonicalize()
@@ -369,6 +379,25 @@ pub fn build_instance(
         }
     }
 
+    instance.initial_prompt = params.prompt.filter(|p| !p.trim().is_empty());
+
+    match config
+        .session
+        .ports
+        .names()
+        .and_then(|names| super::ports::allocate(&instance.id, &names))
+    {
+        Ok(ports) => instance.ports = ports,
+        Err(e) => {
+            cleanup_instance(
+                &instance,
+                created_worktree.as_ref(),
+                &created_workspace_worktrees,
+            );
+            return Err(e);
+        }
+    }
+
     if params.sandbox {
         instance.sandbox_info = Some(SandboxInfo {
             enabled: true,
@@ -382,6 +411,10 @@ pub fn build_instance(
                 Some(params.extra_env.clone())
             },
             custom_instruction: config.sandbox.custom_instruction.clone(),
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         });
     }
 
@@ -397,6 +430,7 @@ pub fn build_instance(
 /// This handles:
 /// - Removing worktrees created by aoe
 /// - Removing Docker containers
+/// - Releasing reserved ports
 /// - Killing tmux sessions
 pub fn cleanup_instance(
     instance: &Instance,
@@ -435,6 +469,12 @@ pub fn cleanup_instance(
         }
     }
 
+    if !instance.ports.is_empty() {
+        if let Err(e) = super::ports::release(&instance.id) {
+            tracing::warn!("Failed to release session ports: {}", e);
+        }
+    }
+
     let _ = instance.kill();
 }
 
@@ -0,0 +1,592 @@
+//! Session bundles - move a session between machines or profiles
+//!
+//! `aoe session export` packs a session into a `.tar.gz` with:
+//! - `manifest.json`: the session's `Instance` (including its sandbox
+//!   settings and custom instruction) and the git state of its branch
+//! - `commits.bundle`: a git bundle of the branch's commits since it left
+//!   the base branch (or its whole history if there is no common base)
+//! - `changes.patch`: uncommitted changes, untracked files included
+//! - `recording.cast`: the session recording, if there is one
+//!
+//! `aoe session import` fetches the commits into a local clone, checks the
+//! branch out in a new worktree, applies the patch and adds the session
+//! under a new ID.
+
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use super::instance::generate_id;
+use super::{ports, recording, Config, Instance, Status, WorktreeInfo};
+use crate::containers;
+use crate::git::GitWorktree;
+
+/// Bumped when the archive layout changes incompatibly.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const COMMITS_FILE: &str = "commits.bundle";
+const PATCH_FILE: &str = "changes.patch";
+const RECORDING_FILE: &str = "recording.cast";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub instance: Instance,
+    /// Absent when the session's directory is not a git repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<GitState>,
+    #[serde(default)]
+    pub has_recording: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitState {
+    pub branch: String,
+    pub head: String,
+    /// Last commit shared with the base branch. The importing repository
+    /// must already have it; `None` means the bundle holds all history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Commits in the bundle (after `base`)
+    pub commits: usize,
+    pub has_patch: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Repository to recreate the worktree in. Defaults to the original
+    /// repository if it exists on this machine.
+    pub repo_path: Option<PathBuf>,
+    /// Import the branch under a different name
+    pub branch: Option<String>,
+    pub title: Option<String>,
+}
+
+/// Write `inst` to a bundle at `output`.
+pub fn export(inst: &Instance, profile: &str, config: &Config, output: &Path) -> Result<Manifest> {
+    if inst.workspace_info.is_some() {
+        bail!("Exporting multi-repo workspace sessions is not supported");
+    }
+
+    let staging = tempfile::tempdir()?;
+    let project_path = Path::new(&inst.project_path);
+    let git = if GitWorktree::is_git_repo(project_path) {
+        Some(export_git(inst, config, staging.path())?)
+    } else {
+        None
+    };
+
+    let recording_path = recording::recording_path(profile, &inst.id)?;
+    let manifest = Manifest {
+        version: FORMAT_VERSION,
+        exported_at: Utc::now(),
+        instance: inst.clone(),
+        git,
+        has_recording: recording_path.exists(),
+    };
+
+    let file =
+        File::create(output).with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(manifest.exported_at.timestamp().max(0) as u64);
+    archive.append_data(&mut header, MANIFEST_FILE, json.as_slice())?;
+    for name in [COMMITS_FILE, PATCH_FILE] {
+        let path = staging.path().join(name);
+        if path.exists() {
+            archive.append_path_with_name(&path, name)?;
+        }
+    }
+    if manifest.has_recording {
+        archive.append_path_with_name(&recording_path, RECORDING_FILE)?;
+    }
+    archive.into_inner()?.finish()?;
+
+    Ok(manifest)
+}
+
+fn export_git(inst: &Instance, config: &Config, staging: &Path) -> Result<GitState> {
+    let path = Path::new(&inst.project_path);
+    let head = git(path, &["rev-parse", "HEAD"])?;
+    let branch = match &inst.worktree_info {
+        Some(info) => info.branch.clone(),
+        None => git(path, &["symbolic-ref", "--short", "HEAD"])
+            .context("Session is on a detached HEAD; check out a branch before exporting")?,
+    };
+
+    let base_branch = config
+        .diff
+        .default_branch
+        .clone()
+        .or_else(|| crate::git::diff::get_default_branch(path).ok())
+        .filter(|b| *b != branch)
+        // Working on the base branch itself: ship what hasn't been pushed
+        .unwrap_or_else(|| format!("{}@{{upstream}}", branch));
+    let base = git(path, &["merge-base", "HEAD", &base_branch]).ok();
+
+    let range = match &base {
+        Some(base) => format!("{}..HEAD", base),
+        None => "HEAD".to_string(),
+    };
+    let commits: usize = git(path, &["rev-list", "--count", &range])?
+        .parse()
+        .unwrap_or(0);
+    if commits > 0 {
+        let bundle = staging.join(COMMITS_FILE);
+        git(
+            path,
+            &["bundle", "create", &bundle.to_string_lossy(), &range],
+        )?;
+    }
+
+    let patch = uncommitted_changes(path, staging)?;
+    let has_patch = !patch.is_empty();
+    if has_patch {
+        fs::write(staging.join(PATCH_FILE), patch)?;
+    }
+
+    Ok(GitState {
+        branch,
+        head,
+        base,
+        commits,
+        has_patch,
+    })
+}
+
+/// Diff of the working tree against HEAD, untracked files included. Files
+/// are staged into a copy of the index so the session's own index is left
+/// untouched.
+fn uncommitted_changes(path: &Path, staging: &Path) -> Result<Vec<u8>> {
+    let index = staging.join("index");
+    let real_index = git(
+        path,
+        &["rev-parse", "--path-format=absolute", "--git-path", "index"],
+    )?;
+    if Path::new(&real_index).exists() {
+        fs::copy(&real_index, &index)?;
+    }
+
+    let run = |args: &[&str]| -> Result<Vec<u8>> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(path)
+            .env("GIT_INDEX_FILE", &index)
+            .output()?;
+        if !output.status.success() {
+            bail!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(output.stdout)
+    };
+    run(&["add", "--all"])?;
+    let patch = run(&["diff", "--cached", "--binary", "HEAD"])?;
+    fs::remove_file(&index)?;
+    Ok(patch)
+}
+
+/// Recreate the session in `archive` for `profile`. The returned instance
+/// has a new ID and is not yet saved.
+pub fn import(archive: &Path, profile: &str, opts: &ImportOptions) -> Result<Instance> {
+    let staging = tempfile::tempdir()?;
+    unpack(archive, staging.path())?;
+    let manifest = load_manifest(staging.path())?;
+
+    let mut instance = manifest.instance;
+    instance.id = generate_id();
+    if let Some(title) = &opts.title {
+        instance.title = title.clone();
+    }
+
+    match &manifest.git {
+        Some(state) => {
+            let repo = target_repo(&instance, opts)?;
+            let branch = opts.branch.as_deref().unwrap_or(&state.branch);
+            let config = super::repo_config::resolve_config_with_repo(profile, &repo)?;
+            let (path, managed) =
+                restore_worktree(&repo, state, branch, staging.path(), &config, &instance.id)?;
+            instance.project_path = path.to_string_lossy().to_string();
+            instance.worktree_info = Some(WorktreeInfo {
+                branch: branch.to_string(),
+                main_repo_path: repo.to_string_lossy().to_string(),
+                managed_by_aoe: managed,
+                created_at: Utc::now(),
+            });
+        }
+        None => {
+            let path = opts
+                .repo_path
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(&instance.project_path));
+            if !path.is_dir() {
+                bail!(
+                    "Project directory {} does not exist on this machine\nTip: pass --path to import into another directory",
+                    path.display()
+                );
+            }
+            instance.project_path = path.to_string_lossy().to_string();
+        }
+    }
+
+    instance.status = Status::Stopped;
+    instance.parent_session_id = None;
+    instance.last_accessed_at = None;
+    instance.terminal_info = None;
+    instance.source_profile = profile.to_string();
+    if let Some(sandbox) = instance.sandbox_info.as_mut() {
+        sandbox.container_id = None;
+        sandbox.created_at = None;
+        sandbox.container_name = containers::DockerContainer::generate_name(&instance.id);
+    }
+
+    if manifest.has_recording {
+        let cast = staging.path().join(RECORDING_FILE);
+        let target = recording::recording_path(profile, &instance.id)?;
+        if cast.exists() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(&cast, &target)?;
+        }
+    }
+
+    // Ports from the other machine may be taken here; reserve new ones under
+    // the same names
+    let port_names: Vec<String> = instance.ports.iter().map(|p| p.name.clone()).collect();
+    instance.ports = ports::allocate(&instance.id, &port_names)?;
+
+    Ok(instance)
+}
+
+/// The main repository to import into: `--path`, else the one the session
+/// came from if it exists here.
+fn target_repo(instance: &Instance, opts: &ImportOptions) -> Result<PathBuf> {
+    let path = match &opts.repo_path {
+        Some(path) => path.clone(),
+        None => instance
+            .worktree_info
+            .as_ref()
+            .map(|wt| PathBuf::from(&wt.main_repo_path))
+            .filter(|p| p.exists())
+            .unwrap_or_else(|| PathBuf::from(&instance.project_path)),
+    };
+    if !GitWorktree::is_git_repo(&path) {
+        bail!(
+            "{} is not a git repository on this machine\nTip: pass --path with a local clone of the session's repository",
+            path.display()
+        );
+    }
+    let main_repo = GitWorktree::find_main_repo(&path)?;
+    Ok(main_repo.canonicalize().unwrap_or(main_repo))
+}
+
+/// Bring the branch into `repo` and check it out in a new worktree, then
+/// apply the uncommitted changes. If the branch is already checked out at
+/// the exported commit, the changes are applied to that worktree instead,
+/// which must be clean. Returns the worktree path and whether aoe created it.
+fn restore_worktree(
+    repo: &Path,
+    state: &GitState,
+    branch: &str,
+    staging: &Path,
+    config: &Config,
+    session_id: &str,
+) -> Result<(PathBuf, bool)> {
+    let git_wt = GitWorktree::new(repo.to_path_buf())?;
+    let branch_ref = format!("refs/heads/{}", branch);
+
+    let created_branch = match git(repo, &["rev-parse", "--verify", "--quiet", &branch_ref]) {
+        Ok(tip) => {
+            if tip != state.head {
+                bail!(
+                    "Branch '{}' already exists in {} at a different commit\nTip: use --branch to import under another name",
+                    branch,
+                    repo.display()
+                );
+            }
+            if let Some(existing) = git_wt
+                .list_worktrees()?
+                .into_iter()
+                .find(|wt| wt.branch.as_deref() == Some(branch))
+            {
+                if state.has_patch {
+                    if !git(&existing.path, &["status", "--porcelain"])?.is_empty() {
+                        bail!(
+                            "Branch '{}' is checked out at {} with uncommitted changes\nTip: commit or stash them, or use --branch to import under another name",
+                            branch,
+                            existing.path.display()
+                        );
+                    }
+                    apply_patch(&existing.path, staging)?;
+                }
+                return Ok((existing.path, false));
+            }
+            false
+        }
+        Err(_) => {
+            let missing_base = || {
+                match &state.base {
+                Some(base) => format!(
+                    "{} does not have commit {}, which the session's branch starts from\nTip: fetch the upstream branch first",
+                    repo.display(),
+                    &base[..base.len().min(12)]
+                ),
+                None => format!("Failed to restore branch '{}'", branch),
+            }
+            };
+            if state.commits > 0 {
+                let bundle = staging.join(COMMITS_FILE);
+                git(
+                    repo,
+                    &[
+                        "fetch",
+                        "--no-tags",
+                        &bundle.to_string_lossy(),
+                        &format!("HEAD:{}", branch_ref),
+                    ],
+                )
+                .with_context(missing_base)?;
+            } else {
+                git(repo, &["branch", branch, &state.head]).with_context(missing_base)?;
+            }
+            true
+        }
+    };
+
+    let template = if GitWorktree::is_bare_repo(repo) {
+        &config.worktree.bare_repo_path_template
+    } else {
+        &config.worktree.path_template
+    };
+    let path = git_wt.compute_path(branch, template, &session_id[..8])?;
+    let restored = git_wt
+        .create_worktree(branch, &path, false)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| {
+            if state.has_patch {
+                apply_patch(&path, staging)?;
+            }
+            Ok(())
+        });
+
+    if let Err(e) = restored {
+        if path.exists() {
+            let _ = git_wt.remove_worktree(&path, true);
+        }
+        if created_branch {
+            let _ = git_wt.delete_branch(branch);
+        }
+        return Err(e);
+    }
+
+    Ok((path, true))
+}
+
+/// Apply the bundle's uncommitted changes to `worktree`. Nothing is applied
+/// unless all of it applies.
+fn apply_patch(worktree: &Path, staging: &Path) -> Result<()> {
+    let patch = staging.join(PATCH_FILE);
+    git(worktree, &["apply", "--binary", &patch.to_string_lossy()])
+        .context("Failed to apply uncommitted changes")?;
+    Ok(())
+}
+
+fn unpack(archive: &Path, dest: &Path) -> Result<()> {
+    let file =
+        File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(dest)
+        .with_context(|| format!("{} is not a session bundle", archive.display()))
+}
+
+fn load_manifest(dir: &Path) -> Result<Manifest> {
+    let content = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .context("Bundle has no manifest; is it a session export?")?;
+    let manifest: Manifest = serde_json::from_str(&content)?;
+    if manifest.version > FORMAT_VERSION {
+        bail!(
+            "Bundle format version {} is newer than this aoe supports ({}); upgrade aoe to import it",
+            manifest.version,
+            FORMAT_VERSION
+        );
+    }
+    Ok(manifest)
+}
+
+/// Run git in `dir` and return its trimmed stdout.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").args(args).current_dir(dir).output()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git_in(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@test.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@test.com")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    #[test]
+    fn test_uncommitted_changes_include_untracked_files() {
+        let repo = TempDir::new().unwrap();
+        git_in(repo.path(), &["init", "-q"]);
+        fs::write(repo.path().join("tracked.txt"), "one\n").unwrap();
+        git_in(repo.path(), &["add", "."]);
+        git_in(repo.path(), &["commit", "-q", "-m", "init"]);
+
+        fs::write(repo.path().join("tracked.txt"), "one\ntwo\n").unwrap();
+        fs::write(repo.path().join("new.txt"), "new\n").unwrap();
+        let status_before = git_in(repo.path(), &["status", "--porcelain"]);
+
+        let staging = TempDir::new().unwrap();
+        let patch = uncommitted_changes(repo.path(), staging.path()).unwrap();
+        let patch = String::from_utf8(patch).unwrap();
+        assert!(patch.contains("+two"), "{}", patch);
+        assert!(patch.contains("new file mode"), "{}", patch);
+        assert!(patch.contains("b/new.txt"), "{}", patch);
+
+        // The session's own index is untouched
+        assert_eq!(
+            git_in(repo.path(), &["status", "--porcelain"]),
+            status_before
+        );
+        assert!(!staging.path().join("index").exists());
+    }
+
+    #[test]
+    fn test_restore_applies_changes_to_existing_worktree() {
+        let repo = TempDir::new().unwrap();
+        git_in(repo.path(), &["init", "-q", "-b", "main"]);
+        fs::write(repo.path().join("tracked.txt"), "one\n").unwrap();
+        git_in(repo.path(), &["add", "."]);
+        git_in(repo.path(), &["commit", "-q", "-m", "init"]);
+        let head = git_in(repo.path(), &["rev-parse", "HEAD"])
+            .trim()
+            .to_string();
+
+        let worktrees = TempDir::new().unwrap();
+        let existing = worktrees.path().join("feature");
+        git_in(
+            repo.path(),
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                &existing.to_string_lossy(),
+            ],
+        );
+
+        // The exported changes, made and then undone in the worktree
+        let staging = TempDir::new().unwrap();
+        fs::write(existing.join("tracked.txt"), "one\ntwo\n").unwrap();
+        let patch = uncommitted_changes(&existing, staging.path()).unwrap();
+        fs::write(staging.path().join(PATCH_FILE), patch).unwrap();
+        git_in(&existing, &["checkout", "--", "."]);
+
+        let state = GitState {
+            branch: "feature".to_string(),
+            head,
+            base: None,
+            commits: 0,
+            has_patch: true,
+        };
+        let (path, created) = restore_worktree(
+            repo.path(),
+            &state,
+            "feature",
+            staging.path(),
+            &Config::default(),
+            "0123456789abcdef",
+        )
+        .unwrap();
+        assert!(!created);
+        assert_eq!(
+            path.canonicalize().unwrap(),
+            existing.canonicalize().unwrap()
+        );
+        assert_eq!(
+            fs::read_to_string(existing.join("tracked.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        // A worktree with changes of its own isn't touched
+        let err = restore_worktree(
+            repo.path(),
+            &state,
+            "feature",
+            staging.path(),
+            &Config::default(),
+            "0123456789abcdef",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("uncommitted changes"), "{}", err);
+        assert_eq!(
+            fs::read_to_string(existing.join("tracked.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+    }
+
+    #[test]
+    fn test_load_manifest_rejects_newer_format() {
+        let dir = TempDir::new().unwrap();
+        let mut manifest = Manifest {
+            version: FORMAT_VERSION,
+            exported_at: Utc::now(),
+            instance: Instance::new("task", "/tmp/project"),
+            git: None,
+            has_recording: false,
+        };
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(load_manifest(dir.path()).unwrap().instance.title, "task");
+
+        manifest.version = FORMAT_VERSION + 1;
+        fs::write(
+            dir.path().join(MANIFEST_FILE),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+        let err = load_manifest(dir.path()).unwrap_err().to_string();
+        assert!(err.contains("upgrade aoe"), "{}", err);
+    }
+}
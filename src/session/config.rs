@@ -2,9 +2,10 @@
 
 use super::get_app_dir;
 use super::repo_config::HooksConfig;
+use super::templates::SessionTemplate;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::PathBuf;
 
@@ -37,17 +38,40 @@ pub struct Config {
     #[serde(default)]
     pub diff: DiffConfig,
 
+    #[serde(default)]
+    pub usage: crate::usage::UsageConfig,
+
+    #[serde(default)]
+    pub metrics: crate::process::metrics::MetricsConfig,
+
     #[serde(default)]
     pub hooks: HooksConfig,
 
     #[serde(default)]
     pub sound: crate::sound::SoundConfig,
 
+    #[serde(default)]
+    pub desktop_notifications: crate::desktop_notifications::DesktopNotificationsConfig,
+
+    #[serde(default)]
+    pub auto_respond: super::auto_respond::AutoRespondConfig,
+
+    #[serde(default)]
+    pub watchdog: super::watchdog::WatchdogConfig,
+
     #[serde(default)]
     pub app_state: AppStateConfig,
 
     #[serde(default)]
     pub web: WebConfig,
+
+    /// Outgoing webhook endpoints (`[[webhooks]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<crate::webhooks::WebhookConfig>,
+
+    /// Named session presets (`[templates.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, SessionTemplate>,
 }
 
 /// Session list sort order
@@ -179,6 +203,16 @@ pub struct SessionConfig {
     /// Maps a custom (or built-in) agent to another agent's status detection heuristics.
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub agent_detect_as: HashMap<String, String>,
+
+    /// Record each agent pane to an asciicast file in the profile directory
+    /// (replay with `aoe session replay`).
+    #[serde(default)]
+    pub record: bool,
+
+    /// Host ports reserved for each session: a count (`AOE_PORT_0`, ...) or
+    /// the names of the variables to expose them under.
+    #[serde(default, skip_serializing_if = "super::ports::PortsConfig::is_empty")]
+    pub ports: super::ports::PortsConfig,
 }
 
 impl SessionConfig {
@@ -285,6 +319,15 @@ pub struct WebConfig {
     /// Server-wide default: fire a push on Running to Error transitions.
     #[serde(default = "default_true")]
     pub notify_on_error: bool,
+
+    /// Serve Prometheus metrics on `/metrics`. Off by default.
+    #[serde(default)]
+    pub metrics_enabled: bool,
+
+    /// Bearer token required by `/metrics`. When unset, only direct
+    /// loopback connections may scrape (never through the tunnel).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_token: Option<String>,
 }
 
 impl Default for WebConfig {
@@ -294,6 +337,8 @@ impl Default for WebConfig {
             notify_on_waiting: true,
             notify_on_idle: false,
             notify_on_error: true,
+            metrics_enabled: false,
+            metrics_token: None,
         }
     }
 }
@@ -453,9 +498,30 @@ pub struct SandboxConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_instruction: Option<String>,
 
-    /// Container runtime to use for sandboxing (docker or apple_container)
+    /// Container runtime to use for sandboxing (docker, podman or apple_container)
     #[serde(default)]
     pub container_runtime: ContainerRuntimeName,
+
+    /// Build sandbox containers from the repo's `.devcontainer/devcontainer.json`
+    #[serde(default)]
+    pub use_devcontainer: bool,
+
+    /// Build the sandbox image from a Dockerfile instead of pulling
+    /// `default_image`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<super::image_build::SandboxBuildConfig>,
+
+    /// Network access: `"default"`, `"none"` or `{ allow = [hosts] }`
+    #[serde(
+        default,
+        skip_serializing_if = "super::egress::NetworkPolicy::is_default"
+    )]
+    pub network: super::egress::NetworkPolicy,
+
+    /// Compose file, relative to the repo root, whose services run alongside
+    /// each session's container
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<String>,
 }
 
 /// Container runtime options for sandboxing
@@ -465,6 +531,7 @@ pub enum ContainerRuntimeName {
     AppleContainer,
     #[default]
     Docker,
+    Podman,
 }
 
 impl Default for SandboxConfig {
@@ -483,6 +550,10 @@ impl Default for SandboxConfig {
             mount_ssh: false,
             custom_instruction: None,
             container_runtime: ContainerRuntimeName::default(),
+            use_devcontainer: false,
+            build: None,
+            network: Default::default(),
+            services: None,
         }
     }
 }
@@ -11,12 +11,16 @@ use anyhow::Result;
 use crate::containers::{ContainerConfig, EnvEntry, VolumeMount};
 use crate::git::GitWorktree;
 
+use super::devcontainer::Devcontainer;
 use super::environment::collect_environment;
 use super::instance::SandboxInfo;
 
 /// Subdirectory name inside each agent's config dir for the shared sandbox config.
 const SANDBOX_SUBDIR: &str = "sandbox";
 
+/// Home directory of the sandbox image's root user, where agent config is mounted
+const DEFAULT_CONTAINER_HOME: &str = "/root";
+
 /// Content seeded into the Claude sandbox `.sandbox-gitconfig`. Scoped to github.com;
 /// the helper emits credentials only on `get` and only when GH_TOKEN is non-empty, so
 /// other remotes and sessions without a forwarded token fall through to normal git
@@ -298,8 +302,6 @@ fn sync_agent_config(
 }
 
 fn rewrite_claude_plugin_paths(sandbox_dir: &Path, host_home: &Path) -> Result<()> {
-    const CONTAINER_HOME: &str = "/root";
-
     let plugins_dir = sandbox_dir.join("plugins");
     if !plugins_dir.exists() {
         return Ok(());
@@ -339,7 +341,12 @@ fn rewrite_claude_plugin_paths(sandbox_dir: &Path, host_home: &Path) -> Result<(
         };
 
         let mut changed = false;
-        rewrite_plugin_value_paths(&mut value, &host_home_str, CONTAINER_HOME, &mut changed);
+        rewrite_plugin_value_paths(
+            &mut value,
+            &host_home_str,
+            DEFAULT_CONTAINER_HOME,
+            &mut changed,
+        );
 
         if changed {
             let serialized = serde_json::to_string(&value)?;
@@ -765,6 +772,26 @@ pub(crate) fn refresh_agent_configs() {
     }
 }
 
+/// The repo's dev container definition, when `sandbox.use_devcontainer` is
+/// on for the project.
+pub(crate) fn load_devcontainer(project_path: &Path) -> Result<Option<Devcontainer>> {
+    let profile = super::config::resolve_default_profile();
+    let enabled = super::repo_config::resolve_config_with_repo(&profile, project_path)
+        .map(|c| c.sandbox.use_devcontainer)
+        .unwrap_or(false);
+    if !enabled {
+        return Ok(None);
+    }
+    let devcontainer = Devcontainer::load(project_path)?;
+    if devcontainer.is_none() {
+        tracing::warn!(
+            "use_devcontainer is set but {} has no devcontainer.json",
+            project_path.display()
+        );
+    }
+    Ok(devcontainer)
+}
+
 /// Build a full `ContainerConfig` for creating a sandboxed container.
 pub(crate) fn build_container_config(
     project_path_str: &str,
@@ -773,6 +800,7 @@ pub(crate) fn build_container_config(
     is_yolo_mode: bool,
     instance_id: &str,
     workspace_info: Option<&super::WorkspaceInfo>,
+    devcontainer: Option<&Devcontainer>,
 ) -> Result<ContainerConfig> {
     let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!("Could not find home directory"))?;
 
@@ -820,13 +848,16 @@ pub(crate) fn build_container_config(
         }
     };
 
-    const CONTAINER_HOME: &str = "/root";
+    // A devcontainer's remoteUser has its own home for agent config
+    let container_home = devcontainer
+        .map(|d| d.container_home())
+        .unwrap_or_else(|| DEFAULT_CONTAINER_HOME.to_string());
 
     let gitconfig = home.join(".gitconfig");
     if gitconfig.exists() {
         volumes.push(VolumeMount {
             host_path: gitconfig.to_string_lossy().to_string(),
-            container_path: format!("{}/.gitconfig", CONTAINER_HOME),
+            container_path: format!("{}/.gitconfig", container_home),
             read_only: true,
         });
     }
@@ -836,7 +867,7 @@ pub(crate) fn build_container_config(
         if ssh_dir.exists() {
             volumes.push(VolumeMount {
                 host_path: ssh_dir.to_string_lossy().to_string(),
-                container_path: format!("{}/.ssh", CONTAINER_HOME),
+                container_path: format!("{}/.ssh", container_home),
                 read_only: true,
             });
         }
@@ -846,7 +877,7 @@ pub(crate) fn build_container_config(
     // bind-mount it read-write. Only mount the config for the active tool.
     // Agent definitions are in AGENT_CONFIG_MOUNTS -- add new agents there, not here.
     for mount in AGENT_CONFIG_MOUNTS.iter().filter(|m| m.tool_name == tool) {
-        let container_path = format!("{}/{}", CONTAINER_HOME, mount.container_suffix);
+        let container_path = format!("{}/{}", container_home, mount.container_suffix);
 
         let sandbox_dir = match prepare_sandbox_dir(mount, &home) {
             Ok(dir) => dir,
@@ -879,7 +910,7 @@ pub(crate) fn build_container_config(
             if file_path.exists() {
                 volumes.push(VolumeMount {
                     host_path: file_path.to_string_lossy().to_string(),
-                    container_path: format!("{}/{}", CONTAINER_HOME, filename),
+                    container_path: format!("{}/{}", container_home, filename),
                     read_only: false,
                 });
             }
@@ -927,13 +958,22 @@ pub(crate) fn build_container_config(
         }
     }
 
-    let mut environment = collect_environment(&sandbox_config, sandbox_info);
+    // Devcontainer entries go first so AoE's own settings win on conflicts
+    let mut environment = devcontainer
+        .map(|d| d.environment(&workspace_path))
+        .unwrap_or_default();
+    environment.extend(collect_environment(&sandbox_config, sandbox_info));
 
     if let Some(agent) = crate::agents::get_agent(tool) {
         for &(key, value) in agent.container_env {
+            // Agent config dirs live under the container home
+            let value = match value.strip_prefix(DEFAULT_CONTAINER_HOME) {
+                Some(rest) => format!("{}{}", container_home, rest),
+                None => value.to_string(),
+            };
             environment.push(EnvEntry::Literal {
                 key: key.to_string(),
-                value: value.to_string(),
+                value,
             });
         }
         if is_yolo_mode {
@@ -946,6 +986,10 @@ pub(crate) fn build_container_config(
         }
     }
 
+    if let Some(devcontainer) = devcontainer {
+        volumes.extend(devcontainer.volumes(&workspace_path));
+    }
+
     // Add extra_volumes from config (host:container format)
     // Also collect container paths to filter conflicting volume_ignores later
     tracing::debug!(
@@ -1010,6 +1054,13 @@ pub(crate) fn build_container_config(
     }
     deduped.reverse();
 
+    let mut port_mappings = sandbox_config.port_mappings.clone();
+    for port in devcontainer.map(|d| d.port_mappings()).unwrap_or_default() {
+        if !port_mappings.contains(&port) {
+            port_mappings.push(port);
+        }
+    }
+
     Ok(ContainerConfig {
         working_dir: workspace_path,
         volumes: deduped,
@@ -1017,7 +1068,8 @@ pub(crate) fn build_container_config(
         environment,
         cpu_limit: sandbox_config.cpu_limit,
         memory_limit: sandbox_config.memory_limit,
-        port_mappings: sandbox_config.port_mappings.clone(),
+        port_mappings,
+        network: None,
     })
 }
 
@@ -2169,6 +2221,10 @@ extra_volumes = ["/host/data:/container/data:ro"]
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let project_path_str = project_dir.path().to_str().unwrap();
@@ -2179,6 +2235,7 @@ extra_volumes = ["/host/data:/container/data:ro"]
             false,
             "test-instance-id",
             None,
+            None,
         )
         .unwrap();
 
@@ -2283,6 +2340,10 @@ volume_ignores = ["target", "node_modules"]
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let project_path_str = worktree_path.to_str().unwrap();
@@ -2293,6 +2354,7 @@ volume_ignores = ["target", "node_modules"]
             false,
             "test-instance-id",
             None,
+            None,
         )
         .unwrap();
 
@@ -2374,6 +2436,10 @@ volume_ignores = ["target"]
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let project_path_str = worktree_path.to_str().unwrap();
@@ -2384,6 +2450,7 @@ volume_ignores = ["target"]
             false,
             "test-instance-id",
             None,
+            None,
         )
         .unwrap();
 
@@ -2,7 +2,7 @@
 
 use std::path::{Path, PathBuf};
 
-use crate::containers::DockerContainer;
+use crate::containers::{self, ContainerRuntimeInterface, DockerContainer};
 use crate::git::cleanup::remove_managed_worktree;
 use crate::git::GitWorktree;
 use crate::session::repo_config;
@@ -160,10 +160,37 @@ pub fn perform_deletion(request: &DeletionRequest) -> DeletionResult {
                         errors.push(format!("Container: {}", e));
                     }
                 }
+                if sandbox.egress_proxy.is_some() {
+                    if let Err(e) = crate::session::egress::remove_network(&request.instance.id) {
+                        errors.push(format!("Network: {:#}", e));
+                    }
+                }
+                let runtime = containers::get_container_runtime();
+                for snapshot in &sandbox.snapshots {
+                    if runtime.image_exists_locally(&snapshot.image) {
+                        if let Err(e) = runtime.remove_image(&snapshot.image) {
+                            errors.push(format!("Snapshot: {}", e));
+                        }
+                    }
+                }
             }
         }
     }
 
+    // The egress proxy and services only serve this session, even if its
+    // container is kept
+    crate::session::egress::cleanup(&request.instance.id);
+    if let Some(project) = request
+        .instance
+        .sandbox_info
+        .as_ref()
+        .and_then(|s| s.compose_project.as_deref())
+    {
+        if let Err(e) = crate::session::services::down(&request.instance.id, project) {
+            errors.push(format!("Services: {:#}", e));
+        }
+    }
+
     // Tmux kill - non-fatal if session already gone
     let _ = request.instance.kill();
 
@@ -173,6 +200,29 @@ pub fn perform_deletion(request: &DeletionRequest) -> DeletionResult {
     // Clean up hook status files
     crate::hooks::cleanup_hook_status_dir(&request.instance.id);
 
+    // Recording is tied to the session; drop it with the session
+    if let Err(e) =
+        crate::session::recording::remove(&request.instance.source_profile, &request.instance.id)
+    {
+        tracing::warn!("Failed to remove session recording: {}", e);
+    }
+
+    if let Err(e) =
+        crate::session::message_queue::clear(&request.instance.source_profile, &request.instance.id)
+    {
+        tracing::warn!("Failed to remove message queue: {}", e);
+    }
+
+    if let Err(e) =
+        crate::session::auto_respond::clear(&request.instance.source_profile, &request.instance.id)
+    {
+        tracing::warn!("Failed to remove auto-responder lock: {}", e);
+    }
+
+    if let Err(e) = crate::session::ports::release(&request.instance.id) {
+        tracing::warn!("Failed to release session ports: {}", e);
+    }
+
     DeletionResult {
         session_id: request.session_id.clone(),
         success: errors.is_empty(),
@@ -238,7 +288,11 @@ fn run_on_destroy_hooks(instance: &Instance) {
             vec![]
         }
     } else {
-        repo_config::execute_hooks_best_effort(&resolved_on_destroy, project_path)
+        repo_config::execute_hooks_best_effort(
+            &resolved_on_destroy,
+            project_path,
+            &crate::session::ports::env(&instance.ports),
+        )
     };
 
     if !errors.is_empty() {
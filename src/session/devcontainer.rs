@@ -0,0 +1,566 @@
+//! Sandbox containers from a repo's `devcontainer.json`
+//!
+//! With `sandbox.use_devcontainer = true`, a sandboxed session's container
+//! comes from the repo's dev container definition instead of
+//! `default_image`. Its image is pulled, or built from its Dockerfile and
+//! tagged by a hash of the build inputs so it is only rebuilt when they
+//! change. `containerEnv`, `mounts` and `forwardPorts` are merged with
+//! AoE's own project, agent config and hook mounts, agents run as
+//! `remoteUser`, and `postCreateCommand` runs once the container is created.
+//!
+//! Only those properties are read. Compose-based definitions, features and
+//! the other lifecycle commands are not supported.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use super::image_build;
+use crate::containers::{self, ContainerRuntimeInterface, EnvEntry, ImageBuild, VolumeMount};
+
+/// Where the spec looks for the definition, in order
+const CONFIG_PATHS: [&str; 2] = [".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// Prefix of the tags of images built from a devcontainer Dockerfile
+pub(crate) const BUILD_TAG_PREFIX: &str = "aoe-devcontainer-";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DevcontainerJson {
+    image: Option<String>,
+    build: Option<BuildJson>,
+    /// Older spelling of `build.dockerfile`
+    docker_file: Option<String>,
+    /// Older spelling of `build.context`
+    context: Option<String>,
+    docker_compose_file: Option<serde_json::Value>,
+    #[serde(default)]
+    container_env: BTreeMap<String, String>,
+    #[serde(default)]
+    mounts: Vec<MountJson>,
+    #[serde(default)]
+    forward_ports: Vec<PortJson>,
+    remote_user: Option<String>,
+    post_create_command: Option<CommandJson>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct BuildJson {
+    dockerfile: Option<String>,
+    context: Option<String>,
+    #[serde(default)]
+    args: BTreeMap<String, String>,
+    target: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum MountJson {
+    /// `source=...,target=...,type=bind`, as passed to `--mount`
+    Spec(String),
+    Object {
+        source: Option<String>,
+        target: String,
+        #[serde(rename = "type")]
+        kind: Option<String>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PortJson {
+    Number(u16),
+    /// A port number as a string, or `service:port` in compose setups
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CommandJson {
+    /// Run by a shell
+    Shell(String),
+    /// Run directly, without a shell
+    Args(Vec<String>),
+    /// Named commands, which the spec runs in parallel
+    Named(BTreeMap<String, CommandJson>),
+}
+
+/// How to get the container image
+#[derive(Debug, PartialEq)]
+pub enum ImageSource {
+    Pull(String),
+    Build(ImageBuild),
+}
+
+/// A repo's dev container definition
+#[derive(Debug)]
+pub struct Devcontainer {
+    /// Directory holding `devcontainer.json`; relative paths resolve from here
+    dir: PathBuf,
+    project_path: PathBuf,
+    json: DevcontainerJson,
+}
+
+impl Devcontainer {
+    /// Load the project's `devcontainer.json`, if it has one.
+    pub fn load(project_path: &Path) -> Result<Option<Self>> {
+        let Some(path) = CONFIG_PATHS
+            .iter()
+            .map(|rel| project_path.join(rel))
+            .find(|p| p.is_file())
+        else {
+            return Ok(None);
+        };
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let dir = path.parent().unwrap_or(project_path).to_path_buf();
+        Self::parse(&content, dir, project_path)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+            .map(Some)
+    }
+
+    fn parse(content: &str, dir: PathBuf, project_path: &Path) -> Result<Self> {
+        // devcontainer.json allows comments and trailing commas
+        let json: DevcontainerJson = json5::from_str(content)?;
+        if json.docker_compose_file.is_some() {
+            bail!("Compose-based dev containers (dockerComposeFile) are not supported");
+        }
+        Ok(Self {
+            dir,
+            project_path: project_path.to_path_buf(),
+            json,
+        })
+    }
+
+    /// The image named by `image`, or the build described by `build` (or the
+    /// older top-level `dockerFile` and `context`).
+    pub fn image_source(&self) -> Result<ImageSource> {
+        let build = self.json.build.as_ref();
+        let dockerfile = build
+            .and_then(|b| b.dockerfile.as_deref())
+            .or(self.json.docker_file.as_deref());
+
+        let Some(dockerfile) = dockerfile else {
+            return match &self.json.image {
+                Some(image) => Ok(ImageSource::Pull(image.clone())),
+                None => bail!("devcontainer.json sets neither image nor build.dockerfile"),
+            };
+        };
+
+        let dockerfile = self.dir.join(dockerfile);
+        let context = self.dir.join(
+            build
+                .and_then(|b| b.context.as_deref())
+                .or(self.json.context.as_deref())
+                .unwrap_or("."),
+        );
+        let args: Vec<(String, String)> = build
+            .map(|b| {
+                b.args
+                    .iter()
+                    .map(|(k, v)| (k.clone(), self.substitute(v, "").0))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let target = build.and_then(|b| b.target.clone());
+
+        Ok(ImageSource::Build(ImageBuild {
+            tag: image_build::content_tag(
+                BUILD_TAG_PREFIX,
+                &dockerfile,
+                &context,
+                &args,
+                target.as_deref(),
+            )?,
+            dockerfile,
+            context,
+            args,
+            target,
+        }))
+    }
+
+    /// Pull or build the image, returning its name. A built image is reused
+    /// while its tag exists locally; build output goes to `on_output`.
+    pub fn prepare_image(&self, on_output: &mut dyn FnMut(&str)) -> Result<String> {
+        match self.image_source()? {
+            ImageSource::Pull(image) => {
+                containers::get_container_runtime().ensure_image(&image)?;
+                Ok(image)
+            }
+            ImageSource::Build(build) => {
+                image_build::ensure_built(&build, on_output)?;
+                Ok(build.tag)
+            }
+        }
+    }
+
+    /// User agents and terminals run as, if not the image's default
+    pub fn remote_user(&self) -> Option<&str> {
+        self.json.remote_user.as_deref()
+    }
+
+    /// Home directory of `remoteUser`, where agent config is mounted
+    pub fn container_home(&self) -> String {
+        match self.remote_user() {
+            None | Some("root") => "/root".to_string(),
+            Some(user) => format!("/home/{}", user),
+        }
+    }
+
+    /// `containerEnv` entries. Values taken from the host environment are
+    /// passed like `KEY` sandbox environment entries, so they stay out of argv.
+    pub fn environment(&self, workdir: &str) -> Vec<EnvEntry> {
+        self.json
+            .container_env
+            .iter()
+            .map(|(key, value)| {
+                let (value, from_host) = self.substitute(value, workdir);
+                let key = key.clone();
+                if from_host {
+                    EnvEntry::Inherit { key, value }
+                } else {
+                    EnvEntry::Literal { key, value }
+                }
+            })
+            .collect()
+    }
+
+    /// Bind and volume `mounts`. Other mount types are skipped.
+    pub fn volumes(&self, workdir: &str) -> Vec<VolumeMount> {
+        self.json
+            .mounts
+            .iter()
+            .filter_map(|mount| {
+                let (source, target, kind, read_only) = match mount {
+                    MountJson::Spec(spec) => parse_mount_spec(spec)?,
+                    MountJson::Object {
+                        source,
+                        target,
+                        kind,
+                    } => (
+                        source.clone()?,
+                        target.clone(),
+                        kind.clone().unwrap_or_else(|| "bind".to_string()),
+                        false,
+                    ),
+                };
+                if kind != "bind" && kind != "volume" {
+                    tracing::warn!("Skipping devcontainer {} mount of {}", kind, target);
+                    return None;
+                }
+                Some(VolumeMount {
+                    host_path: self.substitute(&source, workdir).0,
+                    container_path: self.substitute(&target, workdir).0,
+                    read_only,
+                })
+            })
+            .collect()
+    }
+
+    /// `forwardPorts` as `host:container` mappings on the same port
+    pub fn port_mappings(&self) -> Vec<String> {
+        self.json
+            .forward_ports
+            .iter()
+            .filter_map(|port| {
+                let port = match port {
+                    PortJson::Number(port) => *port,
+                    PortJson::Text(text) => match text.parse::<u16>() {
+                        Ok(port) => port,
+                        Err(_) => {
+                            tracing::warn!("Skipping devcontainer forwardPorts entry '{}'", text);
+                            return None;
+                        }
+                    },
+                };
+                Some(format!("{}:{}", port, port))
+            })
+            .collect()
+    }
+
+    /// `postCreateCommand`, one line per command, for the repo trust prompt.
+    /// Commands run without a shell are shown as their argument list.
+    pub fn post_create_commands(&self) -> Vec<String> {
+        let Some(command) = &self.json.post_create_command else {
+            return Vec::new();
+        };
+        command_lines(command)
+            .into_iter()
+            .map(|args| match args.as_slice() {
+                [sh, c, line] if sh == "/bin/sh" && c == "-c" => line.clone(),
+                _ => format!("{:?}", args),
+            })
+            .collect()
+    }
+
+    /// Run `postCreateCommand` in a newly created container. Named commands
+    /// run one after another.
+    pub fn run_post_create(&self, container_name: &str, workdir: &str) -> Result<()> {
+        let Some(command) = &self.json.post_create_command else {
+            return Ok(());
+        };
+        for args in command_lines(command) {
+            tracing::info!("Running devcontainer postCreateCommand: {}", args.join(" "));
+            let mut cmd = Command::new(containers::runtime_binary());
+            cmd.arg("exec");
+            if let Some(user) = self.remote_user() {
+                cmd.args(["--user", user]);
+            }
+            let output = cmd
+                .args(["--workdir", workdir, container_name])
+                .args(&args)
+                .output()?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                bail!(
+                    "postCreateCommand failed ({}): {}",
+                    output.status,
+                    stderr.trim()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand `${localWorkspaceFolder}`, `${containerWorkspaceFolder}`,
+    /// their `Basename` forms and `${localEnv:NAME[:default]}`. Also
+    /// returns whether a host environment variable was used.
+    fn substitute(&self, value: &str, workdir: &str) -> (String, bool) {
+        let local = self.project_path.to_string_lossy();
+        let mut out = String::with_capacity(value.len());
+        let mut from_host = false;
+        let mut rest = value;
+
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let Some(len) = rest[start..].find('}') else {
+                rest = &rest[start..];
+                break;
+            };
+            let var = &rest[start + 2..start + len];
+            match var {
+                "localWorkspaceFolder" => out.push_str(&local),
+                "localWorkspaceFolderBasename" => out.push_str(basename(&local)),
+                "containerWorkspaceFolder" => out.push_str(workdir),
+                "containerWorkspaceFolderBasename" => out.push_str(basename(workdir)),
+                _ => match var.strip_prefix("localEnv:").or(var.strip_prefix("env:")) {
+                    Some(name) => {
+                        let (name, default) = name.split_once(':').unwrap_or((name, ""));
+                        out.push_str(&std::env::var(name).unwrap_or_else(|_| default.to_string()));
+                        from_host = true;
+                    }
+                    // Left for the container to see, as other tools do
+                    None => out.push_str(&rest[start..start + len + 1]),
+                },
+            }
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        (out, from_host)
+    }
+}
+
+fn basename(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or(path)
+}
+
+/// Parse a `--mount` style spec into (source, target, type, read-only).
+fn parse_mount_spec(spec: &str) -> Option<(String, String, String, bool)> {
+    let mut source = None;
+    let mut target = None;
+    let mut kind = "bind".to_string();
+    let mut read_only = false;
+    for part in spec.split(',') {
+        let (key, value) = part.split_once('=').unwrap_or((part, ""));
+        match key.trim() {
+            "source" | "src" => source = Some(value.to_string()),
+            "target" | "destination" | "dst" => target = Some(value.to_string()),
+            "type" => kind = value.to_string(),
+            "readonly" | "ro" => read_only = value.is_empty() || value == "true" || value == "1",
+            _ => {}
+        }
+    }
+    match (source, target) {
+        (Some(source), Some(target)) => Some((source, target, kind, read_only)),
+        _ => {
+            tracing::warn!("Skipping devcontainer mount '{}'", spec);
+            None
+        }
+    }
+}
+
+/// Each command as argv. Shell strings run through `/bin/sh -c`.
+fn command_lines(command: &CommandJson) -> Vec<Vec<String>> {
+    match command {
+        CommandJson::Shell(line) => vec![vec!["/bin/sh".into(), "-c".into(), line.clone()]],
+        CommandJson::Args(args) if args.is_empty() => Vec::new(),
+        CommandJson::Args(args) => vec![args.clone()],
+        CommandJson::Named(named) => named.values().flat_map(command_lines).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Devcontainer {
+        Devcontainer::parse(
+            content,
+            PathBuf::from("/src/app/.devcontainer"),
+            Path::new("/src/app"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_jsonc_with_image() {
+        let dc = parse(
+            r#"{
+                // The toolchain image
+                "image": "mcr.microsoft.com/devcontainers/rust:1",
+                "containerEnv": { "CARGO_TARGET_DIR": "${containerWorkspaceFolder}/target" },
+                "forwardPorts": [3000, "8080", "db:5432"],
+                "remoteUser": "vscode",
+            }"#,
+        );
+        assert_eq!(
+            dc.image_source().unwrap(),
+            ImageSource::Pull("mcr.microsoft.com/devcontainers/rust:1".to_string())
+        );
+        assert_eq!(
+            dc.environment("/workspace/app"),
+            vec![EnvEntry::Literal {
+                key: "CARGO_TARGET_DIR".to_string(),
+                value: "/workspace/app/target".to_string(),
+            }]
+        );
+        assert_eq!(dc.port_mappings(), vec!["3000:3000", "8080:8080"]);
+        assert_eq!(dc.remote_user(), Some("vscode"));
+        assert_eq!(dc.container_home(), "/home/vscode");
+    }
+
+    #[test]
+    fn test_build_source() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let devcontainer_dir = dir.path().join(".devcontainer");
+        fs::create_dir_all(&devcontainer_dir)?;
+        fs::write(devcontainer_dir.join("Dockerfile"), "FROM ubuntu:24.04\n")?;
+        fs::write(
+            devcontainer_dir.join("devcontainer.json"),
+            r#"{"build": {"dockerfile": "Dockerfile", "context": "..", "args": {"V": "1"}}}"#,
+        )?;
+
+        let dc = Devcontainer::load(dir.path())?.unwrap();
+        let ImageSource::Build(build) = dc.image_source()? else {
+            panic!("expected a build");
+        };
+        assert_eq!(build.dockerfile, devcontainer_dir.join("Dockerfile"));
+        assert_eq!(build.context, devcontainer_dir.join(".."));
+        assert_eq!(build.args, vec![("V".to_string(), "1".to_string())]);
+        assert!(build.tag.starts_with(BUILD_TAG_PREFIX));
+
+        // The tag follows the Dockerfile
+        let tag = build.tag;
+        fs::write(devcontainer_dir.join("Dockerfile"), "FROM ubuntu:25.04\n")?;
+        let ImageSource::Build(build) = dc.image_source()? else {
+            panic!("expected a build");
+        };
+        assert_ne!(build.tag, tag);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_image_and_compose_rejected() {
+        assert!(parse("{}").image_source().is_err());
+        assert!(Devcontainer::parse(
+            r#"{"dockerComposeFile": "compose.yml", "service": "app"}"#,
+            PathBuf::from("/src/app/.devcontainer"),
+            Path::new("/src/app"),
+        )
+        .is_err());
+        assert!(Devcontainer::load(Path::new("/nonexistent"))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_mounts() {
+        let dc = parse(
+            r#"{
+                "image": "alpine",
+                "mounts": [
+                    "source=${localWorkspaceFolder}/.cache,target=/cache,type=bind,readonly",
+                    "source=app-node-modules,target=${containerWorkspaceFolder}/node_modules,type=volume",
+                    { "source": "/data", "target": "/data", "type": "bind" },
+                    { "target": "/tmp/scratch", "type": "tmpfs" }
+                ]
+            }"#,
+        );
+        let volumes = dc.volumes("/workspace/app");
+        let mounts: Vec<(&str, &str, bool)> = volumes
+            .iter()
+            .map(|v| (v.host_path.as_str(), v.container_path.as_str(), v.read_only))
+            .collect();
+        assert_eq!(
+            mounts,
+            vec![
+                ("/src/app/.cache", "/cache", true),
+                ("app-node-modules", "/workspace/app/node_modules", false),
+                ("/data", "/data", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_substitute_local_env() {
+        let dc = parse(r#"{"image": "alpine"}"#);
+        std::env::set_var("AOE_DEVCONTAINER_TEST_VAR", "secret");
+        assert_eq!(
+            dc.substitute("${localEnv:AOE_DEVCONTAINER_TEST_VAR}", ""),
+            ("secret".to_string(), true)
+        );
+        assert_eq!(
+            dc.substitute("${localEnv:AOE_DEVCONTAINER_UNSET_VAR:fallback}", ""),
+            ("fallback".to_string(), true)
+        );
+        assert_eq!(
+            dc.substitute("${localWorkspaceFolderBasename}-${devcontainerId}", ""),
+            ("app-${devcontainerId}".to_string(), false)
+        );
+    }
+
+    #[test]
+    fn test_post_create_command_forms() {
+        let shell = CommandJson::Shell("npm install".to_string());
+        assert_eq!(
+            command_lines(&shell),
+            vec![vec!["/bin/sh", "-c", "npm install"]]
+        );
+
+        let dc = parse(
+            r#"{"image": "alpine", "postCreateCommand": {
+                "deps": ["npm", "ci"],
+                "agent": "npm install -g @anthropic-ai/claude-code"
+            }}"#,
+        );
+        let lines = command_lines(dc.json.post_create_command.as_ref().unwrap());
+        // Named commands run in name order
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0][2], "npm install -g @anthropic-ai/claude-code");
+        assert_eq!(lines[1], vec!["npm", "ci"]);
+        assert_eq!(
+            dc.post_create_commands(),
+            vec![
+                "npm install -g @anthropic-ai/claude-code",
+                r#"["npm", "ci"]"#
+            ]
+        );
+    }
+}
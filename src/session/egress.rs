@@ -0,0 +1,620 @@
+//! Network egress policies for sandboxed sessions
+//!
+//! `sandbox.network` decides what a sandbox container can reach:
+//! `"default"` leaves it on the runtime's default network, `"none"` gives it
+//! no network at all, and `{ allow = [...] }` puts it on an internal network
+//! of its own whose only way out is a filtering proxy AoE runs on the host,
+//! listening on the network's gateway. The proxy passes HTTP CONNECT tunnels and plain
+//! HTTP requests to allowed hosts and logs every denied request to the
+//! session's egress log.
+//!
+//! Each allowlisted session gets its own network (`aoe-egress-<session id>`)
+//! and proxy process (`aoe sandbox proxy`), so one session's sandbox can't
+//! reach another's or use its proxy. The proxy is started with the container
+//! and stopped with it; the network is removed with the container. The
+//! proxy's address is kept in the session's `SandboxInfo` so a restarted
+//! container finds it again.
+
+use std::fs::{self, OpenOptions};
+use std::net::{SocketAddr, TcpListener as StdTcpListener, TcpStream as StdTcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::containers::{self, ContainerConfig, ContainerRuntimeInterface, EnvEntry};
+
+/// Prefix of the internal network each allowlisted container is attached to
+const NETWORK_PREFIX: &str = "aoe-egress-";
+
+/// Environment variables pointing tools at the proxy. Both spellings,
+/// since tools disagree on which one they read.
+const PROXY_ENV: [&str; 4] = ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"];
+const NO_PROXY_ENV: [&str; 2] = ["NO_PROXY", "no_proxy"];
+const NO_PROXY: &str = "localhost,127.0.0.1,::1";
+
+/// How long to wait for a new proxy to accept connections
+const PROXY_START_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Request heads larger than this are refused
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NetworkMode {
+    /// The runtime's default network
+    #[default]
+    Default,
+    /// No network access
+    None,
+}
+
+/// Sandbox network policy (`sandbox.network`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NetworkPolicy {
+    Mode(NetworkMode),
+    /// Only these hosts, through AoE's filtering proxy. `*.example.com`
+    /// matches any subdomain of `example.com`.
+    Allowlist {
+        allow: Vec<String>,
+    },
+}
+
+impl Default for NetworkPolicy {
+    fn default() -> Self {
+        Self::Mode(NetworkMode::Default)
+    }
+}
+
+impl NetworkPolicy {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Whether `host` matches one of the allowlist patterns: an exact host
+/// name, or `*.domain` for any subdomain of `domain` (but not `domain`).
+pub fn host_allowed(allow: &[String], host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allow.iter().any(|pattern| {
+        let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host
+                .strip_suffix(domain)
+                .is_some_and(|sub| sub.len() > 1 && sub.ends_with('.')),
+            None => host == pattern,
+        }
+    })
+}
+
+/// The network policy for sandboxes of the project.
+fn policy_for(project_path: &Path) -> NetworkPolicy {
+    let profile = super::config::resolve_default_profile();
+    super::repo_config::resolve_config_with_repo(&profile, project_path)
+        .map(|c| c.sandbox.network)
+        .unwrap_or_default()
+}
+
+/// Whether sandboxes of the project can publish ports to the host. Internal
+/// networks and `none` have no route from the host.
+pub fn publishes_ports(project_path: &Path) -> bool {
+    policy_for(project_path).is_default()
+}
+
+/// Apply the project's network policy to a container about to be created.
+/// In allowlist mode this starts the session's proxy and returns its
+/// address.
+pub(crate) fn apply(
+    session_id: &str,
+    project_path: &Path,
+    config: &mut ContainerConfig,
+) -> Result<Option<String>> {
+    let allow = match policy_for(project_path) {
+        NetworkPolicy::Mode(NetworkMode::Default) => return Ok(None),
+        NetworkPolicy::Mode(NetworkMode::None) => {
+            config.network = Some("none".to_string());
+            drop_published_ports(config);
+            return Ok(None);
+        }
+        NetworkPolicy::Allowlist { allow } => allow,
+    };
+    drop_published_ports(config);
+
+    let network = network_name(session_id);
+    let runtime = containers::get_container_runtime();
+    let gateway = runtime
+        .ensure_internal_network(&network)
+        .context("sandbox.network allowlist needs an internal network")?;
+    let addr = free_addr(&gateway)?;
+    start_proxy(session_id, &addr, &allow)?;
+
+    let url = format!("http://{}", addr);
+    config.network = Some(network);
+    config
+        .environment
+        .retain(|e| !PROXY_ENV.contains(&e.key()) && !NO_PROXY_ENV.contains(&e.key()));
+    config
+        .environment
+        .extend(PROXY_ENV.iter().map(|key| EnvEntry::Literal {
+            key: key.to_string(),
+            value: url.clone(),
+        }));
+    config
+        .environment
+        .extend(NO_PROXY_ENV.iter().map(|key| EnvEntry::Literal {
+            key: key.to_string(),
+            value: NO_PROXY.to_string(),
+        }));
+    Ok(Some(addr))
+}
+
+/// Let the container reach `hosts` directly rather than through its proxy,
+/// if it has one.
+pub(crate) fn bypass_proxy(config: &mut ContainerConfig, hosts: &[String]) {
+    for entry in &mut config.environment {
+        if let EnvEntry::Literal { key, value } = entry {
+            if NO_PROXY_ENV.contains(&key.as_str()) {
+                for host in hosts {
+                    value.push(',');
+                    value.push_str(host);
+                }
+            }
+        }
+    }
+}
+
+/// Make sure the proxy of an existing allowlisted container is running, as
+/// after a reboot. Uses the project's current allowlist.
+pub(crate) fn resume(session_id: &str, project_path: &Path, proxy_addr: &str) -> Result<()> {
+    if proxy_pid(session_id).is_some() {
+        return Ok(());
+    }
+    let allow = match policy_for(project_path) {
+        NetworkPolicy::Allowlist { allow } => allow,
+        _ => {
+            tracing::warn!(
+                "Session {} was created with a network allowlist that is no longer \
+                 configured; its proxy will deny everything until the container is recreated",
+                session_id
+            );
+            Vec::new()
+        }
+    };
+    start_proxy(session_id, proxy_addr, &allow)
+}
+
+/// Stop the session's proxy, if it has one running.
+pub fn stop_proxy(session_id: &str) {
+    if let Some(pid) = proxy_pid(session_id) {
+        let _ = nix::sys::signal::kill(
+            nix::unistd::Pid::from_raw(pid),
+            nix::sys::signal::Signal::SIGTERM,
+        );
+    }
+    if let Ok(path) = pid_path(session_id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Drop port mappings the runtime wouldn't publish anyway. A network just
+/// for them would be a route around the allowlist.
+fn drop_published_ports(config: &mut ContainerConfig) {
+    if !config.port_mappings.is_empty() {
+        tracing::warn!(
+            "Not publishing ports {}: sandbox.network keeps the container off the host's network",
+            config.port_mappings.join(", ")
+        );
+        config.port_mappings.clear();
+    }
+}
+
+/// Remove the session's network, once its container is gone.
+pub fn remove_network(session_id: &str) -> Result<()> {
+    containers::get_container_runtime().remove_network(&network_name(session_id))?;
+    Ok(())
+}
+
+/// Stop the session's proxy and delete its egress log.
+pub fn cleanup(session_id: &str) {
+    stop_proxy(session_id);
+    if let Ok(path) = log_path(session_id) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn network_name(session_id: &str) -> String {
+    format!("{}{}", NETWORK_PREFIX, session_id)
+}
+
+fn egress_dir() -> Result<PathBuf> {
+    Ok(super::get_app_dir()?.join("egress"))
+}
+
+/// The session's egress log: proxy startup and every denied request
+pub fn log_path(session_id: &str) -> Result<PathBuf> {
+    Ok(egress_dir()?.join(format!("{}.log", session_id)))
+}
+
+fn pid_path(session_id: &str) -> Result<PathBuf> {
+    Ok(egress_dir()?.join(format!("{}.pid", session_id)))
+}
+
+/// PID of the session's proxy, if it's alive.
+fn proxy_pid(session_id: &str) -> Option<i32> {
+    let pid: i32 = fs::read_to_string(pid_path(session_id).ok()?)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+    nix::sys::signal::kill(nix::unistd::Pid::from_raw(pid), None).ok()?;
+    // The PID may have been reused since
+    match fs::read(format!("/proc/{}/cmdline", pid)) {
+        Ok(cmdline) if !String::from_utf8_lossy(&cmdline).contains("proxy") => None,
+        _ => Some(pid),
+    }
+}
+
+/// An unused port on `ip`, as `ip:port`.
+fn free_addr(ip: &str) -> Result<String> {
+    let listener = StdTcpListener::bind((ip, 0))
+        .with_context(|| format!("Cannot listen on the sandbox network gateway {}", ip))?;
+    Ok(listener.local_addr()?.to_string())
+}
+
+fn start_proxy(session_id: &str, addr: &str, allow: &[String]) -> Result<()> {
+    let dir = egress_dir()?;
+    fs::create_dir_all(&dir)?;
+    let log_path = log_path(session_id)?;
+    let log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)?;
+
+    let exe = std::env::current_exe().context("Could not resolve aoe binary path")?;
+    let mut cmd = Command::new(exe);
+    cmd.args(["sandbox", "proxy", "--listen", addr]);
+    for pattern in allow {
+        cmd.args(["--allow", pattern]);
+    }
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::from(log.try_clone()?))
+        .stderr(Stdio::from(log));
+
+    // Outlive the TUI or CLI that started it, like `aoe serve --daemon`
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: setsid() is async-signal-safe per POSIX, which is the
+        // only requirement for pre_exec closures.
+        unsafe {
+            cmd.pre_exec(|| {
+                nix::unistd::setsid().map_err(std::io::Error::other)?;
+                Ok(())
+            });
+        }
+    }
+
+    let mut child = cmd.spawn().context("Failed to start the egress proxy")?;
+    fs::write(pid_path(session_id)?, child.id().to_string())?;
+
+    let addr: SocketAddr = addr.parse()?;
+    let started = Instant::now();
+    while StdTcpStream::connect_timeout(&addr, Duration::from_millis(200)).is_err() {
+        if child.try_wait()?.is_some() || started.elapsed() > PROXY_START_TIMEOUT {
+            stop_proxy(session_id);
+            bail!(
+                "Egress proxy didn't start on {}; see {}",
+                addr,
+                log_path.display()
+            );
+        }
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    tracing::info!("Egress proxy for session {} on {}", session_id, addr);
+    Ok(())
+}
+
+/// Run the filtering proxy until killed. Denied requests are printed to
+/// stdout, which `start_proxy` points at the session's egress log.
+pub async fn run_proxy(listen: SocketAddr, allow: Vec<String>) -> Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    println!(
+        "{} listening on {}, allowing: {}",
+        chrono::Utc::now().to_rfc3339(),
+        listen,
+        allow.join(", ")
+    );
+    let allow = Arc::new(allow);
+    loop {
+        let (client, _) = listener.accept().await?;
+        let allow = allow.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(client, &allow).await {
+                tracing::debug!("egress proxy connection: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_client(mut client: TcpStream, allow: &[String]) -> Result<()> {
+    let (head, rest) = read_head(&mut client).await?;
+    let request = match ProxyRequest::parse(&head) {
+        Ok(request) => request,
+        Err(e) => {
+            respond(&mut client, "400 Bad Request", &format!("{}\n", e)).await?;
+            return Ok(());
+        }
+    };
+
+    if !host_allowed(allow, &request.host) {
+        println!(
+            "{} denied {} {}:{}",
+            chrono::Utc::now().to_rfc3339(),
+            request.method,
+            request.host,
+            request.port
+        );
+        respond(
+            &mut client,
+            "403 Forbidden",
+            &format!(
+                "{} is not in this sandbox's network allowlist\n",
+                request.host
+            ),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let mut upstream = match TcpStream::connect((request.host.as_str(), request.port)).await {
+        Ok(upstream) => upstream,
+        Err(e) => {
+            respond(&mut client, "502 Bad Gateway", &format!("{}\n", e)).await?;
+            return Ok(());
+        }
+    };
+    match &request.forward_head {
+        None => {
+            client
+                .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+                .await?
+        }
+        Some(head) => upstream.write_all(head).await?,
+    }
+    upstream.write_all(&rest).await?;
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+async fn respond(client: &mut TcpStream, status: &str, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    client.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+/// Read up to the end of the request head, returning the head and any bytes
+/// read past it.
+async fn read_head(client: &mut TcpStream) -> Result<(Vec<u8>, Vec<u8>)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            bail!("connection closed before the request head ended");
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let rest = buf.split_off(end + 4);
+            return Ok((buf, rest));
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            bail!("request head too large");
+        }
+    }
+}
+
+/// A request made to the proxy
+#[derive(Debug, PartialEq)]
+struct ProxyRequest {
+    method: String,
+    host: String,
+    port: u16,
+    /// The head to send upstream for plain HTTP; `None` for CONNECT tunnels
+    forward_head: Option<Vec<u8>>,
+}
+
+impl ProxyRequest {
+    fn parse(head: &[u8]) -> Result<Self> {
+        let head = std::str::from_utf8(head).context("request head isn't UTF-8")?;
+        let (request_line, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+        let mut parts = request_line.split_whitespace();
+        let (Some(method), Some(target), Some(version)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            bail!("malformed request line");
+        };
+
+        if method.eq_ignore_ascii_case("CONNECT") {
+            let (host, port) = split_host_port(target, None)?;
+            return Ok(Self {
+                method: method.to_string(),
+                host,
+                port,
+                forward_head: None,
+            });
+        }
+
+        // Plain HTTP through a proxy uses absolute URLs
+        let Some(url) = target.strip_prefix("http://") else {
+            bail!("only CONNECT and http:// requests are proxied");
+        };
+        let (authority, path) = match url.find('/') {
+            Some(i) => (&url[..i], &url[i..]),
+            None => (url, "/"),
+        };
+        let (host, port) = split_host_port(authority, Some(80))?;
+
+        // Origin-form request line, without the headers meant for the proxy
+        let mut forward = format!("{} {} {}\r\n", method, path, version);
+        for line in headers.split("\r\n").filter(|line| !line.is_empty()) {
+            if !line.to_ascii_lowercase().starts_with("proxy-") {
+                forward.push_str(line);
+                forward.push_str("\r\n");
+            }
+        }
+        forward.push_str("\r\n");
+        Ok(Self {
+            method: method.to_string(),
+            host,
+            port,
+            forward_head: Some(forward.into_bytes()),
+        })
+    }
+}
+
+/// Split `host:port` or `[v6]:port`, with `default_port` when there's none.
+fn split_host_port(authority: &str, default_port: Option<u16>) -> Result<(String, u16)> {
+    let (host, port) = if let Some(rest) = authority.strip_prefix('[') {
+        let (host, after) = rest.split_once(']').context("unterminated IPv6 address")?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match authority.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match (port, default_port) {
+        (Some(port), _) => port.parse().context("invalid port")?,
+        (None, Some(port)) => port,
+        (None, None) => bail!("missing port in {}", authority),
+    };
+    if host.is_empty() {
+        bail!("missing host in {}", authority);
+    }
+    Ok((host.to_string(), port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_config() {
+        #[derive(Deserialize)]
+        struct Sandbox {
+            network: NetworkPolicy,
+        }
+        let parse = |toml: &str| toml::from_str::<Sandbox>(toml).unwrap().network;
+
+        assert_eq!(
+            parse(r#"network = "none""#),
+            NetworkPolicy::Mode(NetworkMode::None)
+        );
+        assert!(parse(r#"network = "default""#).is_default());
+        assert_eq!(
+            parse(r#"network = { allow = ["api.anthropic.com", "*.github.com"] }"#),
+            NetworkPolicy::Allowlist {
+                allow: vec!["api.anthropic.com".to_string(), "*.github.com".to_string()]
+            }
+        );
+        assert!(toml::from_str::<Sandbox>(r#"network = "bridge""#).is_err());
+    }
+
+    #[test]
+    fn test_host_allowed() {
+        let allow = vec!["api.anthropic.com".to_string(), "*.github.com".to_string()];
+        assert!(host_allowed(&allow, "api.anthropic.com"));
+        assert!(host_allowed(&allow, "API.Anthropic.com."));
+        assert!(host_allowed(&allow, "codeload.github.com"));
+        assert!(host_allowed(&allow, "objects.githubusercontent.github.com"));
+        assert!(!host_allowed(&allow, "github.com"));
+        assert!(!host_allowed(&allow, "evilgithub.com"));
+        assert!(!host_allowed(&allow, "anthropic.com"));
+        assert!(!host_allowed(&allow, "api.anthropic.com.evil.net"));
+        assert!(!host_allowed(&[], "api.anthropic.com"));
+    }
+
+    #[test]
+    fn test_parse_connect() {
+        let request = ProxyRequest::parse(
+            b"CONNECT api.anthropic.com:443 HTTP/1.1\r\nHost: api.anthropic.com:443\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.host, "api.anthropic.com");
+        assert_eq!(request.port, 443);
+        assert_eq!(request.forward_head, None);
+
+        let request = ProxyRequest::parse(b"CONNECT [::1]:8443 HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!((request.host.as_str(), request.port), ("::1", 8443));
+
+        assert!(ProxyRequest::parse(b"CONNECT example.com HTTP/1.1\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_plain_http() {
+        let request = ProxyRequest::parse(
+            b"GET http://registry.npmjs.org/left-pad HTTP/1.1\r\nHost: registry.npmjs.org\r\nProxy-Connection: keep-alive\r\nAccept: */*\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(request.host, "registry.npmjs.org");
+        assert_eq!(request.port, 80);
+        assert_eq!(
+            String::from_utf8(request.forward_head.unwrap()).unwrap(),
+            "GET /left-pad HTTP/1.1\r\nHost: registry.npmjs.org\r\nAccept: */*\r\n\r\n"
+        );
+
+        let request = ProxyRequest::parse(b"GET http://localhost:8080 HTTP/1.1\r\n\r\n").unwrap();
+        assert_eq!((request.host.as_str(), request.port), ("localhost", 8080));
+        assert!(String::from_utf8(request.forward_head.unwrap())
+            .unwrap()
+            .starts_with("GET / HTTP/1.1\r\n"));
+
+        // Origin-form requests aren't meant for a proxy
+        assert!(ProxyRequest::parse(b"GET /index.html HTTP/1.1\r\n\r\n").is_err());
+        assert!(ProxyRequest::parse(b"GET https://example.com/ HTTP/1.1\r\n\r\n").is_err());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_isolated_container_publishes_no_ports() -> Result<()> {
+        let home = tempfile::tempdir()?;
+        std::env::set_var("HOME", home.path());
+        #[cfg(target_os = "linux")]
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join(".config"));
+
+        let project = tempfile::tempdir()?;
+        assert!(publishes_ports(project.path()));
+        fs::create_dir_all(project.path().join(".agent-of-empires"))?;
+        fs::write(
+            project.path().join(".agent-of-empires/config.toml"),
+            "[sandbox]\nnetwork = \"none\"\n",
+        )?;
+        assert!(!publishes_ports(project.path()));
+
+        let mut config = ContainerConfig {
+            working_dir: "/workspace".to_string(),
+            volumes: Vec::new(),
+            anonymous_volumes: Vec::new(),
+            environment: Vec::new(),
+            cpu_limit: None,
+            memory_limit: None,
+            port_mappings: vec!["20005:20005".to_string()],
+            network: None,
+        };
+        assert_eq!(apply("abc", project.path(), &mut config)?, None);
+        assert_eq!(config.network.as_deref(), Some("none"));
+        assert!(config.port_mappings.is_empty());
+        Ok(())
+    }
+}
@@ -528,6 +528,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -551,6 +555,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -571,6 +579,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["AOE_TEST_EXTRA".to_string(), "FOO=bar".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -597,6 +609,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["DUP_KEY=from_session".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -619,6 +635,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -641,6 +661,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -664,6 +688,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let result = collect_environment(&config, &info);
@@ -725,6 +753,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["AOE_TEST_TOKEN=$AOE_TEST_TOKEN".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
         let result = build_docker_env_args(&sandbox, std::path::Path::new("/nonexistent"));
         // docker_args should have the key but NOT the secret value
@@ -761,6 +793,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["MY_MAPPED=$AOE_TEST_SOURCE".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
         let result = build_docker_env_args(&sandbox, std::path::Path::new("/nonexistent"));
         assert!(
@@ -797,6 +833,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["AOE_TEST_BARE".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
         let result = build_docker_env_args(&sandbox, std::path::Path::new("/nonexistent"));
         assert!(
@@ -832,6 +872,10 @@ mod tests {
             created_at: None,
             extra_env: Some(vec!["MY_LITERAL=some_value".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
         let result = build_docker_env_args(&sandbox, std::path::Path::new("/nonexistent"));
         assert!(
@@ -866,6 +910,10 @@ mod tests {
                 "MY_LITERAL=public_val".to_string(),
             ]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
         let result = build_docker_env_args(&sandbox, std::path::Path::new("/nonexistent"));
         // Secret: key only in docker_args, value in exports
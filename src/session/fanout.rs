@@ -0,0 +1,165 @@
+//! Fan-out - hand the same task to several agents at once
+//!
+//! `aoe add --fanout claude,codex` (or `--count N`) creates one worktree
+//! session per agent or replica. Each gets its own branch, suffixed from a
+//! shared base name, and they all land in a group named after that base so
+//! the results can be compared side by side (`C` in the TUI).
+
+use anyhow::{Context, Result};
+
+use super::builder::{self, BuildResult, InstanceParams};
+use crate::git::template::{sanitize_branch_name, suffixed_branch_name};
+use crate::git::GitWorktree;
+
+/// Parent group for fan-outs created without `--group`.
+pub const DEFAULT_GROUP: &str = "fanout";
+
+/// One session of a fan-out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FanoutMember {
+    pub tool: String,
+    pub branch: String,
+}
+
+/// One member per agent and replica. Branches are named `<base>-<agent>`
+/// when fanning out across agents, `<base>-<n>` for replicas of a single
+/// agent, and `<base>-<agent>-<n>` for both, skipping names in `taken`.
+pub fn plan(
+    base_branch: &str,
+    agents: &[String],
+    count: usize,
+    taken: &[String],
+) -> Vec<FanoutMember> {
+    let mut taken = taken.to_vec();
+    let mut members = Vec::new();
+    for tool in agents {
+        for n in 1..=count.max(1) {
+            let suffix = match (agents.len() > 1, count > 1) {
+                (true, true) => format!("{}-{}", tool, n),
+                (_, true) => n.to_string(),
+                (_, false) => tool.clone(),
+            };
+            let branch = suffixed_branch_name(base_branch, &suffix, &taken);
+            taken.push(branch.clone());
+            members.push(FanoutMember {
+                tool: tool.clone(),
+                branch,
+            });
+        }
+    }
+    members
+}
+
+/// Group for a fan-out: the base branch name, nested under `parent` (or
+/// [`DEFAULT_GROUP`]).
+pub fn group_path(parent: Option<&str>, base_branch: &str) -> String {
+    let parent = parent
+        .map(|p| p.trim().trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .unwrap_or(DEFAULT_GROUP);
+    format!("{}/{}", parent, sanitize_branch_name(base_branch))
+}
+
+/// Build a worktree session for every member from the shared `params`. If
+/// any of them fails, the ones already built are rolled back.
+pub fn build_all(
+    params: &InstanceParams,
+    members: &[FanoutMember],
+    profile: &str,
+) -> Result<Vec<BuildResult>> {
+    let mut built = Vec::new();
+    for member in members {
+        let member_params = InstanceParams {
+            title: member.branch.clone(),
+            tool: member.tool.clone(),
+            worktree_branch: Some(member.branch.clone()),
+            create_new_branch: true,
+            ..params.clone()
+        };
+        match builder::build_instance(member_params, &[], profile) {
+            Ok(result) => built.push(result),
+            Err(e) => {
+                rollback(&built);
+                return Err(e).with_context(|| {
+                    format!("Failed to create fan-out session '{}'", member.branch)
+                });
+            }
+        }
+    }
+    Ok(built)
+}
+
+/// Remove the worktrees and branches created by [`build_all`].
+pub fn rollback(built: &[BuildResult]) {
+    for result in built {
+        builder::cleanup_instance(
+            &result.instance,
+            result.created_worktree.as_ref(),
+            &result.created_workspace_worktrees,
+        );
+        let (Some(wt), Some(info)) = (&result.created_worktree, &result.instance.worktree_info)
+        else {
+            continue;
+        };
+        if let Ok(git_wt) = GitWorktree::new(wt.main_repo_path.clone()) {
+            if let Err(e) = git_wt.delete_branch(&info.branch) {
+                tracing::warn!("Failed to delete fan-out branch {}: {}", info.branch, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agents(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn branches(members: &[FanoutMember]) -> Vec<&str> {
+        members.iter().map(|m| m.branch.as_str()).collect()
+    }
+
+    #[test]
+    fn test_plan_one_session_per_agent() {
+        let members = plan("fix-login", &agents(&["claude", "codex"]), 1, &[]);
+        assert_eq!(branches(&members), ["fix-login-claude", "fix-login-codex"]);
+        assert_eq!(members[1].tool, "codex");
+    }
+
+    #[test]
+    fn test_plan_replicas_of_one_agent() {
+        let members = plan("task", &agents(&["claude"]), 3, &[]);
+        assert_eq!(branches(&members), ["task-1", "task-2", "task-3"]);
+        assert!(members.iter().all(|m| m.tool == "claude"));
+    }
+
+    #[test]
+    fn test_plan_agents_and_replicas() {
+        let members = plan("task", &agents(&["claude", "codex"]), 2, &[]);
+        assert_eq!(
+            branches(&members),
+            [
+                "task-claude-1",
+                "task-claude-2",
+                "task-codex-1",
+                "task-codex-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_skips_existing_and_repeated_names() {
+        let taken = agents(&["task-claude"]);
+        let members = plan("task", &agents(&["claude", "claude"]), 1, &taken);
+        assert_eq!(branches(&members), ["task-claude-2", "task-claude-3"]);
+    }
+
+    #[test]
+    fn test_group_path() {
+        assert_eq!(group_path(None, "fix/login"), "fanout/fix-login");
+        assert_eq!(group_path(Some("work/"), "task"), "work/task");
+        assert_eq!(group_path(Some(" "), "task"), "fanout/task");
+    }
+}
@@ -0,0 +1,333 @@
+//! Status history - per-profile JSONL log of session status transitions
+//!
+//! Every transition the TUI status poller or `aoe serve` observes is appended
+//! to `status_history.jsonl` in the profile directory, one JSON object per
+//! line. Both writers may run at once, so appends take an advisory lock and
+//! skip an event when the session's last recorded status already matches.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use super::{get_profile_dir, Status};
+
+const HISTORY_FILE: &str = "status_history.jsonl";
+
+/// Once the log grows past this, the oldest half is dropped on the next append.
+const MAX_LOG_BYTES: u64 = 2 * 1024 * 1024;
+
+/// How far back from the end of the log to look for a session's last event
+/// when deduplicating appends.
+const TAIL_SCAN_BYTES: u64 = 64 * 1024;
+
+/// What produced a status value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusSource {
+    /// Agent hook status file under `/tmp/aoe-hooks`
+    Hook,
+    /// Parsing the tmux pane content
+    #[default]
+    Pane,
+    /// tmux session or pane liveness (missing session, dead pane)
+    Tmux,
+    /// Sandbox container health check
+    Container,
+    /// Explicit user action (stop, restart)
+    User,
+    /// The stall watchdog, for Running sessions that have gone quiet
+    Watchdog,
+}
+
+impl StatusSource {
+    pub fn label(self) -> &'static str {
+        match self {
+            StatusSource::Hook => "hook",
+            StatusSource::Pane => "pane",
+            StatusSource::Tmux => "tmux",
+            StatusSource::Container => "container",
+            StatusSource::User => "user",
+            StatusSource::Watchdog => "watchdog",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusEvent {
+    pub session_id: String,
+    pub at: DateTime<Utc>,
+    pub from: Status,
+    pub to: Status,
+    pub source: StatusSource,
+}
+
+impl StatusEvent {
+    pub fn new(session_id: &str, from: Status, to: Status, source: StatusSource) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            at: Utc::now(),
+            from,
+            to,
+            source,
+        }
+    }
+}
+
+/// Statuses worth keeping a timeline for. Transient states (Starting,
+/// Creating, Deleting, Unknown) are not recorded on their own.
+pub fn is_tracked(status: Status) -> bool {
+    matches!(
+        status,
+        Status::Running
+            | Status::Waiting
+            | Status::Idle
+            | Status::Stalled
+            | Status::Error
+            | Status::Stopped
+    )
+}
+
+pub fn history_path(profile: &str) -> Result<PathBuf> {
+    Ok(get_profile_dir(profile)?.join(HISTORY_FILE))
+}
+
+/// Append a transition to the profile's log. Untracked target statuses and
+/// repeats of the session's last recorded status are ignored.
+pub fn record(profile: &str, event: &StatusEvent) -> Result<()> {
+    if !is_tracked(event.to) {
+        return Ok(());
+    }
+    append_to(&history_path(profile)?, event)
+}
+
+/// Record a transition, logging instead of returning failures. For callers
+/// on a polling path where a history write must never interrupt the loop.
+pub fn record_best_effort(profile: &str, event: &StatusEvent) {
+    if let Err(e) = record(profile, event) {
+        tracing::warn!("Failed to record status history: {}", e);
+    }
+}
+
+/// All recorded events for one session, oldest first.
+pub fn load(profile: &str, session_id: &str) -> Result<Vec<StatusEvent>> {
+    load_from(&history_path(profile)?, session_id)
+}
+
+/// Time spent in each status over the events' span, ending at `now` for the
+/// current (last) status. Ordered by first appearance.
+pub fn time_in_status(events: &[StatusEvent], now: DateTime<Utc>) -> Vec<(Status, Duration)> {
+    let mut totals: Vec<(Status, Duration)> = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        let end = events.get(i + 1).map(|next| next.at).unwrap_or(now);
+        let spent = (end - event.at).max(Duration::zero());
+        match totals.iter_mut().find(|(s, _)| *s == event.to) {
+            Some((_, total)) => *total += spent,
+            None => totals.push((event.to, spent)),
+        }
+    }
+    totals
+}
+
+/// Compact human-readable duration: `45s`, `12m`, `3h 5m`, `2d 4h`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.num_seconds().max(0);
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s => format!("{}d {}h", s / 86_400, (s % 86_400) / 3600),
+    }
+}
+
+fn append_to(path: &Path, event: &StatusEvent) -> Result<()> {
+    // Lock a sidecar file rather than the log itself, since compaction
+    // replaces the log's inode.
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path.with_extension("jsonl.lock"))?;
+    FileExt::lock_exclusive(&lock_file)?;
+
+    let result = (|| -> Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        if last_status_in_tail(&mut file, &event.session_id)? == Some(event.to) {
+            return Ok(());
+        }
+        let mut line = serde_json::to_string(event)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())?;
+
+        if file.metadata()?.len() > MAX_LOG_BYTES {
+            let content = fs::read_to_string(path)?;
+            let lines: Vec<&str> = content.lines().collect();
+            rewrite(path, &lines[lines.len() / 2..])?;
+        }
+        Ok(())
+    })();
+
+    let _ = FileExt::unlock(&lock_file);
+    result
+}
+
+fn last_status_in_tail(file: &mut File, session_id: &str) -> Result<Option<Status>> {
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(TAIL_SCAN_BYTES);
+    file.seek(SeekFrom::Start(start))?;
+    let mut tail = String::new();
+    file.read_to_string(&mut tail)?;
+
+    // The first line may be cut in half when we started mid-file; it simply
+    // fails to parse and is skipped.
+    Ok(tail
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<StatusEvent>(line).ok())
+        .find(|e| e.session_id == session_id)
+        .map(|e| e.to))
+}
+
+fn load_from(path: &Path, session_id: &str) -> Result<Vec<StatusEvent>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<StatusEvent>(line).ok())
+        .filter(|e| e.session_id == session_id)
+        .collect())
+}
+
+/// Replace the log with `lines` via temp file + rename. Caller holds the lock.
+fn rewrite(path: &Path, lines: &[&str]) -> Result<()> {
+    let tmp = path.with_extension("jsonl.tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        for line in lines {
+            file.write_all(line.as_bytes())?;
+            file.write_all(b"\n")?;
+        }
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn event(id: &str, from: Status, to: Status, secs: i64) -> StatusEvent {
+        StatusEvent {
+            session_id: id.to_string(),
+            at: DateTime::from_timestamp(1_700_000_000 + secs, 0).unwrap(),
+            from,
+            to,
+            source: StatusSource::Hook,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_filters_by_session() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join(HISTORY_FILE);
+
+        append_to(&path, &event("a", Status::Idle, Status::Running, 0))?;
+        append_to(&path, &event("b", Status::Idle, Status::Running, 1))?;
+        append_to(&path, &event("a", Status::Running, Status::Waiting, 2))?;
+
+        let events = load_from(&path, "a")?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].to, Status::Waiting);
+        assert_eq!(load_from(&path, "b")?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_append_skips_repeat_of_last_status() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join(HISTORY_FILE);
+
+        // Same transition seen by both the TUI and `aoe serve`
+        append_to(&path, &event("a", Status::Running, Status::Waiting, 0))?;
+        append_to(&path, &event("b", Status::Idle, Status::Running, 1))?;
+        append_to(&path, &event("a", Status::Running, Status::Waiting, 2))?;
+
+        assert_eq!(load_from(&path, "a")?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() -> Result<()> {
+        let temp = tempdir()?;
+        assert!(load_from(&temp.path().join(HISTORY_FILE), "a")?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_skips_corrupt_lines() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join(HISTORY_FILE);
+        append_to(&path, &event("a", Status::Idle, Status::Running, 0))?;
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(b"{ truncated\n")?;
+        append_to(&path, &event("a", Status::Running, Status::Idle, 5))?;
+
+        assert_eq!(load_from(&path, "a")?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_in_status_accumulates_per_status() {
+        let events = vec![
+            event("a", Status::Idle, Status::Running, 0),
+            event("a", Status::Running, Status::Waiting, 60),
+            event("a", Status::Waiting, Status::Running, 660),
+            event("a", Status::Running, Status::Idle, 720),
+        ];
+        let now = events[3].at + Duration::seconds(30);
+
+        let totals = time_in_status(&events, now);
+        assert_eq!(
+            totals,
+            vec![
+                (Status::Running, Duration::seconds(120)),
+                (Status::Waiting, Duration::seconds(600)),
+                (Status::Idle, Duration::seconds(30)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::seconds(42)), "42s");
+        assert_eq!(format_duration(Duration::seconds(12 * 60 + 5)), "12m");
+        assert_eq!(
+            format_duration(Duration::seconds(3 * 3600 + 5 * 60)),
+            "3h 5m"
+        );
+        assert_eq!(
+            format_duration(Duration::seconds(2 * 86_400 + 4 * 3600)),
+            "2d 4h"
+        );
+        assert_eq!(format_duration(Duration::seconds(-5)), "0s");
+    }
+
+    #[test]
+    fn test_is_tracked() {
+        assert!(is_tracked(Status::Waiting));
+        assert!(is_tracked(Status::Stopped));
+        assert!(!is_tracked(Status::Starting));
+        assert!(!is_tracked(Status::Unknown));
+    }
+}
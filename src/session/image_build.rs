@@ -0,0 +1,357 @@
+//! Sandbox images built from a repo's Dockerfile
+//!
+//! `[sandbox.build]` in a repo's config names a Dockerfile, build context
+//! and build args. Instead of pulling `default_image`, AoE builds the image
+//! locally and tags it by a hash of the Dockerfile, the args and the files
+//! in the context, so every session reuses the same image until one of
+//! those inputs changes. `aoe sandbox build` builds ahead of time and
+//! `aoe sandbox prune` removes built images no session uses.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::containers::{self, ContainerRuntimeInterface, ImageBuild};
+
+/// Prefix of the tags of images built from `sandbox.build`
+pub const BUILD_TAG_PREFIX: &str = "aoe-build-";
+
+/// Prefixes of the tags of every image AoE builds
+pub const BUILT_IMAGE_PREFIXES: [&str; 2] =
+    [BUILD_TAG_PREFIX, super::devcontainer::BUILD_TAG_PREFIX];
+
+/// Files excluded from the build context, and so from the tag's hash. Only
+/// the one at the root of the context counts, as with Docker.
+const IGNORE_FILE: &str = ".dockerignore";
+
+/// Sandbox image build configuration (`[sandbox.build]`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxBuildConfig {
+    /// Dockerfile, relative to the repo root
+    pub dockerfile: String,
+
+    /// Build context, relative to the repo root. Defaults to the repo root.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+
+    /// `--build-arg` values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub args: BTreeMap<String, String>,
+}
+
+impl SandboxBuildConfig {
+    /// The build for the repo at `project_path`, tagged by its inputs.
+    pub fn resolve(&self, project_path: &Path) -> Result<ImageBuild> {
+        let dockerfile = project_path.join(&self.dockerfile);
+        let context = project_path.join(self.context.as_deref().unwrap_or("."));
+        let args: Vec<(String, String)> = self
+            .args
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        Ok(ImageBuild {
+            tag: content_tag(BUILD_TAG_PREFIX, &dockerfile, &context, &args, None)?,
+            dockerfile,
+            context,
+            args,
+            target: None,
+        })
+    }
+}
+
+/// The `sandbox.build` image for the project, if its config has one.
+pub fn for_project(project_path: &Path) -> Result<Option<ImageBuild>> {
+    let profile = super::config::resolve_default_profile();
+    let config = super::repo_config::resolve_config_with_repo(&profile, project_path)?;
+    config
+        .sandbox
+        .build
+        .map(|build| build.resolve(project_path))
+        .transpose()
+}
+
+/// Build the image unless an image with its tag already exists, passing
+/// build output to `on_output`.
+pub fn ensure_built(build: &ImageBuild, on_output: &mut dyn FnMut(&str)) -> Result<()> {
+    let runtime = containers::get_container_runtime();
+    if runtime.image_exists_locally(&build.tag) {
+        tracing::info!("Using built sandbox image '{}'", build.tag);
+        return Ok(());
+    }
+    runtime.build_image(build, on_output)?;
+    Ok(())
+}
+
+/// Tag for an image built from these inputs: `prefix` and a hash of the
+/// Dockerfile, args, target and every file in the build context that
+/// `.dockerignore` doesn't exclude.
+pub fn content_tag(
+    prefix: &str,
+    dockerfile: &Path,
+    context: &Path,
+    args: &[(String, String)],
+    target: Option<&str>,
+) -> Result<String> {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        fs::read(dockerfile).with_context(|| format!("Failed to read {}", dockerfile.display()))?,
+    );
+    for (key, value) in args {
+        hasher.update(format!("\narg:{}={}", key, value).as_bytes());
+    }
+    if let Some(target) = target {
+        hasher.update(format!("\ntarget:{}", target).as_bytes());
+    }
+    hash_context(&mut hasher, context)
+        .with_context(|| format!("Failed to read build context {}", context.display()))?;
+
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .take(6)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    Ok(format!("{}{}", prefix, hash))
+}
+
+fn hash_context(hasher: &mut Sha256, context: &Path) -> Result<()> {
+    let ignore = Arc::new(DockerIgnore::load(context)?);
+    let filter = {
+        let ignore = ignore.clone();
+        let context = context.to_path_buf();
+        move |entry: &ignore::DirEntry| {
+            // Git metadata changes with every commit and checkout; it would
+            // invalidate the image without changing what gets built
+            if entry.file_name() == ".git" {
+                return false;
+            }
+            // An excluded directory may still have files brought back by a
+            // `!` pattern
+            let relative = entry.path().strip_prefix(&context).unwrap_or(entry.path());
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !(is_dir && !ignore.has_exceptions() && ignore.excludes(relative))
+        }
+    };
+    let walker = WalkBuilder::new(context)
+        .standard_filters(false)
+        .filter_entry(filter)
+        .build();
+
+    let mut files = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_type().is_some_and(|t| !t.is_dir()) {
+            let relative = entry.path().strip_prefix(context).unwrap_or(entry.path());
+            if !ignore.excludes(relative) {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    files.sort();
+
+    for path in files {
+        let relative = path.strip_prefix(context).unwrap_or(&path);
+        hasher.update(format!("\nfile:{}\n", relative.display()).as_bytes());
+        if path.is_symlink() {
+            hasher.update(fs::read_link(&path)?.as_os_str().as_encoded_bytes());
+        } else {
+            hasher.update(fs::read(&path)?);
+        }
+    }
+    Ok(())
+}
+
+/// A `.dockerignore`, matched the way Docker does: patterns are relative to
+/// the context root, a pattern matching a directory excludes everything in
+/// it, and the last pattern to match a path decides, so `!` can bring a file
+/// back.
+#[derive(Default)]
+struct DockerIgnore {
+    /// Each pattern and whether it excludes (rather than brings back)
+    patterns: Vec<(GlobMatcher, bool)>,
+}
+
+impl DockerIgnore {
+    fn load(context: &Path) -> Result<Self> {
+        match fs::read_to_string(context.join(IGNORE_FILE)) {
+            Ok(content) => Self::parse(&content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn parse(content: &str) -> Result<Self> {
+        let mut patterns = Vec::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (pattern, exclude) = match line.strip_prefix('!') {
+                Some(pattern) => (pattern.trim(), false),
+                None => (line, true),
+            };
+            let mut pattern = pattern.trim_start_matches('/');
+            while let Some(rest) = pattern.strip_prefix("./") {
+                pattern = rest;
+            }
+            let pattern = pattern.trim_end_matches('/');
+            if pattern.is_empty() || pattern == "." {
+                continue;
+            }
+            let glob = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid {} pattern {:?}", IGNORE_FILE, line))?;
+            patterns.push((glob.compile_matcher(), exclude));
+        }
+        Ok(Self { patterns })
+    }
+
+    fn has_exceptions(&self) -> bool {
+        self.patterns.iter().any(|(_, exclude)| !exclude)
+    }
+
+    /// Whether `relative`, a path in the context, is left out of it
+    fn excludes(&self, relative: &Path) -> bool {
+        let mut excluded = false;
+        for (glob, exclude) in &self.patterns {
+            if *exclude != excluded
+                && relative
+                    .ancestors()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .any(|p| glob.is_match(p))
+            {
+                excluded = *exclude;
+            }
+        }
+        excluded
+    }
+}
+
+/// Images AoE built, and snapshots, that no session in any profile uses,
+/// as `name:tag`.
+pub fn unused_images() -> Result<Vec<String>> {
+    let mut in_use = HashSet::new();
+    for profile in super::list_profiles()? {
+        let (instances, _) = super::Storage::new(&profile)?.load_with_groups()?;
+        for sandbox in instances.into_iter().filter_map(|inst| inst.sandbox_info) {
+            in_use.insert(image_name(&sandbox.image).to_string());
+            in_use.extend(sandbox.snapshots.into_iter().map(|s| s.image));
+        }
+    }
+
+    let runtime = containers::get_container_runtime();
+    Ok(BUILT_IMAGE_PREFIXES
+        .iter()
+        .chain([&super::snapshot::SNAPSHOT_TAG_PREFIX])
+        .flat_map(|prefix| runtime.list_images(prefix))
+        .filter(|image| !in_use.contains(image_name(image)))
+        .collect())
+}
+
+/// An image reference without the implicit `:latest` tag
+fn image_name(image: &str) -> &str {
+    image.strip_suffix(":latest").unwrap_or(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tag_follows_context_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let repo = dir.path();
+        fs::write(repo.join("Dockerfile"), "FROM ubuntu:24.04\nCOPY . /src\n")?;
+        fs::write(repo.join("requirements.txt"), "requests\n")?;
+        fs::write(repo.join(IGNORE_FILE), "notes.md\n")?;
+        fs::create_dir_all(repo.join(".git"))?;
+
+        let config: SandboxBuildConfig = toml::from_str(
+            r#"
+            dockerfile = "Dockerfile"
+            args = { PYTHON = "3.12" }
+            "#,
+        )?;
+        let build = config.resolve(repo)?;
+        assert!(build.tag.starts_with(BUILD_TAG_PREFIX));
+        assert_eq!(build.context, repo.join("."));
+        assert_eq!(build.args, vec![("PYTHON".to_string(), "3.12".to_string())]);
+        let tag = build.tag;
+
+        // Ignored files and git metadata don't change the tag
+        fs::write(repo.join("notes.md"), "todo\n")?;
+        fs::write(repo.join(".git").join("HEAD"), "ref: refs/heads/main\n")?;
+        assert_eq!(config.resolve(repo)?.tag, tag);
+
+        // Patterns are anchored to the context root, and nested ignore
+        // files don't count
+        fs::create_dir_all(repo.join("sub"))?;
+        fs::write(repo.join("sub").join("notes.md"), "todo\n")?;
+        let with_sub = config.resolve(repo)?.tag;
+        assert_ne!(with_sub, tag);
+        fs::write(repo.join("sub").join(IGNORE_FILE), "*\n")?;
+        fs::write(repo.join("sub").join("notes.md"), "done\n")?;
+        assert_ne!(config.resolve(repo)?.tag, with_sub);
+        fs::remove_dir_all(repo.join("sub"))?;
+        assert_eq!(config.resolve(repo)?.tag, tag);
+
+        fs::write(repo.join("requirements.txt"), "requests\nnumpy\n")?;
+        let changed = config.resolve(repo)?.tag;
+        assert_ne!(changed, tag);
+
+        let mut with_args = config.clone();
+        with_args
+            .args
+            .insert("PYTHON".to_string(), "3.13".to_string());
+        assert_ne!(with_args.resolve(repo)?.tag, changed);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dockerignore_matching() -> Result<()> {
+        let ignore = DockerIgnore::parse(
+            "# comment\n/notes.md\nbuild/\n*.log\n**/*.tmp\n!build/keep.txt\n\n./docs/*.md\n",
+        )?;
+        let excludes = |path: &str| ignore.excludes(Path::new(path));
+        assert!(excludes("notes.md"));
+        assert!(!excludes("sub/notes.md"));
+        assert!(excludes("build"));
+        assert!(excludes("build/out/app"));
+        assert!(!excludes("build/keep.txt"));
+        assert!(excludes("server.log"));
+        assert!(!excludes("logs/server.log"));
+        assert!(excludes("a/b/c.tmp"));
+        assert!(excludes("docs/readme.md"));
+        assert!(!excludes("docs/api/readme.md"));
+        assert!(!excludes("Dockerfile"));
+        assert!(ignore.has_exceptions());
+
+        assert!(DockerIgnore::parse("[").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_dockerfile() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = SandboxBuildConfig {
+            dockerfile: "Dockerfile".to_string(),
+            ..Default::default()
+        };
+        assert!(config.resolve(dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_image_name() {
+        assert_eq!(image_name("aoe-build-0123:latest"), "aoe-build-0123");
+        assert_eq!(image_name("aoe-build-0123"), "aoe-build-0123");
+        assert_eq!(image_name("my-sandbox:v2"), "my-sandbox:v2");
+    }
+}
@@ -0,0 +1,180 @@
+//! Initial prompt delivery - hand a new session its first task
+//!
+//! A session created with a prompt (`aoe add --prompt`, the new-session dialog
+//! or `POST /api/sessions`) stores it in `Instance::initial_prompt`. Starting
+//! the session launches a hidden `aoe tmux deliver-prompt` process through
+//! `tmux run-shell -b`, so it outlives the CLI or TUI that started it. That
+//! process waits for the agent's status to first turn Idle or Waiting, then
+//! types the prompt into the pane with `send_keys`.
+//!
+//! The prompt is taken out of storage in the same locked update that decides
+//! to send it, so it is delivered at most once, even when the session is
+//! restarted before the agent was ready.
+
+use anyhow::{bail, Context, Result};
+use std::time::{Duration, Instant};
+
+use super::environment::shell_escape;
+use super::{Instance, Status, Storage};
+use crate::tmux;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long to wait for the agent to become ready before giving up.
+const READY_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Launch the background delivery process for a session that has a pending
+/// prompt.
+pub fn start(session: &tmux::Session, profile: &str, session_id: &str) -> Result<()> {
+    run_aoe_background(
+        session,
+        &[
+            "tmux",
+            "deliver-prompt",
+            "--profile",
+            profile,
+            "--session",
+            session_id,
+        ],
+    )
+}
+
+/// Run this aoe binary with `args` in the background through `tmux run-shell`,
+/// detached from the CLI or TUI that launched it.
+pub(super) fn run_aoe_background(session: &tmux::Session, args: &[&str]) -> Result<()> {
+    let exe = std::env::current_exe().context("Could not resolve aoe binary path")?;
+    // tmux runs the command with the server's environment, which may point
+    // at a different app directory than ours (e.g. a server started under
+    // another HOME), so pass along the variables that locate storage.
+    let env: String = ["HOME", "XDG_CONFIG_HOME"]
+        .iter()
+        .filter_map(|var| {
+            std::env::var(var)
+                .ok()
+                .map(|value| format!("{}={} ", var, shell_escape(&value)))
+        })
+        .collect();
+    let args: Vec<String> = args.iter().map(|arg| shell_escape(arg)).collect();
+    let command = format!(
+        "{}{} {} >/dev/null 2>&1",
+        env,
+        shell_escape(&exe.to_string_lossy()),
+        args.join(" "),
+    );
+    session.run_shell_background(&command)
+}
+
+/// Take the pending prompt out of storage. Returns `None` if it was already
+/// delivered (or the session is gone).
+pub fn claim(profile: &str, session_id: &str) -> Result<Option<String>> {
+    Storage::new(profile)?.update(|instances| {
+        Ok(instances
+            .iter_mut()
+            .find(|i| i.id == session_id)
+            .and_then(|i| i.initial_prompt.take()))
+    })
+}
+
+/// Whether a status means the agent is ready to receive input.
+pub fn is_ready(status: Status) -> bool {
+    matches!(status, Status::Idle | Status::Waiting)
+}
+
+/// Poll the session until the agent is ready, then send the pending prompt.
+/// Returns `Ok(false)` when there was nothing to send: the prompt was already
+/// claimed, or the session was stopped or deleted first. Stopping kills the
+/// tmux session; starting it again launches a new delivery process.
+pub fn deliver_when_ready(profile: &str, session_id: &str) -> Result<bool> {
+    let storage = Storage::new(profile)?;
+    let started = Instant::now();
+    // Kept across polls so status detection sees a continuous history (and
+    // the usual startup grace period) instead of whatever was last saved.
+    let mut instance: Option<Instance> = None;
+
+    loop {
+        if started.elapsed() >= READY_TIMEOUT {
+            bail!(
+                "Timed out waiting for session {} to become ready",
+                session_id
+            );
+        }
+        std::thread::sleep(POLL_INTERVAL);
+
+        // The session may not be saved yet when the agent is launched
+        let Some(stored) = storage.load()?.into_iter().find(|i| i.id == session_id) else {
+            if instance.is_some() {
+                return Ok(false);
+            }
+            continue;
+        };
+        if stored.initial_prompt.is_none() {
+            return Ok(false);
+        }
+
+        let inst = instance.get_or_insert_with(|| {
+            let mut inst = stored;
+            inst.status = Status::Starting;
+            inst.last_start_time = Some(started);
+            inst
+        });
+        if !inst.tmux_session()?.exists() {
+            return Ok(false);
+        }
+        inst.update_status_with_metadata(None);
+        if !is_ready(inst.status) {
+            continue;
+        }
+
+        let Some(prompt) = claim(profile, session_id)? else {
+            return Ok(false);
+        };
+        inst.tmux_session()?.send_keys(&prompt)?;
+        return Ok(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::tempdir;
+
+    fn setup_test_home(temp: &std::path::Path) {
+        std::env::set_var("HOME", temp);
+        #[cfg(target_os = "linux")]
+        std::env::set_var("XDG_CONFIG_HOME", temp.join(".config"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_claim_takes_prompt_once() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let mut inst = Instance::new("test", "/tmp/test");
+        inst.initial_prompt = Some("fix the build".to_string());
+        Storage::new("test-profile")?.save(std::slice::from_ref(&inst))?;
+
+        assert_eq!(
+            claim("test-profile", &inst.id)?.as_deref(),
+            Some("fix the build")
+        );
+        assert_eq!(claim("test-profile", &inst.id)?, None);
+        assert_eq!(claim("test-profile", "missing")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_ready_only_for_idle_and_waiting() {
+        assert!(is_ready(Status::Idle));
+        assert!(is_ready(Status::Waiting));
+        for status in [
+            Status::Starting,
+            Status::Running,
+            Status::Error,
+            Status::Stopped,
+        ] {
+            assert!(!is_ready(status));
+        }
+    }
+}
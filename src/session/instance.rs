@@ -1,6 +1,7 @@
 //! Session instance definition and operations
 
 use std::path::Path;
+use std::sync::mpsc;
 
 use anyhow::Result;
 use chrono::{DateTime, Utc};
@@ -11,7 +12,14 @@ use crate::containers::{self, ContainerRuntimeInterface, DockerContainer};
 use crate::tmux;
 
 use super::container_config;
+use super::egress;
 use super::environment::{build_docker_env_args, shell_escape};
+use super::history::StatusSource;
+use super::image_build;
+use super::ports::{self, PortAllocation};
+use super::repo_config::HookProgress;
+use super::services;
+use super::snapshot::{self, SandboxSnapshot};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TerminalInfo {
@@ -28,6 +36,9 @@ pub enum Status {
     Waiting,
     #[default]
     Idle,
+    /// Running, but neither the pane nor the agent's hooks have changed for
+    /// the watchdog's `stall_after_minutes`
+    Stalled,
     Unknown,
     Stopped,
     Error,
@@ -84,6 +95,18 @@ pub struct SandboxInfo {
     /// Custom instruction text to inject into agent launch command
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_instruction: Option<String>,
+    /// User to exec as, from the devcontainer's `remoteUser`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_user: Option<String>,
+    /// Address of the egress proxy, when `sandbox.network` is an allowlist
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress_proxy: Option<String>,
+    /// Snapshots of the container, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshots: Vec<SandboxSnapshot>,
+    /// Compose project of the session's `sandbox.services`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_project: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -146,6 +169,25 @@ pub struct Instance {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub notify_on_error: Option<bool>,
 
+    // Webhook per-session overrides. None means each `[[webhooks]]`
+    // endpoint's `events` filter decides; Some(v) replaces that filter for
+    // the event type on every endpoint. The error override also covers
+    // Stalled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_on_waiting: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_on_idle: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_on_error: Option<bool>,
+
+    /// Prompt to send once the agent is first ready; cleared on delivery.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_prompt: Option<String>,
+
+    /// Host ports reserved for the session, from `session.ports`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<PortAllocation>,
+
     // Runtime state (not serialized)
     #[serde(skip)]
     pub last_error_check: Option<std::time::Instant>,
@@ -153,6 +195,9 @@ pub struct Instance {
     pub last_start_time: Option<std::time::Instant>,
     #[serde(skip)]
     pub last_error: Option<String>,
+    /// What produced the current `status` (hook file, pane parsing, ...)
+    #[serde(skip)]
+    pub status_source: StatusSource,
 }
 
 impl Instance {
@@ -179,9 +224,15 @@ impl Instance {
             notify_on_waiting: None,
             notify_on_idle: None,
             notify_on_error: None,
+            webhook_on_waiting: None,
+            webhook_on_idle: None,
+            webhook_on_error: None,
+            initial_prompt: None,
+            ports: Vec::new(),
             last_error_check: None,
             last_start_time: None,
             last_error: None,
+            status_source: StatusSource::default(),
         }
     }
 
@@ -311,7 +362,12 @@ impl Instance {
         let container_workdir = self.container_workdir();
 
         let cmd = container.exec_command(
-            Some(&format!("-w {} {}", container_workdir, env_part)),
+            Some(&format!(
+                "-w {} {}{}",
+                container_workdir,
+                exec_user_flag(sandbox),
+                env_part
+            )),
             "/bin/bash",
         );
 
@@ -501,7 +557,12 @@ impl Instance {
 
             let env_info = build_docker_env_args(sandbox, std::path::Path::new(&self.project_path));
             // AOE_INSTANCE_ID is not secret, goes directly in docker args
-            let docker_args = format!("{} -e AOE_INSTANCE_ID={}", env_info.docker_args, self.id);
+            let docker_args = format!(
+                "{}{} -e AOE_INSTANCE_ID={}",
+                exec_user_flag(sandbox),
+                env_info.docker_args,
+                self.id
+            );
             let env_part = format!("{} ", docker_args);
             let wrapped =
                 wrap_command_ignore_suspend(&container.exec_command(Some(&env_part), &tool_cmd));
@@ -520,9 +581,11 @@ impl Instance {
         } else {
             // Run on_launch hooks on host for non-sandboxed sessions
             if let Some(ref hook_cmds) = on_launch_hooks {
-                if let Err(e) =
-                    super::repo_config::execute_hooks(hook_cmds, Path::new(&self.project_path))
-                {
+                if let Err(e) = super::repo_config::execute_hooks(
+                    hook_cmds,
+                    Path::new(&self.project_path),
+                    &ports::env(&self.ports),
+                ) {
                     tracing::warn!("on_launch hook failed: {}", e);
                 }
             }
@@ -531,11 +594,12 @@ impl Instance {
             // (either JSON-based hook_config or settl's TOML hooks)
             let has_hooks =
                 agent.and_then(|a| a.hook_config.as_ref()).is_some() || self.tool == "settl";
-            let env_prefix = if has_hooks {
+            let mut env_prefix = if has_hooks {
                 format!("AOE_INSTANCE_ID={} ", self.id)
             } else {
                 String::new()
             };
+            env_prefix.push_str(&ports::shell_prefix(&self.ports));
 
             if self.command.is_empty() {
                 crate::agents::get_agent(&self.tool).map(|a| {
@@ -595,6 +659,38 @@ impl Instance {
         // Apply all configured tmux options (status bar, mouse, etc.)
         self.apply_tmux_options();
 
+        if super::recording::is_enabled(&self.source_profile, Path::new(&self.project_path)) {
+            if let Err(e) =
+                super::recording::start(&session, &self.source_profile, &self.id, &self.title)
+            {
+                tracing::warn!("Failed to start session recording: {}", e);
+            }
+        }
+
+        if self.initial_prompt.is_some() {
+            if let Err(e) = super::initial_prompt::start(&session, &self.source_profile, &self.id) {
+                tracing::warn!("Failed to schedule initial prompt: {}", e);
+            }
+        }
+
+        if super::message_queue::has_pending(&self.source_profile, &self.id) {
+            if let Err(e) =
+                super::message_queue::start_dispatcher(&session, &self.source_profile, &self.id)
+            {
+                tracing::warn!("Failed to start message queue dispatcher: {}", e);
+            }
+        }
+
+        if super::auto_respond::is_enabled(
+            &self.source_profile,
+            Path::new(&self.project_path),
+            &self.tool,
+        ) {
+            if let Err(e) = super::auto_respond::start(&session, &self.source_profile, &self.id) {
+                tracing::warn!("Failed to start auto-responder: {}", e);
+            }
+        }
+
         self.status = Status::Starting;
         self.last_start_time = Some(std::time::Instant::now());
 
@@ -612,40 +708,195 @@ impl Instance {
     }
 
     pub fn get_container_for_instance(&mut self) -> Result<containers::DockerContainer> {
+        self.ensure_container(None, None)
+    }
+
+    /// Like `get_container_for_instance`, streaming sandbox image build
+    /// output through `progress_tx`.
+    pub fn get_container_for_instance_streamed(
+        &mut self,
+        progress_tx: &mpsc::Sender<HookProgress>,
+    ) -> Result<containers::DockerContainer> {
+        self.ensure_container(Some(progress_tx), None)
+    }
+
+    /// Make sure the session's container exists and is running, creating it
+    /// from `snapshot_image` if given.
+    fn ensure_container(
+        &mut self,
+        progress_tx: Option<&mpsc::Sender<HookProgress>>,
+        snapshot_image: Option<&str>,
+    ) -> Result<containers::DockerContainer> {
         let sandbox = self
             .sandbox_info
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Cannot ensure container for non-sandboxed session"))?;
 
-        let image = &sandbox.image;
-        let container = DockerContainer::new(&self.id, image);
-
-        if container.is_running()? {
-            container_config::refresh_agent_configs();
-            return Ok(container);
-        }
+        let container = DockerContainer::new(&self.id, &sandbox.image);
+        let project_path = Path::new(&self.project_path);
 
         if container.exists()? {
+            if let Some(addr) = &sandbox.egress_proxy {
+                egress::resume(&self.id, project_path, addr)?;
+            }
+            if let Some(project) = &sandbox.compose_project {
+                if let Err(e) = services::start(project) {
+                    tracing::warn!("{:#}", e);
+                }
+            }
             container_config::refresh_agent_configs();
-            container.start()?;
+            if !container.is_running()? {
+                container.start()?;
+            }
             return Ok(container);
         }
 
-        // Ensure image is available (always pulls to get latest)
-        let runtime = containers::get_container_runtime();
-        runtime.ensure_image(image)?;
-
-        let config = self.build_container_config()?;
-        let container_id = container.create(&config)?;
+        let mut on_build_output = progress_output(progress_tx, "Building sandbox image");
+
+        // A snapshot, a devcontainer's image, then a `sandbox.build` image,
+        // replace the sandbox image
+        let devcontainer = container_config::load_devcontainer(project_path)?;
+        let image = match (snapshot_image, &devcontainer) {
+            (Some(image), _) => image.to_string(),
+            (None, Some(devcontainer)) => devcontainer.prepare_image(&mut on_build_output)?,
+            (None, None) => match image_build::for_project(project_path)? {
+                Some(build) => {
+                    image_build::ensure_built(&build, &mut on_build_output)?;
+                    build.tag
+                }
+                None => {
+                    // Ensure image is available (always pulls to get latest)
+                    let runtime = containers::get_container_runtime();
+                    runtime.ensure_image(&sandbox.image)?;
+                    sandbox.image.clone()
+                }
+            },
+        };
+        let container = DockerContainer::new(&self.id, &image);
+
+        let mut config = self.build_container_config(devcontainer.as_ref())?;
+        let egress_proxy = egress::apply(&self.id, project_path, &mut config)?;
+        let mut on_services_output = progress_output(progress_tx, "Starting services");
+        let compose_project =
+            match services::apply(&self.id, project_path, &mut config, &mut on_services_output) {
+                Ok(project) => project,
+                Err(e) => {
+                    egress::stop_proxy(&self.id);
+                    if egress_proxy.is_some() {
+                        let _ = egress::remove_network(&self.id);
+                    }
+                    return Err(e);
+                }
+            };
+        let created = container.create(&config).map_err(anyhow::Error::from);
+        let created = match (created, &compose_project) {
+            (Ok(id), Some(project)) => services::connect(project, &container.name).map(|()| id),
+            (created, _) => created,
+        };
+        let container_id = match created {
+            Ok(id) => id,
+            Err(e) => {
+                if container.exists().unwrap_or(false) {
+                    let _ = container.remove(true);
+                }
+                egress::stop_proxy(&self.id);
+                if egress_proxy.is_some() {
+                    let _ = egress::remove_network(&self.id);
+                }
+                if let Some(project) = &compose_project {
+                    if let Err(e) = services::down(&self.id, project) {
+                        tracing::warn!("{:#}", e);
+                    }
+                }
+                return Err(e);
+            }
+        };
 
         if let Some(ref mut sandbox) = self.sandbox_info {
             sandbox.container_id = Some(container_id);
             sandbox.created_at = Some(Utc::now());
+            sandbox.image = image;
+            sandbox.remote_user = devcontainer
+                .as_ref()
+                .and_then(|d| d.remote_user())
+                .map(String::from);
+            sandbox.egress_proxy = egress_proxy;
+            sandbox.compose_project = compose_project;
+        }
+
+        // A snapshot already has whatever postCreateCommand set up. It comes
+        // from the repo, so like `on_create` it only runs once trusted.
+        if let (None, Some(devcontainer)) = (snapshot_image, &devcontainer) {
+            let trusted = devcontainer.post_create_commands().is_empty()
+                || matches!(
+                    super::repo_config::check_hook_trust(project_path),
+                    Ok(super::repo_config::HookTrustStatus::Trusted(_))
+                );
+            if !trusted {
+                tracing::warn!(
+                    "Skipping devcontainer postCreateCommand: the repo's hooks aren't trusted"
+                );
+            } else if let Err(e) =
+                devcontainer.run_post_create(&container.name, &config.working_dir)
+            {
+                tracing::warn!("{:#}", e);
+            }
         }
 
         Ok(container)
     }
 
+    /// Commit the sandbox container to a new snapshot image and record it.
+    pub fn snapshot(&mut self) -> Result<SandboxSnapshot> {
+        let Some(sandbox) = self.sandbox_info.as_mut() else {
+            anyhow::bail!("Session is not sandboxed");
+        };
+        let container = DockerContainer::from_session_id(&self.id);
+        if !container.exists()? {
+            anyhow::bail!("Session has no container to snapshot; start it first");
+        }
+
+        let snapshot = SandboxSnapshot::new(&self.id, Utc::now());
+        container.commit(&snapshot.image)?;
+        // Two snapshots in the same second share a tag; the newer one wins
+        sandbox.snapshots.retain(|s| s.image != snapshot.image);
+        sandbox.snapshots.push(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Replace the sandbox container with a new one created from the
+    /// snapshot named `query` (see [`snapshot::find`]), restarting the agent
+    /// if it was running. Everything since the snapshot outside the mounted
+    /// project directory is lost.
+    pub fn restore_snapshot(&mut self, query: &str) -> Result<SandboxSnapshot> {
+        let Some(sandbox) = self.sandbox_info.as_ref() else {
+            anyhow::bail!("Session is not sandboxed");
+        };
+        let Some(snapshot) = snapshot::find(&sandbox.snapshots, query).cloned() else {
+            anyhow::bail!("No snapshot '{}' for session {}", query, self.title);
+        };
+        let runtime = containers::get_container_runtime();
+        if !runtime.image_exists_locally(&snapshot.image) {
+            anyhow::bail!("Snapshot image {} no longer exists", snapshot.image);
+        }
+
+        let was_running = self.tmux_session()?.exists();
+        self.kill()?;
+        self.kill_container_terminal()?;
+
+        let container = DockerContainer::from_session_id(&self.id);
+        if container.exists()? {
+            container.remove(true)?;
+        }
+        egress::stop_proxy(&self.id);
+
+        self.ensure_container(None, Some(&snapshot.image))?;
+        if was_running {
+            self.start()?;
+        }
+        Ok(snapshot)
+    }
+
     /// Get the container working directory for this instance.
     pub fn container_workdir(&self) -> String {
         container_config::compute_volume_paths(Path::new(&self.project_path), &self.project_path)
@@ -653,15 +904,21 @@ impl Instance {
             .unwrap_or_else(|_| "/workspace".to_string())
     }
 
-    fn build_container_config(&self) -> Result<crate::containers::ContainerConfig> {
-        container_config::build_container_config(
+    fn build_container_config(
+        &self,
+        devcontainer: Option<&super::devcontainer::Devcontainer>,
+    ) -> Result<crate::containers::ContainerConfig> {
+        let mut config = container_config::build_container_config(
             &self.project_path,
             self.sandbox_info.as_ref().unwrap(),
             &self.tool,
             self.is_yolo_mode(),
             &self.id,
             self.workspace_info.as_ref(),
-        )
+            devcontainer,
+        )?;
+        ports::apply(&self.ports, &mut config);
+        Ok(config)
     }
 
     pub fn restart(&mut self) -> Result<()> {
@@ -691,15 +948,26 @@ impl Instance {
 
     /// Stop the session: kill the tmux session and stop the Docker container
     /// (if sandboxed). The container is stopped but not removed, so it can be
-    /// restarted on re-attach.
+    /// restarted on re-attach. `on_stop` hooks run in between.
     pub fn stop(&self) -> Result<()> {
         self.kill()?;
+        super::status_hooks::session_stopping(self);
 
         if self.is_sandboxed() {
             let container = containers::DockerContainer::from_session_id(&self.id);
             if container.is_running().unwrap_or(false) {
                 container.stop()?;
             }
+            egress::stop_proxy(&self.id);
+            if let Some(project) = self
+                .sandbox_info
+                .as_ref()
+                .and_then(|s| s.compose_project.as_deref())
+            {
+                if let Err(e) = services::stop(project) {
+                    tracing::warn!("{:#}", e);
+                }
+            }
         }
 
         crate::hooks::cleanup_hook_status_dir(&self.id);
@@ -710,6 +978,14 @@ impl Instance {
     /// Update status using pre-fetched pane metadata to avoid per-instance
     /// subprocess spawns. Falls back to subprocess calls if metadata is missing.
     pub fn update_status_with_metadata(&mut self, metadata: Option<&tmux::PaneMetadata>) {
+        self.detect_status(metadata);
+        if self.status == Status::Running && super::watchdog::is_stalled(self, metadata) {
+            self.status = Status::Stalled;
+            self.status_source = StatusSource::Watchdog;
+        }
+    }
+
+    fn detect_status(&mut self, metadata: Option<&tmux::PaneMetadata>) {
         if matches!(
             self.status,
             Status::Stopped | Status::Deleting | Status::Creating
@@ -728,6 +1004,7 @@ impl Instance {
         if let Some(start_time) = self.last_start_time {
             if start_time.elapsed().as_secs() < 3 {
                 self.status = Status::Starting;
+                self.status_source = StatusSource::Tmux;
                 return;
             }
         }
@@ -740,6 +1017,7 @@ impl Instance {
                     self.title
                 );
                 self.status = Status::Error;
+                self.status_source = StatusSource::Tmux;
                 if self.last_error.is_none() {
                     self.last_error = Some(
                         "Could not reach tmux. Is tmux still running on the host?".to_string(),
@@ -757,6 +1035,7 @@ impl Instance {
                 tmux::Session::generate_name(&self.id, &self.title)
             );
             self.status = Status::Error;
+            self.status_source = StatusSource::Tmux;
             if self.last_error.is_none() {
                 self.last_error = Some(
                     "tmux session is gone. The agent process may have exited or been killed."
@@ -796,12 +1075,14 @@ impl Instance {
             );
             if is_dead {
                 self.status = Status::Error;
+                self.status_source = StatusSource::Tmux;
                 if self.last_error.is_none() {
                     let pane_content = session.capture_pane(20).unwrap_or_default();
                     self.last_error = Some(summarize_error_from_pane(&pane_content));
                 }
             } else {
                 self.status = hook_status;
+                self.status_source = StatusSource::Hook;
                 self.last_error = None;
             }
             return;
@@ -873,6 +1154,7 @@ impl Instance {
             other => other,
         };
 
+        self.status_source = StatusSource::Pane;
         tracing::trace!("status '{}': final={:?}", self.title, self.status);
 
         if self.status == Status::Error {
@@ -899,7 +1181,7 @@ impl Instance {
     }
 }
 
-fn generate_id() -> String {
+pub(crate) fn generate_id() -> String {
     Uuid::new_v4().to_string().replace("-", "")[..16].to_string()
 }
 
@@ -973,6 +1255,34 @@ fn format_env_var_prefix(key: &str, value: &str, cmd: &str) -> String {
     format!("{}={} {}", key, escaped, cmd)
 }
 
+/// `docker exec` flag selecting the devcontainer's `remoteUser`, with a
+/// trailing space, or nothing to run as the image's user.
+fn exec_user_flag(sandbox: &SandboxInfo) -> String {
+    match &sandbox.remote_user {
+        Some(user) => format!("-u {} ", shell_escape(user)),
+        None => String::new(),
+    }
+}
+
+/// Progress callback for container setup output: sends `label` once, then
+/// each line, through `progress_tx`, or logs the lines without one.
+fn progress_output<'a>(
+    progress_tx: Option<&'a mpsc::Sender<HookProgress>>,
+    label: &'a str,
+) -> impl FnMut(&str) + 'a {
+    let mut started = false;
+    move |line: &str| match progress_tx {
+        Some(tx) => {
+            if !started {
+                started = true;
+                let _ = tx.send(HookProgress::Started(label.to_string()));
+            }
+            let _ = tx.send(HookProgress::Output(line.to_string()));
+        }
+        None => tracing::debug!("{}: {}", label, line),
+    }
+}
+
 /// Wrap a command to disable Ctrl-Z (SIGTSTP) suspension.
 ///
 /// When running agents directly as tmux session commands (without a parent shell),
@@ -1132,6 +1442,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         });
         assert!(!inst.is_sandboxed());
     }
@@ -1147,6 +1461,10 @@ mod tests {
             created_at: None,
             extra_env: None,
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         });
         assert!(inst.is_sandboxed());
     }
@@ -1252,6 +1570,10 @@ mod tests {
             created_at: Some(Utc::now()),
             extra_env: Some(vec!["MY_VAR".to_string(), "OTHER_VAR".to_string()]),
             custom_instruction: None,
+            remote_user: None,
+            egress_proxy: None,
+            snapshots: Vec::new(),
+            compose_project: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
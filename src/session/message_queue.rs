@@ -0,0 +1,386 @@
+//! Message queue - per-session outbox delivered when the agent goes idle
+//!
+//! `aoe send --queue`, the TUI queue dialog and the web API append messages
+//! to `queue/<session id>.json` in the profile directory. While a session has
+//! queued messages, a hidden `aoe tmux dispatch-queue` process (launched
+//! through `tmux run-shell -b`, like initial prompt delivery) watches its
+//! status and types the next message once the agent has taken the last one
+//! and is Idle again. A turn can start and end between two status polls, so
+//! a change to the session's hook status file or pane after a send also
+//! counts as the agent having taken it. The dispatcher exits once the queue
+//! is empty or the tmux session is gone; starting the session again
+//! launches a new one.
+//!
+//! The dispatcher holds a lock on `<session id>.dispatch.lock` for as long as
+//! it runs, so there is at most one per session however many places queue
+//! messages.
+
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::{get_profile_dir, Instance, Status, Storage};
+use crate::tmux;
+
+const QUEUE_DIR: &str = "queue";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Pane lines compared to tell whether the agent answered
+pub(crate) const PANE_LINES: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueuedMessage {
+    pub id: String,
+    pub text: String,
+    pub queued_at: DateTime<Utc>,
+}
+
+impl QueuedMessage {
+    fn new(text: &str) -> Self {
+        Self {
+            id: super::instance::generate_id(),
+            text: text.to_string(),
+            queued_at: Utc::now(),
+        }
+    }
+}
+
+pub fn queue_path(profile: &str, session_id: &str) -> Result<PathBuf> {
+    Ok(get_profile_dir(profile)?
+        .join(QUEUE_DIR)
+        .join(format!("{}.json", session_id)))
+}
+
+/// Queued messages for a session, next to be delivered first.
+pub fn load(profile: &str, session_id: &str) -> Result<Vec<QueuedMessage>> {
+    load_from(&queue_path(profile, session_id)?)
+}
+
+/// Append a message to the session's queue and make sure a dispatcher is
+/// running for it. A stopped session keeps the message until it is started.
+pub fn enqueue(profile: &str, inst: &Instance, text: &str) -> Result<QueuedMessage> {
+    if text.trim().is_empty() {
+        bail!("Message cannot be empty");
+    }
+    let message = QueuedMessage::new(text);
+    update(&queue_path(profile, &inst.id)?, |queue| {
+        queue.push(message.clone())
+    })?;
+
+    let session = inst.tmux_session()?;
+    if session.exists() {
+        if let Err(e) = start_dispatcher(&session, profile, &inst.id) {
+            tracing::warn!("Failed to start message queue dispatcher: {}", e);
+        }
+    }
+    Ok(message)
+}
+
+/// Replace the text of a queued message. Returns `false` if it was already
+/// delivered or removed.
+pub fn edit(profile: &str, session_id: &str, message_id: &str, text: &str) -> Result<bool> {
+    if text.trim().is_empty() {
+        bail!("Message cannot be empty");
+    }
+    update(&queue_path(profile, session_id)?, |queue| {
+        match queue.iter_mut().find(|m| m.id == message_id) {
+            Some(message) => {
+                message.text = text.to_string();
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Drop a queued message. Returns `false` if it was already delivered or
+/// removed.
+pub fn remove(profile: &str, session_id: &str, message_id: &str) -> Result<bool> {
+    update(&queue_path(profile, session_id)?, |queue| {
+        let before = queue.len();
+        queue.retain(|m| m.id != message_id);
+        queue.len() != before
+    })
+}
+
+/// Delete a session's queue and its lock files, for when the session is
+/// deleted.
+pub fn clear(profile: &str, session_id: &str) -> Result<()> {
+    let path = queue_path(profile, session_id)?;
+    for file in [
+        path.clone(),
+        path.with_extension("lock"),
+        path.with_extension("dispatch.lock"),
+    ] {
+        if file.exists() {
+            fs::remove_file(&file)?;
+        }
+    }
+    Ok(())
+}
+
+/// Launch the background dispatcher for a session with queued messages.
+pub fn start_dispatcher(session: &tmux::Session, profile: &str, session_id: &str) -> Result<()> {
+    super::initial_prompt::run_aoe_background(
+        session,
+        &[
+            "tmux",
+            "dispatch-queue",
+            "--profile",
+            profile,
+            "--session",
+            session_id,
+        ],
+    )
+}
+
+/// Whether a session has anything queued, for deciding to start a
+/// dispatcher.
+pub fn has_pending(profile: &str, session_id: &str) -> bool {
+    queue_path(profile, session_id).is_ok_and(|path| path.exists())
+}
+
+/// What a session looked like when a message was sent to it, to tell when
+/// the agent has taken it.
+pub(crate) struct Sent {
+    hook_updated: Option<SystemTime>,
+    /// The pane a poll after sending, once the message has been typed
+    pane: Option<String>,
+    pub(crate) taken: bool,
+}
+
+impl Sent {
+    pub(crate) fn new(hook_updated: Option<SystemTime>) -> Self {
+        Self {
+            hook_updated,
+            pane: None,
+            taken: false,
+        }
+    }
+
+    /// Record a poll. The message was taken once the agent was seen busy,
+    /// its hooks wrote a status or its pane changed.
+    pub(crate) fn observe(
+        &mut self,
+        idle: bool,
+        hook_updated: Option<SystemTime>,
+        pane: Option<String>,
+    ) {
+        if !idle || hook_updated != self.hook_updated {
+            self.taken = true;
+        }
+        match (&self.pane, pane) {
+            (None, pane) => self.pane = pane,
+            (Some(before), Some(now)) if *before != now => self.taken = true,
+            _ => {}
+        }
+    }
+}
+
+/// When the session's agent hooks last wrote a status
+pub(crate) fn hook_updated(session_id: &str) -> Option<SystemTime> {
+    fs::metadata(crate::hooks::hook_status_dir(session_id).join("status"))
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+/// Deliver queued messages, each once the agent has taken the previous one
+/// and is Idle, until the queue is empty, the session's tmux session is
+/// gone, or it is deleted. Returns the number of messages sent; 0 right away
+/// if another dispatcher is running.
+pub fn dispatch(profile: &str, session_id: &str) -> Result<usize> {
+    let path = queue_path(profile, session_id)?;
+    let Some(mut lease) = try_lease(&path)? else {
+        return Ok(0);
+    };
+    let storage = Storage::new(profile)?;
+    let mut instance: Option<Instance> = None;
+    // None until something is sent, so a session that is already idle gets
+    // its first message right away
+    let mut sent: Option<Sent> = None;
+    let mut delivered = 0;
+
+    loop {
+        if load_from(&path)?.is_empty() {
+            // A message queued between this check and exiting would find
+            // the lease still held and not start a dispatcher of its own, so
+            // release first and look once more.
+            drop(lease);
+            if load_from(&path)?.is_empty() {
+                return Ok(delivered);
+            }
+            match try_lease(&path)? {
+                Some(reacquired) => lease = reacquired,
+                None => return Ok(delivered),
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+
+        let Some(stored) = storage.load()?.into_iter().find(|i| i.id == session_id) else {
+            return Ok(delivered);
+        };
+        if stored.initial_prompt.is_some() {
+            // Let the first task go first, then wait for it to finish
+            sent.get_or_insert_with(|| Sent::new(hook_updated(session_id)));
+            continue;
+        }
+
+        // Kept across polls so status detection sees a continuous history
+        let inst = instance.get_or_insert(stored);
+        let session = inst.tmux_session()?;
+        if !session.exists() {
+            return Ok(delivered);
+        }
+        inst.update_status_with_metadata(None);
+        let idle = inst.status == Status::Idle;
+        let ready = match sent.as_mut() {
+            None => idle,
+            Some(sent) => {
+                sent.observe(
+                    idle,
+                    hook_updated(session_id),
+                    session.capture_pane(PANE_LINES).ok(),
+                );
+                idle && sent.taken
+            }
+        };
+        if ready {
+            if let Some(message) =
+                update(&path, |queue| (!queue.is_empty()).then(|| queue.remove(0)))?
+            {
+                let before = hook_updated(session_id);
+                if let Err(e) = session.send_keys(&message.text) {
+                    update(&path, |queue| queue.insert(0, message))?;
+                    return Err(e);
+                }
+                sent = Some(Sent::new(before));
+                delivered += 1;
+            }
+        }
+    }
+}
+
+fn load_from(path: &Path) -> Result<Vec<QueuedMessage>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+}
+
+/// Read-modify-write the queue at `path` under its lock. An emptied queue is
+/// removed rather than saved.
+fn update<T>(path: &Path, f: impl FnOnce(&mut Vec<QueuedMessage>) -> T) -> Result<T> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path.with_extension("lock"))?;
+    FileExt::lock_exclusive(&lock_file)?;
+
+    let result = (|| -> Result<T> {
+        let mut queue = load_from(path)?;
+        let out = f(&mut queue);
+        if queue.is_empty() {
+            if path.exists() {
+                fs::remove_file(path)?;
+            }
+        } else {
+            let tmp = path.with_extension("json.tmp");
+            fs::write(&tmp, serde_json::to_string_pretty(&queue)?)?;
+            fs::rename(&tmp, path)?;
+        }
+        Ok(out)
+    })();
+
+    let _ = FileExt::unlock(&lock_file);
+    result
+}
+
+/// Take the dispatcher lock for the queue at `path`, or `None` if another
+/// process holds it. Released when the returned file is dropped.
+fn try_lease(path: &Path) -> Result<Option<File>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path.with_extension("dispatch.lock"))?;
+    Ok(FileExt::try_lock_exclusive(&file).ok().map(|_| file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_update_keeps_order_and_removes_empty_queue() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join(QUEUE_DIR).join("abc.json");
+
+        let first = QueuedMessage::new("first");
+        let second = QueuedMessage::new("second");
+        update(&path, |q| q.push(first.clone()))?;
+        update(&path, |q| q.push(second.clone()))?;
+        assert_eq!(load_from(&path)?, vec![first.clone(), second.clone()]);
+
+        let popped = update(&path, |q| q.remove(0))?;
+        assert_eq!(popped, first);
+        update(&path, |q| q.retain(|m| m.id != second.id))?;
+        assert!(!path.exists());
+        assert!(load_from(&path)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sent_is_taken_on_any_sign_of_a_turn() {
+        let pane = |s: &str| Some(s.to_string());
+        let then = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let later = then + Duration::from_secs(1);
+
+        // Nothing happened after the message was typed
+        let mut sent = Sent::new(Some(then));
+        sent.observe(true, Some(then), pane("> fix it"));
+        sent.observe(true, Some(then), pane("> fix it"));
+        assert!(!sent.taken);
+
+        // A turn too short to be seen busy still changes the pane
+        sent.observe(true, Some(then), pane("> fix it\nDone."));
+        assert!(sent.taken);
+
+        // or the hook status file
+        let mut sent = Sent::new(Some(then));
+        sent.observe(true, Some(later), pane("> fix it"));
+        assert!(sent.taken);
+        let mut sent = Sent::new(None);
+        sent.observe(true, Some(later), None);
+        assert!(sent.taken);
+
+        let mut sent = Sent::new(None);
+        sent.observe(false, None, None);
+        assert!(sent.taken);
+    }
+
+    #[test]
+    fn test_lease_is_exclusive_until_dropped() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join("abc.json");
+
+        let lease = try_lease(&path)?;
+        assert!(lease.is_some());
+        assert!(try_lease(&path)?.is_none());
+        drop(lease);
+        assert!(try_lease(&path)?.is_some());
+        Ok(())
+    }
+}
@@ -1,17 +1,33 @@
 //! Session management module
 
+pub mod auto_respond;
 pub mod builder;
+pub mod bundle;
 pub mod civilizations;
 pub mod config;
 pub(crate) mod container_config;
 pub mod deletion;
+pub mod devcontainer;
+pub mod egress;
 pub(crate) mod environment;
+pub mod fanout;
 mod groups;
+pub mod history;
+pub mod image_build;
+pub mod initial_prompt;
 mod instance;
+pub mod message_queue;
+pub mod ports;
 pub mod profile_config;
+pub mod recording;
 pub mod repo_config;
 pub(crate) mod serde_helpers;
+pub mod services;
+pub mod snapshot;
+pub mod status_hooks;
 mod storage;
+pub mod templates;
+pub mod watchdog;
 
 pub use crate::sound::{SoundConfig, SoundConfigOverride};
 pub use config::{
@@ -37,7 +53,8 @@ pub use repo_config::{
     merge_repo_config, profile_to_repo_config, repo_config_to_profile, resolve_config_with_repo,
     save_repo_config, trust_repo, HookTrustStatus, HooksConfig, RepoConfig,
 };
-pub use storage::Storage;
+pub use storage::{Storage, StorageLock, SyncBase};
+pub use templates::SessionTemplate;
 
 use anyhow::Result;
 use std::fs;
@@ -0,0 +1,313 @@
+//! Per-session host ports
+//!
+//! Sessions of the same project, say in parallel worktrees, would otherwise
+//! all start their dev servers on the same port. `session.ports` in the
+//! config asks for a number of ports per session, exposed as `AOE_PORT_0`,
+//! `AOE_PORT_1`, ..., or for a list of variable names to expose them under.
+//! Ports are picked from [`PORT_RANGE`] when the session is created, stored
+//! in the session and reserved in `ports.json` in the app directory, so no
+//! two sessions in any profile get the same port. Deleting the session
+//! releases them.
+//!
+//! The variables are set for the agent and for hooks. A sandboxed session's
+//! container publishes each port on the same host port, unless
+//! `sandbox.network` keeps it off the host's network.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::net::TcpListener;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+
+use crate::containers::{ContainerConfig, EnvEntry};
+
+/// Host ports sessions are given
+pub const PORT_RANGE: RangeInclusive<u16> = 20000..=29999;
+
+const REGISTRY_FILE: &str = "ports.json";
+
+/// `session.ports`: how many ports each session gets, or the names of the
+/// variables to expose them under
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PortsConfig {
+    Count(u16),
+    Named(Vec<String>),
+}
+
+impl Default for PortsConfig {
+    fn default() -> Self {
+        Self::Count(0)
+    }
+}
+
+impl PortsConfig {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Count(count) => *count == 0,
+            Self::Named(names) => names.is_empty(),
+        }
+    }
+
+    /// Names of the variables the ports are exposed under
+    pub fn names(&self) -> Result<Vec<String>> {
+        match self {
+            Self::Count(count) => Ok((0..*count).map(|i| format!("AOE_PORT_{}", i)).collect()),
+            Self::Named(names) => {
+                for (i, name) in names.iter().enumerate() {
+                    if !is_env_name(name) {
+                        bail!("session.ports: {:?} is not a valid variable name", name);
+                    }
+                    if names[..i].contains(name) {
+                        bail!("session.ports: {} is listed more than once", name);
+                    }
+                }
+                Ok(names.clone())
+            }
+        }
+    }
+}
+
+/// A host port reserved for a session
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortAllocation {
+    /// Variable the port is exposed under
+    pub name: String,
+    pub port: u16,
+}
+
+/// Reserve a port for each of `names` for the session. Ports already
+/// reserved by any session or in use on the host are skipped.
+pub fn allocate(session_id: &str, names: &[String]) -> Result<Vec<PortAllocation>> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    allocate_in(&registry_path()?, session_id, names, port_is_free)
+}
+
+/// Release the session's ports.
+pub fn release(session_id: &str) -> Result<()> {
+    let path = registry_path()?;
+    if !path.exists() {
+        return Ok(());
+    }
+    release_in(&path, session_id)
+}
+
+/// The ports as environment variables
+pub fn env(ports: &[PortAllocation]) -> Vec<(String, String)> {
+    ports
+        .iter()
+        .map(|p| (p.name.clone(), p.port.to_string()))
+        .collect()
+}
+
+/// The ports as `NAME=port`, comma separated, for display
+pub fn summary(ports: &[PortAllocation]) -> String {
+    ports
+        .iter()
+        .map(|p| format!("{}={}", p.name, p.port))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Publish the ports from a container about to be created and expose them
+/// to it.
+pub(crate) fn apply(ports: &[PortAllocation], config: &mut ContainerConfig) {
+    for p in ports {
+        let mapping = format!("{0}:{0}", p.port);
+        if !config.port_mappings.contains(&mapping) {
+            config.port_mappings.push(mapping);
+        }
+        config.environment.push(EnvEntry::Literal {
+            key: p.name.clone(),
+            value: p.port.to_string(),
+        });
+    }
+}
+
+/// `NAME=port ` prefixes setting the ports for a shell command
+pub(crate) fn shell_prefix(ports: &[PortAllocation]) -> String {
+    ports
+        .iter()
+        .map(|p| format!("{}={} ", p.name, p.port))
+        .collect()
+}
+
+fn allocate_in(
+    path: &Path,
+    session_id: &str,
+    names: &[String],
+    is_free: impl Fn(u16) -> bool,
+) -> Result<Vec<PortAllocation>> {
+    with_registry(path, |registry| {
+        let mut candidates = PORT_RANGE.filter(|port| !registry.contains_key(port));
+        let mut ports = Vec::with_capacity(names.len());
+        for name in names {
+            let Some(port) = candidates.by_ref().find(|port| is_free(*port)) else {
+                bail!(
+                    "No free port left in {}-{}",
+                    PORT_RANGE.start(),
+                    PORT_RANGE.end()
+                );
+            };
+            ports.push(PortAllocation {
+                name: name.clone(),
+                port,
+            });
+        }
+        for p in &ports {
+            registry.insert(p.port, session_id.to_string());
+        }
+        Ok(ports)
+    })
+}
+
+fn release_in(path: &Path, session_id: &str) -> Result<()> {
+    with_registry(path, |registry| {
+        registry.retain(|_, owner| owner != session_id);
+        Ok(())
+    })
+}
+
+/// Run `f` on the registry of reserved ports (port -> session ID) under an
+/// exclusive lock, saving it afterwards.
+fn with_registry<T>(
+    path: &Path,
+    f: impl FnOnce(&mut BTreeMap<u16, String>) -> Result<T>,
+) -> Result<T> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock_file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(path.with_extension("lock"))?;
+    FileExt::lock_exclusive(&lock_file)?;
+
+    let result = (|| -> Result<T> {
+        let mut registry: BTreeMap<u16, String> = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        let out = f(&mut registry)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(&registry)?)?;
+        fs::rename(&tmp, path)?;
+        Ok(out)
+    })();
+
+    let _ = FileExt::unlock(&lock_file);
+    result
+}
+
+fn registry_path() -> Result<PathBuf> {
+    Ok(super::get_app_dir()?.join(REGISTRY_FILE))
+}
+
+fn port_is_free(port: u16) -> bool {
+    TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_config_names() {
+        assert_eq!(
+            PortsConfig::Count(2).names().unwrap(),
+            names(&["AOE_PORT_0", "AOE_PORT_1"])
+        );
+        assert!(PortsConfig::default().names().unwrap().is_empty());
+        assert_eq!(
+            PortsConfig::Named(names(&["WEB_PORT", "API_PORT"]))
+                .names()
+                .unwrap(),
+            names(&["WEB_PORT", "API_PORT"])
+        );
+        assert!(PortsConfig::Named(names(&["web-port"])).names().is_err());
+        assert!(PortsConfig::Named(names(&["PORT", "PORT"]))
+            .names()
+            .is_err());
+    }
+
+    #[test]
+    fn test_config_parses_count_or_names() {
+        #[derive(Deserialize)]
+        struct Session {
+            ports: PortsConfig,
+        }
+        let count: Session = toml::from_str("ports = 3").unwrap();
+        assert_eq!(count.ports, PortsConfig::Count(3));
+        let named: Session = toml::from_str(r#"ports = ["WEB_PORT"]"#).unwrap();
+        assert_eq!(named.ports, PortsConfig::Named(names(&["WEB_PORT"])));
+    }
+
+    #[test]
+    fn test_sessions_get_distinct_ports() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(REGISTRY_FILE);
+        let start = *PORT_RANGE.start();
+        let busy = start + 1;
+
+        let first = allocate_in(&path, "s1", &names(&["A", "B"]), |p| p != busy).unwrap();
+        assert_eq!(
+            first.iter().map(|p| p.port).collect::<Vec<_>>(),
+            vec![start, start + 2]
+        );
+        assert_eq!(first[1].name, "B");
+
+        let second = allocate_in(&path, "s2", &names(&["A"]), |_| true).unwrap();
+        assert_eq!(second[0].port, start + 1);
+
+        // Released ports are handed out again
+        release_in(&path, "s1").unwrap();
+        let third = allocate_in(&path, "s3", &names(&["A"]), |_| true).unwrap();
+        assert_eq!(third[0].port, start);
+    }
+
+    #[test]
+    fn test_apply_publishes_and_exposes_ports() {
+        let ports = vec![PortAllocation {
+            name: "AOE_PORT_0".to_string(),
+            port: 20005,
+        }];
+        let mut config = ContainerConfig {
+            working_dir: "/workspace".to_string(),
+            volumes: Vec::new(),
+            anonymous_volumes: Vec::new(),
+            environment: Vec::new(),
+            cpu_limit: None,
+            memory_limit: None,
+            port_mappings: Vec::new(),
+            network: None,
+        };
+        apply(&ports, &mut config);
+        assert_eq!(config.port_mappings, vec!["20005:20005".to_string()]);
+        assert!(config.environment.contains(&EnvEntry::Literal {
+            key: "AOE_PORT_0".to_string(),
+            value: "20005".to_string(),
+        }));
+        assert_eq!(shell_prefix(&ports), "AOE_PORT_0=20005 ");
+        assert_eq!(summary(&ports), "AOE_PORT_0=20005");
+    }
+}
@@ -5,13 +5,14 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 
 use super::config::{
     Config, ContainerRuntimeName, DefaultTerminalMode, TmuxMouseMode, TmuxStatusBarMode,
 };
 use super::get_profile_dir;
+use super::templates::{apply_template_overrides, SessionTemplate};
 
 /// Profile-specific settings. All fields are Option<T> - None means "inherit from global"
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -42,6 +43,16 @@ pub struct ProfileConfig {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sound: Option<crate::sound::SoundConfigOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_respond: Option<super::auto_respond::AutoRespondConfigOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watchdog: Option<super::watchdog::WatchdogConfigOverride>,
+
+    /// Templates merged by name into the global ones, field by field
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, SessionTemplate>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -151,6 +162,18 @@ pub struct SandboxConfigOverride {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub container_runtime: Option<ContainerRuntimeName>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub use_devcontainer: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<super::image_build::SandboxBuildConfig>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<super::egress::NetworkPolicy>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub services: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -184,6 +207,12 @@ pub struct SessionConfigOverride {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent_detect_as: Option<HashMap<String, String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub record: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ports: Option<super::ports::PortsConfig>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -208,6 +237,40 @@ pub struct HooksConfigOverride {
         deserialize_with = "super::serde_helpers::option_string_or_vec"
     )]
     pub on_destroy: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::serde_helpers::option_string_or_vec"
+    )]
+    pub on_waiting: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::serde_helpers::option_string_or_vec"
+    )]
+    pub on_idle: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::serde_helpers::option_string_or_vec"
+    )]
+    pub on_error: Option<Vec<String>>,
+
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "super::serde_helpers::option_string_or_vec"
+    )]
+    pub on_stop: Option<Vec<String>>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_hook_timeout_secs: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_hooks_in_container: Option<bool>,
 }
 
 /// Load profile-specific config. Returns empty config if file doesn't exist.
@@ -248,6 +311,9 @@ pub fn profile_has_overrides(config: &ProfileConfig) -> bool {
         || config.session.is_some()
         || config.hooks.is_some()
         || config.sound.is_some()
+        || config.auto_respond.is_some()
+        || config.watchdog.is_some()
+        || !config.templates.is_empty()
 }
 
 /// Load effective config for a profile (global + profile overrides merged)
@@ -301,6 +367,18 @@ pub fn apply_sandbox_overrides(
     if let Some(container_runtime) = source.container_runtime {
         target.container_runtime = container_runtime;
     }
+    if let Some(use_devcontainer) = source.use_devcontainer {
+        target.use_devcontainer = use_devcontainer;
+    }
+    if let Some(ref build) = source.build {
+        target.build = Some(build.clone());
+    }
+    if let Some(ref network) = source.network {
+        target.network = network.clone();
+    }
+    if let Some(ref services) = source.services {
+        target.services = Some(services.clone());
+    }
 }
 
 /// Apply worktree config overrides to a target config.
@@ -345,6 +423,24 @@ pub fn apply_hooks_overrides(
     if let Some(ref on_destroy) = source.on_destroy {
         target.on_destroy = on_destroy.clone();
     }
+    if let Some(ref on_waiting) = source.on_waiting {
+        target.on_waiting = on_waiting.clone();
+    }
+    if let Some(ref on_idle) = source.on_idle {
+        target.on_idle = on_idle.clone();
+    }
+    if let Some(ref on_error) = source.on_error {
+        target.on_error = on_error.clone();
+    }
+    if let Some(ref on_stop) = source.on_stop {
+        target.on_stop = on_stop.clone();
+    }
+    if source.status_hook_timeout_secs.is_some() {
+        target.status_hook_timeout_secs = source.status_hook_timeout_secs;
+    }
+    if source.status_hooks_in_container.is_some() {
+        target.status_hooks_in_container = source.status_hooks_in_container;
+    }
 }
 
 /// Apply session config overrides to a target config.
@@ -367,12 +463,18 @@ pub fn apply_session_overrides(
     if let Some(agent_status_hooks) = source.agent_status_hooks {
         target.agent_status_hooks = agent_status_hooks;
     }
+    if let Some(record) = source.record {
+        target.record = record;
+    }
     if let Some(ref custom_agents) = source.custom_agents {
         target.custom_agents = custom_agents.clone();
     }
     if let Some(ref detect_as) = source.agent_detect_as {
         target.agent_detect_as = detect_as.clone();
     }
+    if let Some(ref ports) = source.ports {
+        target.ports = ports.clone();
+    }
 }
 
 /// Apply tmux config overrides to a target config.
@@ -438,6 +540,19 @@ pub fn merge_configs(mut global: Config, profile: &ProfileConfig) -> Config {
         crate::sound::apply_sound_overrides(&mut global.sound, sound_override);
     }
 
+    if let Some(ref auto_respond_override) = profile.auto_respond {
+        super::auto_respond::apply_auto_respond_overrides(
+            &mut global.auto_respond,
+            auto_respond_override,
+        );
+    }
+
+    if let Some(ref watchdog_override) = profile.watchdog {
+        super::watchdog::apply_watchdog_overrides(&mut global.watchdog, watchdog_override);
+    }
+
+    apply_template_overrides(&mut global.templates, &profile.templates);
+
     global
 }
 
@@ -613,6 +728,37 @@ mod tests {
             ..Default::default()
         };
         assert!(profile_has_overrides(&with_override));
+
+        let with_template = ProfileConfig {
+            templates: BTreeMap::from([("review".to_string(), SessionTemplate::default())]),
+            ..Default::default()
+        };
+        assert!(profile_has_overrides(&with_template));
+    }
+
+    #[test]
+    fn test_merge_configs_with_template_override() {
+        let mut global = Config::default();
+        global.templates.insert(
+            "review".to_string(),
+            SessionTemplate {
+                agent: Some("claude".to_string()),
+                group: Some("reviews".to_string()),
+                ..Default::default()
+            },
+        );
+        let profile: ProfileConfig = toml::from_str(
+            r#"
+            [templates.review]
+            agent = "codex"
+            "#,
+        )
+        .unwrap();
+
+        let merged = merge_configs(global, &profile);
+        let review = &merged.templates["review"];
+        assert_eq!(review.agent.as_deref(), Some("codex"));
+        assert_eq!(review.group.as_deref(), Some("reviews"));
     }
 
     #[test]
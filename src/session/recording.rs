@@ -0,0 +1,352 @@
+//! Session recording - asciicast v2 capture and playback of agent panes
+//!
+//! When `session.record` is enabled, the agent pane is piped through
+//! `tmux pipe-pane` into a hidden `aoe tmux record` process that timestamps
+//! the output and appends it to `recordings/<session id>.cast` in the profile
+//! directory. The file survives the tmux session being killed, and restarting
+//! the session keeps appending to it on the same timeline.
+//!
+//! See <https://docs.asciinema.org/manual/asciicast/v2/> for the format.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::environment::shell_escape;
+use super::get_profile_dir;
+use crate::tmux;
+
+const RECORDINGS_DIR: &str = "recordings";
+
+/// Fallback terminal size when the pane size can't be queried.
+const DEFAULT_SIZE: (u16, u16) = (120, 40);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CastHeader {
+    pub version: u8,
+    pub width: u16,
+    pub height: u16,
+    /// Unix timestamp of the first recorded output
+    pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+/// One output event: seconds since the header timestamp and the text written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEvent {
+    pub time: f64,
+    pub data: String,
+}
+
+pub fn recordings_dir(profile: &str) -> Result<PathBuf> {
+    Ok(get_profile_dir(profile)?.join(RECORDINGS_DIR))
+}
+
+pub fn recording_path(profile: &str, session_id: &str) -> Result<PathBuf> {
+    Ok(recordings_dir(profile)?.join(format!("{}.cast", session_id)))
+}
+
+/// Whether `session.record` is on for this profile and project (global >
+/// profile > repo config).
+pub fn is_enabled(profile: &str, project_path: &Path) -> bool {
+    super::repo_config::resolve_config_with_repo(profile, project_path)
+        .map(|c| c.session.record)
+        .unwrap_or(false)
+}
+
+/// Attach a recorder to the session's agent pane. `pipe-pane -o` only opens
+/// a pipe when none is active, so calling this on an already-recorded pane
+/// is a no-op.
+pub fn start(session: &tmux::Session, profile: &str, session_id: &str, title: &str) -> Result<()> {
+    let path = recording_path(profile, session_id)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let exe = std::env::current_exe().context("Could not resolve aoe binary path")?;
+    let (width, height) = session.pane_size().unwrap_or(DEFAULT_SIZE);
+    let command = format!(
+        "exec {} tmux record --output {} --width {} --height {} --title {}",
+        shell_escape(&exe.to_string_lossy()),
+        shell_escape(&path.to_string_lossy()),
+        width,
+        height,
+        shell_escape(title),
+    );
+    session.pipe_pane(&command)
+}
+
+/// Delete a session's recording, if any.
+pub fn remove(profile: &str, session_id: &str) -> Result<()> {
+    let path = recording_path(profile, session_id)?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Copy `input` into the cast file at `path` until EOF, one event per read.
+/// A new file gets a header; an existing one is appended to with times offset
+/// from its original header, so restarts continue the same timeline.
+pub fn write_stream(
+    mut input: impl Read,
+    path: &Path,
+    width: u16,
+    height: u16,
+    title: Option<&str>,
+) -> Result<()> {
+    let now = chrono::Utc::now();
+    let offset = match read_header(path) {
+        Ok(header) => {
+            // Wall-clock time since the header, but never behind the last
+            // event already written (the header only has second precision).
+            let since_header =
+                now.timestamp_micros() as f64 / 1_000_000.0 - header.timestamp as f64;
+            since_header.max(last_event_time(path).unwrap_or(0.0))
+        }
+        Err(_) => {
+            let header = CastHeader {
+                version: 2,
+                width,
+                height,
+                timestamp: now.timestamp(),
+                title: title.map(str::to_string),
+            };
+            let mut file = File::create(path)?;
+            writeln!(file, "{}", serde_json::to_string(&header)?)?;
+            0.0
+        }
+    };
+
+    let mut file = OpenOptions::new().append(true).open(path)?;
+    let started = Instant::now();
+    let mut buf = [0u8; 8192];
+    // Bytes of a UTF-8 sequence split across two reads
+    let mut pending: Vec<u8> = Vec::new();
+
+    loop {
+        let n = match input.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        pending.extend_from_slice(&buf[..n]);
+        let valid = utf8_prefix_len(&pending);
+        if valid == 0 {
+            continue;
+        }
+        let data = String::from_utf8_lossy(&pending[..valid]).into_owned();
+        pending.drain(..valid);
+
+        let time = offset + started.elapsed().as_secs_f64();
+        write_event(&mut file, time, &data)?;
+    }
+
+    if !pending.is_empty() {
+        let time = offset + started.elapsed().as_secs_f64();
+        write_event(&mut file, time, &String::from_utf8_lossy(&pending))?;
+    }
+    Ok(())
+}
+
+fn write_event(file: &mut File, time: f64, data: &str) -> Result<()> {
+    let line = serde_json::to_string(&(round_time(time), "o", data))?;
+    writeln!(file, "{}", line)?;
+    file.flush()?;
+    Ok(())
+}
+
+fn round_time(time: f64) -> f64 {
+    (time * 1_000_000.0).round() / 1_000_000.0
+}
+
+/// Length of `bytes` up to the start of a trailing incomplete UTF-8 sequence.
+/// Invalid bytes elsewhere are left for lossy conversion.
+fn utf8_prefix_len(bytes: &[u8]) -> usize {
+    match std::str::from_utf8(bytes) {
+        Ok(_) => bytes.len(),
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        Err(_) => bytes.len(),
+    }
+}
+
+/// Time of the last complete event, read from the end of the file.
+fn last_event_time(path: &Path) -> Option<f64> {
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(64 * 1024)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    String::from_utf8_lossy(&tail)
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<(f64, String, String)>(line).ok())
+        .map(|(time, _, _)| time)
+}
+
+pub fn read_header(path: &Path) -> Result<CastHeader> {
+    let file = File::open(path)?;
+    let mut first = String::new();
+    BufReader::new(file).read_line(&mut first)?;
+    let header: CastHeader =
+        serde_json::from_str(first.trim()).context("Not an asciicast v2 recording")?;
+    if header.version != 2 {
+        bail!("Unsupported asciicast version: {}", header.version);
+    }
+    Ok(header)
+}
+
+/// Read a whole recording. Malformed event lines (e.g. one cut short by a
+/// crash) are skipped; non-output events are ignored.
+pub fn read(path: &Path) -> Result<(CastHeader, Vec<CastEvent>)> {
+    let header = read_header(path)?;
+    let file = File::open(path)?;
+    let events = BufReader::new(file)
+        .lines()
+        .skip(1)
+        .map_while(|line| line.ok())
+        .filter_map(|line| serde_json::from_str::<(f64, String, String)>(&line).ok())
+        .filter(|(_, kind, _)| kind == "o")
+        .map(|(time, _, data)| CastEvent { time, data })
+        .collect();
+    Ok((header, events))
+}
+
+/// Delay before each event when playing back at `speed`, with pauses capped
+/// at `idle_limit` seconds so long stretches of inactivity don't stall replay.
+pub fn playback_delays(events: &[CastEvent], speed: f64, idle_limit: Option<f64>) -> Vec<Duration> {
+    let speed = if speed > 0.0 { speed } else { 1.0 };
+    let mut last = 0.0;
+    events
+        .iter()
+        .map(|event| {
+            let mut gap = (event.time - last).max(0.0);
+            last = event.time;
+            if let Some(limit) = idle_limit {
+                gap = gap.min(limit);
+            }
+            Duration::from_secs_f64(gap / speed)
+        })
+        .collect()
+}
+
+/// Write a recording to `out` in real time.
+pub fn replay(
+    path: &Path,
+    out: &mut impl Write,
+    speed: f64,
+    idle_limit: Option<f64>,
+) -> Result<()> {
+    let (_, events) = read(path)?;
+    let delays = playback_delays(&events, speed, idle_limit);
+    for (event, delay) in events.iter().zip(delays) {
+        std::thread::sleep(delay);
+        out.write_all(event.data.as_bytes())?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_stream_creates_header_and_events() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join("s.cast");
+
+        write_stream(&b"hello \xe2\x9c\x93"[..], &path, 80, 24, Some("demo"))?;
+
+        let (header, events) = read(&path)?;
+        assert_eq!(header.version, 2);
+        assert_eq!((header.width, header.height), (80, 24));
+        assert_eq!(header.title.as_deref(), Some("demo"));
+        let text: String = events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(text, "hello ✓");
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_stream_appends_to_existing_recording() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join("s.cast");
+
+        write_stream(&b"first"[..], &path, 80, 24, None)?;
+        write_stream(&b"second"[..], &path, 100, 30, None)?;
+
+        let (header, events) = read(&path)?;
+        // The original header is kept
+        assert_eq!((header.width, header.height), (80, 24));
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].data, "second");
+        assert!(events[1].time >= events[0].time);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_skips_truncated_event() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join("s.cast");
+        write_stream(&b"ok"[..], &path, 80, 24, None)?;
+        let mut file = OpenOptions::new().append(true).open(&path)?;
+        file.write_all(b"[1.5, \"o\", \"cut")?;
+
+        let (_, events) = read(&path)?;
+        assert_eq!(events.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_read_rejects_non_cast_file() -> Result<()> {
+        let temp = tempdir()?;
+        let path = temp.path().join("s.cast");
+        fs::write(&path, "not json\n")?;
+        assert!(read(&path).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_utf8_prefix_len_holds_back_split_sequence() {
+        assert_eq!(utf8_prefix_len(b"ab"), 2);
+        // First two bytes of a three-byte checkmark
+        assert_eq!(utf8_prefix_len(b"ab\xe2\x9c"), 2);
+        // Invalid byte mid-stream is passed through for lossy conversion
+        assert_eq!(utf8_prefix_len(b"a\xffb"), 3);
+    }
+
+    #[test]
+    fn test_playback_delays_apply_speed_and_idle_limit() {
+        let events = vec![
+            CastEvent {
+                time: 1.0,
+                data: "a".into(),
+            },
+            CastEvent {
+                time: 3.0,
+                data: "b".into(),
+            },
+            CastEvent {
+                time: 603.0,
+                data: "c".into(),
+            },
+        ];
+
+        let delays = playback_delays(&events, 2.0, Some(5.0));
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(500),
+                Duration::from_secs(1),
+                Duration::from_millis(2500),
+            ]
+        );
+    }
+}
@@ -7,9 +7,11 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 /// Progress messages streamed from hook execution.
 #[derive(Debug, Clone)]
@@ -25,6 +27,7 @@ use super::profile_config::{
     HooksConfigOverride, ProfileConfig, SandboxConfigOverride, SessionConfigOverride,
     TmuxConfigOverride, UpdatesConfigOverride, WorktreeConfigOverride,
 };
+use super::templates::SessionTemplate;
 
 /// Repository-level configuration loaded from `.agent-of-empires/config.toml`.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -49,6 +52,12 @@ pub struct RepoConfig {
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sound: Option<crate::sound::SoundConfigOverride>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_respond: Option<super::auto_respond::AutoRespondConfigOverride>,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub templates: BTreeMap<String, SessionTemplate>,
 }
 
 /// Hook commands to run at various lifecycle points.
@@ -61,6 +70,9 @@ pub struct RepoConfig {
 /// - `on_destroy`: failures are logged as warnings but do not prevent session
 ///   deletion. Runs before worktree/sandbox cleanup so resources are still
 ///   available for teardown commands (e.g. `docker-compose down`).
+/// - `on_waiting`, `on_idle`, `on_error`, `on_stop` (status hooks): run when a
+///   session changes status, with the session described in `AOE_*` environment
+///   variables. Failures and timeouts are logged as warnings.
 ///
 /// All fields accept either a single string or an array of strings in TOML:
 ///   `on_launch = "npm start"`  or  `on_launch = ["npm install", "npm start"]`
@@ -90,11 +102,122 @@ pub struct HooksConfig {
         deserialize_with = "super::serde_helpers::string_or_vec"
     )]
     pub on_destroy: Vec<String>,
+
+    /// Commands run when the agent starts waiting for input.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "super::serde_helpers::string_or_vec"
+    )]
+    pub on_waiting: Vec<String>,
+
+    /// Commands run when the agent finishes its turn and goes idle.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "super::serde_helpers::string_or_vec"
+    )]
+    pub on_idle: Vec<String>,
+
+    /// Commands run when the session enters the error state.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "super::serde_helpers::string_or_vec"
+    )]
+    pub on_error: Vec<String>,
+
+    /// Commands run when the session is stopped. Executed after the agent
+    /// exits but before a sandbox container is stopped.
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "super::serde_helpers::string_or_vec"
+    )]
+    pub on_stop: Vec<String>,
+
+    /// Seconds each status hook command may run before it is killed
+    /// (default 60).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_hook_timeout_secs: Option<u64>,
+
+    /// Run status hooks of sandboxed sessions inside the container rather
+    /// than on the host (default true).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_hooks_in_container: Option<bool>,
+
+    /// The repo's `[auto_respond]` settings that answer prompts, one line
+    /// each, so they are trusted along with the hooks. Filled in by
+    /// `check_hook_trust`, never read from `[hooks]`.
+    #[serde(skip)]
+    pub auto_respond: Vec<String>,
+
+    /// The devcontainer's `postCreateCommand` when sandboxes use it, which
+    /// runs in the container like `on_create`. Filled in by
+    /// `check_hook_trust`, never read from `[hooks]`.
+    #[serde(skip)]
+    pub post_create: Vec<String>,
 }
 
 impl HooksConfig {
     pub fn is_empty(&self) -> bool {
-        self.on_create.is_empty() && self.on_launch.is_empty() && self.on_destroy.is_empty()
+        self.on_create.is_empty()
+            && self.on_launch.is_empty()
+            && self.on_destroy.is_empty()
+            && !self.has_status_hooks()
+            && self.auto_respond.is_empty()
+            && self.post_create.is_empty()
+    }
+
+    /// Configured hooks by name (`on_create`, ...), skipping empty ones,
+    /// for showing the user what they are asked to trust.
+    pub fn named_commands(&self) -> Vec<(&'static str, &[String])> {
+        [
+            ("on_create", &self.on_create),
+            ("on_launch", &self.on_launch),
+            ("on_destroy", &self.on_destroy),
+            ("on_waiting", &self.on_waiting),
+            ("on_idle", &self.on_idle),
+            ("on_error", &self.on_error),
+            ("on_stop", &self.on_stop),
+            ("auto_respond", &self.auto_respond),
+            ("postCreateCommand", &self.post_create),
+        ]
+        .into_iter()
+        .filter(|(_, cmds)| !cmds.is_empty())
+        .map(|(name, cmds)| (name, cmds.as_slice()))
+        .collect()
+    }
+
+    /// Whether any of `on_waiting`, `on_idle`, `on_error`, `on_stop` is set.
+    pub fn has_status_hooks(&self) -> bool {
+        !(self.on_waiting.is_empty()
+            && self.on_idle.is_empty()
+            && self.on_error.is_empty()
+            && self.on_stop.is_empty())
+    }
+
+    /// Replace status hook settings with the ones `other` sets. Lists
+    /// override per field, like the other hooks.
+    pub fn merge_status_hooks(&mut self, other: &HooksConfig) {
+        if !other.on_waiting.is_empty() {
+            self.on_waiting = other.on_waiting.clone();
+        }
+        if !other.on_idle.is_empty() {
+            self.on_idle = other.on_idle.clone();
+        }
+        if !other.on_error.is_empty() {
+            self.on_error = other.on_error.clone();
+        }
+        if !other.on_stop.is_empty() {
+            self.on_stop = other.on_stop.clone();
+        }
+        if other.status_hook_timeout_secs.is_some() {
+            self.status_hook_timeout_secs = other.status_hook_timeout_secs;
+        }
+        if other.status_hooks_in_container.is_some() {
+            self.status_hooks_in_container = other.status_hooks_in_container;
+        }
     }
 }
 
@@ -202,6 +325,7 @@ pub fn merge_repo_config(mut config: Config, repo: &RepoConfig) -> Config {
         if !hooks.on_destroy.is_empty() {
             config.hooks.on_destroy = hooks.on_destroy.clone();
         }
+        config.hooks.merge_status_hooks(hooks);
     }
 
     if let Some(ref updates_override) = repo.updates {
@@ -227,6 +351,15 @@ pub fn merge_repo_config(mut config: Config, repo: &RepoConfig) -> Config {
         crate::sound::apply_sound_overrides(&mut config.sound, sound_override);
     }
 
+    if let Some(ref auto_respond_override) = repo.auto_respond {
+        super::auto_respond::apply_auto_respond_overrides(
+            &mut config.auto_respond,
+            auto_respond_override,
+        );
+    }
+
+    super::templates::apply_template_overrides(&mut config.templates, &repo.templates);
+
     config
 }
 
@@ -241,6 +374,8 @@ pub fn repo_config_to_profile(repo: &RepoConfig) -> ProfileConfig {
         tmux: repo.tmux.clone(),
         session: repo.session.clone(),
         sound: repo.sound.clone(),
+        auto_respond: repo.auto_respond.clone(),
+        templates: repo.templates.clone(),
         hooks: repo.hooks.as_ref().map(|h| HooksConfigOverride {
             on_create: if h.on_create.is_empty() {
                 None
@@ -257,6 +392,28 @@ pub fn repo_config_to_profile(repo: &RepoConfig) -> ProfileConfig {
             } else {
                 Some(h.on_destroy.clone())
             },
+            on_waiting: if h.on_waiting.is_empty() {
+                None
+            } else {
+                Some(h.on_waiting.clone())
+            },
+            on_idle: if h.on_idle.is_empty() {
+                None
+            } else {
+                Some(h.on_idle.clone())
+            },
+            on_error: if h.on_error.is_empty() {
+                None
+            } else {
+                Some(h.on_error.clone())
+            },
+            on_stop: if h.on_stop.is_empty() {
+                None
+            } else {
+                Some(h.on_stop.clone())
+            },
+            status_hook_timeout_secs: h.status_hook_timeout_secs,
+            status_hooks_in_container: h.status_hooks_in_container,
         }),
         ..Default::default()
     }
@@ -269,6 +426,13 @@ pub fn profile_to_repo_config(profile: &ProfileConfig) -> RepoConfig {
             on_create: h.on_create.clone().unwrap_or_default(),
             on_launch: h.on_launch.clone().unwrap_or_default(),
             on_destroy: h.on_destroy.clone().unwrap_or_default(),
+            on_waiting: h.on_waiting.clone().unwrap_or_default(),
+            on_idle: h.on_idle.clone().unwrap_or_default(),
+            on_error: h.on_error.clone().unwrap_or_default(),
+            on_stop: h.on_stop.clone().unwrap_or_default(),
+            status_hook_timeout_secs: h.status_hook_timeout_secs,
+            status_hooks_in_container: h.status_hooks_in_container,
+            ..Default::default()
         }),
         session: profile.session.clone(),
         sandbox: profile.sandbox.clone(),
@@ -276,6 +440,8 @@ pub fn profile_to_repo_config(profile: &ProfileConfig) -> RepoConfig {
         updates: profile.updates.clone(),
         tmux: profile.tmux.clone(),
         sound: profile.sound.clone(),
+        auto_respond: profile.auto_respond.clone(),
+        templates: profile.templates.clone(),
     }
 }
 
@@ -284,7 +450,21 @@ pub fn resolve_config_with_repo(profile: &str, project_path: &Path) -> Result<Co
     let config = super::profile_config::resolve_config(profile)?;
 
     match load_repo_config(project_path)? {
-        Some(repo_config) => Ok(merge_repo_config(config, &repo_config)),
+        Some(mut repo_config) => {
+            // Prompts are only answered for the repo once the user trusts it
+            if repo_config.auto_respond.is_some() {
+                let normalized = normalize_path(project_path);
+                if !matches!(
+                    hook_trust_of(&normalized, &repo_config)?,
+                    HookTrustStatus::Trusted(_)
+                ) {
+                    if let Some(auto_respond) = repo_config.auto_respond.as_mut() {
+                        auto_respond.hold_back_only();
+                    }
+                }
+            }
+            Ok(merge_repo_config(config, &repo_config))
+        }
         None => Ok(config),
     }
 }
@@ -326,6 +506,35 @@ pub fn compute_hooks_hash(hooks: &HooksConfig) -> String {
         hasher.update(cmd.as_bytes());
         hasher.update(b"\n");
     }
+    // Status hooks only add to the hash when set, so hashes of repos that
+    // don't use them are unchanged.
+    let status_hooks = [
+        ("on_waiting:", &hooks.on_waiting),
+        ("on_idle:", &hooks.on_idle),
+        ("on_error:", &hooks.on_error),
+        ("on_stop:", &hooks.on_stop),
+    ];
+    for (prefix, cmds) in status_hooks {
+        for cmd in cmds {
+            hasher.update(prefix.as_bytes());
+            hasher.update(cmd.as_bytes());
+            hasher.update(b"\n");
+        }
+    }
+    // Where the commands run is part of what the user approves
+    if let Some(in_container) = hooks.status_hooks_in_container {
+        hasher.update(format!("status_hooks_in_container:{}\n", in_container).as_bytes());
+    }
+    for line in &hooks.auto_respond {
+        hasher.update(b"auto_respond:");
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    for cmd in &hooks.post_create {
+        hasher.update(b"post_create:");
+        hasher.update(cmd.as_bytes());
+        hasher.update(b"\n");
+    }
     hasher
         .finalize()
         .iter()
@@ -440,26 +649,57 @@ pub enum HookTrustStatus {
 /// Loads the repo config, checks for hooks, and validates trust.
 pub fn check_hook_trust(project_path: &Path) -> Result<HookTrustStatus> {
     let normalized = normalize_path(project_path);
-    let repo_config = match load_repo_config(Path::new(&normalized))? {
-        Some(rc) => rc,
-        None => return Ok(HookTrustStatus::NoHooks),
-    };
+    match load_repo_config(Path::new(&normalized))? {
+        Some(repo_config) => hook_trust_of(&normalized, &repo_config),
+        None => Ok(HookTrustStatus::NoHooks),
+    }
+}
 
-    let hooks = match repo_config.hooks {
-        Some(h) if !h.is_empty() => h,
-        _ => return Ok(HookTrustStatus::NoHooks),
-    };
+/// Trust status of a loaded repo config. Besides its hooks, the user trusts
+/// the `auto_respond` settings that let the repo answer prompts and the
+/// devcontainer's `postCreateCommand`.
+fn hook_trust_of(normalized: &str, repo_config: &RepoConfig) -> Result<HookTrustStatus> {
+    let mut hooks = repo_config.hooks.clone().unwrap_or_default();
+    hooks.auto_respond = repo_config
+        .auto_respond
+        .as_ref()
+        .map(|a| a.trust_summary())
+        .unwrap_or_default();
+    if uses_devcontainer(repo_config) {
+        hooks.post_create = super::devcontainer::Devcontainer::load(Path::new(normalized))
+            .ok()
+            .flatten()
+            .map(|d| d.post_create_commands())
+            .unwrap_or_default();
+    }
+    if hooks.is_empty() {
+        return Ok(HookTrustStatus::NoHooks);
+    }
 
     let hooks_hash = compute_hooks_hash(&hooks);
 
     // Pass already-normalized path to avoid double canonicalization
-    if is_repo_trusted_normalized(&normalized, &hooks_hash)? {
+    if is_repo_trusted_normalized(normalized, &hooks_hash)? {
         Ok(HookTrustStatus::Trusted(hooks))
     } else {
         Ok(HookTrustStatus::NeedsTrust { hooks, hooks_hash })
     }
 }
 
+/// Whether sandboxes use the repo's devcontainer, from the repo config or
+/// else the global and profile config.
+fn uses_devcontainer(repo_config: &RepoConfig) -> bool {
+    repo_config
+        .sandbox
+        .as_ref()
+        .and_then(|s| s.use_devcontainer)
+        .unwrap_or_else(|| {
+            let profile = super::config::resolve_default_profile();
+            super::profile_config::resolve_config(&profile)
+                .is_ok_and(|c| c.sandbox.use_devcontainer)
+        })
+}
+
 // ---------------------------------------------------------------------------
 // Hook resolution helpers (shared by CLI and TUI)
 // ---------------------------------------------------------------------------
@@ -514,7 +754,13 @@ enum HookTarget<'a> {
 
 /// Build a `Command` for running a hook. Local hooks use the user's `$SHELL`;
 /// container hooks use `bash` since the user shell may not be installed.
-fn build_hook_command(cmd: &str, target: &HookTarget, merge_stderr: bool) -> std::process::Command {
+/// `env` is added to the hook's environment in either case.
+fn build_hook_command(
+    cmd: &str,
+    target: &HookTarget,
+    merge_stderr: bool,
+    env: &[(String, String)],
+) -> std::process::Command {
     let shell_cmd = if merge_stderr {
         format!("{} 2>&1", cmd)
     } else {
@@ -525,7 +771,11 @@ fn build_hook_command(cmd: &str, target: &HookTarget, merge_stderr: bool) -> std
         HookTarget::Local { project_path } => {
             let shell = super::environment::user_shell();
             let mut command = std::process::Command::new(shell);
-            command.arg("-c").arg(shell_cmd).current_dir(project_path);
+            command
+                .arg("-c")
+                .arg(shell_cmd)
+                .current_dir(project_path)
+                .envs(env.iter().map(|(k, v)| (k, v)));
             command
         }
         HookTarget::Container {
@@ -534,8 +784,11 @@ fn build_hook_command(cmd: &str, target: &HookTarget, merge_stderr: bool) -> std
         } => {
             let binary = crate::containers::runtime_binary();
             let mut command = std::process::Command::new(binary);
+            command.arg("exec");
+            for (key, value) in env {
+                command.arg("-e").arg(format!("{}={}", key, value));
+            }
             command.args([
-                "exec",
                 "--workdir",
                 workdir,
                 container_name,
@@ -577,12 +830,16 @@ fn format_hook_error(
 }
 
 /// Run hook commands with captured output (non-streamed).
-fn run_hooks_captured(commands: &[String], target: &HookTarget) -> Result<()> {
+fn run_hooks_captured(
+    commands: &[String],
+    target: &HookTarget,
+    env: &[(String, String)],
+) -> Result<()> {
     let in_container = matches!(target, HookTarget::Container { .. });
 
     for cmd in commands {
         tracing::info!("Running hook: {}", cmd);
-        let mut command = build_hook_command(cmd, target, false);
+        let mut command = build_hook_command(cmd, target, false, env);
         let output = command
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -615,6 +872,7 @@ fn run_hooks_captured(commands: &[String], target: &HookTarget) -> Result<()> {
 fn run_hooks_streamed(
     commands: &[String],
     target: &HookTarget,
+    env: &[(String, String)],
     progress_tx: &mpsc::Sender<HookProgress>,
 ) -> Result<()> {
     use std::io::BufRead;
@@ -625,7 +883,7 @@ fn run_hooks_streamed(
         tracing::info!("Running hook (streamed): {}", cmd);
         let _ = progress_tx.send(HookProgress::Started(cmd.clone()));
 
-        let mut command = build_hook_command(cmd, target, true);
+        let mut command = build_hook_command(cmd, target, true, env);
         let mut child = command
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::null())
@@ -651,9 +909,13 @@ fn run_hooks_streamed(
     Ok(())
 }
 
-/// Execute a list of hook commands in the given directory.
-pub fn execute_hooks(commands: &[String], project_path: &Path) -> Result<()> {
-    run_hooks_captured(commands, &HookTarget::Local { project_path })
+/// Execute a list of hook commands in the given directory, with `env` set.
+pub fn execute_hooks(
+    commands: &[String],
+    project_path: &Path,
+    env: &[(String, String)],
+) -> Result<()> {
+    run_hooks_captured(commands, &HookTarget::Local { project_path }, env)
 }
 
 /// Execute hooks inside a Docker container.
@@ -668,19 +930,24 @@ pub fn execute_hooks_in_container(
             container_name,
             workdir,
         },
+        &[],
     )
 }
 
 /// Execute hooks with best-effort semantics: all commands are attempted even if
 /// some fail. Returns collected error messages. Designed for teardown hooks
 /// (on_destroy) where partial cleanup is better than aborting on first failure.
-fn run_hooks_best_effort(commands: &[String], target: &HookTarget) -> Vec<String> {
+fn run_hooks_best_effort(
+    commands: &[String],
+    target: &HookTarget,
+    env: &[(String, String)],
+) -> Vec<String> {
     let in_container = matches!(target, HookTarget::Container { .. });
     let mut errors = Vec::new();
 
     for cmd in commands {
         tracing::info!("Running hook (best-effort): {}", cmd);
-        let mut command = build_hook_command(cmd, target, false);
+        let mut command = build_hook_command(cmd, target, false, env);
         match command
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -718,10 +985,14 @@ fn run_hooks_best_effort(commands: &[String], target: &HookTarget) -> Vec<String
     errors
 }
 
-/// Execute hooks locally with best-effort semantics (all commands attempted).
-/// Returns a list of error messages for any hooks that failed.
-pub fn execute_hooks_best_effort(commands: &[String], project_path: &Path) -> Vec<String> {
-    run_hooks_best_effort(commands, &HookTarget::Local { project_path })
+/// Execute hooks locally with best-effort semantics (all commands attempted),
+/// with `env` set. Returns a list of error messages for any hooks that failed.
+pub fn execute_hooks_best_effort(
+    commands: &[String],
+    project_path: &Path,
+    env: &[(String, String)],
+) -> Vec<String> {
+    run_hooks_best_effort(commands, &HookTarget::Local { project_path }, env)
 }
 
 /// Execute hooks in a container with best-effort semantics (all commands attempted).
@@ -737,16 +1008,136 @@ pub fn execute_hooks_in_container_best_effort(
             container_name,
             workdir,
         },
+        &[],
     )
 }
 
-/// Execute a list of hook commands with streamed output.
+/// Run status hook commands with best-effort semantics. Each command gets
+/// `env` and is killed, along with anything it started, once it has run for
+/// `timeout`. Returns collected error messages.
+fn run_hooks_with_timeout(
+    commands: &[String],
+    target: &HookTarget,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    for cmd in commands {
+        tracing::info!("Running status hook: {}", cmd);
+        if let Err(err) = run_hook_with_timeout(cmd, target, env, timeout) {
+            tracing::warn!("{}", err);
+            errors.push(err);
+        }
+    }
+    errors
+}
+
+fn run_hook_with_timeout(
+    cmd: &str,
+    target: &HookTarget,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> std::result::Result<(), String> {
+    use std::io::{Read, Seek, SeekFrom};
+    use std::os::unix::process::CommandExt;
+
+    let in_container = matches!(target, HookTarget::Container { .. });
+    let failed = |e: &dyn std::fmt::Display| format!("Failed to execute hook: {}: {}", cmd, e);
+
+    // Output goes to a file rather than a pipe so a hook that leaves a
+    // background process holding the pipe open can't block us.
+    let mut output = tempfile::tempfile().map_err(|e| failed(&e))?;
+    let stdout = output.try_clone().map_err(|e| failed(&e))?;
+    let mut command = build_hook_command(cmd, target, true, env);
+    let mut child = command
+        .stdin(std::process::Stdio::null())
+        .stdout(stdout)
+        .stderr(std::process::Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|e| failed(&e))?;
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = nix::sys::signal::killpg(
+                    nix::unistd::Pid::from_raw(child.id() as i32),
+                    nix::sys::signal::Signal::SIGKILL,
+                );
+                let _ = child.wait();
+                return Err(format!(
+                    "Hook command timed out after {}s: {}",
+                    timeout.as_secs(),
+                    cmd
+                ));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => return Err(failed(&e)),
+        }
+    };
+
+    let mut captured = String::new();
+    if output.seek(SeekFrom::Start(0)).is_ok() {
+        let _ = output.read_to_string(&mut captured);
+    }
+    if !status.success() {
+        return Err(format_hook_error(
+            cmd,
+            status.code(),
+            "",
+            &captured,
+            in_container,
+        ));
+    }
+    tracing::debug!("Status hook completed: {} ({} bytes)", cmd, captured.len());
+    Ok(())
+}
+
+/// Execute status hooks locally. See `run_hooks_with_timeout`.
+pub fn execute_status_hooks(
+    commands: &[String],
+    project_path: &Path,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Vec<String> {
+    run_hooks_with_timeout(commands, &HookTarget::Local { project_path }, env, timeout)
+}
+
+/// Execute status hooks in a container. A timeout ends the `docker exec`
+/// client; see `run_hooks_with_timeout`.
+pub fn execute_status_hooks_in_container(
+    commands: &[String],
+    container_name: &str,
+    workdir: &str,
+    env: &[(String, String)],
+    timeout: Duration,
+) -> Vec<String> {
+    run_hooks_with_timeout(
+        commands,
+        &HookTarget::Container {
+            container_name,
+            workdir,
+        },
+        env,
+        timeout,
+    )
+}
+
+/// Execute a list of hook commands with streamed output, with `env` set.
 pub fn execute_hooks_streamed(
     commands: &[String],
     project_path: &Path,
+    env: &[(String, String)],
     progress_tx: &mpsc::Sender<HookProgress>,
 ) -> Result<()> {
-    run_hooks_streamed(commands, &HookTarget::Local { project_path }, progress_tx)
+    run_hooks_streamed(
+        commands,
+        &HookTarget::Local { project_path },
+        env,
+        progress_tx,
+    )
 }
 
 /// Execute hooks inside a Docker container with streamed output.
@@ -762,6 +1153,7 @@ pub fn execute_hooks_in_container_streamed(
             container_name,
             workdir,
         },
+        &[],
         progress_tx,
     )
 }
@@ -778,9 +1170,19 @@ pub const INIT_TEMPLATE: &str = r#"# Agent of Empires - Repository Configuration
 # on_launch = ["npm install"]
 # Commands run when a session is deleted (before cleanup)
 # on_destroy = ["docker-compose down"]
+# Commands run when the agent waits for input, finishes a turn, errors or is
+# stopped. $AOE_SESSION_TITLE, $AOE_STATUS and friends describe the session.
+# on_idle = ["cargo test --quiet"]
+# on_waiting = ["notify-send \"$AOE_SESSION_TITLE needs input\""]
+# on_error = []
+# on_stop = []
+# status_hook_timeout_secs = 60
 
 # [session]
 # default_tool = "claude"
+# Unique host ports for each session's dev servers. A count sets
+# $AOE_PORT_0, $AOE_PORT_1, ...; a list of names sets those variables instead.
+# ports = ["WEB_PORT", "API_PORT"]
 
 # [sandbox]
 # enabled_by_default = true
@@ -788,6 +1190,17 @@ pub const INIT_TEMPLATE: &str = r#"# Agent of Empires - Repository Configuration
 # List fields below replace (not append to) global settings when set:
 # environment = ["NODE_ENV", "DATABASE_URL"]
 # volume_ignores = ["node_modules", ".next"]
+# Limit network access to these hosts ("none" blocks everything)
+# network = { allow = ["api.anthropic.com", "*.github.com", "registry.npmjs.org"] }
+# Compose file whose services (database, cache, ...) run alongside each session
+# services = "docker-compose.agent.yml"
+
+# Build the sandbox image from a Dockerfile instead of pulling default_image.
+# The image is reused until the Dockerfile, args or build context change.
+# [sandbox.build]
+# dockerfile = "Dockerfile.sandbox"
+# context = "."
+# args = { NODE_VERSION = "22" }
 
 # [worktree]
 # enabled = true
@@ -801,6 +1214,25 @@ pub const INIT_TEMPLATE: &str = r#"# Agent of Empires - Repository Configuration
 
 # [sound]
 # enabled = false
+
+# Answer agent permission prompts by rule; the first match wins. Repo rules
+# are checked after global and profile deny/notify rules, before their approvals.
+# Approvals need the same trust as hooks
+# [[auto_respond.rules]]
+# pane = "rm -rf"
+# action = "notify"
+# [[auto_respond.rules]]
+# agent = "claude"
+# pane = "cargo (test|check|clippy)"
+# action = "approve"
+
+# Session presets, picked with `aoe add --template bugfix` or Ctrl+T in the
+# new session dialog
+# [templates.bugfix]
+# agent = "claude"
+# sandbox = true
+# extra_env = ["DATABASE_URL"]
+# group = "bugfixes"
 "#;
 
 #[cfg(test)]
@@ -879,6 +1311,89 @@ mod tests {
         assert_ne!(compute_hooks_hash(&hooks1), compute_hooks_hash(&hooks2));
     }
 
+    #[test]
+    fn test_compute_hooks_hash_status_hooks() {
+        let base = HooksConfig {
+            on_create: vec!["npm install".to_string()],
+            ..Default::default()
+        };
+        // A timeout alone doesn't change what runs, so existing trust holds
+        let with_timeout = HooksConfig {
+            status_hook_timeout_secs: Some(5),
+            ..base.clone()
+        };
+        assert_eq!(compute_hooks_hash(&base), compute_hooks_hash(&with_timeout));
+
+        let with_idle = HooksConfig {
+            on_idle: vec!["cargo test".to_string()],
+            ..base.clone()
+        };
+        assert_ne!(compute_hooks_hash(&base), compute_hooks_hash(&with_idle));
+        let on_host = HooksConfig {
+            status_hooks_in_container: Some(false),
+            ..with_idle.clone()
+        };
+        assert_ne!(compute_hooks_hash(&with_idle), compute_hooks_hash(&on_host));
+    }
+
+    #[test]
+    fn test_status_hooks_parse_and_merge() {
+        let toml = r#"
+            [hooks]
+            on_idle = "cargo test"
+            on_waiting = ["notify-send waiting"]
+            status_hook_timeout_secs = 300
+        "#;
+        let config: RepoConfig = toml::from_str(toml).unwrap();
+        let hooks = config.hooks.unwrap();
+        assert!(!hooks.is_empty());
+        assert!(hooks.has_status_hooks());
+        assert_eq!(hooks.on_idle, vec!["cargo test"]);
+        let names: Vec<&str> = hooks.named_commands().iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, vec!["on_waiting", "on_idle"]);
+
+        let mut base = HooksConfig {
+            on_idle: vec!["make".to_string()],
+            on_error: vec!["say error".to_string()],
+            ..Default::default()
+        };
+        base.merge_status_hooks(&hooks);
+        assert_eq!(base.on_idle, vec!["cargo test"]);
+        assert_eq!(base.on_error, vec!["say error"]);
+        assert_eq!(base.status_hook_timeout_secs, Some(300));
+        assert_eq!(base.status_hooks_in_container, None);
+    }
+
+    #[test]
+    fn test_status_hook_env_and_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = vec![("AOE_STATUS".to_string(), "idle".to_string())];
+        let timeout = Duration::from_secs(5);
+
+        let errors = execute_status_hooks(
+            &[
+                "test \"$AOE_STATUS\" = idle".to_string(),
+                "exit 3".to_string(),
+            ],
+            dir.path(),
+            &env,
+            timeout,
+        );
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("exit code 3"));
+
+        let started = Instant::now();
+        let errors = execute_status_hooks(
+            &["sleep 30".to_string()],
+            dir.path(),
+            &env,
+            Duration::from_millis(300),
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("timed out"));
+    }
+
     #[test]
     fn test_repo_config_deserialization() {
         let toml = r#"
@@ -1023,6 +1538,27 @@ mod tests {
         assert_eq!(merged.session.default_tool, Some("opencode".to_string()));
     }
 
+    #[test]
+    fn test_merge_repo_config_templates() {
+        let config: RepoConfig = toml::from_str(
+            r#"
+            [templates.bugfix]
+            agent = "codex"
+            sandbox = true
+            extra_env = ["DATABASE_URL"]
+            "#,
+        )
+        .unwrap();
+        let merged = merge_repo_config(Config::default(), &config);
+        let bugfix = &merged.templates["bugfix"];
+        assert_eq!(bugfix.agent.as_deref(), Some("codex"));
+        assert_eq!(bugfix.sandbox, Some(true));
+
+        // Survives the round trip through the settings TUI representation
+        let round_trip = profile_to_repo_config(&repo_config_to_profile(&config));
+        assert_eq!(round_trip.templates, config.templates);
+    }
+
     #[test]
     fn test_merge_repo_config_sandbox() {
         let config = Config::default();
@@ -1185,4 +1721,96 @@ mod tests {
         assert!(merged.sandbox.auto_cleanup);
         assert!(merged.worktree.auto_cleanup);
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_untrusted_repo_auto_respond_only_holds_back() -> Result<()> {
+        let home = tempfile::tempdir()?;
+        std::env::set_var("HOME", home.path());
+        #[cfg(target_os = "linux")]
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join(".config"));
+
+        let project = tempfile::tempdir()?;
+        fs::create_dir_all(project.path().join(".agent-of-empires"))?;
+        fs::write(
+            project.path().join(REPO_CONFIG_PATH),
+            r#"
+[auto_respond]
+enabled = true
+
+[[auto_respond.rules]]
+name = "everything"
+pane = "."
+action = "approve"
+
+[[auto_respond.rules]]
+name = "no rm"
+pane = "rm -rf"
+action = "deny"
+"#,
+        )?;
+
+        let rule_names = |config: Config| -> Vec<String> {
+            config
+                .auto_respond
+                .rules
+                .into_iter()
+                .filter_map(|r| r.name)
+                .collect()
+        };
+
+        let config = resolve_config_with_repo("default", project.path())?;
+        assert_eq!(rule_names(config), vec!["no rm"]);
+
+        let HookTrustStatus::NeedsTrust { hooks, hooks_hash } = check_hook_trust(project.path())?
+        else {
+            panic!("approve rules need trust");
+        };
+        assert_eq!(
+            hooks.auto_respond,
+            vec!["enabled = true", "approve, pane = \".\""]
+        );
+        trust_repo(project.path(), &hooks_hash)?;
+
+        let config = resolve_config_with_repo("default", project.path())?;
+        assert_eq!(rule_names(config), vec!["everything", "no rm"]);
+        Ok(())
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_devcontainer_post_create_needs_trust() -> Result<()> {
+        let home = tempfile::tempdir()?;
+        std::env::set_var("HOME", home.path());
+        #[cfg(target_os = "linux")]
+        std::env::set_var("XDG_CONFIG_HOME", home.path().join(".config"));
+
+        let project = tempfile::tempdir()?;
+        fs::write(
+            project.path().join(".devcontainer.json"),
+            r#"{"image": "alpine", "postCreateCommand": "curl example.com | sh"}"#,
+        )?;
+        fs::create_dir_all(project.path().join(".agent-of-empires"))?;
+        fs::write(project.path().join(REPO_CONFIG_PATH), "[session]\n")?;
+        assert!(matches!(
+            check_hook_trust(project.path())?,
+            HookTrustStatus::NoHooks
+        ));
+
+        fs::write(
+            project.path().join(REPO_CONFIG_PATH),
+            "[sandbox]\nuse_devcontainer = true\n",
+        )?;
+        let HookTrustStatus::NeedsTrust { hooks, hooks_hash } = check_hook_trust(project.path())?
+        else {
+            panic!("postCreateCommand needs trust");
+        };
+        assert_eq!(hooks.post_create, vec!["curl example.com | sh"]);
+        trust_repo(project.path(), &hooks_hash)?;
+        assert!(matches!(
+            check_hook_trust(project.path())?,
+            HookTrustStatus::Trusted(_)
+        ));
+        Ok(())
+    }
 }
@@ -0,0 +1,308 @@
+//! Companion services for sandboxed sessions
+//!
+//! `sandbox.services` in a repo's config names a compose file. Each
+//! sandboxed session gets its own compose project, `aoe-services-<id>`,
+//! whose services share a private network with the session's container, so
+//! parallel sessions don't share or collide on their databases, caches and
+//! the like. The agent reaches a service by its name, which AoE also sets in
+//! `AOE_SERVICE_<NAME>_HOST`. Services stop with the session and are removed
+//! when it's deleted.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::containers::runtime_base::run_streamed;
+use crate::containers::{
+    self, ContainerConfig, ContainerRuntimeInterface, DockerContainer, EnvEntry,
+};
+
+use super::egress::{self, NetworkMode, NetworkPolicy};
+
+/// Prefix of session compose projects. The project's network has the same
+/// name.
+pub const PROJECT_PREFIX: &str = "aoe-services-";
+
+/// State of one service container, from `compose ps`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ServiceStatus {
+    #[serde(rename = "Service")]
+    pub name: String,
+    /// `running`, `exited`, ...
+    #[serde(rename = "State", default)]
+    pub state: String,
+    /// `healthy`, `unhealthy` or `starting`; empty without a healthcheck
+    #[serde(rename = "Health", default)]
+    pub health: String,
+}
+
+impl ServiceStatus {
+    /// Health if the service has a healthcheck and is running, otherwise
+    /// its state
+    pub fn summary(&self) -> &str {
+        if self.state == "running" && !self.health.is_empty() {
+            &self.health
+        } else {
+            &self.state
+        }
+    }
+}
+
+/// Compose project name of the session's services
+fn project_name(session_id: &str) -> String {
+    format!("{}{}", PROJECT_PREFIX, session_id)
+}
+
+/// Bring up the project's services for a container about to be created and
+/// point the container at them. Returns the compose project, if the project
+/// has services. Compose output is passed to `on_output`.
+pub(crate) fn apply(
+    session_id: &str,
+    project_path: &Path,
+    config: &mut ContainerConfig,
+    on_output: &mut dyn FnMut(&str),
+) -> Result<Option<String>> {
+    let profile = super::config::resolve_default_profile();
+    let sandbox = super::repo_config::resolve_config_with_repo(&profile, project_path)?.sandbox;
+    let Some(compose_file) = sandbox.services else {
+        return Ok(None);
+    };
+    // Allowlisted containers only get out through the proxy, so the
+    // services network mustn't be another way out
+    let internal = match sandbox.network {
+        NetworkPolicy::Mode(NetworkMode::None) => bail!(
+            "sandbox.services needs a network, but sandbox.network is \"none\"; \
+             use an allowlist to restrict network access instead"
+        ),
+        NetworkPolicy::Mode(NetworkMode::Default) => false,
+        NetworkPolicy::Allowlist { .. } => true,
+    };
+
+    let compose_file = project_path.join(compose_file);
+    if !compose_file.is_file() {
+        bail!("sandbox.services file {} not found", compose_file.display());
+    }
+    let project = project_name(session_id);
+    let names = service_names(&project, std::slice::from_ref(&compose_file))?;
+    let override_file = write_override(session_id, &project, internal, &names)?;
+    let files = [compose_file, override_file];
+
+    tracing::info!("Starting services for {}: {}", project, names.join(", "));
+    let mut cmd = compose(&project, &files)?;
+    cmd.args(["up", "--detach", "--remove-orphans"]);
+    let (success, tail) = run_streamed(&mut cmd, on_output)?;
+    if !success {
+        bail!("Failed to start services: {}", tail.join("\n"));
+    }
+
+    config
+        .environment
+        .extend(names.iter().map(|name| EnvEntry::Literal {
+            key: host_env_key(name),
+            value: name.clone(),
+        }));
+    egress::bypass_proxy(config, &names);
+    Ok(Some(project))
+}
+
+/// Attach the session's container to its services' network.
+pub(crate) fn connect(project: &str, container_name: &str) -> Result<()> {
+    containers::get_container_runtime()
+        .connect_network(project, container_name)
+        .context("Failed to connect the sandbox to its services")
+}
+
+/// Start the services of an existing session again.
+pub(crate) fn start(project: &str) -> Result<()> {
+    run(project, &["start"])
+}
+
+/// Stop the session's services, keeping their containers and data.
+pub fn stop(project: &str) -> Result<()> {
+    run(project, &["stop"])
+}
+
+/// Remove the session's services, their volumes and their network.
+pub fn down(session_id: &str, project: &str) -> Result<()> {
+    // A kept sandbox container would hold on to the network
+    let container = DockerContainer::generate_name(session_id);
+    let _ = containers::get_container_runtime().disconnect_network(project, &container);
+
+    run(project, &["down", "--volumes", "--remove-orphans"])?;
+    if let Ok(path) = override_path(session_id) {
+        let _ = fs::remove_file(path);
+    }
+    Ok(())
+}
+
+/// Status of each of the session's service containers.
+pub fn status(project: &str) -> Vec<ServiceStatus> {
+    let output = containers::get_container_runtime()
+        .compose_command(project)
+        .and_then(|mut cmd| Ok(cmd.args(["ps", "--all", "--format", "json"]).output()?));
+    match output {
+        Ok(o) if o.status.success() => parse_ps(&String::from_utf8_lossy(&o.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+/// `compose ps --format json` prints a JSON array in older Compose
+/// releases and one object per line in newer ones.
+fn parse_ps(stdout: &str) -> Vec<ServiceStatus> {
+    let stdout = stdout.trim();
+    let mut services: Vec<ServiceStatus> = if stdout.starts_with('[') {
+        serde_json::from_str(stdout).unwrap_or_default()
+    } else {
+        stdout
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    };
+    services.sort_by(|a, b| a.name.cmp(&b.name));
+    services
+}
+
+/// `AOE_SERVICE_<NAME>_HOST`, with anything but letters and digits in the
+/// service name replaced by `_`
+fn host_env_key(service: &str) -> String {
+    let name: String = service
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("AOE_SERVICE_{}_HOST", name)
+}
+
+fn service_names(project: &str, files: &[PathBuf]) -> Result<Vec<String>> {
+    let output = compose(project, files)?
+        .args(["config", "--services"])
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "Invalid sandbox.services file: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn compose(project: &str, files: &[PathBuf]) -> Result<Command> {
+    let mut cmd = containers::get_container_runtime().compose_command(project)?;
+    for file in files {
+        cmd.arg("--file").arg(file);
+    }
+    Ok(cmd)
+}
+
+fn run(project: &str, args: &[&str]) -> Result<()> {
+    let output = containers::get_container_runtime()
+        .compose_command(project)?
+        .args(args)
+        .output()?;
+    if !output.status.success() {
+        bail!(
+            "compose {} failed for {}: {}",
+            args[0],
+            project,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Compose file layered over the repo's, naming the project's network after
+/// the project so the sandbox container can join it, and dropping published
+/// ports, which parallel sessions would collide on
+fn write_override(
+    session_id: &str,
+    project: &str,
+    internal: bool,
+    services: &[String],
+) -> Result<PathBuf> {
+    let path = override_path(session_id)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, override_yaml(project, internal, services))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+fn override_yaml(project: &str, internal: bool, services: &[String]) -> String {
+    let mut yaml = format!(
+        "networks:\n  default:\n    name: {}\n    internal: {}\n",
+        project, internal
+    );
+    if !services.is_empty() {
+        yaml.push_str("services:\n");
+        for service in services {
+            yaml.push_str(&format!("  {}:\n    ports: !reset []\n", service));
+        }
+    }
+    yaml
+}
+
+fn override_path(session_id: &str) -> Result<PathBuf> {
+    Ok(super::get_app_dir()?
+        .join("services")
+        .join(format!("{}.yml", session_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_env_key() {
+        assert_eq!(host_env_key("db"), "AOE_SERVICE_DB_HOST");
+        assert_eq!(host_env_key("redis-cache"), "AOE_SERVICE_REDIS_CACHE_HOST");
+    }
+
+    #[test]
+    fn test_override_yaml() {
+        assert_eq!(
+            override_yaml("aoe-services-0123", true, &[]),
+            "networks:\n  default:\n    name: aoe-services-0123\n    internal: true\n"
+        );
+        assert_eq!(
+            override_yaml(
+                "aoe-services-0123",
+                false,
+                &["db".to_string(), "redis-cache".to_string()]
+            ),
+            "networks:\n  default:\n    name: aoe-services-0123\n    internal: false\n\
+             services:\n  db:\n    ports: !reset []\n  redis-cache:\n    ports: !reset []\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_ps_lines_and_array() {
+        let lines = r#"{"Service":"redis","State":"running","Health":""}
+{"Service":"db","State":"running","Health":"healthy","Name":"aoe-services-0123-db-1"}"#;
+        let services = parse_ps(lines);
+        assert_eq!(services.len(), 2);
+        assert_eq!(services[0].name, "db");
+        assert_eq!(services[0].summary(), "healthy");
+        assert_eq!(services[1].summary(), "running");
+
+        let array = r#"[{"Service":"db","State":"exited","Health":"unhealthy"}]"#;
+        let services = parse_ps(array);
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].summary(), "exited");
+
+        assert!(parse_ps("").is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Sandbox container snapshots
+//!
+//! A snapshot commits a session's sandbox container to a local image tagged
+//! `aoe-snapshot-<session id>:<timestamp>` and records it in the session's
+//! `SandboxInfo`. Restoring one recreates the container from that image, so
+//! the agent can roll back anything it installed or changed outside the
+//! mounted project directory. Snapshot images go with the session when its
+//! sandbox is deleted; `aoe sandbox prune` removes any left behind.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prefix of the names of snapshot images
+pub const SNAPSHOT_TAG_PREFIX: &str = "aoe-snapshot-";
+
+/// Timestamp format of snapshot image tags
+const TAG_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SandboxSnapshot {
+    /// Image the container was committed to, as `name:tag`
+    pub image: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SandboxSnapshot {
+    pub fn new(session_id: &str, created_at: DateTime<Utc>) -> Self {
+        Self {
+            image: format!(
+                "{}{}:{}",
+                SNAPSHOT_TAG_PREFIX,
+                session_id,
+                created_at.format(TAG_FORMAT)
+            ),
+            created_at,
+        }
+    }
+
+    /// The image tag, which is the snapshot's timestamp
+    pub fn tag(&self) -> &str {
+        self.image
+            .rsplit_once(':')
+            .map_or(self.image.as_str(), |(_, tag)| tag)
+    }
+}
+
+/// The snapshot `query` names, by full image or by tag.
+pub fn find<'a>(snapshots: &'a [SandboxSnapshot], query: &str) -> Option<&'a SandboxSnapshot> {
+    snapshots
+        .iter()
+        .find(|s| s.image == query || s.tag() == query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_snapshot_image_name() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 4, 15, 6, 7).unwrap();
+        let snapshot = SandboxSnapshot::new("0123456789abcdef", at);
+        assert_eq!(
+            snapshot.image,
+            "aoe-snapshot-0123456789abcdef:20260304-150607"
+        );
+        assert_eq!(snapshot.tag(), "20260304-150607");
+    }
+
+    #[test]
+    fn test_find_by_image_or_tag() {
+        let first = SandboxSnapshot::new("s1", Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap());
+        let second = SandboxSnapshot::new("s1", Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap());
+        let snapshots = vec![first.clone(), second.clone()];
+
+        assert_eq!(find(&snapshots, "20260102-000000"), Some(&second));
+        assert_eq!(find(&snapshots, &first.image), Some(&first));
+        assert_eq!(find(&snapshots, "20260103-000000"), None);
+    }
+}
@@ -0,0 +1,291 @@
+//! Hooks run on status changes
+//!
+//! `on_waiting`, `on_idle` and `on_error` run in the background when a
+//! status poller sees a session enter that status, by whichever of the TUI
+//! and `aoe serve` claims the transition first. `on_stop` runs from
+//! `Instance::stop`, after the agent exits and before a sandbox container is
+//! stopped.
+//!
+//! Hooks are resolved like `on_destroy`: global and profile hooks are
+//! trusted, repo hooks only run once the repo's hooks have been trusted.
+
+use anyhow::Result;
+use std::path::Path;
+use std::time::Duration;
+
+use super::repo_config::{self, HookTrustStatus, HooksConfig};
+use super::{Instance, Status};
+
+/// Seconds a status hook command may run when `status_hook_timeout_secs`
+/// isn't set
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+const MARKER_FILE: &str = "status_hooks";
+
+/// Pollers see the same transition within a few seconds of each other;
+/// a repeat of the same status after this long is a new transition.
+const CLAIM_WINDOW_SECS: i64 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatusHook {
+    Waiting,
+    Idle,
+    Error,
+    Stop,
+}
+
+impl StatusHook {
+    fn for_status(status: Status) -> Option<Self> {
+        match status {
+            Status::Waiting => Some(Self::Waiting),
+            Status::Idle => Some(Self::Idle),
+            Status::Error => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Waiting => "on_waiting",
+            Self::Idle => "on_idle",
+            Self::Error => "on_error",
+            Self::Stop => "on_stop",
+        }
+    }
+
+    fn commands(self, hooks: &HooksConfig) -> &[String] {
+        match self {
+            Self::Waiting => &hooks.on_waiting,
+            Self::Idle => &hooks.on_idle,
+            Self::Error => &hooks.on_error,
+            Self::Stop => &hooks.on_stop,
+        }
+    }
+}
+
+/// Report a status transition seen by a status poller. Hooks for the new
+/// status run on a background thread.
+pub fn status_changed(inst: &Instance, old: Status, new: Status) {
+    if old == new {
+        return;
+    }
+    let Some(hook) = StatusHook::for_status(new) else {
+        return;
+    };
+    let inst = inst.clone();
+    std::thread::spawn(move || {
+        if let Err(e) = run(&inst, hook, old, new) {
+            tracing::warn!("{} hooks for session {}: {}", hook.name(), inst.id, e);
+        }
+    });
+}
+
+/// Run `on_stop` hooks for a session being stopped. Blocks until they
+/// finish or time out.
+pub fn session_stopping(inst: &Instance) {
+    if let Err(e) = run(inst, StatusHook::Stop, inst.status, Status::Stopped) {
+        tracing::warn!("on_stop hooks for session {}: {}", inst.id, e);
+    }
+}
+
+fn run(inst: &Instance, hook: StatusHook, old: Status, new: Status) -> Result<()> {
+    let hooks = resolve_hooks(inst);
+    let commands = hook.commands(&hooks);
+    if commands.is_empty() {
+        return Ok(());
+    }
+    // Only one process stops a session, so there is nothing to claim
+    if hook != StatusHook::Stop && !claim(&inst.id, new, chrono::Utc::now().timestamp())? {
+        return Ok(());
+    }
+
+    tracing::info!("Running {} hooks for session {}", hook.name(), inst.id);
+    let env = hook_env(inst, old, new);
+    let timeout = Duration::from_secs(
+        hooks
+            .status_hook_timeout_secs
+            .unwrap_or(DEFAULT_TIMEOUT_SECS),
+    );
+    let in_container = hooks.status_hooks_in_container.unwrap_or(true);
+
+    let errors = match inst.sandbox_info.as_ref().filter(|s| s.enabled) {
+        Some(sandbox) if in_container => repo_config::execute_status_hooks_in_container(
+            commands,
+            &sandbox.container_name,
+            &inst.container_workdir(),
+            &env,
+            timeout,
+        ),
+        _ => repo_config::execute_status_hooks(
+            commands,
+            Path::new(&inst.project_path),
+            &env,
+            timeout,
+        ),
+    };
+
+    if !errors.is_empty() {
+        tracing::warn!(
+            "{} hooks had {} failure(s) for session {}",
+            hook.name(),
+            errors.len(),
+            inst.id
+        );
+    }
+    Ok(())
+}
+
+/// Global and profile hooks, with trusted repo hooks on top.
+fn resolve_hooks(inst: &Instance) -> HooksConfig {
+    let profile = if inst.source_profile.is_empty() {
+        "default"
+    } else {
+        &inst.source_profile
+    };
+    let mut hooks = super::profile_config::resolve_config(profile)
+        .map(|c| c.hooks)
+        .unwrap_or_default();
+
+    match repo_config::check_hook_trust(Path::new(&inst.project_path)) {
+        Ok(HookTrustStatus::Trusted(repo)) => hooks.merge_status_hooks(&repo),
+        Ok(HookTrustStatus::NeedsTrust { hooks: repo, .. }) if repo.has_status_hooks() => {
+            tracing::warn!(
+                "Repo hooks changed since last trust approval; skipping repo status hooks"
+            );
+        }
+        _ => {}
+    }
+    hooks
+}
+
+/// Environment describing the session, passed to every status hook.
+fn hook_env(inst: &Instance, old: Status, new: Status) -> Vec<(String, String)> {
+    let worktree = inst.worktree_info.as_ref();
+    let project_path = worktree
+        .map(|w| w.main_repo_path.clone())
+        .unwrap_or_else(|| inst.project_path.clone());
+    let vars = [
+        ("AOE_SESSION_ID", inst.id.clone()),
+        ("AOE_SESSION_TITLE", inst.title.clone()),
+        ("AOE_PROFILE", inst.source_profile.clone()),
+        ("AOE_AGENT", inst.tool.clone()),
+        ("AOE_PROJECT_PATH", project_path),
+        (
+            "AOE_WORKTREE_PATH",
+            worktree
+                .map(|_| inst.project_path.clone())
+                .unwrap_or_default(),
+        ),
+        (
+            "AOE_BRANCH",
+            worktree.map(|w| w.branch.clone()).unwrap_or_default(),
+        ),
+        ("AOE_PREVIOUS_STATUS", status_label(old)),
+        ("AOE_STATUS", status_label(new)),
+    ];
+    vars.into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .chain(super::ports::env(&inst.ports))
+        .collect()
+}
+
+fn status_label(status: Status) -> String {
+    format!("{:?}", status).to_lowercase()
+}
+
+/// Record that hooks for the session entering `status` are being run.
+/// `false` if another process already did for this transition.
+fn claim(session_id: &str, status: Status, now: i64) -> Result<bool> {
+    claim_in(
+        &crate::hooks::claim_marker_path(session_id, MARKER_FILE)?,
+        &status_label(status),
+        now,
+    )
+}
+
+fn claim_in(path: &Path, status: &str, now: i64) -> Result<bool> {
+    crate::hooks::claim_event(path, &format!("{} {}", status, now), |last| {
+        last.split_once(' ').is_some_and(|(last, at)| {
+            last == status
+                && at
+                    .parse::<i64>()
+                    .is_ok_and(|at| now - at < CLAIM_WINDOW_SECS)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::WorktreeInfo;
+
+    #[test]
+    fn test_hook_for_status() {
+        assert_eq!(
+            StatusHook::for_status(Status::Waiting),
+            Some(StatusHook::Waiting)
+        );
+        assert_eq!(StatusHook::for_status(Status::Idle), Some(StatusHook::Idle));
+        assert_eq!(
+            StatusHook::for_status(Status::Error),
+            Some(StatusHook::Error)
+        );
+        assert_eq!(StatusHook::for_status(Status::Running), None);
+        // on_stop runs from the stop path, not from a poller
+        assert_eq!(StatusHook::for_status(Status::Stopped), None);
+    }
+
+    #[test]
+    fn test_hook_env_describes_session() {
+        let mut inst = Instance::new("fix-login", "/src/app-worktrees/fix-login");
+        inst.source_profile = "work".to_string();
+        inst.worktree_info = Some(WorktreeInfo {
+            branch: "fix-login".to_string(),
+            main_repo_path: "/src/app".to_string(),
+            managed_by_aoe: true,
+            created_at: chrono::Utc::now(),
+        });
+        inst.ports = vec![crate::session::ports::PortAllocation {
+            name: "WEB_PORT".to_string(),
+            port: 20000,
+        }];
+
+        let env: std::collections::HashMap<String, String> =
+            hook_env(&inst, Status::Running, Status::Idle)
+                .into_iter()
+                .collect();
+        assert_eq!(env["AOE_SESSION_ID"], inst.id);
+        assert_eq!(env["AOE_SESSION_TITLE"], "fix-login");
+        assert_eq!(env["AOE_PROFILE"], "work");
+        assert_eq!(env["AOE_PROJECT_PATH"], "/src/app");
+        assert_eq!(env["AOE_WORKTREE_PATH"], "/src/app-worktrees/fix-login");
+        assert_eq!(env["AOE_BRANCH"], "fix-login");
+        assert_eq!(env["AOE_PREVIOUS_STATUS"], "running");
+        assert_eq!(env["AOE_STATUS"], "idle");
+        assert_eq!(env["WEB_PORT"], "20000");
+
+        inst.worktree_info = None;
+        let env: std::collections::HashMap<String, String> =
+            hook_env(&inst, Status::Running, Status::Waiting)
+                .into_iter()
+                .collect();
+        assert_eq!(env["AOE_PROJECT_PATH"], "/src/app-worktrees/fix-login");
+        assert_eq!(env["AOE_WORKTREE_PATH"], "");
+        assert_eq!(env["AOE_BRANCH"], "");
+    }
+
+    #[test]
+    fn test_claim_once_per_transition() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(MARKER_FILE);
+
+        assert!(claim_in(&path, "idle", 1_000)?);
+        // The other poller seeing the same transition
+        assert!(!claim_in(&path, "idle", 1_003)?);
+        assert!(claim_in(&path, "waiting", 1_005)?);
+        assert!(claim_in(&path, "idle", 1_010)?);
+        // The same status again, well after the last transition
+        assert!(claim_in(&path, "idle", 1_010 + CLAIM_WINDOW_SECS)?);
+        Ok(())
+    }
+}
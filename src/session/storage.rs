@@ -1,17 +1,61 @@
 //! Session storage - JSON file persistence
+//!
+//! `sessions.json` and `groups.json` are shared between the TUI, `aoe serve`
+//! and one-shot CLI commands, which may all run at the same time. Writers
+//! serialize on an advisory lock file in the profile directory and commit
+//! each file with write-to-temp-then-rename, so readers never observe a
+//! truncated file. Long-lived writers that keep their own in-memory copy
+//! (the TUI) use [`Storage::save_merged`] to fold in changes other processes
+//! made since they last synced instead of clobbering them.
 
 use anyhow::Result;
-use std::fs;
-use std::path::PathBuf;
+use fs2::FileExt;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use tracing::warn;
 
 use super::{get_profile_dir, Group, GroupTree, Instance, DEFAULT_PROFILE};
 
+const LOCK_FILE: &str = ".storage.lock";
+
 pub struct Storage {
     profile: String,
     sessions_path: PathBuf,
 }
 
+/// Held advisory lock on a profile's storage files. Released on drop.
+pub struct StorageLock {
+    file: File,
+}
+
+impl Drop for StorageLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// What a long-lived writer last read from or wrote to disk. Used as the
+/// common ancestor when merging its in-memory state with the current files.
+#[derive(Debug, Clone, Default)]
+pub struct SyncBase {
+    instances: HashMap<String, serde_json::Value>,
+    group_paths: HashSet<String>,
+}
+
+impl SyncBase {
+    pub fn new(instances: &[Instance], groups: &[Group]) -> Self {
+        Self {
+            instances: instances
+                .iter()
+                .filter_map(|i| Some((i.id.clone(), serde_json::to_value(i).ok()?)))
+                .collect(),
+            group_paths: groups.iter().map(|g| g.path.clone()).collect(),
+        }
+    }
+}
+
 impl Storage {
     pub fn new(profile: &str) -> Result<Self> {
         let profile_name = if profile.is_empty() {
@@ -33,40 +77,58 @@ impl Storage {
         &self.profile
     }
 
-    pub fn load(&self) -> Result<Vec<Instance>> {
-        if !self.sessions_path.exists() {
-            return Ok(Vec::new());
-        }
+    fn groups_path(&self) -> PathBuf {
+        self.sessions_path.with_file_name("groups.json")
+    }
 
-        let content = fs::read_to_string(&self.sessions_path)?;
-        if content.trim().is_empty() {
-            return Ok(Vec::new());
-        }
+    fn open_lock_file(&self) -> Result<File> {
+        let lock_path = self.sessions_path.with_file_name(LOCK_FILE);
+        Ok(OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(lock_path)?)
+    }
 
-        let instances: Vec<Instance> = serde_json::from_str(&content)?;
-        Ok(instances)
+    /// Take the exclusive storage lock for this profile, blocking until any
+    /// other writer (in this or another process) releases it.
+    ///
+    /// The lock is not reentrant: do not call `save*`/`update*` while holding it.
+    pub fn lock(&self) -> Result<StorageLock> {
+        let file = self.open_lock_file()?;
+        FileExt::lock_exclusive(&file)?;
+        Ok(StorageLock { file })
     }
 
-    pub fn load_with_groups(&self) -> Result<(Vec<Instance>, Vec<Group>)> {
-        let instances = self.load()?;
+    fn lock_shared(&self) -> Result<StorageLock> {
+        let file = self.open_lock_file()?;
+        FileExt::lock_shared(&file)?;
+        Ok(StorageLock { file })
+    }
 
-        // Load groups from separate file
-        let groups_path = self.sessions_path.with_file_name("groups.json");
-        let groups = if groups_path.exists() {
-            let content = fs::read_to_string(&groups_path)?;
-            if content.trim().is_empty() {
-                Vec::new()
-            } else {
-                serde_json::from_str(&content)?
-            }
-        } else {
-            Vec::new()
-        };
+    pub fn load(&self) -> Result<Vec<Instance>> {
+        read_json_list(&self.sessions_path)
+    }
 
+    pub fn load_with_groups(&self) -> Result<(Vec<Instance>, Vec<Group>)> {
+        // Shared lock so we never pair sessions from one commit with groups
+        // from another.
+        let _lock = self.lock_shared()?;
+        self.load_with_groups_unlocked()
+    }
+
+    fn load_with_groups_unlocked(&self) -> Result<(Vec<Instance>, Vec<Group>)> {
+        let instances = self.load()?;
+        let groups = read_json_list(&self.groups_path())?;
         Ok((instances, groups))
     }
 
     pub fn save(&self, instances: &[Instance]) -> Result<()> {
+        let _lock = self.lock()?;
+        self.save_unlocked(instances)
+    }
+
+    fn save_unlocked(&self, instances: &[Instance]) -> Result<()> {
         // Create backup
         if self.sessions_path.exists() {
             let backup_path = self.sessions_path.with_extension("json.bak");
@@ -76,21 +138,196 @@ impl Storage {
         }
 
         let content = serde_json::to_string_pretty(instances)?;
-        fs::write(&self.sessions_path, content)?;
-        Ok(())
+        write_atomic(&self.sessions_path, &content)
     }
 
     pub fn save_with_groups(&self, instances: &[Instance], group_tree: &GroupTree) -> Result<()> {
-        self.save(instances)?;
+        let _lock = self.lock()?;
+        self.save_with_groups_unlocked(instances, &group_tree.get_all_groups())
+    }
 
-        // Save groups
-        let groups_path = self.sessions_path.with_file_name("groups.json");
-        let groups = group_tree.get_all_groups();
-        let content = serde_json::to_string_pretty(&groups)?;
-        fs::write(&groups_path, content)?;
+    fn save_with_groups_unlocked(&self, instances: &[Instance], groups: &[Group]) -> Result<()> {
+        self.save_unlocked(instances)?;
+        let content = serde_json::to_string_pretty(groups)?;
+        write_atomic(&self.groups_path(), &content)
+    }
 
-        Ok(())
+    /// Re-read sessions under the lock, let `f` modify them, and write them back.
+    ///
+    /// Use this instead of `load` + `save` whenever the caller does not own
+    /// the whole file, so concurrent writers do not lose each other's edits.
+    /// Nothing is written if `f` returns an error.
+    pub fn update<T>(&self, f: impl FnOnce(&mut Vec<Instance>) -> Result<T>) -> Result<T> {
+        let _lock = self.lock()?;
+        let mut instances = self.load()?;
+        let out = f(&mut instances)?;
+        self.save_unlocked(&instances)?;
+        Ok(out)
     }
+
+    /// Write back a session that was modified outside the lock (e.g. by
+    /// starting it), leaving every other session as other processes left it.
+    pub fn replace_instance(&self, instance: &Instance) -> Result<()> {
+        self.update(|instances| {
+            match instances.iter_mut().find(|i| i.id == instance.id) {
+                Some(stored) => *stored = instance.clone(),
+                None => anyhow::bail!("Session was removed: {}", instance.title),
+            }
+            Ok(())
+        })
+    }
+
+    /// Like [`Storage::update`], but also hands `f` the profile's group tree
+    /// and persists `groups.json` in the same commit.
+    pub fn update_with_groups<T>(
+        &self,
+        f: impl FnOnce(&mut Vec<Instance>, &mut GroupTree) -> Result<T>,
+    ) -> Result<T> {
+        let _lock = self.lock()?;
+        let (mut instances, groups) = self.load_with_groups_unlocked()?;
+        let mut tree = GroupTree::new_with_groups(&instances, &groups);
+        let out = f(&mut instances, &mut tree)?;
+        // Rebuild so groups referenced by new or moved sessions exist
+        let tree = GroupTree::new_with_groups(&instances, &tree.get_all_groups());
+        self.save_with_groups_unlocked(&instances, &tree.get_all_groups())?;
+        Ok(out)
+    }
+
+    /// Save a long-lived in-memory copy of this profile, three-way merging it
+    /// against the files on disk using `base` as the common ancestor.
+    ///
+    /// - Sessions and groups another process added since `base` are kept.
+    /// - Sessions and groups another process removed since `base` stay removed.
+    /// - For sessions present everywhere, each top-level field that we did not
+    ///   change since `base` takes the on-disk value; fields we changed win.
+    ///
+    /// Returns the new base to use for the next save.
+    pub fn save_merged(
+        &self,
+        base: &SyncBase,
+        instances: &[Instance],
+        group_tree: &GroupTree,
+    ) -> Result<SyncBase> {
+        let _lock = self.lock()?;
+        let (theirs, their_groups) = self.load_with_groups_unlocked()?;
+        let our_groups = group_tree.get_all_groups();
+
+        let merged = merge_instances(base, instances, theirs)?;
+        let merged_groups = merge_groups(base, &our_groups, their_groups);
+        self.save_with_groups_unlocked(&merged, &merged_groups)?;
+
+        Ok(SyncBase::new(instances, &our_groups))
+    }
+}
+
+fn read_json_list<T: serde::de::DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Write `content` to a sibling temp file, fsync it, and rename it over
+/// `path`, so a crash leaves either the old or the new file intact.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    {
+        let mut file = File::create(&tmp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+    }
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+fn merge_instances(
+    base: &SyncBase,
+    ours: &[Instance],
+    theirs: Vec<Instance>,
+) -> Result<Vec<Instance>> {
+    let mut theirs: HashMap<String, Instance> =
+        theirs.into_iter().map(|i| (i.id.clone(), i)).collect();
+    let our_ids: HashSet<&str> = ours.iter().map(|i| i.id.as_str()).collect();
+    let mut added_by_others: Vec<Instance> = theirs
+        .values()
+        .filter(|i| !our_ids.contains(i.id.as_str()) && !base.instances.contains_key(&i.id))
+        .cloned()
+        .collect();
+    added_by_others.sort_by_key(|i| i.created_at);
+
+    let mut merged = Vec::with_capacity(ours.len() + added_by_others.len());
+    for inst in ours {
+        match (base.instances.get(&inst.id), theirs.remove(&inst.id)) {
+            // Created by us and not yet on disk
+            (None, None) => merged.push(inst.clone()),
+            // Deleted by another process
+            (Some(_), None) => {}
+            (None, Some(_)) => merged.push(inst.clone()),
+            (Some(base_value), Some(their_inst)) => {
+                let ours_value = serde_json::to_value(inst)?;
+                let their_value = serde_json::to_value(&their_inst)?;
+                let value = merge_fields(base_value, ours_value, their_value);
+                merged.push(serde_json::from_value(value)?);
+            }
+        }
+    }
+    merged.extend(added_by_others);
+    Ok(merged)
+}
+
+fn merge_fields(
+    base: &serde_json::Value,
+    ours: serde_json::Value,
+    theirs: serde_json::Value,
+) -> serde_json::Value {
+    use serde_json::Value;
+
+    if !(base.is_object() && ours.is_object() && theirs.is_object()) {
+        return ours;
+    }
+    let (Value::Object(base), Value::Object(mut ours), Value::Object(mut theirs)) =
+        (base, ours, theirs)
+    else {
+        unreachable!("checked above");
+    };
+
+    let keys: HashSet<String> = ours.keys().chain(theirs.keys()).cloned().collect();
+    for key in keys {
+        if ours.get(&key) != base.get(&key) {
+            continue;
+        }
+        match theirs.remove(&key) {
+            Some(value) => {
+                ours.insert(key, value);
+            }
+            None => {
+                ours.remove(&key);
+            }
+        }
+    }
+    Value::Object(ours)
+}
+
+fn merge_groups(base: &SyncBase, ours: &[Group], theirs: Vec<Group>) -> Vec<Group> {
+    let their_paths: HashSet<&str> = theirs.iter().map(|g| g.path.as_str()).collect();
+    let mut merged: Vec<Group> = ours
+        .iter()
+        .filter(|g| !base.group_paths.contains(&g.path) || their_paths.contains(g.path.as_str()))
+        .cloned()
+        .collect();
+    let our_paths: HashSet<String> = ours.iter().map(|g| g.path.clone()).collect();
+    merged.extend(
+        theirs
+            .into_iter()
+            .filter(|g| !our_paths.contains(&g.path) && !base.group_paths.contains(&g.path)),
+    );
+    merged
 }
 
 #[cfg(test)]
@@ -355,4 +592,177 @@ mod tests {
         assert!(groups.is_empty());
         Ok(())
     }
+
+    #[test]
+    #[serial]
+    fn test_storage_save_leaves_no_temp_file() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-atomic")?;
+        let instances = vec![Instance::new("test", "/tmp/test")];
+        let group_tree = GroupTree::new_with_groups(&instances, &[]);
+        storage.save_with_groups(&instances, &group_tree)?;
+
+        assert!(!storage.sessions_path.with_extension("json.tmp").exists());
+        assert!(!storage.groups_path().with_extension("json.tmp").exists());
+        assert_eq!(storage.load()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_update_rereads_under_lock() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-update")?;
+        let other = Storage::new("test-update")?;
+        storage.save(&[Instance::new("first", "/tmp/first")])?;
+
+        // A second writer appends after our load; update must not lose it
+        let (stale, _) = storage.load_with_groups()?;
+        other.update(|instances| {
+            instances.push(Instance::new("second", "/tmp/second"));
+            Ok(())
+        })?;
+        storage.update(|instances| {
+            assert_eq!(instances.len(), stale.len() + 1);
+            instances.push(Instance::new("third", "/tmp/third"));
+            Ok(())
+        })?;
+
+        let titles: Vec<String> = storage.load()?.into_iter().map(|i| i.title).collect();
+        assert_eq!(titles, vec!["first", "second", "third"]);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_update_error_writes_nothing() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-update-err")?;
+        storage.save(&[Instance::new("keep", "/tmp/keep")])?;
+
+        let result: Result<()> = storage.update(|instances| {
+            instances.clear();
+            anyhow::bail!("abort")
+        });
+        assert!(result.is_err());
+        assert_eq!(storage.load()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_update_with_groups_persists_both() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-update-groups")?;
+        storage.update_with_groups(|instances, tree| {
+            let mut inst = Instance::new("grouped", "/tmp/g");
+            inst.group_path = "work".to_string();
+            instances.push(inst);
+            tree.create_group("empty");
+            Ok(())
+        })?;
+
+        let (instances, groups) = storage.load_with_groups()?;
+        assert_eq!(instances.len(), 1);
+        assert!(groups.iter().any(|g| g.path == "work"));
+        assert!(groups.iter().any(|g| g.path == "empty"));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_replace_instance_missing_session_errors() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-replace")?;
+        let mut inst = Instance::new("replace", "/tmp/r");
+        storage.save(&[inst.clone()])?;
+
+        inst.title = "renamed".to_string();
+        storage.replace_instance(&inst)?;
+        assert_eq!(storage.load()?[0].title, "renamed");
+
+        storage.save(&[])?;
+        assert!(storage.replace_instance(&inst).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_save_merged_keeps_concurrent_changes() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-merge")?;
+        let keep = Instance::new("keep", "/tmp/keep");
+        let gone = Instance::new("gone", "/tmp/gone");
+        let ours_deleted = Instance::new("ours-deleted", "/tmp/od");
+        storage.save(&[keep.clone(), gone.clone(), ours_deleted.clone()])?;
+
+        let (loaded, groups) = storage.load_with_groups()?;
+        let base = SyncBase::new(&loaded, &groups);
+
+        // Another process renames `keep`, deletes `gone` and adds `theirs`
+        let theirs = Instance::new("theirs", "/tmp/theirs");
+        storage.update(|instances| {
+            instances[0].title = "renamed-elsewhere".to_string();
+            instances.retain(|i| i.id != gone.id);
+            instances.push(theirs.clone());
+            Ok(())
+        })?;
+
+        // Meanwhile we change `keep`'s group, delete `ours-deleted` and add `mine`
+        let mut ours = vec![loaded[0].clone(), loaded[1].clone()];
+        ours[0].group_path = "work".to_string();
+        let mine = Instance::new("mine", "/tmp/mine");
+        ours.push(mine.clone());
+        let tree = GroupTree::new_with_groups(&ours, &groups);
+        storage.save_merged(&base, &ours, &tree)?;
+
+        let (merged, groups) = storage.load_with_groups()?;
+        let ids: Vec<&str> = merged.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![keep.id.as_str(), mine.id.as_str(), theirs.id.as_str()]
+        );
+        assert_eq!(merged[0].title, "renamed-elsewhere");
+        assert_eq!(merged[0].group_path, "work");
+        assert!(groups.iter().any(|g| g.path == "work"));
+        Ok(())
+    }
+
+    #[test]
+    #[serial]
+    fn test_storage_save_merged_does_not_resurrect_after_repeat_save() -> Result<()> {
+        let temp = tempdir()?;
+        setup_test_home(temp.path());
+
+        let storage = Storage::new("test-merge-repeat")?;
+        let theirs = Instance::new("theirs", "/tmp/theirs");
+        let mine = Instance::new("mine", "/tmp/mine");
+
+        let base = SyncBase::default();
+        storage.save(std::slice::from_ref(&theirs))?;
+        let tree = GroupTree::new_with_groups(&[], &[]);
+        let base = storage.save_merged(&base, std::slice::from_ref(&mine), &tree)?;
+        assert_eq!(storage.load()?.len(), 2);
+
+        // Deleting our own session later must not bring it back, and the
+        // other process's session must survive even though we never loaded it
+        let base = storage.save_merged(&base, &[], &tree)?;
+        storage.save_merged(&base, &[], &tree)?;
+        let loaded = storage.load()?;
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].id, theirs.id);
+        Ok(())
+    }
 }
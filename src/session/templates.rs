@@ -0,0 +1,264 @@
+//! Session templates - named presets for creating sessions
+//!
+//! A `[templates.<name>]` table bundles the choices usually re-entered for
+//! every new session (agent, YOLO mode, sandbox image, environment, group,
+//! ...). Templates can be defined in the global, profile and repo config; a
+//! template of the same name in a later layer overrides it field by field.
+//!
+//! Explicitly requested values (CLI flags, dialog edits, API fields) take
+//! priority over the template, which takes priority over config defaults.
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Config;
+
+/// A named session preset. Fields left unset fall back to config defaults.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionTemplate {
+    /// Agent to run (e.g. "claude", "codex")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub yolo: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<bool>,
+
+    /// Container image, used when the session is sandboxed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_image: Option<String>,
+
+    /// Container environment on top of `[sandbox] environment`.
+    /// `KEY` = pass through from host, `KEY=VALUE` = set explicitly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_env: Option<Vec<String>>,
+
+    /// Replaces `[sandbox] custom_instruction`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_instruction: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+
+    /// Worktree path template, for both regular and bare repos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worktree_template: Option<String>,
+
+    /// Extra arguments to append after the agent binary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra_args: Option<String>,
+}
+
+impl SessionTemplate {
+    /// Override fields with the ones set in `other`.
+    pub fn merge(&mut self, other: &SessionTemplate) {
+        if other.agent.is_some() {
+            self.agent = other.agent.clone();
+        }
+        if other.yolo.is_some() {
+            self.yolo = other.yolo;
+        }
+        if other.sandbox.is_some() {
+            self.sandbox = other.sandbox;
+        }
+        if other.sandbox_image.is_some() {
+            self.sandbox_image = other.sandbox_image.clone();
+        }
+        if other.extra_env.is_some() {
+            self.extra_env = other.extra_env.clone();
+        }
+        if other.custom_instruction.is_some() {
+            self.custom_instruction = other.custom_instruction.clone();
+        }
+        if other.group.is_some() {
+            self.group = other.group.clone();
+        }
+        if other.worktree_template.is_some() {
+            self.worktree_template = other.worktree_template.clone();
+        }
+        if other.extra_args.is_some() {
+            self.extra_args = other.extra_args.clone();
+        }
+    }
+
+    /// Apply the settings that are read from config while a session is
+    /// built rather than passed in explicitly.
+    pub fn apply_to_config(&self, config: &mut Config) {
+        if let Some(ref template) = self.worktree_template {
+            config.worktree.path_template = template.clone();
+            config.worktree.bare_repo_path_template = template.clone();
+        }
+        if let Some(ref instruction) = self.custom_instruction {
+            config.sandbox.custom_instruction = Some(instruction.clone());
+        }
+    }
+
+    /// Per-session container environment: the configured `environment`
+    /// followed by the template's entries. `None` when the template adds
+    /// none, so the configured environment applies as usual.
+    pub fn sandbox_env(&self, configured: &[String]) -> Option<Vec<String>> {
+        let extra = self.extra_env.as_ref()?;
+        let mut env = configured.to_vec();
+        for entry in extra {
+            if !env.contains(entry) {
+                env.push(entry.clone());
+            }
+        }
+        Some(env)
+    }
+}
+
+/// Merge templates from a profile or repo config into `target`.
+pub fn apply_template_overrides(
+    target: &mut BTreeMap<String, SessionTemplate>,
+    source: &BTreeMap<String, SessionTemplate>,
+) {
+    for (name, template) in source {
+        target.entry(name.clone()).or_default().merge(template);
+    }
+}
+
+/// Look up a template in the resolved config.
+pub fn find<'a>(config: &'a Config, name: &str) -> Result<&'a SessionTemplate> {
+    if let Some(template) = config.templates.get(name) {
+        return Ok(template);
+    }
+    if config.templates.is_empty() {
+        bail!(
+            "Unknown template '{}'\nTip: define one in config.toml under [templates.{}]",
+            name,
+            name
+        );
+    }
+    let names: Vec<&str> = config.templates.keys().map(|s| s.as_str()).collect();
+    bail!(
+        "Unknown template '{}'. Available: {}",
+        name,
+        names.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_deserialization() {
+        let toml = r#"
+            [templates.review]
+            agent = "codex"
+            yolo = true
+            sandbox_image = "ghcr.io/acme/dev:latest"
+            extra_env = ["GH_TOKEN", "CI=1"]
+        "#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let review = &config.templates["review"];
+        assert_eq!(review.agent.as_deref(), Some("codex"));
+        assert_eq!(review.yolo, Some(true));
+        assert_eq!(review.sandbox, None);
+        assert_eq!(
+            review.extra_env,
+            Some(vec!["GH_TOKEN".to_string(), "CI=1".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_overrides_merge_per_field() {
+        let mut target = BTreeMap::from([(
+            "review".to_string(),
+            SessionTemplate {
+                agent: Some("claude".to_string()),
+                yolo: Some(true),
+                group: Some("reviews".to_string()),
+                ..Default::default()
+            },
+        )]);
+        let source = BTreeMap::from([
+            (
+                "review".to_string(),
+                SessionTemplate {
+                    agent: Some("codex".to_string()),
+                    yolo: Some(false),
+                    ..Default::default()
+                },
+            ),
+            (
+                "docs".to_string(),
+                SessionTemplate {
+                    agent: Some("gemini".to_string()),
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        apply_template_overrides(&mut target, &source);
+
+        let review = &target["review"];
+        assert_eq!(review.agent.as_deref(), Some("codex"));
+        assert_eq!(review.yolo, Some(false));
+        assert_eq!(review.group.as_deref(), Some("reviews"));
+        assert_eq!(target["docs"].agent.as_deref(), Some("gemini"));
+    }
+
+    #[test]
+    fn test_apply_to_config() {
+        let mut config = Config::default();
+        let template = SessionTemplate {
+            worktree_template: Some("../wt/{branch}".to_string()),
+            custom_instruction: Some("Run the tests first".to_string()),
+            ..Default::default()
+        };
+        template.apply_to_config(&mut config);
+        assert_eq!(config.worktree.path_template, "../wt/{branch}");
+        assert_eq!(config.worktree.bare_repo_path_template, "../wt/{branch}");
+        assert_eq!(
+            config.sandbox.custom_instruction.as_deref(),
+            Some("Run the tests first")
+        );
+
+        let before = config.worktree.path_template.clone();
+        SessionTemplate::default().apply_to_config(&mut config);
+        assert_eq!(config.worktree.path_template, before);
+    }
+
+    #[test]
+    fn test_sandbox_env_extends_configured_environment() {
+        let template = SessionTemplate {
+            extra_env: Some(vec!["GH_TOKEN".to_string(), "CI=1".to_string()]),
+            ..Default::default()
+        };
+        assert_eq!(
+            template.sandbox_env(&["TERM".to_string(), "GH_TOKEN".to_string()]),
+            Some(vec![
+                "TERM".to_string(),
+                "GH_TOKEN".to_string(),
+                "CI=1".to_string()
+            ])
+        );
+        assert_eq!(
+            SessionTemplate::default().sandbox_env(&["TERM".to_string()]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_lists_available_templates() {
+        let mut config = Config::default();
+        let err = find(&config, "review").unwrap_err().to_string();
+        assert!(err.contains("[templates.review]"), "{}", err);
+
+        config
+            .templates
+            .insert("docs".to_string(), SessionTemplate::default());
+        config
+            .templates
+            .insert("bugfix".to_string(), SessionTemplate::default());
+        assert!(find(&config, "docs").is_ok());
+        let err = find(&config, "review").unwrap_err().to_string();
+        assert!(err.contains("Available: bugfix, docs"), "{}", err);
+    }
+}
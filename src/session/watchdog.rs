@@ -0,0 +1,259 @@
+//! Stall watchdog - flags Running sessions whose agent has gone quiet
+//!
+//! A session reports `Stalled` instead of `Running` once neither its pane
+//! (tmux `#{window_activity}`) nor its agent's hook status file has changed
+//! for `stall_after_minutes`. The check is stateless, so the TUI, `aoe serve`
+//! and the CLI all agree on it without sharing anything.
+//!
+//! When the TUI or `aoe serve` sees a session turn Stalled it runs the
+//! configured recovery action, once per stall: the stall is claimed by the
+//! pane's last activity time.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use super::Instance;
+use crate::tmux::PaneMetadata;
+
+/// How long a profile's resolved watchdog config is reused before it is
+/// read from disk again. Status is checked every poll cycle.
+const CONFIG_TTL: Duration = Duration::from_secs(10);
+
+const MARKER_FILE: &str = "watchdog";
+
+/// Stall watchdog configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Mark quiet Running sessions as Stalled
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Minutes without pane output or hook updates before a Running session
+    /// counts as stalled
+    #[serde(default = "default_stall_after_minutes")]
+    pub stall_after_minutes: u64,
+
+    /// What to do when a session stalls
+    #[serde(default)]
+    pub recovery: RecoveryAction,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            stall_after_minutes: default_stall_after_minutes(),
+            recovery: RecoveryAction::default(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_stall_after_minutes() -> u64 {
+    15
+}
+
+/// Profile override for the watchdog config (all fields optional, None = inherit)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchdogConfigOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stall_after_minutes: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recovery: Option<RecoveryAction>,
+}
+
+pub fn apply_watchdog_overrides(target: &mut WatchdogConfig, source: &WatchdogConfigOverride) {
+    if let Some(enabled) = source.enabled {
+        target.enabled = enabled;
+    }
+    if let Some(stall_after_minutes) = source.stall_after_minutes {
+        target.stall_after_minutes = stall_after_minutes;
+    }
+    if let Some(recovery) = source.recovery {
+        target.recovery = recovery;
+    }
+}
+
+/// Recovery action for a stalled session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecoveryAction {
+    /// Only notify
+    #[default]
+    None,
+    /// Send Escape to interrupt the agent
+    Escape,
+    /// Type "continue" and submit it
+    Continue,
+    /// Restart the session
+    Restart,
+}
+
+static CONFIG_CACHE: RwLock<Option<ConfigCache>> = RwLock::new(None);
+
+struct ConfigCache {
+    loaded: Instant,
+    by_profile: HashMap<String, WatchdogConfig>,
+}
+
+/// The watchdog config for a profile (global > profile), cached for a few
+/// seconds. An empty profile name gets the global config.
+pub fn config_for(profile: &str) -> WatchdogConfig {
+    if let Ok(cache) = CONFIG_CACHE.read() {
+        if let Some(cache) = cache.as_ref() {
+            if cache.loaded.elapsed() < CONFIG_TTL {
+                if let Some(config) = cache.by_profile.get(profile) {
+                    return config.clone();
+                }
+            }
+        }
+    }
+
+    let config = if profile.is_empty() {
+        super::Config::load().map(|c| c.watchdog)
+    } else {
+        super::profile_config::resolve_config(profile).map(|c| c.watchdog)
+    }
+    .unwrap_or_default();
+
+    if let Ok(mut cache) = CONFIG_CACHE.write() {
+        let fresh = cache
+            .as_ref()
+            .is_some_and(|c| c.loaded.elapsed() < CONFIG_TTL);
+        if !fresh {
+            *cache = Some(ConfigCache {
+                loaded: Instant::now(),
+                by_profile: HashMap::new(),
+            });
+        }
+        if let Some(cache) = cache.as_mut() {
+            cache.by_profile.insert(profile.to_string(), config.clone());
+        }
+    }
+    config
+}
+
+/// Whether a session that otherwise looks Running has gone quiet for longer
+/// than its profile's `stall_after_minutes`. Uses `metadata` for the pane's
+/// last activity when given, else asks tmux.
+pub fn is_stalled(inst: &Instance, metadata: Option<&PaneMetadata>) -> bool {
+    let config = config_for(&inst.source_profile);
+    if !config.enabled || config.stall_after_minutes == 0 {
+        return false;
+    }
+    let activity = match metadata {
+        Some(m) => m.window_activity,
+        None => inst.tmux_session().ok().and_then(|s| s.window_activity()),
+    };
+    let hook_updated = fs::metadata(crate::hooks::hook_status_dir(&inst.id).join("status"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64);
+    quiet_for(
+        chrono::Utc::now().timestamp(),
+        activity,
+        hook_updated,
+        config.stall_after_minutes as i64 * 60,
+    )
+}
+
+/// Whether the later of `activity` and `hook_updated` is at least `secs`
+/// before `now`. Unknown pane activity never counts as quiet.
+fn quiet_for(now: i64, activity: Option<i64>, hook_updated: Option<i64>, secs: i64) -> bool {
+    let Some(activity) = activity else {
+        return false;
+    };
+    let last = hook_updated.map_or(activity, |hook| hook.max(activity));
+    now - last >= secs
+}
+
+/// Run the profile's recovery action for a session that just turned
+/// Stalled. Returns the action taken, or `None` when none is configured or
+/// another process already handled this stall.
+pub fn recover(inst: &mut Instance) -> Result<Option<RecoveryAction>> {
+    let action = config_for(&inst.source_profile).recovery;
+    if action == RecoveryAction::None {
+        return Ok(None);
+    }
+    let session = inst.tmux_session()?;
+    let Some(activity) = session.window_activity() else {
+        return Ok(None);
+    };
+    if !claim(&inst.id, activity)? {
+        return Ok(None);
+    }
+
+    tracing::info!(
+        "Session '{}' stalled, recovering with {:?}",
+        inst.title,
+        action
+    );
+    match action {
+        RecoveryAction::None => {}
+        RecoveryAction::Escape => session.send_key_names(&["Escape".to_string()])?,
+        RecoveryAction::Continue => session.send_keys("continue")?,
+        RecoveryAction::Restart => inst.restart()?,
+    }
+    Ok(Some(action))
+}
+
+/// Record that the stall whose last pane activity was `activity` is being
+/// handled. `false` if it already was.
+fn claim(session_id: &str, activity: i64) -> Result<bool> {
+    let activity = activity.to_string();
+    crate::hooks::claim_event(
+        &crate::hooks::claim_marker_path(session_id, MARKER_FILE)?,
+        &activity,
+        |last| last == activity,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_for_uses_latest_signal() {
+        let now = 10_000;
+        assert!(quiet_for(now, Some(now - 900), None, 900));
+        assert!(!quiet_for(now, Some(now - 899), None, 900));
+        // A recent hook write keeps a silent pane from counting as stalled
+        assert!(!quiet_for(now, Some(now - 5_000), Some(now - 60), 900));
+        assert!(quiet_for(now, Some(now - 5_000), Some(now - 1_000), 900));
+        assert!(!quiet_for(now, None, Some(0), 900));
+    }
+
+    #[test]
+    fn test_claim_once_per_stall() -> Result<()> {
+        let id = "test_watchdog_claim";
+        let dir = crate::hooks::hook_status_dir(id);
+        assert!(claim(id, 100)?);
+        assert!(!claim(id, 100)?);
+        assert!(claim(id, 200)?);
+        fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_override() {
+        let mut config = WatchdogConfig::default();
+        let source: WatchdogConfigOverride =
+            toml::from_str("stall_after_minutes = 30\nrecovery = \"continue\"").unwrap();
+        apply_watchdog_overrides(&mut config, &source);
+        assert!(config.enabled);
+        assert_eq!(config.stall_after_minutes, 30);
+        assert_eq!(config.recovery, RecoveryAction::Continue);
+    }
+}
@@ -456,7 +456,8 @@ pub fn play_for_transition(old: Status, new: Status, config: &SoundConfig) {
         Status::Running => config.on_running.as_deref(),
         Status::Waiting => config.on_waiting.as_deref(),
         Status::Idle => config.on_idle.as_deref(),
-        Status::Error => config.on_error.as_deref(),
+        // A stalled agent needs attention much like a failed one
+        Status::Error | Status::Stalled => config.on_error.as_deref(),
         Status::Unknown => return,
         Status::Stopped => return,
         Status::Deleting => return,
@@ -25,6 +25,8 @@ pub const CONTAINER_TERMINAL_PREFIX: &str = "aoe_cterm_";
 pub struct PaneMetadata {
     pub pane_dead: bool,
     pub pane_current_command: Option<String>,
+    /// Unix time of the window's last output (`#{window_activity}`)
+    pub window_activity: Option<i64>,
 }
 
 static SESSION_CACHE: RwLock<SessionCache> = RwLock::new(SessionCache {
@@ -79,7 +81,7 @@ pub fn batch_pane_metadata() -> HashMap<String, PaneMetadata> {
             "list-panes",
             "-a",
             "-F",
-            "#{session_name}|#{pane_index}|#{pane_dead}|#{pane_current_command}",
+            "#{session_name}|#{pane_index}|#{pane_dead}|#{pane_current_command}|#{window_activity}",
         ])
         .output();
 
@@ -128,6 +130,7 @@ fn parse_pane_metadata(output: &str) -> HashMap<String, PaneMetadata> {
                 } else {
                     Some(parts[3].to_string())
                 },
+                window_activity: parts.get(4).and_then(|a| a.parse().ok()),
             },
         );
     }
@@ -270,6 +273,17 @@ mod tests {
         assert_eq!(meta.pane_current_command.as_deref(), Some("claude"));
     }
 
+    #[test]
+    fn test_parse_pane_metadata_window_activity() {
+        let map = parse_pane_metadata("aoe_proj_abc12345|0|0|claude|1760000000\n");
+        let meta = map.get("aoe_proj_abc12345").unwrap();
+        assert_eq!(meta.window_activity, Some(1760000000));
+        assert_eq!(meta.pane_current_command.as_deref(), Some("claude"));
+
+        let map = parse_pane_metadata("aoe_proj_abc12345|0|0|claude\n");
+        assert_eq!(map["aoe_proj_abc12345"].window_activity, None);
+    }
+
     #[test]
     fn test_parse_pane_metadata_dead_pane() {
         let output = "aoe_proj_abc12345|0|1|bash\n";
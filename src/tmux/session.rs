@@ -276,6 +276,71 @@ impl Session {
         ))
     }
 
+    /// Width and height of the session's first window pane.
+    pub fn pane_size(&self) -> Option<(u16, u16)> {
+        let target = format!("{}:^.0", self.name);
+        let output = Command::new("tmux")
+            .args([
+                "display-message",
+                "-p",
+                "-t",
+                &target,
+                "#{pane_width} #{pane_height}",
+            ])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (w, h) = stdout.trim().split_once(' ')?;
+        Some((w.parse().ok()?, h.parse().ok()?))
+    }
+
+    /// Unix time of the first window's last output.
+    pub fn window_activity(&self) -> Option<i64> {
+        let target = format!("{}:^.0", self.name);
+        let output = Command::new("tmux")
+            .args(["display-message", "-p", "-t", &target, "#{window_activity}"])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8_lossy(&output.stdout).trim().parse().ok()
+    }
+
+    /// Pipe the first window pane's output into `command` (run by tmux via
+    /// the shell). Uses `-o` so an existing pipe is left alone.
+    pub fn pipe_pane(&self, command: &str) -> Result<()> {
+        let target = format!("{}:^.0", self.name);
+        let output = Command::new("tmux")
+            .args(["pipe-pane", "-o", "-t", &target, command])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to pipe pane: {}", stderr);
+        }
+
+        Ok(())
+    }
+
+    /// Run a shell command in the background from the tmux server, so it
+    /// keeps running after the calling process exits.
+    pub fn run_shell_background(&self, command: &str) -> Result<()> {
+        let output = Command::new("tmux")
+            .args(["run-shell", "-b", "-t", &self.name, command])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("Failed to run background command: {}", stderr);
+        }
+
+        Ok(())
+    }
+
     /// Send literal text to the session's first window pane, followed by Enter.
     /// For multi-line text, newlines are sent as ESC+CR (the same sequence
     /// terminals send for Shift+Enter) so the coding agent inserts a newline
@@ -302,6 +367,21 @@ impl Session {
         Ok(())
     }
 
+    /// Send tmux key names (`Enter`, `Escape`, `Down`, `2`) to the session's
+    /// first window pane, one at a time.
+    pub fn send_key_names(&self, keys: &[String]) -> Result<()> {
+        if !self.exists() {
+            bail!("Session does not exist: {}", self.name);
+        }
+
+        let target = format!("{}:^.0", self.name);
+        for key in keys {
+            Self::tmux_send(&target, &[key])?;
+        }
+
+        Ok(())
+    }
+
     fn tmux_send(target: &str, args: &[&str]) -> Result<()> {
         let output = Command::new("tmux")
             .arg("send-keys")
@@ -845,6 +925,62 @@ mod tests {
             .output();
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_pane_size_and_pipe_pane() {
+        if !tmux_available() {
+            eprintln!("Skipping test: tmux not available");
+            return;
+        }
+
+        let session_name = format!("aoe_test_pipe_{}", std::process::id());
+        let output = Command::new("tmux")
+            .args([
+                "new-session",
+                "-d",
+                "-s",
+                &session_name,
+                "-x",
+                "100",
+                "-y",
+                "30",
+                "sh",
+            ])
+            .output()
+            .expect("tmux new-session");
+        assert!(output.status.success());
+
+        let session = Session::from_name(&session_name);
+        assert_eq!(session.pane_size(), Some((100, 30)));
+
+        let temp = tempfile::tempdir().unwrap();
+        let out = temp.path().join("pane.log");
+        session
+            .pipe_pane(&format!("cat >> '{}'", out.display()))
+            .unwrap();
+        Command::new("tmux")
+            .args([
+                "send-keys",
+                "-t",
+                &session_name,
+                "echo aoe-pipe-marker",
+                "Enter",
+            ])
+            .output()
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(500));
+
+        let _ = Command::new("tmux")
+            .args(["kill-session", "-t", &session_name])
+            .output();
+
+        let logged = std::fs::read_to_string(&out).unwrap_or_default();
+        assert!(
+            logged.contains("aoe-pipe-marker"),
+            "pipe output: {logged:?}"
+        );
+    }
+
     #[test]
     fn test_sanitize_session_name() {
         assert_eq!(sanitize_session_name("my-project"), "my-project");
@@ -187,10 +187,16 @@ impl App {
         let mut refresh_interval = tokio::time::interval(Duration::from_millis(50));
         refresh_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         let mut last_status_refresh = std::time::Instant::now();
+        // In the past so usage shows up right after launch
+        let mut last_usage_refresh = std::time::Instant::now() - USAGE_REFRESH_INTERVAL;
+        // In the past so the first sample, which only sets the CPU baseline, is taken right away
+        let mut last_metrics_refresh = std::time::Instant::now() - METRICS_REFRESH_INTERVAL;
         let mut last_disk_refresh = std::time::Instant::now();
         let mut last_spinner_redraw = std::time::Instant::now();
         const STATUS_REFRESH_INTERVAL: Duration = Duration::from_millis(500);
         const DISK_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+        const USAGE_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+        const METRICS_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
         // Fastest spinner (breathe) changes every 180ms; 120ms ensures smooth animation
         const SPINNER_REDRAW_INTERVAL: Duration = Duration::from_millis(120);
 
@@ -297,6 +303,33 @@ impl App {
                 refresh_needed = true;
             }
 
+            if let Some(session_id) = crate::desktop_notifications::take_focus_request() {
+                self.attach_session(&session_id, terminal)?;
+                refresh_needed = true;
+            }
+
+            if last_usage_refresh.elapsed() >= USAGE_REFRESH_INTERVAL {
+                self.home.request_usage_refresh();
+                last_usage_refresh = std::time::Instant::now();
+            }
+
+            if self.home.apply_usage_updates() {
+                refresh_needed = true;
+            }
+
+            if last_metrics_refresh.elapsed() >= METRICS_REFRESH_INTERVAL {
+                self.home.request_metrics_refresh();
+                last_metrics_refresh = std::time::Instant::now();
+            }
+
+            if self.home.apply_metrics_updates() {
+                refresh_needed = true;
+            }
+
+            if self.home.apply_compare_results() {
+                refresh_needed = true;
+            }
+
             if self.home.apply_deletion_results() {
                 refresh_needed = true;
             }
@@ -453,6 +486,16 @@ impl App {
                         .set_instance_status(&id, crate::session::Status::Stopped);
                     match inst_clone.stop() {
                         Ok(()) => {
+                            use crate::session::history::{self, StatusEvent, StatusSource};
+                            history::record_best_effort(
+                                &inst_clone.source_profile,
+                                &StatusEvent::new(
+                                    &id,
+                                    inst_clone.status,
+                                    crate::session::Status::Stopped,
+                                    StatusSource::User,
+                                ),
+                            );
                             crate::tmux::refresh_session_cache();
                             self.home.reload()?;
                             self.home
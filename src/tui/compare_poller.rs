@@ -0,0 +1,59 @@
+//! Background diffs for the comparison view
+//!
+//! Comparing a group runs git once per session, which can take a while for
+//! big worktrees, so it runs in its own thread. The dialog opens right away
+//! and fills in when the diffs arrive.
+
+use std::sync::mpsc;
+use std::thread;
+
+use super::dialogs::CompareColumn;
+use crate::session::{Config, Instance};
+
+pub struct ComparePoller {
+    request_tx: mpsc::Sender<(String, Vec<Instance>)>,
+    result_rx: mpsc::Receiver<(String, Vec<CompareColumn>)>,
+    _handle: thread::JoinHandle<()>,
+}
+
+impl ComparePoller {
+    pub fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<(String, Vec<Instance>)>();
+        let (result_tx, result_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            while let Ok((group, instances)) = request_rx.recv() {
+                let config = Config::load().unwrap_or_default();
+                let columns = instances
+                    .iter()
+                    .map(|inst| CompareColumn::compute(inst, &config))
+                    .collect();
+                if result_tx.send((group, columns)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            request_tx,
+            result_rx,
+            _handle: handle,
+        }
+    }
+
+    /// Request the diffs of a group's sessions (non-blocking).
+    pub fn request(&self, group: String, instances: Vec<Instance>) {
+        let _ = self.request_tx.send((group, instances));
+    }
+
+    /// Try to receive a group's diffs without blocking.
+    pub fn try_recv(&self) -> Option<(String, Vec<CompareColumn>)> {
+        self.result_rx.try_recv().ok()
+    }
+}
+
+impl Default for ComparePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -7,7 +7,7 @@ use crate::session::config::SortOrder;
 use crate::tui::styles::Theme;
 
 const DIALOG_WIDTH: u16 = 50;
-const DIALOG_HEIGHT: u16 = 39;
+const DIALOG_HEIGHT: u16 = 43;
 #[cfg(test)]
 const BORDER_HEIGHT: u16 = 2;
 #[cfg(test)]
@@ -39,6 +39,9 @@ fn shortcuts() -> Vec<(&'static str, Vec<(&'static str, &'static str)>)> {
                 ("d", "Delete session/group"),
                 ("r", "Rename session/group"),
                 ("m", "Send message to agent"),
+                ("M", "Message queue (send when idle)"),
+                ("S", "Sandbox snapshots / roll back"),
+                ("i", "Session info & status history"),
             ],
         ),
         (
@@ -47,6 +50,7 @@ fn shortcuts() -> Vec<(&'static str, Vec<(&'static str, &'static str)>)> {
                 ("t", "Toggle Agent/Terminal view"),
                 ("c", "Toggle container/host (sandbox)"),
                 ("D", "Diff view (git changes)"),
+                ("C", "Compare sessions in group"),
                 ("H/L", "Resize list panel"),
                 ("o", "Cycle sort forward"),
                 ("Ctrl+o", "Cycle sort backward"),
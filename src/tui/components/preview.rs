@@ -4,9 +4,13 @@ use ansi_to_tui::IntoText;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
+use crate::process::metrics::{format_bytes, SessionMetrics};
 use crate::session::Instance;
 use crate::tui::styles::Theme;
 
+/// Width of each sparkline in the preview's info section
+const SPARKLINE_WIDTH: usize = 20;
+
 pub struct Preview;
 
 impl Preview {
@@ -114,16 +118,19 @@ impl Preview {
         frame: &mut Frame,
         area: Rect,
         instance: &Instance,
+        metrics: Option<&SessionMetrics>,
         cached_output: &str,
         theme: &Theme,
     ) {
-        // 3 base lines (profile+tool / path / status) + optional sandbox + optional worktree block
+        // 3 base lines (profile+tool / path / status) + optional metrics + optional sandbox
+        // + optional worktree block
         let base = 3;
+        let metrics_lines = if metrics.is_some() { 1 } else { 0 };
         let sandbox_lines = if instance.is_sandboxed() { 1 } else { 0 };
         let info_height = if instance.worktree_info.is_some() {
-            base + sandbox_lines + 4 // blank + header + branch + main
+            base + metrics_lines + sandbox_lines + 4 // blank + header + branch + main
         } else {
-            base + sandbox_lines
+            base + metrics_lines + sandbox_lines
         };
 
         let chunks = Layout::default()
@@ -134,11 +141,17 @@ impl Preview {
             ])
             .split(area);
 
-        Self::render_info(frame, chunks[0], instance, theme);
+        Self::render_info(frame, chunks[0], instance, metrics, theme);
         Self::render_output_cached(frame, chunks[1], instance, cached_output, theme);
     }
 
-    fn render_info(frame: &mut Frame, area: Rect, instance: &Instance, theme: &Theme) {
+    fn render_info(
+        frame: &mut Frame,
+        area: Rect,
+        instance: &Instance,
+        metrics: Option<&SessionMetrics>,
+        theme: &Theme,
+    ) {
         let mut info_lines = Vec::new();
 
         // Profile and Tool on the same row to save vertical space
@@ -177,6 +190,7 @@ impl Preview {
                         crate::session::Status::Unknown => theme.waiting,
                         crate::session::Status::Stopped => theme.dimmed,
                         crate::session::Status::Error => theme.error,
+                        crate::session::Status::Stalled => theme.error,
                         crate::session::Status::Starting => theme.dimmed,
                         crate::session::Status::Deleting => theme.waiting,
                         crate::session::Status::Creating => theme.accent,
@@ -185,6 +199,25 @@ impl Preview {
             ]),
         ]);
 
+        if let Some(metrics) = metrics {
+            let cpu: Vec<f64> = metrics.history.iter().map(|s| s.cpu_percent).collect();
+            let rss: Vec<f64> = metrics.history.iter().map(|s| s.rss_bytes as f64).collect();
+            info_lines.push(Line::from(vec![
+                Span::styled("CPU:     ", Style::default().fg(theme.dimmed)),
+                Span::styled(sparkline(&cpu, 100.0), Style::default().fg(theme.running)),
+                Span::styled(
+                    format!(" {:.0}%", metrics.current.cpu_percent),
+                    Style::default().fg(theme.text),
+                ),
+                Span::styled("  Mem: ", Style::default().fg(theme.dimmed)),
+                Span::styled(sparkline(&rss, 0.0), Style::default().fg(theme.accent)),
+                Span::styled(
+                    format!(" {}", format_bytes(metrics.current.rss_bytes)),
+                    Style::default().fg(theme.text),
+                ),
+            ]));
+        }
+
         // Add sandbox information if present
         if let Some(sandbox) = &instance.sandbox_info {
             if sandbox.enabled {
@@ -285,6 +318,24 @@ fn parse_output_text(content: &str) -> Text<'static> {
         .unwrap_or_else(|_| Text::from(content.to_string()))
 }
 
+/// Block-character sparkline of the last `SPARKLINE_WIDTH` values, scaled
+/// to the larger of `floor` and the highest value shown
+fn sparkline(values: &[f64], floor: f64) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let shown = &values[values.len().saturating_sub(SPARKLINE_WIDTH)..];
+    let max = shown.iter().copied().fold(floor, f64::max);
+    shown
+        .iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                return BARS[0];
+            }
+            let idx = ((v / max) * (BARS.len() - 1) as f64).round() as usize;
+            BARS[idx.min(BARS.len() - 1)]
+        })
+        .collect()
+}
+
 fn shorten_path(path: &str) -> String {
     let path_buf = std::path::PathBuf::from(path);
 
@@ -314,6 +365,15 @@ fn shorten_path(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sparkline_scales_to_floor_or_max() {
+        assert_eq!(sparkline(&[0.0, 50.0, 100.0], 100.0), "▁▅█");
+        // A spike past the floor rescales the rest
+        assert_eq!(sparkline(&[100.0, 400.0], 100.0), "▃█");
+        assert_eq!(sparkline(&[0.0, 0.0], 0.0), "▁▁");
+        assert_eq!(sparkline(&[1.0; 30], 0.0).chars().count(), SPARKLINE_WIDTH);
+    }
+
     #[test]
     fn test_shorten_path_with_home() {
         if let Some(home) = dirs::home_dir() {
@@ -7,6 +7,7 @@ use std::sync::mpsc;
 use std::thread;
 
 use crate::session::builder::{self, CreatedWorktree, InstanceParams};
+use crate::session::ports;
 use crate::session::repo_config::{self, HookProgress, HooksConfig};
 use crate::session::Instance;
 use crate::tui::dialogs::NewSessionData;
@@ -114,6 +115,8 @@ impl CreationPoller {
             extra_args: data.extra_args,
             command_override: data.command_override,
             extra_repo_paths: data.extra_repo_paths,
+            prompt: data.prompt,
+            template: data.template,
         };
 
         let build_result = match builder::build_instance(params, &existing_titles, &profile) {
@@ -136,7 +139,7 @@ impl CreationPoller {
                 // Ensure the container is running so we can exec hooks inside it.
                 // Don't create the tmux session yet -- that happens at attach time
                 // where the terminal size is available.
-                if let Err(e) = instance.get_container_for_instance() {
+                if let Err(e) = instance.get_container_for_instance_streamed(progress_tx) {
                     builder::cleanup_instance(
                         &instance,
                         created_worktree.as_ref(),
@@ -160,6 +163,7 @@ impl CreationPoller {
             } else if let Err(e) = repo_config::execute_hooks_streamed(
                 &hooks.on_create,
                 std::path::Path::new(&instance.project_path),
+                &ports::env(&instance.ports),
                 progress_tx,
             ) {
                 builder::cleanup_instance(
@@ -177,7 +181,7 @@ impl CreationPoller {
             let hooks = hooks.as_ref().unwrap();
             if data.sandbox {
                 if !container_started {
-                    if let Err(e) = instance.get_container_for_instance() {
+                    if let Err(e) = instance.get_container_for_instance_streamed(progress_tx) {
                         let msg = format!("Container startup warning: {:#}", e);
                         tracing::warn!("{}", msg);
                         let _ = progress_tx.send(HookProgress::Output(msg));
@@ -201,6 +205,7 @@ impl CreationPoller {
             } else if let Err(e) = repo_config::execute_hooks_streamed(
                 &hooks.on_launch,
                 std::path::Path::new(&instance.project_path),
+                &ports::env(&instance.ports),
                 progress_tx,
             ) {
                 tracing::warn!("on_launch hook failed: {}", e);
@@ -211,7 +216,7 @@ impl CreationPoller {
             // Only ensure the container is running here if hooks didn't already
             // start it. Don't create the tmux session yet -- that happens at attach time
             // where the terminal size is available.
-            if let Err(e) = instance.get_container_for_instance() {
+            if let Err(e) = instance.get_container_for_instance_streamed(progress_tx) {
                 builder::cleanup_instance(
                     &instance,
                     created_worktree.as_ref(),
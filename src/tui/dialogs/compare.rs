@@ -0,0 +1,399 @@
+//! Comparison view - changed-file stats of a group's sessions side by side
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use super::DialogResult;
+use crate::git::diff::{compute_changed_files, get_default_branch, DiffFile};
+use crate::session::{Config, Instance};
+use crate::tui::styles::Theme;
+
+/// Width of each session's column, including the gap before it.
+const COLUMN_WIDTH: u16 = 18;
+const MIN_FILE_COLUMN: u16 = 24;
+/// Header rows above the file list: title, agent, files, lines, separator.
+const HEADER_ROWS: u16 = 5;
+
+/// One session's changes against its base branch.
+pub struct CompareColumn {
+    pub title: String,
+    pub tool: String,
+    pub files: Result<Vec<DiffFile>, String>,
+}
+
+impl CompareColumn {
+    /// Compute the changed files of a session's working directory, against
+    /// the same base branch the diff view uses.
+    pub fn compute(instance: &Instance, config: &Config) -> Self {
+        let path = Path::new(&instance.project_path);
+        let base_branch = config
+            .diff
+            .default_branch
+            .clone()
+            .or_else(|| get_default_branch(path).ok())
+            .unwrap_or_else(|| "main".to_string());
+        Self {
+            title: instance.title.clone(),
+            tool: instance.tool.clone(),
+            files: compute_changed_files(path, &base_branch).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn file(&self, path: &Path) -> Option<&DiffFile> {
+        self.files.as_ref().ok()?.iter().find(|f| f.path == path)
+    }
+}
+
+pub struct CompareDialog {
+    group: String,
+    columns: Vec<CompareColumn>,
+    /// Every path changed by at least one session, sorted.
+    paths: Vec<PathBuf>,
+    scroll: usize,
+    /// First session column shown, for groups wider than the screen.
+    column_offset: usize,
+    /// Waiting for the sessions' diffs
+    loading: bool,
+}
+
+impl CompareDialog {
+    pub fn new(group: &str, columns: Vec<CompareColumn>) -> Self {
+        let mut dialog = Self::loading(group);
+        dialog.fill(columns);
+        dialog
+    }
+
+    /// An empty dialog for the group, filled in once its diffs are computed.
+    pub fn loading(group: &str) -> Self {
+        Self {
+            group: group.to_string(),
+            columns: Vec::new(),
+            paths: Vec::new(),
+            scroll: 0,
+            column_offset: 0,
+            loading: true,
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        &self.group
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn fill(&mut self, columns: Vec<CompareColumn>) {
+        let paths: BTreeSet<PathBuf> = columns
+            .iter()
+            .filter_map(|c| c.files.as_ref().ok())
+            .flatten()
+            .map(|f| f.path.clone())
+            .collect();
+        self.columns = columns;
+        self.paths = paths.into_iter().collect();
+        self.loading = false;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> DialogResult<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('C') => {
+                DialogResult::Cancel
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                DialogResult::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.scroll + 1 < self.paths.len() {
+                    self.scroll += 1;
+                }
+                DialogResult::Continue
+            }
+            KeyCode::Left | KeyCode::Char('h') => {
+                self.column_offset = self.column_offset.saturating_sub(1);
+                DialogResult::Continue
+            }
+            KeyCode::Right | KeyCode::Char('l') => {
+                if self.column_offset + 1 < self.columns.len() {
+                    self.column_offset += 1;
+                }
+                DialogResult::Continue
+            }
+            _ => DialogResult::Continue,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let width = area.width.saturating_sub(4).max(40);
+        let height = area.height.saturating_sub(2).max(10);
+        let dialog_area = super::centered_rect(area, width, height);
+
+        frame.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .style(Style::default().bg(theme.background))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.border))
+            .title(format!(" Compare: {} ", self.group))
+            .title_style(Style::default().fg(theme.title).bold())
+            .title_bottom(
+                Line::from(vec![
+                    Span::styled(" j/k", Style::default().fg(theme.accent)),
+                    Span::styled(" scroll ", Style::default().fg(theme.dimmed)),
+                    Span::styled("h/l", Style::default().fg(theme.accent)),
+                    Span::styled(" sessions ", Style::default().fg(theme.dimmed)),
+                    Span::styled("Esc", Style::default().fg(theme.accent)),
+                    Span::styled(" close ", Style::default().fg(theme.dimmed)),
+                ])
+                .right_aligned(),
+            );
+
+        let inner = block.inner(dialog_area);
+        frame.render_widget(block, dialog_area);
+        let inner = inner.inner(Margin::new(1, 0));
+
+        let visible_columns = (inner.width.saturating_sub(MIN_FILE_COLUMN) / COLUMN_WIDTH).max(1);
+        let columns: Vec<&CompareColumn> = self
+            .columns
+            .iter()
+            .skip(self.column_offset)
+            .take(visible_columns as usize)
+            .collect();
+        let file_width = inner
+            .width
+            .saturating_sub(COLUMN_WIDTH * columns.len() as u16) as usize;
+
+        let dimmed = Style::default().fg(theme.dimmed);
+        let text = Style::default().fg(theme.text);
+
+        let mut lines = vec![
+            header_line(
+                "Session",
+                file_width,
+                &columns,
+                |c| Span::styled(cell(&c.title), Style::default().fg(theme.title).bold()),
+                dimmed,
+            ),
+            header_line(
+                "Agent",
+                file_width,
+                &columns,
+                |c| Span::styled(cell(&c.tool), dimmed),
+                dimmed,
+            ),
+            header_line(
+                "Files changed",
+                file_width,
+                &columns,
+                |c| match &c.files {
+                    Ok(files) => Span::styled(cell(&files.len().to_string()), text),
+                    Err(_) => Span::styled(cell("error"), Style::default().fg(theme.error)),
+                },
+                dimmed,
+            ),
+            header_line(
+                "Lines",
+                file_width,
+                &columns,
+                |c| match &c.files {
+                    Ok(files) => {
+                        let additions: usize = files.iter().map(|f| f.additions).sum();
+                        let deletions: usize = files.iter().map(|f| f.deletions).sum();
+                        stat_span(additions, deletions, theme)
+                    }
+                    Err(_) => Span::raw(cell("")),
+                },
+                dimmed,
+            ),
+            Line::from(Span::styled(
+                "─".repeat(inner.width as usize),
+                Style::default().fg(theme.border),
+            )),
+        ];
+
+        if self.loading {
+            lines.push(Line::from(Span::styled("Computing changes…", dimmed)));
+        } else if self.paths.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No changes in any session",
+                dimmed,
+            )));
+        }
+
+        let rows = inner.height.saturating_sub(HEADER_ROWS) as usize;
+        for path in self.paths.iter().skip(self.scroll).take(rows) {
+            let mut spans = vec![Span::styled(fit_path(path, file_width), text)];
+            for column in &columns {
+                spans.push(match column.file(path) {
+                    Some(file) => file_span(file, theme),
+                    None => Span::styled(cell("·"), dimmed),
+                });
+            }
+            lines.push(Line::from(spans));
+        }
+
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+fn header_line<'a>(
+    label: &str,
+    file_width: usize,
+    columns: &[&'a CompareColumn],
+    value: impl Fn(&'a CompareColumn) -> Span<'a>,
+    label_style: Style,
+) -> Line<'a> {
+    let mut spans = vec![Span::styled(pad(label, file_width), label_style)];
+    spans.extend(columns.iter().map(|c| value(c)));
+    Line::from(spans)
+}
+
+fn file_span(file: &DiffFile, theme: &Theme) -> Span<'static> {
+    let stat = format!(
+        "{} +{} -{}",
+        file.status.indicator(),
+        file.additions,
+        file.deletions
+    );
+    Span::styled(cell(&stat), Style::default().fg(theme.diff_modified))
+}
+
+fn stat_span(additions: usize, deletions: usize, theme: &Theme) -> Span<'static> {
+    let color = if additions >= deletions {
+        theme.diff_add
+    } else {
+        theme.diff_delete
+    };
+    Span::styled(
+        cell(&format!("+{} -{}", additions, deletions)),
+        Style::default().fg(color),
+    )
+}
+
+/// A session column's content, truncated and padded to the column width.
+fn cell(s: &str) -> String {
+    format!("  {}", pad(s, COLUMN_WIDTH as usize - 2))
+}
+
+fn pad(s: &str, width: usize) -> String {
+    let truncated = crate::cli::truncate(s, width);
+    format!("{:<width$}", truncated, width = width)
+}
+
+/// Fit a path into `width` columns, keeping the end (the file name).
+fn fit_path(path: &Path, width: usize) -> String {
+    let s = path.to_string_lossy();
+    let len = s.chars().count();
+    if len <= width {
+        return pad(&s, width);
+    }
+    let tail: String = s.chars().skip(len + 1 - width).collect();
+    format!("…{}", tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::diff::FileStatus;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn diff_file(path: &str) -> DiffFile {
+        DiffFile {
+            path: PathBuf::from(path),
+            old_path: None,
+            status: FileStatus::Modified,
+            additions: 3,
+            deletions: 1,
+        }
+    }
+
+    fn column(title: &str, paths: &[&str]) -> CompareColumn {
+        CompareColumn {
+            title: title.to_string(),
+            tool: "claude".to_string(),
+            files: Ok(paths.iter().map(|p| diff_file(p)).collect()),
+        }
+    }
+
+    #[test]
+    fn test_paths_are_union_of_all_sessions() {
+        let dialog = CompareDialog::new(
+            "fanout/task",
+            vec![
+                column("a", &["src/lib.rs", "README.md"]),
+                column("b", &["src/lib.rs", "src/main.rs"]),
+                CompareColumn {
+                    title: "c".to_string(),
+                    tool: "codex".to_string(),
+                    files: Err("not a git repository".to_string()),
+                },
+            ],
+        );
+        assert_eq!(
+            dialog.paths,
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("src/lib.rs"),
+                PathBuf::from("src/main.rs"),
+            ]
+        );
+        assert!(dialog.columns[1].file(Path::new("README.md")).is_none());
+        assert!(dialog.columns[1].file(Path::new("src/main.rs")).is_some());
+    }
+
+    #[test]
+    fn test_loading_dialog_fills_in() {
+        let mut dialog = CompareDialog::loading("g");
+        assert!(dialog.is_loading());
+        assert!(dialog.paths.is_empty());
+        dialog.fill(vec![column("a", &["x"]), column("b", &["y"])]);
+        assert!(!dialog.is_loading());
+        assert_eq!(dialog.paths.len(), 2);
+    }
+
+    #[test]
+    fn test_scroll_and_column_offset_are_clamped() {
+        let mut dialog =
+            CompareDialog::new("g", vec![column("a", &["x", "y"]), column("b", &["z"])]);
+        for _ in 0..5 {
+            dialog.handle_key(key(KeyCode::Char('j')));
+            dialog.handle_key(key(KeyCode::Char('l')));
+        }
+        assert_eq!(dialog.scroll, 2);
+        assert_eq!(dialog.column_offset, 1);
+
+        for _ in 0..5 {
+            dialog.handle_key(key(KeyCode::Char('k')));
+            dialog.handle_key(key(KeyCode::Char('h')));
+        }
+        assert_eq!(dialog.scroll, 0);
+        assert_eq!(dialog.column_offset, 0);
+    }
+
+    #[test]
+    fn test_close_keys() {
+        for code in [KeyCode::Esc, KeyCode::Enter, KeyCode::Char('C')] {
+            let mut dialog = CompareDialog::new("g", vec![column("a", &[])]);
+            assert!(matches!(dialog.handle_key(key(code)), DialogResult::Cancel));
+        }
+    }
+
+    #[test]
+    fn test_fit_path_keeps_file_name() {
+        assert_eq!(fit_path(Path::new("a/b.rs"), 8), "a/b.rs  ");
+        assert_eq!(
+            fit_path(Path::new("src/deeply/nested/file.rs"), 10),
+            "…d/file.rs"
+        );
+    }
+}
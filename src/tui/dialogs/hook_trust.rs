@@ -20,7 +20,7 @@ pub struct HookTrustDialog {
 pub enum HookTrustAction {
     /// User trusts the hooks; proceed with execution.
     Trust {
-        hooks: HooksConfig,
+        hooks: Box<HooksConfig>,
         hooks_hash: String,
         project_path: String,
     },
@@ -46,7 +46,7 @@ impl HookTrustDialog {
             KeyCode::Enter => {
                 if self.selected {
                     DialogResult::Submit(HookTrustAction::Trust {
-                        hooks: self.hooks.clone(),
+                        hooks: Box::new(self.hooks.clone()),
                         hooks_hash: self.hooks_hash.clone(),
                         project_path: self.project_path.clone(),
                     })
@@ -56,7 +56,7 @@ impl HookTrustDialog {
             }
             KeyCode::Char('y') | KeyCode::Char('Y') => {
                 DialogResult::Submit(HookTrustAction::Trust {
-                    hooks: self.hooks.clone(),
+                    hooks: Box::new(self.hooks.clone()),
                     hooks_hash: self.hooks_hash.clone(),
                     project_path: self.project_path.clone(),
                 })
@@ -91,38 +91,25 @@ impl HookTrustDialog {
     fn build_hook_lines(&self) -> Vec<Line<'_>> {
         let mut lines = Vec::new();
 
-        if !self.hooks.on_create.is_empty() {
-            lines.push(Line::from(Span::styled(
-                "on_create:",
-                Style::default().bold(),
-            )));
-            for cmd in &self.hooks.on_create {
-                lines.push(Line::from(format!("  {}", cmd)));
+        for (name, cmds) in self.hooks.named_commands() {
+            if !lines.is_empty() {
+                lines.push(Line::from(""));
             }
-            lines.push(Line::from(""));
-        }
-
-        if !self.hooks.on_launch.is_empty() {
             lines.push(Line::from(Span::styled(
-                "on_launch:",
+                format!("{}:", name),
                 Style::default().bold(),
             )));
-            for cmd in &self.hooks.on_launch {
+            for cmd in cmds {
                 lines.push(Line::from(format!("  {}", cmd)));
             }
-            if !self.hooks.on_destroy.is_empty() {
-                lines.push(Line::from(""));
-            }
         }
 
-        if !self.hooks.on_destroy.is_empty() {
+        if self.hooks.status_hooks_in_container == Some(false) && self.hooks.has_status_hooks() {
+            lines.push(Line::from(""));
             lines.push(Line::from(Span::styled(
-                "on_destroy:",
+                "Status hooks run on the host, even for sandboxed sessions.",
                 Style::default().bold(),
             )));
-            for cmd in &self.hooks.on_destroy {
-                lines.push(Line::from(format!("  {}", cmd)));
-            }
         }
 
         lines
@@ -303,4 +290,25 @@ mod tests {
         let lines = dialog.build_hook_lines();
         assert!(lines.is_empty());
     }
+
+    #[test]
+    fn test_status_hooks_listed() {
+        let dialog = HookTrustDialog::new(
+            HooksConfig {
+                on_idle: vec!["cargo test".to_string()],
+                status_hooks_in_container: Some(false),
+                ..Default::default()
+            },
+            "hash".to_string(),
+            "/some/path".to_string(),
+        );
+        let text: Vec<String> = dialog
+            .build_hook_lines()
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        assert_eq!(text[0], "on_idle:");
+        assert_eq!(text[1], "  cargo test");
+        assert!(text.last().unwrap().contains("on the host"));
+    }
 }
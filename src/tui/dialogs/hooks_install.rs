@@ -104,6 +104,7 @@ impl HooksInstallDialog {
             "Each hook runs:",
             Style::default().bold(),
         )));
+        lines.push(Line::from("  cat > /tmp/aoe-hooks/$ID/{event}.json"));
         lines.push(Line::from("  printf {status} > /tmp/aoe-hooks/$ID/status"));
 
         lines.push(Line::from(""));
@@ -0,0 +1,392 @@
+//! Message queue dialog: list, add, edit and remove a session's queued
+//! messages
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+use ratatui_textarea::TextArea;
+
+use super::DialogResult;
+use crate::session::message_queue::QueuedMessage;
+use crate::tui::styles::Theme;
+
+const DIALOG_WIDTH: u16 = 72;
+const MAX_VISIBLE: usize = 10;
+
+pub enum MessageQueueAction {
+    Add(String),
+    Edit { id: String, text: String },
+    Remove(String),
+}
+
+enum Mode {
+    List,
+    /// Writing a new message (`None`) or editing the one with this id
+    Compose(Option<String>),
+}
+
+pub struct MessageQueueDialog {
+    session_id: String,
+    session_title: String,
+    messages: Vec<QueuedMessage>,
+    selected: usize,
+    mode: Mode,
+    text_area: TextArea<'static>,
+}
+
+impl MessageQueueDialog {
+    pub fn new(session_id: &str, session_title: &str, messages: Vec<QueuedMessage>) -> Self {
+        let mut dialog = Self {
+            session_id: session_id.to_string(),
+            session_title: session_title.to_string(),
+            messages: Vec::new(),
+            selected: 0,
+            mode: Mode::List,
+            text_area: TextArea::default(),
+        };
+        dialog.set_messages(messages);
+        // An empty queue has nothing to browse; start writing right away
+        if dialog.messages.is_empty() {
+            dialog.compose(None);
+        }
+        dialog
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Show the queue as it is after an action, back in the list.
+    pub fn set_messages(&mut self, messages: Vec<QueuedMessage>) {
+        self.messages = messages;
+        self.selected = self.selected.min(self.messages.len().saturating_sub(1));
+        self.mode = Mode::List;
+    }
+
+    fn compose(&mut self, editing: Option<&QueuedMessage>) {
+        let lines = match editing {
+            Some(message) => message.text.lines().map(String::from).collect(),
+            None => vec![String::new()],
+        };
+        self.text_area = TextArea::new(lines);
+        self.text_area.set_cursor_line_style(Style::default());
+        self.text_area
+            .move_cursor(ratatui_textarea::CursorMove::Bottom);
+        self.text_area
+            .move_cursor(ratatui_textarea::CursorMove::End);
+        self.mode = Mode::Compose(editing.map(|m| m.id.clone()));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> DialogResult<MessageQueueAction> {
+        match self.mode {
+            Mode::List => self.handle_list_key(key),
+            Mode::Compose(_) => self.handle_compose_key(key),
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) -> DialogResult<MessageQueueAction> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => DialogResult::Cancel,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                DialogResult::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.messages.len() {
+                    self.selected += 1;
+                }
+                DialogResult::Continue
+            }
+            KeyCode::Char('a') | KeyCode::Char('n') => {
+                self.compose(None);
+                DialogResult::Continue
+            }
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(message) = self.messages.get(self.selected).cloned() {
+                    self.compose(Some(&message));
+                }
+                DialogResult::Continue
+            }
+            KeyCode::Char('d') | KeyCode::Delete => match self.messages.get(self.selected) {
+                Some(message) => {
+                    DialogResult::Submit(MessageQueueAction::Remove(message.id.clone()))
+                }
+                None => DialogResult::Continue,
+            },
+            _ => DialogResult::Continue,
+        }
+    }
+
+    fn handle_compose_key(&mut self, key: KeyEvent) -> DialogResult<MessageQueueAction> {
+        match key.code {
+            KeyCode::Esc => {
+                if self.messages.is_empty() {
+                    return DialogResult::Cancel;
+                }
+                self.mode = Mode::List;
+                DialogResult::Continue
+            }
+            // Shift+Enter (or Alt+Enter, see SendMessageDialog) inserts a newline
+            KeyCode::Enter
+                if key.modifiers.contains(KeyModifiers::SHIFT)
+                    || key.modifiers.contains(KeyModifiers::ALT) =>
+            {
+                self.text_area.insert_newline();
+                DialogResult::Continue
+            }
+            KeyCode::Enter => {
+                let text = self.text_area.lines().join("\n").trim().to_string();
+                let Mode::Compose(editing) = &self.mode else {
+                    return DialogResult::Continue;
+                };
+                if text.is_empty() {
+                    if self.messages.is_empty() {
+                        return DialogResult::Cancel;
+                    }
+                    self.mode = Mode::List;
+                    return DialogResult::Continue;
+                }
+                DialogResult::Submit(match editing {
+                    Some(id) => MessageQueueAction::Edit {
+                        id: id.clone(),
+                        text,
+                    },
+                    None => MessageQueueAction::Add(text),
+                })
+            }
+            _ => {
+                self.text_area.input(key);
+                DialogResult::Continue
+            }
+        }
+    }
+
+    pub fn handle_paste(&mut self, text: &str) {
+        if let Mode::Compose(_) = self.mode {
+            self.text_area.insert_str(text);
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        match self.mode {
+            Mode::List => self.render_list(frame, area, theme),
+            Mode::Compose(_) => self.render_compose(frame, area, theme),
+        }
+    }
+
+    fn render_list(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let list_height = self.messages.len().clamp(1, MAX_VISIBLE) as u16;
+        // list + hint (1) + borders (2) + margin (2)
+        let dialog_height = (list_height + 5).min(area.height);
+        let dialog_area = super::centered_rect(area, DIALOG_WIDTH, dialog_height);
+        frame.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.accent))
+            .title(format!(" Queue: {} ", self.session_title))
+            .title_style(Style::default().fg(theme.title).bold());
+
+        let inner = block.inner(dialog_area);
+        frame.render_widget(block, dialog_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let visible_height = chunks[0].height as usize;
+        let scroll_offset = (self.selected + 1).saturating_sub(visible_height);
+        let text_width = (chunks[0].width as usize).saturating_sub(6);
+
+        let lines: Vec<Line> = if self.messages.is_empty() {
+            vec![Line::from(Span::styled(
+                "  Nothing queued",
+                Style::default().fg(theme.dimmed),
+            ))]
+        } else {
+            self.messages
+                .iter()
+                .enumerate()
+                .skip(scroll_offset)
+                .take(visible_height)
+                .map(|(i, message)| {
+                    let is_selected = i == self.selected;
+                    let style = if is_selected {
+                        Style::default().fg(theme.accent).bold()
+                    } else {
+                        Style::default().fg(theme.text)
+                    };
+                    // Only the first line; longer messages get an ellipsis
+                    let first_line = message.text.lines().next().unwrap_or_default();
+                    let mut text: String = first_line.chars().take(text_width).collect();
+                    if text.len() < message.text.len() {
+                        if text.chars().count() >= text_width {
+                            text.pop();
+                        }
+                        text.push('…');
+                    }
+                    Line::from(vec![
+                        Span::styled(if is_selected { "> " } else { "  " }, style),
+                        Span::styled(format!("{:>2}. ", i + 1), Style::default().fg(theme.dimmed)),
+                        Span::styled(text, style),
+                    ])
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+        let mut hint_spans = vec![
+            Span::styled("a", Style::default().fg(theme.hint)),
+            Span::raw(" add  "),
+        ];
+        if !self.messages.is_empty() {
+            hint_spans.extend([
+                Span::styled("e", Style::default().fg(theme.hint)),
+                Span::raw(" edit  "),
+                Span::styled("d", Style::default().fg(theme.hint)),
+                Span::raw(" remove  "),
+            ]);
+        }
+        hint_spans.extend([
+            Span::styled("Esc", Style::default().fg(theme.hint)),
+            Span::raw(" close"),
+        ]);
+        frame.render_widget(Paragraph::new(Line::from(hint_spans)), chunks[1]);
+    }
+
+    fn render_compose(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        // 2 for borders + 1 per content line, min 3 (single line), max 12
+        let content_lines = self.text_area.lines().len() as u16;
+        let height = (content_lines + 2).clamp(3, 12);
+        let dialog_width = (area.width * 80 / 100).max(60).min(area.width);
+        let dialog_area = super::centered_rect(area, dialog_width, height);
+
+        frame.render_widget(Clear, dialog_area);
+
+        let verb = match self.mode {
+            Mode::Compose(Some(_)) => "save",
+            _ => "queue",
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.accent))
+            .title(format!(" Queue > {} ", self.session_title))
+            .title_style(Style::default().fg(theme.accent).bold())
+            .title_bottom(
+                Line::from(vec![
+                    Span::styled(" Enter", Style::default().fg(theme.accent)),
+                    Span::styled(format!(" {} ", verb), Style::default().fg(theme.dimmed)),
+                    Span::styled("Esc", Style::default().fg(theme.accent)),
+                    Span::styled(" cancel ", Style::default().fg(theme.dimmed)),
+                ])
+                .right_aligned(),
+            );
+
+        let inner = block.inner(dialog_area);
+        frame.render_widget(block, dialog_area);
+
+        let mut text_area = self.text_area.clone();
+        text_area.set_style(Style::default().fg(theme.text));
+        text_area.set_cursor_style(Style::default().fg(theme.background).bg(theme.accent));
+
+        frame.render_widget(&text_area, inner);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn message(id: &str, text: &str) -> QueuedMessage {
+        QueuedMessage {
+            id: id.to_string(),
+            text: text.to_string(),
+            queued_at: chrono::Utc::now(),
+        }
+    }
+
+    fn type_text(dialog: &mut MessageQueueDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_empty_queue_starts_composing() {
+        let mut dialog = MessageQueueDialog::new("s1", "Session", Vec::new());
+        type_text(&mut dialog, "run the tests");
+        let result = dialog.handle_key(key(KeyCode::Enter));
+        assert!(
+            matches!(result, DialogResult::Submit(MessageQueueAction::Add(ref t)) if t == "run the tests")
+        );
+    }
+
+    #[test]
+    fn test_esc_in_empty_compose_closes() {
+        let mut dialog = MessageQueueDialog::new("s1", "Session", Vec::new());
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Esc)),
+            DialogResult::Cancel
+        ));
+    }
+
+    #[test]
+    fn test_edit_prefills_selected_message() {
+        let mut dialog = MessageQueueDialog::new(
+            "s1",
+            "Session",
+            vec![message("m1", "first"), message("m2", "second")],
+        );
+        dialog.handle_key(key(KeyCode::Char('j')));
+        dialog.handle_key(key(KeyCode::Char('e')));
+        type_text(&mut dialog, "!");
+        let result = dialog.handle_key(key(KeyCode::Enter));
+        assert!(matches!(
+            result,
+            DialogResult::Submit(MessageQueueAction::Edit { ref id, ref text })
+                if id == "m2" && text == "second!"
+        ));
+    }
+
+    #[test]
+    fn test_remove_selected_and_keep_selection_in_range() {
+        let mut dialog = MessageQueueDialog::new(
+            "s1",
+            "Session",
+            vec![message("m1", "first"), message("m2", "second")],
+        );
+        dialog.handle_key(key(KeyCode::Down));
+        let result = dialog.handle_key(key(KeyCode::Char('d')));
+        assert!(
+            matches!(result, DialogResult::Submit(MessageQueueAction::Remove(ref id)) if id == "m2")
+        );
+
+        dialog.set_messages(vec![message("m1", "first")]);
+        let result = dialog.handle_key(key(KeyCode::Char('d')));
+        assert!(
+            matches!(result, DialogResult::Submit(MessageQueueAction::Remove(ref id)) if id == "m1")
+        );
+    }
+
+    #[test]
+    fn test_esc_in_compose_returns_to_list() {
+        let mut dialog = MessageQueueDialog::new("s1", "Session", vec![message("m1", "first")]);
+        dialog.handle_key(key(KeyCode::Char('a')));
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Esc)),
+            DialogResult::Continue
+        ));
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Esc)),
+            DialogResult::Cancel
+        ));
+    }
+}
@@ -1,6 +1,7 @@
 //! TUI dialog components
 
 mod changelog;
+mod compare;
 mod confirm;
 mod custom_instruction;
 mod delete_options;
@@ -8,15 +9,19 @@ mod group_delete_options;
 mod hook_trust;
 mod hooks_install;
 mod info;
+mod message_queue;
 mod new_session;
 mod profile_picker;
 mod rename;
 mod send_message;
 #[cfg(feature = "serve")]
 mod serve;
+mod session_info;
+mod snapshot;
 mod welcome;
 
 pub use changelog::ChangelogDialog;
+pub use compare::{CompareColumn, CompareDialog};
 pub use confirm::ConfirmDialog;
 pub use custom_instruction::CustomInstructionDialog;
 pub use delete_options::{DeleteDialogConfig, DeleteOptions, UnifiedDeleteDialog};
@@ -24,12 +29,15 @@ pub use group_delete_options::{GroupDeleteOptions, GroupDeleteOptionsDialog};
 pub use hook_trust::{HookTrustAction, HookTrustDialog};
 pub use hooks_install::HooksInstallDialog;
 pub use info::InfoDialog;
+pub use message_queue::{MessageQueueAction, MessageQueueDialog};
 pub use new_session::{NewSessionData, NewSessionDialog};
 pub use profile_picker::{ProfileEntry, ProfilePickerAction, ProfilePickerDialog};
 pub use rename::{RenameData, RenameDialog, RenameMode};
 pub use send_message::SendMessageDialog;
 #[cfg(feature = "serve")]
 pub use serve::ServeDialog;
+pub use session_info::SessionInfoDialog;
+pub use snapshot::{SnapshotAction, SnapshotDialog};
 pub use welcome::WelcomeDialog;
 
 pub enum DialogResult<T> {
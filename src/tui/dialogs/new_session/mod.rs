@@ -8,6 +8,9 @@ mod render;
 mod tests;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::style::Style;
+use ratatui_textarea::TextArea;
+use std::collections::BTreeMap;
 use std::time::Instant;
 use tui_input::backend::crossterm::EventHandler;
 use tui_input::Input;
@@ -16,9 +19,9 @@ use super::DialogResult;
 use crate::containers::{self, ContainerRuntimeInterface};
 use crate::session::config::{DefaultTerminalMode, SandboxConfig};
 use crate::session::repo_config::HookProgress;
-use crate::session::resolve_config;
 #[cfg(test)]
 use crate::session::Config;
+use crate::session::{resolve_config, SessionTemplate};
 use crate::tmux::AvailableTools;
 use crate::tui::components::{
     DirPicker, DirPickerResult, GroupGhostCompletion, ListPicker, ListPickerResult,
@@ -75,6 +78,10 @@ pub(super) const FIELD_HELP: &[FieldHelp] = &[
         name: "Group",
         description: "Optional grouping for organization (Ctrl+P to browse existing groups)",
     },
+    FieldHelp {
+        name: "Prompt",
+        description: "Optional first task, sent once the agent is ready (Shift+Enter for newline)",
+    },
 ];
 
 #[derive(Clone)]
@@ -98,6 +105,10 @@ pub struct NewSessionData {
     pub extra_args: String,
     /// Command override for the agent binary (replaces the default binary)
     pub command_override: String,
+    /// Initial prompt to send once the agent is first ready
+    pub prompt: Option<String>,
+    /// Session template the fields were filled from
+    pub template: Option<String>,
 }
 
 pub struct NewSessionDialog {
@@ -157,7 +168,14 @@ pub struct NewSessionDialog {
     pub(super) extra_args: Input,
     /// Command override for the selected tool (loaded from config)
     pub(super) command_override: Input,
+    /// Initial prompt (multi-line), sent once the agent is first ready
+    pub(super) prompt: TextArea<'static>,
     pub(super) existing_groups: Vec<String>,
+    /// Session templates from the resolved config (Ctrl+T to pick one)
+    pub(super) templates: BTreeMap<String, SessionTemplate>,
+    /// Template the fields were last filled from
+    pub(super) template: Option<String>,
+    pub(super) template_picker: ListPicker,
     pub(super) group_picker: ListPicker,
     pub(super) branch_picker: ListPicker,
     pub(super) dir_picker: DirPicker,
@@ -295,6 +313,12 @@ fn build_inherited_settings(sandbox: &SandboxConfig) -> Vec<(String, String)> {
     settings
 }
 
+fn new_prompt_area() -> TextArea<'static> {
+    let mut text_area = TextArea::new(vec![String::new()]);
+    text_area.set_cursor_line_style(Style::default());
+    text_area
+}
+
 impl NewSessionDialog {
     pub fn new(
         tools: AvailableTools,
@@ -373,6 +397,9 @@ impl NewSessionDialog {
             focused_field: 0,
             available_tools,
             existing_groups,
+            templates: config.templates.clone(),
+            template: None,
+            template_picker: ListPicker::new("Select Template"),
             group_picker: ListPicker::new("Select Group"),
             branch_picker: ListPicker::new("Select Branch"),
             dir_picker: DirPicker::new(),
@@ -406,6 +433,7 @@ impl NewSessionDialog {
             tool_config_focused_field: 0,
             extra_args: Input::new(extra_args_value),
             command_override: Input::new(command_override_value),
+            prompt: new_prompt_area(),
             error_message: None,
             show_help: false,
             loading: false,
@@ -526,6 +554,29 @@ impl NewSessionDialog {
         }
     }
 
+    /// The field index of the prompt field (always the last field, after group)
+    pub(super) fn prompt_field(&self) -> usize {
+        let is_host_only = self.selected_tool_host_only();
+        let mut fi = if self.has_profile_selection() { 1 } else { 0 } + 2; // title + path
+        if self.available_tools.len() > 1 {
+            fi += 1;
+        }
+        if !self.selected_tool_always_yolo() {
+            fi += 1;
+        }
+        if !is_host_only {
+            fi += 1;
+        }
+        if self.docker_available && !is_host_only {
+            fi += 1;
+        }
+        fi + 1 // group
+    }
+
+    pub(super) fn prompt_text(&self) -> String {
+        self.prompt.lines().join("\n")
+    }
+
     /// Re-resolve config defaults when the profile changes.
     /// Resets tool, yolo, sandbox, and env settings but preserves user inputs
     /// (title, path, group, worktree).
@@ -582,6 +633,10 @@ impl NewSessionDialog {
         self.tool_config_mode = false;
         self.tool_config_focused_field = 0;
 
+        // Templates can differ per profile
+        self.templates = config.templates.clone();
+        self.template = None;
+
         // Reset expanded states
         self.env_list_expanded = false;
         self.env_editing_input = None;
@@ -609,6 +664,9 @@ impl NewSessionDialog {
             focused_field: 0,
             available_tools: tools,
             existing_groups: Vec::new(),
+            templates: config.templates.clone(),
+            template: None,
+            template_picker: ListPicker::new("Select Template"),
             group_picker: ListPicker::new("Select Group"),
             branch_picker: ListPicker::new("Select Branch"),
             dir_picker: DirPicker::new(),
@@ -642,6 +700,7 @@ impl NewSessionDialog {
             tool_config_focused_field: 0,
             extra_args: Input::default(),
             command_override: Input::default(),
+            prompt: new_prompt_area(),
             error_message: None,
             show_help: false,
             loading: false,
@@ -668,6 +727,9 @@ impl NewSessionDialog {
             focused_field: 0,
             available_tools: tools.iter().map(|s| s.to_string()).collect(),
             existing_groups: Vec::new(),
+            templates: BTreeMap::new(),
+            template: None,
+            template_picker: ListPicker::new("Select Template"),
             group_picker: ListPicker::new("Select Group"),
             branch_picker: ListPicker::new("Select Branch"),
             dir_picker: DirPicker::new(),
@@ -701,6 +763,7 @@ impl NewSessionDialog {
             tool_config_focused_field: 0,
             extra_args: Input::default(),
             command_override: Input::default(),
+            prompt: new_prompt_area(),
             error_message: None,
             show_help: false,
             loading: false,
@@ -769,6 +832,13 @@ impl NewSessionDialog {
             return DialogResult::Continue;
         }
 
+        if self.template_picker.is_active() {
+            if let ListPickerResult::Selected(name) = self.template_picker.handle_key(key) {
+                self.apply_template(&name);
+            }
+            return DialogResult::Continue;
+        }
+
         if self.dir_picker.is_active() {
             match self.dir_picker.handle_key(key) {
                 DirPickerResult::Selected(path) => {
@@ -797,7 +867,7 @@ impl NewSessionDialog {
         let is_host_only = self.selected_tool_host_only();
         let has_sandbox = self.docker_available && !is_host_only;
         let has_yolo = !self.selected_tool_always_yolo();
-        // Field order: [profile], title, path, [tool], [yolo], worktree, [sandbox], group
+        // Field order: [profile], title, path, [tool], [yolo], worktree, [sandbox], group, prompt
         // Worktree sub-options (new_branch, extra_repos) are in a Ctrl+P overlay.
         // Tool config (extra_args, command_override) is in a Ctrl+P overlay on tool field.
         // Sandbox sub-options are in a separate sandbox_config_mode overlay.
@@ -834,8 +904,24 @@ impl NewSessionDialog {
         };
         let group_field = fi;
         fi += 1;
+        let prompt_field = fi;
+        fi += 1;
         let max_field = fi;
 
+        // Ctrl+T fills the fields from a session template, from any field
+        if key.code == KeyCode::Char('t')
+            && key.modifiers.contains(KeyModifiers::CONTROL)
+            && !self.templates.is_empty()
+        {
+            self.template_picker
+                .activate(self.templates.keys().cloned().collect());
+            return DialogResult::Continue;
+        }
+
+        if self.focused_field == prompt_field && self.handle_prompt_key(key) {
+            return DialogResult::Continue;
+        }
+
         // Ctrl+P opens a context-sensitive picker/config overlay
         if key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL) {
             if self.focused_field == self.path_field() {
@@ -946,48 +1032,17 @@ impl NewSessionDialog {
                 DialogResult::Continue
             }
             KeyCode::Left | KeyCode::Right if self.focused_field == tool_field => {
-                self.tool_index = (self.tool_index + 1) % self.available_tools.len();
-                if self.selected_tool_always_yolo() {
-                    self.yolo_mode = true;
-                } else {
-                    self.yolo_mode = self.yolo_mode_default;
-                }
-                if self.selected_tool_host_only() {
-                    self.sandbox_enabled = false;
-                    self.worktree_branch.reset();
-                }
-                self.reload_tool_config();
+                self.select_tool((self.tool_index + 1) % self.available_tools.len());
                 DialogResult::Continue
             }
             KeyCode::Char(' ') if self.focused_field == tool_field => {
-                self.tool_index = (self.tool_index + 1) % self.available_tools.len();
-                if self.selected_tool_always_yolo() {
-                    self.yolo_mode = true;
-                } else {
-                    self.yolo_mode = self.yolo_mode_default;
-                }
-                if self.selected_tool_host_only() {
-                    self.sandbox_enabled = false;
-                    self.worktree_branch.reset();
-                }
-                self.reload_tool_config();
+                self.select_tool((self.tool_index + 1) % self.available_tools.len());
                 DialogResult::Continue
             }
             KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
                 if self.focused_field == sandbox_field =>
             {
-                self.sandbox_enabled = !self.sandbox_enabled;
-                if self.sandbox_enabled {
-                    let config = resolve_config(&self.profile).unwrap_or_default();
-                    self.extra_env = config.sandbox.environment.clone();
-                    self.inherited_settings = build_inherited_settings(&config.sandbox);
-                } else {
-                    self.extra_env.clear();
-                    self.env_list_expanded = false;
-                    self.env_editing_input = None;
-                    self.inherited_settings.clear();
-                    self.sandbox_config_mode = false;
-                }
+                self.set_sandbox_enabled(!self.sandbox_enabled);
                 DialogResult::Continue
             }
             KeyCode::Left | KeyCode::Right | KeyCode::Char(' ')
@@ -1018,6 +1073,110 @@ impl NewSessionDialog {
         }
     }
 
+    /// Switch to the tool at `index`, resetting the options that depend on it.
+    fn select_tool(&mut self, index: usize) {
+        self.tool_index = index;
+        if self.selected_tool_always_yolo() {
+            self.yolo_mode = true;
+        } else {
+            self.yolo_mode = self.yolo_mode_default;
+        }
+        if self.selected_tool_host_only() {
+            self.sandbox_enabled = false;
+            self.worktree_branch.reset();
+        }
+        self.reload_tool_config();
+    }
+
+    /// Turn the sandbox on (seeding env entries from config) or off.
+    fn set_sandbox_enabled(&mut self, enabled: bool) {
+        self.sandbox_enabled = enabled;
+        if enabled {
+            let config = resolve_config(&self.profile).unwrap_or_default();
+            self.extra_env = config.sandbox.environment.clone();
+            self.inherited_settings = build_inherited_settings(&config.sandbox);
+        } else {
+            self.extra_env.clear();
+            self.env_list_expanded = false;
+            self.env_editing_input = None;
+            self.inherited_settings.clear();
+            self.sandbox_config_mode = false;
+        }
+    }
+
+    /// Fill the fields from a session template. Fields the template leaves
+    /// unset keep their current values; the worktree path template and custom
+    /// instruction are applied when the session is built.
+    pub(super) fn apply_template(&mut self, name: &str) {
+        let Some(template) = self.templates.get(name).cloned() else {
+            return;
+        };
+        self.template = Some(name.to_string());
+        self.error_message = None;
+
+        if let Some(ref agent) = template.agent {
+            match self.available_tools.iter().position(|t| t == agent) {
+                Some(index) => self.select_tool(index),
+                None => {
+                    self.error_message =
+                        Some(format!("Template agent '{}' is not available", agent));
+                }
+            }
+        }
+        if let Some(yolo) = template.yolo {
+            if !self.selected_tool_always_yolo() {
+                self.yolo_mode = yolo;
+            }
+        }
+        if let Some(sandbox) = template.sandbox {
+            let sandbox = sandbox && self.docker_available && !self.selected_tool_host_only();
+            if sandbox != self.sandbox_enabled {
+                self.set_sandbox_enabled(sandbox);
+            }
+        }
+        if let Some(ref image) = template.sandbox_image {
+            self.sandbox_image = Input::new(image.clone());
+        }
+        if self.sandbox_enabled {
+            if let Some(env) = template.sandbox_env(&self.extra_env) {
+                self.extra_env = env;
+            }
+        }
+        if let Some(ref group) = template.group {
+            self.group = Input::new(group.clone());
+            self.clear_group_ghost();
+        }
+        if let Some(ref extra_args) = template.extra_args {
+            self.extra_args = Input::new(extra_args.clone());
+        }
+    }
+
+    /// Handle a key while the prompt field is focused. Returns false for keys
+    /// the dialog handles itself (submit, cancel, moving between fields).
+    fn handle_prompt_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Esc | KeyCode::Tab | KeyCode::BackTab => false,
+            // Shift+Enter inserts a newline. Most terminals send it as ESC + CR,
+            // which crossterm decodes as Alt+Enter, so accept both.
+            KeyCode::Enter
+                if key.modifiers.contains(KeyModifiers::SHIFT)
+                    || key.modifiers.contains(KeyModifiers::ALT) =>
+            {
+                self.prompt.insert_newline();
+                true
+            }
+            KeyCode::Enter => false,
+            // Up/Down move between prompt lines, leaving the field at the edges
+            KeyCode::Up if self.prompt.cursor().0 == 0 => false,
+            KeyCode::Down if self.prompt.cursor().0 + 1 >= self.prompt.lines().len() => false,
+            _ => {
+                self.prompt.input(key);
+                self.error_message = None;
+                true
+            }
+        }
+    }
+
     /// Handle key events when in sandbox configuration mode.
     fn handle_sandbox_config_key(&mut self, key: KeyEvent) -> DialogResult<NewSessionData> {
         // Sandbox config fields: 0=image, 1=env (inherited is always-visible, not focusable)
@@ -1392,6 +1551,16 @@ impl NewSessionDialog {
     }
 
     pub fn handle_paste(&mut self, text: &str) {
+        // The prompt is the only multi-line field, so it keeps newlines
+        let in_sub_mode = self.env_editing_input.is_some()
+            || self.workspace_repo_editing_input.is_some()
+            || self.tool_config_mode
+            || self.sandbox_config_mode;
+        if !in_sub_mode && self.focused_field == self.prompt_field() {
+            self.prompt.insert_str(text);
+            return;
+        }
+
         let sanitized: String = text.chars().filter(|c| *c != '\n' && *c != '\r').collect();
 
         // Route to the active sub-mode input if one is open
@@ -1448,6 +1617,8 @@ impl NewSessionDialog {
             },
             extra_args: self.extra_args.value().trim().to_string(),
             command_override: self.command_override.value().trim().to_string(),
+            prompt: Some(self.prompt_text().trim().to_string()).filter(|p| !p.is_empty()),
+            template: self.template.clone(),
         })
     }
 
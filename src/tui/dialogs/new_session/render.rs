@@ -62,7 +62,8 @@ impl NewSessionDialog {
         if has_sandbox {
             constraints.push(Constraint::Length(2)); // Sandbox checkbox (summary only)
         }
-        constraints.push(Constraint::Length(2)); // Group (always, at the bottom)
+        constraints.push(Constraint::Length(2)); // Group (always)
+        constraints.push(Constraint::Length(self.prompt_height())); // Prompt (always, at the bottom)
 
         // For errors, calculate how many lines we need based on the text length.
         // Inner width = dialog_width - 2 (border) - 2 (margin) = 76
@@ -91,12 +92,25 @@ impl NewSessionDialog {
 
         frame.render_widget(Clear, dialog_area);
 
-        let block = Block::default()
+        let title = match &self.template {
+            Some(name) => format!(" New Session: {} ", name),
+            None => " New Session ".to_string(),
+        };
+        let mut block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
             .border_style(Style::default().fg(theme.accent))
-            .title(" New Session ")
+            .title(title)
             .title_style(Style::default().fg(theme.title).bold());
+        if !self.templates.is_empty() {
+            block = block.title_bottom(
+                Line::from(vec![
+                    Span::styled(" C-t", Style::default().fg(theme.hint)),
+                    Span::styled(" templates ", Style::default().fg(theme.dimmed)),
+                ])
+                .right_aligned(),
+            );
+        }
 
         let inner = block.inner(dialog_area);
         frame.render_widget(block, dialog_area);
@@ -136,6 +150,7 @@ impl NewSessionDialog {
             usize::MAX
         };
         let group_field = fi;
+        let prompt_field = fi + 1;
 
         // Profile picker (only when multiple profiles)
         if has_profile_selection {
@@ -406,6 +421,10 @@ impl NewSessionDialog {
         );
         ci += 1;
 
+        // Prompt (multi-line, grows with its content)
+        self.render_prompt_field(frame, chunks[ci], self.focused_field == prompt_field, theme);
+        ci += 1;
+
         // Hints/errors (last chunk)
         let hint_chunk = ci;
         if self.confirm_create_dir.is_some() {
@@ -468,6 +487,10 @@ impl NewSessionDialog {
                 hint_spans.push(Span::styled("C-p", Style::default().fg(theme.hint)));
                 hint_spans.push(Span::raw(" configure  "));
             }
+            if self.focused_field == prompt_field {
+                hint_spans.push(Span::styled("S-Enter", Style::default().fg(theme.hint)));
+                hint_spans.push(Span::raw(" newline  "));
+            }
             if self.focused_field == worktree_field && has_worktree {
                 hint_spans.push(Span::styled("C-p", Style::default().fg(theme.hint)));
                 hint_spans.push(Span::raw(" configure  "));
@@ -496,6 +519,10 @@ impl NewSessionDialog {
         if self.dir_picker.is_active() {
             self.dir_picker.render(frame, area, theme);
         }
+
+        if self.template_picker.is_active() {
+            self.template_picker.render(frame, area, theme);
+        }
     }
 
     fn render_profile_field(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
@@ -1172,6 +1199,58 @@ impl NewSessionDialog {
         frame.render_widget(Paragraph::new(lines), area);
     }
 
+    /// Rows for the prompt field: one per line (up to a cap) plus spacing.
+    fn prompt_height(&self) -> u16 {
+        const MAX_PROMPT_LINES: u16 = 6;
+        (self.prompt.lines().len() as u16).clamp(1, MAX_PROMPT_LINES) + 1
+    }
+
+    fn render_prompt_field(&self, frame: &mut Frame, area: Rect, is_focused: bool, theme: &Theme) {
+        const LABEL: &str = "Prompt: ";
+        let label_style = if is_focused {
+            Style::default().fg(theme.accent).underlined()
+        } else {
+            Style::default().fg(theme.text)
+        };
+
+        let [label_area, text_area] =
+            Layout::horizontal([Constraint::Length(LABEL.len() as u16), Constraint::Min(1)]).areas(
+                Rect {
+                    height: area.height.saturating_sub(1).max(1),
+                    ..area
+                },
+            );
+        frame.render_widget(
+            Paragraph::new(Span::styled(LABEL.trim_end(), label_style)),
+            label_area,
+        );
+
+        if !is_focused && self.prompt_text().is_empty() {
+            frame.render_widget(
+                Paragraph::new(Span::styled(
+                    "(optional, sent when the agent is ready)",
+                    Style::default().fg(theme.text),
+                )),
+                text_area,
+            );
+            return;
+        }
+
+        let mut prompt = self.prompt.clone();
+        let value_style = if is_focused {
+            Style::default().fg(theme.accent)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        prompt.set_style(value_style);
+        prompt.set_cursor_style(if is_focused {
+            Style::default().fg(theme.background).bg(theme.accent)
+        } else {
+            value_style
+        });
+        frame.render_widget(&prompt, text_area);
+    }
+
     fn render_help_overlay(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
         let has_tool_selection = self.available_tools.len() > 1;
         let has_sandbox = self.docker_available;
@@ -1179,8 +1258,8 @@ impl NewSessionDialog {
 
         let dialog_width: u16 = HELP_DIALOG_WIDTH;
         let has_profile_selection = self.has_profile_selection();
-        // Base fields: Title, Path, YOLO, Worktree, Group + close hint
-        let base_height: u16 = 17;
+        // Base fields: Title, Path, YOLO, Worktree, Group, Prompt + close hint
+        let base_height: u16 = 20;
         let dialog_height: u16 = base_height
             + if has_profile_selection { 3 } else { 0 }
             + if has_tool_selection { 3 } else { 0 }
@@ -1221,7 +1300,7 @@ impl NewSessionDialog {
             if (7..=8).contains(&idx) && !show_sandbox_options_help {
                 continue; // Image, Env
             }
-            // idx 9 (Group) always shown
+            // idx 9 (Group), idx 10 (Prompt) always shown
 
             lines.push(Line::from(Span::styled(
                 help.name,
@@ -94,6 +94,9 @@ fn test_tab_cycles_fields_single_tool() {
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 4); // group
 
+    dialog.handle_key(key(KeyCode::Tab));
+    assert_eq!(dialog.focused_field, 5); // prompt
+
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 0); // wrap to start
 }
@@ -118,6 +121,9 @@ fn test_tab_cycles_fields_single_tool_with_worktree() {
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 4); // group
 
+    dialog.handle_key(key(KeyCode::Tab));
+    assert_eq!(dialog.focused_field, 5); // prompt
+
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 0); // wrap to start
 }
@@ -142,6 +148,9 @@ fn test_tab_cycles_fields_multi_tool() {
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 5); // group
 
+    dialog.handle_key(key(KeyCode::Tab));
+    assert_eq!(dialog.focused_field, 6); // prompt
+
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 0); // wrap to start (no new_branch without worktree)
 }
@@ -152,7 +161,10 @@ fn test_backtab_cycles_fields_reverse() {
     assert_eq!(dialog.focused_field, 0); // title
 
     dialog.handle_key(shift_key(KeyCode::BackTab));
-    assert_eq!(dialog.focused_field, 4); // group (last field without worktree/docker)
+    assert_eq!(dialog.focused_field, 5); // prompt (last field)
+
+    dialog.handle_key(shift_key(KeyCode::BackTab));
+    assert_eq!(dialog.focused_field, 4); // group
 
     dialog.handle_key(shift_key(KeyCode::BackTab));
     assert_eq!(dialog.focused_field, 3); // worktree branch
@@ -573,12 +585,13 @@ fn test_new_branch_field_hidden_without_worktree() {
     let mut dialog = single_tool_dialog();
     assert_eq!(dialog.focused_field, 0);
 
-    // Tab through (single profile): title(0) -> path(1) -> yolo(2) -> worktree(3) -> group(4) -> wrap to 0
+    // Tab through (single profile): title(0) -> path(1) -> yolo(2) -> worktree(3) -> group(4) -> prompt(5) -> wrap to 0
     dialog.handle_key(key(KeyCode::Tab)); // 1 (path)
     dialog.handle_key(key(KeyCode::Tab)); // 2 (yolo)
     dialog.handle_key(key(KeyCode::Tab)); // 3 (worktree)
     dialog.handle_key(key(KeyCode::Tab)); // 4 (group)
-    assert_eq!(dialog.focused_field, 4);
+    dialog.handle_key(key(KeyCode::Tab)); // 5 (prompt)
+    assert_eq!(dialog.focused_field, 5);
     dialog.handle_key(key(KeyCode::Tab)); // Should wrap to 0
     assert_eq!(dialog.focused_field, 0);
 }
@@ -606,7 +619,7 @@ fn test_tab_skips_sandbox_options_in_main_form() {
     dialog.sandbox_enabled = true;
 
     // With sandbox enabled, sandbox sub-options are in separate mode now.
-    // Main form (single profile): title(0), path(1), tool(2), yolo(3), worktree(4), sandbox(5), group(6)
+    // Main form (single profile): title(0), path(1), tool(2), yolo(3), worktree(4), sandbox(5), group(6), prompt(7)
     for _ in 0..5 {
         dialog.handle_key(key(KeyCode::Tab));
     }
@@ -615,6 +628,9 @@ fn test_tab_skips_sandbox_options_in_main_form() {
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 6); // group field (no sandbox sub-options inline)
 
+    dialog.handle_key(key(KeyCode::Tab));
+    assert_eq!(dialog.focused_field, 7); // prompt field
+
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 0); // wrap to start
 }
@@ -625,7 +641,7 @@ fn test_tab_skips_sandbox_when_disabled() {
     dialog.docker_available = true;
     dialog.sandbox_enabled = false;
 
-    // Single profile: title(0), path(1), tool(2), yolo(3), worktree(4), sandbox(5), group(6)
+    // Single profile: title(0), path(1), tool(2), yolo(3), worktree(4), sandbox(5), group(6), prompt(7)
     for _ in 0..5 {
         dialog.handle_key(key(KeyCode::Tab));
     }
@@ -634,6 +650,9 @@ fn test_tab_skips_sandbox_when_disabled() {
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 6); // group field
 
+    dialog.handle_key(key(KeyCode::Tab));
+    assert_eq!(dialog.focused_field, 7); // prompt field
+
     dialog.handle_key(key(KeyCode::Tab));
     assert_eq!(dialog.focused_field, 0); // wrap to start
 }
@@ -1135,3 +1154,137 @@ fn test_sandbox_config_mode_enter_on_image_returns_to_main() {
     assert!(matches!(result, DialogResult::Continue));
     assert!(!dialog.sandbox_config_mode);
 }
+
+#[test]
+fn test_prompt_is_none_when_empty() {
+    let mut dialog = single_tool_dialog();
+    match dialog.handle_key(key(KeyCode::Enter)) {
+        DialogResult::Submit(data) => assert_eq!(data.prompt, None),
+        _ => panic!("Expected Submit"),
+    }
+}
+
+#[test]
+fn test_prompt_field_accepts_multiline_text() {
+    let mut dialog = single_tool_dialog();
+    dialog.focused_field = dialog.prompt_field();
+    assert_eq!(dialog.focused_field, 5);
+
+    // '?' and space are typed rather than opening help or toggling
+    for c in "why? ok".chars() {
+        dialog.handle_key(key(KeyCode::Char(c)));
+    }
+    dialog.handle_key(shift_key(KeyCode::Enter));
+    dialog.handle_key(key(KeyCode::Char('b')));
+    dialog.handle_key(alt_key(KeyCode::Enter));
+    dialog.handle_key(key(KeyCode::Char('c')));
+    assert!(!dialog.show_help);
+
+    match dialog.handle_key(key(KeyCode::Enter)) {
+        DialogResult::Submit(data) => assert_eq!(data.prompt.as_deref(), Some("why? ok\nb\nc")),
+        _ => panic!("Expected Submit"),
+    }
+}
+
+#[test]
+fn test_prompt_field_up_down_leave_at_edges() {
+    let mut dialog = single_tool_dialog();
+    let prompt_field = dialog.prompt_field();
+    dialog.focused_field = prompt_field;
+    dialog.handle_paste("one\ntwo");
+
+    // Cursor is on the last line: Up moves within the prompt first
+    dialog.handle_key(key(KeyCode::Up));
+    assert_eq!(dialog.focused_field, prompt_field);
+    dialog.handle_key(key(KeyCode::Up));
+    assert_eq!(dialog.focused_field, prompt_field - 1);
+
+    dialog.handle_key(key(KeyCode::Down));
+    assert_eq!(dialog.focused_field, prompt_field);
+    dialog.handle_key(key(KeyCode::Down));
+    dialog.handle_key(key(KeyCode::Down));
+    assert_eq!(dialog.focused_field, 0); // wrap to start
+}
+
+#[test]
+fn test_paste_into_prompt_keeps_newlines() {
+    let mut dialog = single_tool_dialog();
+    dialog.focused_field = dialog.prompt_field();
+    dialog.handle_paste("line1\nline2");
+    assert_eq!(dialog.prompt_text(), "line1\nline2");
+    assert_eq!(dialog.title.value(), "");
+}
+
+fn template_config() -> Config {
+    let mut config = Config::default();
+    config.templates.insert(
+        "review".to_string(),
+        SessionTemplate {
+            agent: Some("opencode".to_string()),
+            yolo: Some(true),
+            group: Some("reviews".to_string()),
+            extra_args: Some("--fast".to_string()),
+            ..Default::default()
+        },
+    );
+    config
+}
+
+#[test]
+fn test_ctrl_t_fills_fields_from_template() {
+    let mut dialog = NewSessionDialog::new_with_config(
+        vec!["claude", "opencode"],
+        TEST_PATH.to_string(),
+        template_config(),
+    );
+
+    dialog.handle_key(ctrl_key(KeyCode::Char('t')));
+    assert!(dialog.template_picker.is_active());
+    dialog.handle_key(key(KeyCode::Enter));
+    assert!(!dialog.template_picker.is_active());
+    assert_eq!(dialog.template.as_deref(), Some("review"));
+
+    match dialog.handle_key(key(KeyCode::Enter)) {
+        DialogResult::Submit(data) => {
+            assert_eq!(data.template.as_deref(), Some("review"));
+            assert_eq!(data.tool, "opencode");
+            assert!(data.yolo_mode);
+            assert_eq!(data.group, "reviews");
+            assert_eq!(data.extra_args, "--fast");
+        }
+        _ => panic!("Expected Submit"),
+    }
+}
+
+#[test]
+fn test_template_leaves_unset_fields_alone() {
+    let mut dialog = NewSessionDialog::new_with_config(
+        vec!["claude", "opencode"],
+        TEST_PATH.to_string(),
+        template_config(),
+    );
+    dialog.group = Input::new("mine".to_string());
+    dialog.templates.insert(
+        "yolo".to_string(),
+        SessionTemplate {
+            yolo: Some(true),
+            ..Default::default()
+        },
+    );
+
+    dialog.apply_template("yolo");
+    assert!(dialog.yolo_mode);
+    assert_eq!(dialog.tool_index, 0);
+    assert_eq!(dialog.group.value(), "mine");
+}
+
+#[test]
+fn test_ctrl_t_without_templates_does_nothing() {
+    let mut dialog = single_tool_dialog();
+    dialog.handle_key(ctrl_key(KeyCode::Char('t')));
+    assert!(!dialog.template_picker.is_active());
+    match dialog.handle_key(key(KeyCode::Enter)) {
+        DialogResult::Submit(data) => assert_eq!(data.template, None),
+        _ => panic!("Expected Submit"),
+    }
+}
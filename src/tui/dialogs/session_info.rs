@@ -0,0 +1,324 @@
+//! Session info dialog with a status history timeline
+
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use super::DialogResult;
+use crate::session::history::{self, StatusEvent};
+use crate::session::ports::{self, PortAllocation};
+use crate::session::services::ServiceStatus;
+use crate::session::{Instance, Status};
+use crate::tui::styles::Theme;
+
+const DIALOG_WIDTH: u16 = 72;
+const DIALOG_HEIGHT: u16 = 28;
+
+pub struct SessionInfoDialog {
+    title: String,
+    id: String,
+    tool: String,
+    project_path: String,
+    branch: Option<String>,
+    profile: String,
+    status: Status,
+    created_at: DateTime<Utc>,
+    events: Vec<StatusEvent>,
+    ports: Vec<PortAllocation>,
+    /// The sandbox's companion services, if it has any
+    services: Vec<ServiceStatus>,
+    /// Rows scrolled up from the newest timeline entry.
+    scroll: usize,
+}
+
+impl SessionInfoDialog {
+    pub fn new(
+        instance: &Instance,
+        events: Vec<StatusEvent>,
+        services: Vec<ServiceStatus>,
+    ) -> Self {
+        Self {
+            title: instance.title.clone(),
+            id: instance.id.clone(),
+            tool: instance.tool.clone(),
+            project_path: instance.project_path.clone(),
+            branch: instance.worktree_info.as_ref().map(|w| w.branch.clone()),
+            profile: instance.source_profile.clone(),
+            status: instance.status,
+            created_at: instance.created_at,
+            events,
+            ports: instance.ports.clone(),
+            services,
+            scroll: 0,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> DialogResult<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') | KeyCode::Char('i') => {
+                DialogResult::Cancel
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if self.scroll + 1 < self.events.len() {
+                    self.scroll += 1;
+                }
+                DialogResult::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                DialogResult::Continue
+            }
+            _ => DialogResult::Continue,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let dialog_area = super::centered_rect(area, DIALOG_WIDTH, DIALOG_HEIGHT);
+
+        frame.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .style(Style::default().bg(theme.background))
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.border))
+            .title(format!(" {} ", self.title))
+            .title_style(Style::default().fg(theme.title).bold())
+            .title_bottom(
+                Line::from(vec![
+                    Span::styled(" j/k", Style::default().fg(theme.accent)),
+                    Span::styled(" scroll ", Style::default().fg(theme.dimmed)),
+                    Span::styled("Esc", Style::default().fg(theme.accent)),
+                    Span::styled(" close ", Style::default().fg(theme.dimmed)),
+                ])
+                .right_aligned(),
+            );
+
+        let inner = block.inner(dialog_area);
+        frame.render_widget(block, dialog_area);
+
+        let now = Utc::now();
+        let totals = history::time_in_status(&self.events, now);
+        let summary_height = if totals.is_empty() { 0 } else { 2 };
+
+        let details_height = 6
+            + self.branch.is_some() as u16
+            + (!self.ports.is_empty()) as u16
+            + (!self.services.is_empty()) as u16;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .horizontal_margin(1)
+            .constraints([
+                Constraint::Length(details_height),
+                Constraint::Min(3),
+                Constraint::Length(summary_height),
+            ])
+            .split(inner);
+
+        self.render_details(frame, chunks[0], theme);
+        self.render_timeline(frame, chunks[1], theme, now);
+        if !totals.is_empty() {
+            render_totals(frame, chunks[2], theme, &totals);
+        }
+    }
+
+    fn render_details(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let label = |s: &'static str| Span::styled(s, Style::default().fg(theme.dimmed));
+        let value = |s: String| Span::styled(s, Style::default().fg(theme.text));
+
+        let mut lines = vec![
+            Line::from(vec![label("ID:       "), value(self.id.clone())]),
+            Line::from(vec![label("Agent:    "), value(self.tool.clone())]),
+            Line::from(vec![label("Path:     "), value(self.project_path.clone())]),
+        ];
+        if let Some(branch) = &self.branch {
+            lines.push(Line::from(vec![label("Branch:   "), value(branch.clone())]));
+        }
+        lines.push(Line::from(vec![
+            label("Profile:  "),
+            value(self.profile.clone()),
+        ]));
+        lines.push(Line::from(vec![
+            label("Created:  "),
+            value(format_local(self.created_at)),
+        ]));
+        lines.push(Line::from(vec![
+            label("Status:   "),
+            Span::styled(
+                format!("{:?}", self.status),
+                Style::default().fg(status_color(self.status, theme)),
+            ),
+        ]));
+        if !self.ports.is_empty() {
+            lines.push(Line::from(vec![
+                label("Ports:    "),
+                value(ports::summary(&self.ports)),
+            ]));
+        }
+        if !self.services.is_empty() {
+            let mut spans = vec![label("Services: ")];
+            for (i, service) in self.services.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::styled(", ", Style::default().fg(theme.dimmed)));
+                }
+                spans.push(value(format!("{} ", service.name)));
+                spans.push(Span::styled(
+                    service.summary().to_string(),
+                    Style::default().fg(service_color(service, theme)),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+
+    fn render_timeline(&self, frame: &mut Frame, area: Rect, theme: &Theme, now: DateTime<Utc>) {
+        let block = Block::default()
+            .borders(Borders::TOP)
+            .border_style(Style::default().fg(theme.border))
+            .title(" Timeline ")
+            .title_style(Style::default().fg(theme.title));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if self.events.is_empty() {
+            frame.render_widget(
+                Paragraph::new("No status history recorded yet")
+                    .style(Style::default().fg(theme.dimmed)),
+                inner,
+            );
+            return;
+        }
+
+        // Newest at the bottom, scrolled back by `scroll` rows
+        let rows = inner.height as usize;
+        let end = self.events.len() - self.scroll.min(self.events.len() - 1);
+        let start = end.saturating_sub(rows);
+
+        let lines: Vec<Line> = (start..end)
+            .map(|i| {
+                let event = &self.events[i];
+                let until = self.events.get(i + 1).map(|e| e.at).unwrap_or(now);
+                Line::from(vec![
+                    Span::styled(
+                        format!("{}  ", format_local(event.at)),
+                        Style::default().fg(theme.dimmed),
+                    ),
+                    Span::styled(
+                        format!("{:<8}", format!("{:?}", event.to)),
+                        Style::default().fg(status_color(event.to, theme)),
+                    ),
+                    Span::styled(
+                        format!(" {:>7}", history::format_duration(until - event.at)),
+                        Style::default().fg(theme.text),
+                    ),
+                    Span::styled(
+                        format!("  {}", event.source.label()),
+                        Style::default().fg(theme.dimmed),
+                    ),
+                ])
+            })
+            .collect();
+
+        frame.render_widget(Paragraph::new(lines), inner);
+    }
+}
+
+fn render_totals(
+    frame: &mut Frame,
+    area: Rect,
+    theme: &Theme,
+    totals: &[(Status, chrono::Duration)],
+) {
+    let mut spans = Vec::new();
+    for (status, spent) in totals {
+        spans.push(Span::styled(
+            format!("{:?} ", status),
+            Style::default().fg(status_color(*status, theme)),
+        ));
+        spans.push(Span::styled(
+            format!("{}   ", history::format_duration(*spent)),
+            Style::default().fg(theme.text),
+        ));
+    }
+
+    let block = Block::default()
+        .borders(Borders::TOP)
+        .border_style(Style::default().fg(theme.border));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+    frame.render_widget(Paragraph::new(Line::from(spans)), inner);
+}
+
+fn format_local(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn service_color(service: &ServiceStatus, theme: &Theme) -> Color {
+    match service.summary() {
+        "healthy" | "running" => theme.running,
+        "starting" | "created" | "restarting" => theme.waiting,
+        "unhealthy" | "dead" => theme.error,
+        _ => theme.dimmed,
+    }
+}
+
+fn status_color(status: Status, theme: &Theme) -> Color {
+    match status {
+        Status::Running => theme.running,
+        Status::Waiting => theme.waiting,
+        Status::Idle => theme.idle,
+        Status::Unknown => theme.waiting,
+        Status::Stopped => theme.dimmed,
+        Status::Error => theme.error,
+        Status::Stalled => theme.error,
+        Status::Starting => theme.dimmed,
+        Status::Deleting => theme.waiting,
+        Status::Creating => theme.accent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::history::StatusSource;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn dialog_with_events(n: usize) -> SessionInfoDialog {
+        let inst = Instance::new("test", "/tmp/test");
+        let events = (0..n)
+            .map(|_| StatusEvent::new(&inst.id, Status::Idle, Status::Running, StatusSource::Hook))
+            .collect();
+        SessionInfoDialog::new(&inst, events, Vec::new())
+    }
+
+    #[test]
+    fn test_close_keys() {
+        for code in [KeyCode::Esc, KeyCode::Enter, KeyCode::Char('i')] {
+            let mut dialog = dialog_with_events(0);
+            assert!(matches!(dialog.handle_key(key(code)), DialogResult::Cancel));
+        }
+    }
+
+    #[test]
+    fn test_scroll_is_clamped_to_events() {
+        let mut dialog = dialog_with_events(3);
+        for _ in 0..5 {
+            dialog.handle_key(key(KeyCode::Char('k')));
+        }
+        assert_eq!(dialog.scroll, 2);
+
+        for _ in 0..5 {
+            dialog.handle_key(key(KeyCode::Char('j')));
+        }
+        assert_eq!(dialog.scroll, 0);
+    }
+}
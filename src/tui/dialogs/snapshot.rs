@@ -0,0 +1,202 @@
+//! Sandbox snapshot dialog: list a session's snapshots, take a new one or
+//! roll back to one
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+
+use super::DialogResult;
+use crate::session::snapshot::SandboxSnapshot;
+use crate::tui::styles::Theme;
+
+const DIALOG_WIDTH: u16 = 60;
+const MAX_VISIBLE: usize = 10;
+
+pub enum SnapshotAction {
+    Take,
+    /// Roll back to the snapshot with this image
+    Restore(String),
+}
+
+pub struct SnapshotDialog {
+    session_id: String,
+    session_title: String,
+    /// Newest first
+    snapshots: Vec<SandboxSnapshot>,
+    selected: usize,
+}
+
+impl SnapshotDialog {
+    pub fn new(session_id: &str, session_title: &str, snapshots: Vec<SandboxSnapshot>) -> Self {
+        let mut dialog = Self {
+            session_id: session_id.to_string(),
+            session_title: session_title.to_string(),
+            snapshots: Vec::new(),
+            selected: 0,
+        };
+        dialog.set_snapshots(snapshots);
+        dialog
+    }
+
+    pub fn session_id(&self) -> &str {
+        &self.session_id
+    }
+
+    /// Show the session's snapshots (oldest first, as stored) newest first,
+    /// with the newest selected.
+    pub fn set_snapshots(&mut self, mut snapshots: Vec<SandboxSnapshot>) {
+        snapshots.reverse();
+        self.snapshots = snapshots;
+        self.selected = 0;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> DialogResult<SnapshotAction> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => DialogResult::Cancel,
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                DialogResult::Continue
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.snapshots.len() {
+                    self.selected += 1;
+                }
+                DialogResult::Continue
+            }
+            KeyCode::Char('s') | KeyCode::Char('n') => DialogResult::Submit(SnapshotAction::Take),
+            KeyCode::Char('r') | KeyCode::Enter => match self.snapshots.get(self.selected) {
+                Some(snapshot) => {
+                    DialogResult::Submit(SnapshotAction::Restore(snapshot.image.clone()))
+                }
+                None => DialogResult::Continue,
+            },
+            _ => DialogResult::Continue,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let list_height = self.snapshots.len().clamp(1, MAX_VISIBLE) as u16;
+        // list + hint (1) + borders (2) + margin (2)
+        let dialog_height = (list_height + 5).min(area.height);
+        let dialog_area = super::centered_rect(area, DIALOG_WIDTH, dialog_height);
+        frame.render_widget(Clear, dialog_area);
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(theme.accent))
+            .title(format!(" Snapshots: {} ", self.session_title))
+            .title_style(Style::default().fg(theme.title).bold());
+
+        let inner = block.inner(dialog_area);
+        frame.render_widget(block, dialog_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner);
+
+        let visible_height = chunks[0].height as usize;
+        let scroll_offset = (self.selected + 1).saturating_sub(visible_height);
+
+        let lines: Vec<Line> = if self.snapshots.is_empty() {
+            vec![Line::from(Span::styled(
+                "  No snapshots yet",
+                Style::default().fg(theme.dimmed),
+            ))]
+        } else {
+            self.snapshots
+                .iter()
+                .enumerate()
+                .skip(scroll_offset)
+                .take(visible_height)
+                .map(|(i, snapshot)| {
+                    let is_selected = i == self.selected;
+                    let style = if is_selected {
+                        Style::default().fg(theme.accent).bold()
+                    } else {
+                        Style::default().fg(theme.text)
+                    };
+                    let created = snapshot
+                        .created_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S");
+                    Line::from(vec![
+                        Span::styled(if is_selected { "> " } else { "  " }, style),
+                        Span::styled(created.to_string(), style),
+                        Span::styled(
+                            format!("  {}", snapshot.tag()),
+                            Style::default().fg(theme.dimmed),
+                        ),
+                    ])
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+        let mut hint_spans = vec![
+            Span::styled("s", Style::default().fg(theme.hint)),
+            Span::raw(" snapshot  "),
+        ];
+        if !self.snapshots.is_empty() {
+            hint_spans.extend([
+                Span::styled("Enter", Style::default().fg(theme.hint)),
+                Span::raw(" roll back  "),
+            ]);
+        }
+        hint_spans.extend([
+            Span::styled("Esc", Style::default().fg(theme.hint)),
+            Span::raw(" close"),
+        ]);
+        frame.render_widget(Paragraph::new(Line::from(hint_spans)), chunks[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn snapshot(day: u32) -> SandboxSnapshot {
+        SandboxSnapshot::new("s1", Utc.with_ymd_and_hms(2026, 1, day, 0, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_newest_snapshot_selected_first() {
+        let mut dialog = SnapshotDialog::new("s1", "Session", vec![snapshot(1), snapshot(2)]);
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Enter)),
+            DialogResult::Submit(SnapshotAction::Restore(ref image)) if *image == snapshot(2).image
+        ));
+
+        dialog.handle_key(key(KeyCode::Down));
+        dialog.handle_key(key(KeyCode::Down));
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Char('r'))),
+            DialogResult::Submit(SnapshotAction::Restore(ref image)) if *image == snapshot(1).image
+        ));
+    }
+
+    #[test]
+    fn test_empty_list_can_only_take() {
+        let mut dialog = SnapshotDialog::new("s1", "Session", Vec::new());
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Enter)),
+            DialogResult::Continue
+        ));
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Char('s'))),
+            DialogResult::Submit(SnapshotAction::Take)
+        ));
+        assert!(matches!(
+            dialog.handle_key(key(KeyCode::Esc)),
+            DialogResult::Cancel
+        ));
+    }
+}
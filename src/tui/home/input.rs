@@ -6,12 +6,13 @@ use tui_input::Input;
 
 use super::{HomeView, TerminalMode, ViewMode};
 use crate::session::config::{load_config, save_config, GroupByMode, SortOrder};
-use crate::session::{list_profiles, repo_config, resolve_config, Item, Status};
+use crate::session::{list_profiles, message_queue, repo_config, resolve_config, Item, Status};
 use crate::tui::app::Action;
 use crate::tui::dialogs::{
-    ConfirmDialog, DeleteDialogConfig, DialogResult, GroupDeleteOptionsDialog, HookTrustAction,
-    HooksInstallDialog, InfoDialog, NewSessionData, NewSessionDialog, ProfilePickerAction,
-    RenameDialog, RenameMode, SendMessageDialog, UnifiedDeleteDialog,
+    CompareDialog, ConfirmDialog, DeleteDialogConfig, DialogResult, GroupDeleteOptionsDialog,
+    HookTrustAction, HooksInstallDialog, InfoDialog, MessageQueueDialog, NewSessionData,
+    NewSessionDialog, ProfilePickerAction, RenameDialog, RenameMode, SendMessageDialog,
+    SessionInfoDialog, SnapshotAction, SnapshotDialog, UnifiedDeleteDialog,
 };
 use crate::tui::diff::{DiffAction, DiffView};
 use crate::tui::settings::{SettingsAction, SettingsView};
@@ -142,6 +143,26 @@ impl HomeView {
             return None;
         }
 
+        if let Some(dialog) = &mut self.session_info_dialog {
+            match dialog.handle_key(key) {
+                DialogResult::Continue => {}
+                DialogResult::Cancel | DialogResult::Submit(_) => {
+                    self.session_info_dialog = None;
+                }
+            }
+            return None;
+        }
+
+        if let Some(dialog) = &mut self.compare_dialog {
+            match dialog.handle_key(key) {
+                DialogResult::Continue => {}
+                DialogResult::Cancel | DialogResult::Submit(_) => {
+                    self.compare_dialog = None;
+                }
+            }
+            return None;
+        }
+
         // Handle other dialog input
         if self.show_help {
             if matches!(
@@ -201,7 +222,7 @@ impl HomeView {
                                     tracing::error!("Failed to trust repo: {}", e);
                                 }
                                 let merged =
-                                    repo_config::merge_hooks_with_config(&data.profile, hooks);
+                                    repo_config::merge_hooks_with_config(&data.profile, *hooks);
                                 return self.create_session_with_hooks(data, merged);
                             }
                             HookTrustAction::Skip => {
@@ -274,6 +295,7 @@ impl HomeView {
                     self.confirm_dialog = None;
                     self.pending_stop_session = None;
                     self.pending_force_remove_session = None;
+                    self.pending_restore_snapshot = None;
                 }
                 DialogResult::Submit(_) => {
                     let action = dialog.action().to_string();
@@ -292,6 +314,10 @@ impl HomeView {
                                 tracing::error!("Failed to force remove session: {}", e);
                             }
                         }
+                    } else if action == "restore_snapshot" {
+                        if let Some((session_id, image)) = self.pending_restore_snapshot.take() {
+                            self.restore_snapshot(&session_id, &image);
+                        }
                     } else if action == "quit_during_creation" {
                         return Some(Action::Quit);
                     }
@@ -473,6 +499,52 @@ impl HomeView {
             return None;
         }
 
+        // Message queue dialog
+        if let Some(dialog) = &mut self.message_queue_dialog {
+            match dialog.handle_key(key) {
+                DialogResult::Continue => {}
+                DialogResult::Cancel => {
+                    self.message_queue_dialog = None;
+                }
+                DialogResult::Submit(action) => {
+                    let session_id = dialog.session_id().to_string();
+                    self.apply_message_queue_action(&session_id, action);
+                }
+            }
+            return None;
+        }
+
+        if let Some(dialog) = &mut self.snapshot_dialog {
+            match dialog.handle_key(key) {
+                DialogResult::Continue => {}
+                DialogResult::Cancel => {
+                    self.snapshot_dialog = None;
+                }
+                DialogResult::Submit(SnapshotAction::Take) => {
+                    let session_id = dialog.session_id().to_string();
+                    self.take_snapshot(&session_id);
+                }
+                DialogResult::Submit(SnapshotAction::Restore(image)) => {
+                    let session_id = dialog.session_id().to_string();
+                    self.snapshot_dialog = None;
+                    if let Some(inst) = self.get_instance(&session_id) {
+                        let message = format!(
+                            "Roll back '{}' to this snapshot? Changes in its container since \
+                             then are lost; the project directory is kept.",
+                            inst.title
+                        );
+                        self.pending_restore_snapshot = Some((session_id, image));
+                        self.confirm_dialog = Some(ConfirmDialog::new(
+                            "Roll Back Sandbox",
+                            &message,
+                            "restore_snapshot",
+                        ));
+                    }
+                }
+            }
+            return None;
+        }
+
         // Search mode
         if self.search_active {
             match key.code {
@@ -716,6 +788,7 @@ impl HomeView {
                     }
                 }
             }
+            KeyCode::Char('C') => self.open_compare_dialog(),
             KeyCode::Char('x') => {
                 if let Some(session_id) = &self.selected_session {
                     if let Some(inst) = self.get_instance(session_id) {
@@ -873,6 +946,25 @@ impl HomeView {
                     ));
                 }
             }
+            KeyCode::Char('i') => {
+                if let Some(id) = self.selected_session.clone() {
+                    if let Some(inst) = self.get_instance(&id) {
+                        let events = crate::session::history::load(&inst.source_profile, &inst.id)
+                            .unwrap_or_else(|e| {
+                                tracing::warn!("Failed to load status history: {}", e);
+                                Vec::new()
+                            });
+                        let services = inst
+                            .sandbox_info
+                            .as_ref()
+                            .and_then(|s| s.compose_project.as_deref())
+                            .map(crate::session::services::status)
+                            .unwrap_or_default();
+                        self.session_info_dialog =
+                            Some(SessionInfoDialog::new(inst, events, services));
+                    }
+                }
+            }
             KeyCode::Char('m') => {
                 if let Some(id) = self.selected_session.clone() {
                     if let Some(inst) = self.get_instance(&id) {
@@ -890,6 +982,46 @@ impl HomeView {
                     }
                 }
             }
+            KeyCode::Char('M') => {
+                if let Some(id) = self.selected_session.clone() {
+                    if let Some(inst) = self.get_instance(&id) {
+                        if matches!(inst.status, Status::Creating | Status::Deleting) {
+                            return None;
+                        }
+                        let messages = message_queue::load(&inst.source_profile, &inst.id)
+                            .unwrap_or_else(|e| {
+                                tracing::warn!("Failed to load message queue: {}", e);
+                                Vec::new()
+                            });
+                        self.message_queue_dialog =
+                            Some(MessageQueueDialog::new(&inst.id, &inst.title, messages));
+                    }
+                }
+            }
+            KeyCode::Char('S') => {
+                if let Some(id) = self.selected_session.clone() {
+                    if let Some(inst) = self.get_instance(&id) {
+                        if matches!(inst.status, Status::Creating | Status::Deleting) {
+                            return None;
+                        }
+                        match &inst.sandbox_info {
+                            Some(sandbox) if sandbox.enabled => {
+                                self.snapshot_dialog = Some(SnapshotDialog::new(
+                                    &inst.id,
+                                    &inst.title,
+                                    sandbox.snapshots.clone(),
+                                ));
+                            }
+                            _ => {
+                                self.info_dialog = Some(InfoDialog::new(
+                                    "Not Sandboxed",
+                                    "Snapshots are only available for sandboxed sessions.",
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
             KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.apply_sort_order(self.sort_order.cycle_reverse());
             }
@@ -1047,6 +1179,49 @@ impl HomeView {
         }
     }
 
+    /// Open the comparison view for the selected group, or for the group of
+    /// the selected session.
+    fn open_compare_dialog(&mut self) {
+        let (group, profile) = if let Some(group) = &self.selected_group {
+            (group.clone(), self.selected_group_profile.clone())
+        } else if let Some(inst) = self
+            .selected_session
+            .as_deref()
+            .and_then(|id| self.get_instance(id))
+        {
+            (inst.group_path.clone(), Some(inst.source_profile.clone()))
+        } else {
+            return;
+        };
+
+        if group.is_empty() {
+            self.info_dialog = Some(InfoDialog::new(
+                "Not in a Group",
+                "Select a group, or a session in one, to compare its sessions.",
+            ));
+            return;
+        }
+
+        let prefix = format!("{}/", group);
+        let members: Vec<_> = self
+            .instances
+            .iter()
+            .filter(|i| i.group_path == group || i.group_path.starts_with(&prefix))
+            .filter(|i| profile.as_ref().is_none_or(|p| &i.source_profile == p))
+            .collect();
+        if members.len() < 2 {
+            self.info_dialog = Some(InfoDialog::new(
+                "Nothing to Compare",
+                "The group needs at least two sessions to compare.",
+            ));
+            return;
+        }
+
+        self.compare_poller
+            .request(group.clone(), members.into_iter().cloned().collect());
+        self.compare_dialog = Some(CompareDialog::loading(&group));
+    }
+
     fn toggle_group_collapsed(&mut self, path: &str) {
         if self.group_by == GroupByMode::Project {
             let collapsed = self
@@ -1086,6 +1261,10 @@ impl HomeView {
             dialog.handle_paste(text);
             return;
         }
+        if let Some(ref mut dialog) = self.message_queue_dialog {
+            dialog.handle_paste(text);
+            return;
+        }
         if let Some(ref mut dialog) = self.new_dialog {
             dialog.handle_paste(text);
         }
@@ -15,10 +15,11 @@ use tui_input::Input;
 use crate::session::{
     config::{load_config, save_config, GroupByMode, SortOrder},
     flatten_tree, flatten_tree_all_profiles, resolve_config, DefaultTerminalMode, Group, GroupTree,
-    Instance, Item, Storage,
+    Instance, Item, Storage, SyncBase,
 };
 use crate::tmux::AvailableTools;
 
+use super::compare_poller::ComparePoller;
 use super::creation_poller::{CreationPoller, CreationRequest};
 use super::deletion_poller::DeletionPoller;
 #[cfg(feature = "serve")]
@@ -29,8 +30,10 @@ use super::dialogs::{
     UnifiedDeleteDialog, WelcomeDialog,
 };
 use super::diff::DiffView;
+use super::metrics_poller::MetricsPoller;
 use super::settings::SettingsView;
 use super::status_poller::StatusPoller;
+use super::usage_poller::UsagePoller;
 
 /// Extract a project group name from a session instance.
 /// Uses `worktree_info.main_repo_path` for worktree sessions (so all branches of the
@@ -114,6 +117,7 @@ pub(super) fn get_indent(depth: usize) -> &'static str {
 
 pub(super) const ICON_IDLE: &str = "⠒";
 pub(super) const ICON_ERROR: &str = "✕";
+pub(super) const ICON_STALLED: &str = "⠿";
 pub(super) const ICON_UNKNOWN: &str = "⠤";
 pub(super) const ICON_STOPPED: &str = "⠒";
 pub(super) const ICON_DELETING: &str = "✕";
@@ -128,6 +132,9 @@ pub(super) struct CreatingHookProgress {
 
 pub struct HomeView {
     pub(super) storages: HashMap<String, Storage>,
+    /// Per-profile snapshot of what was last loaded or saved, used to merge
+    /// our writes with changes made by other processes
+    sync_bases: HashMap<String, SyncBase>,
     pub(super) active_profile: Option<String>,
     instances: Vec<Instance>,
     instance_map: HashMap<String, Instance>,
@@ -163,10 +170,14 @@ pub struct HomeView {
     pub(super) welcome_dialog: Option<WelcomeDialog>,
     pub(super) changelog_dialog: Option<ChangelogDialog>,
     pub(super) info_dialog: Option<InfoDialog>,
+    pub(super) session_info_dialog: Option<super::dialogs::SessionInfoDialog>,
+    pub(super) compare_dialog: Option<super::dialogs::CompareDialog>,
     pub(super) profile_picker_dialog: Option<ProfilePickerDialog>,
     #[cfg(feature = "serve")]
     pub(super) serve_dialog: Option<ServeDialog>,
     pub(super) send_message_dialog: Option<super::dialogs::SendMessageDialog>,
+    pub(super) message_queue_dialog: Option<super::dialogs::MessageQueueDialog>,
+    pub(super) snapshot_dialog: Option<super::dialogs::SnapshotDialog>,
     /// Session to receive the message from the send dialog
     pub(super) pending_send_session: Option<String>,
     /// Session to attach after the custom instruction warning dialog is dismissed
@@ -175,6 +186,9 @@ pub struct HomeView {
     pub(super) pending_stop_session: Option<String>,
     /// Session to force-remove after the confirmation dialog is accepted
     pub(super) pending_force_remove_session: Option<String>,
+    /// Session and snapshot image to roll back to after the confirmation
+    /// dialog is accepted
+    pub(super) pending_restore_snapshot: Option<(String, String)>,
     // Search
     pub(super) search_active: bool,
     pub(super) search_query: Input,
@@ -188,6 +202,21 @@ pub struct HomeView {
     pub(super) status_poller: StatusPoller,
     pub(super) pending_status_refresh: bool,
 
+    // Background token usage polling, results by session ID
+    pub(super) usage_poller: UsagePoller,
+    pub(super) pending_usage_refresh: bool,
+    pub(super) usage: HashMap<String, crate::usage::SessionUsage>,
+    pub(super) usage_config: crate::usage::UsageConfig,
+
+    // Background diffs for the comparison view
+    pub(super) compare_poller: ComparePoller,
+
+    // Background CPU/memory sampling, results by session ID
+    pub(super) metrics_poller: MetricsPoller,
+    pub(super) pending_metrics_refresh: bool,
+    pub(super) metrics: HashMap<String, crate::process::metrics::SessionMetrics>,
+    pub(super) metrics_config: crate::process::metrics::MetricsConfig,
+
     // Performance: background deletion
     pub(super) deletion_poller: DeletionPoller,
 
@@ -215,6 +244,7 @@ pub struct HomeView {
 
     // Sound config for state transition sounds
     pub(super) sound_config: crate::sound::SoundConfig,
+    desktop_notifications: crate::desktop_notifications::DesktopNotificationsConfig,
 
     // Settings view
     pub(super) settings_view: Option<SettingsView>,
@@ -236,6 +266,7 @@ impl HomeView {
         use crate::session::list_profiles;
 
         let mut storages = HashMap::new();
+        let mut sync_bases = HashMap::new();
         let mut all_instances = Vec::new();
         let mut group_trees = HashMap::new();
 
@@ -247,6 +278,7 @@ impl HomeView {
         for profile_name in &profile_names {
             let storage = Storage::new(profile_name)?;
             let (mut instances, groups) = storage.load_with_groups()?;
+            sync_bases.insert(profile_name.clone(), SyncBase::new(&instances, &groups));
             for inst in &mut instances {
                 inst.source_profile = profile_name.clone();
             }
@@ -275,6 +307,18 @@ impl HomeView {
             .as_ref()
             .map(|config| config.sound.clone())
             .unwrap_or_default();
+        let desktop_notifications = resolved
+            .as_ref()
+            .map(|config| config.desktop_notifications.clone())
+            .unwrap_or_default();
+        let usage_config = resolved
+            .as_ref()
+            .map(|config| config.usage.clone())
+            .unwrap_or_default();
+        let metrics_config = resolved
+            .as_ref()
+            .map(|config| config.metrics.clone())
+            .unwrap_or_default();
         let user_config = load_config().ok().flatten();
         let sort_order = user_config
             .as_ref()
@@ -299,6 +343,7 @@ impl HomeView {
 
         let mut view = Self {
             storages,
+            sync_bases,
             active_profile,
             instances: all_instances,
             instance_map,
@@ -326,14 +371,19 @@ impl HomeView {
             welcome_dialog: None,
             changelog_dialog: None,
             info_dialog: None,
+            session_info_dialog: None,
+            compare_dialog: None,
             profile_picker_dialog: None,
             #[cfg(feature = "serve")]
             serve_dialog: None,
             send_message_dialog: None,
+            message_queue_dialog: None,
+            snapshot_dialog: None,
             pending_send_session: None,
             pending_attach_after_warning: None,
             pending_stop_session: None,
             pending_force_remove_session: None,
+            pending_restore_snapshot: None,
             search_active: false,
             search_query: Input::default(),
             search_matches: Vec::new(),
@@ -341,6 +391,15 @@ impl HomeView {
             available_tools,
             status_poller: StatusPoller::new(),
             pending_status_refresh: false,
+            usage_poller: UsagePoller::new(),
+            pending_usage_refresh: false,
+            usage: HashMap::new(),
+            usage_config,
+            compare_poller: ComparePoller::new(),
+            metrics_poller: MetricsPoller::new(),
+            pending_metrics_refresh: false,
+            metrics: HashMap::new(),
+            metrics_config,
             deletion_poller: DeletionPoller::new(),
             creation_poller: CreationPoller::new(),
             creation_cancelled: false,
@@ -353,6 +412,7 @@ impl HomeView {
             terminal_modes: HashMap::new(),
             default_terminal_mode,
             sound_config,
+            desktop_notifications,
             settings_view: None,
             settings_close_confirm: false,
             diff_view: None,
@@ -395,10 +455,13 @@ impl HomeView {
                 }
             }
             self.storages.retain(|k, _| current_profiles.contains(k));
+            self.sync_bases.retain(|k, _| current_profiles.contains(k));
         }
 
         for (profile_name, storage) in &self.storages {
             let (mut instances, groups) = storage.load_with_groups()?;
+            self.sync_bases
+                .insert(profile_name.clone(), SyncBase::new(&instances, &groups));
             for inst in &mut instances {
                 inst.source_profile = profile_name.clone();
                 if let Some(prev) = self.instance_map.get(&inst.id) {
@@ -523,6 +586,14 @@ impl HomeView {
                     if let Some(old) = old_status {
                         if old != new_status {
                             crate::sound::play_for_transition(old, new_status, &self.sound_config);
+                            if let Some(inst) = self.get_instance(&update.id) {
+                                crate::desktop_notifications::status_changed(
+                                    inst,
+                                    old,
+                                    new_status,
+                                    &self.desktop_notifications,
+                                );
+                            }
                         }
                     }
                 }
@@ -533,11 +604,76 @@ impl HomeView {
         false
     }
 
+    /// Request a token usage refresh in the background (non-blocking).
+    pub fn request_usage_refresh(&mut self) {
+        if !self.pending_usage_refresh {
+            self.usage_poller
+                .request_refresh(self.instances.clone(), self.usage_config.clone());
+            self.pending_usage_refresh = true;
+        }
+    }
+
+    /// Apply the latest token usage from the background poller.
+    /// Returns true if it changed.
+    pub fn apply_usage_updates(&mut self) -> bool {
+        let Some(usage) = self.usage_poller.try_recv() else {
+            return false;
+        };
+        self.pending_usage_refresh = false;
+        let changed = usage != self.usage;
+        self.usage = usage;
+        changed
+    }
+
+    /// Fill in the comparison view with diffs from the background poller.
+    /// Returns true if it changed.
+    pub fn apply_compare_results(&mut self) -> bool {
+        let mut changed = false;
+        while let Some((group, columns)) = self.compare_poller.try_recv() {
+            if let Some(dialog) = self
+                .compare_dialog
+                .as_mut()
+                .filter(|d| d.is_loading() && d.group() == group)
+            {
+                dialog.fill(columns);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Request a CPU/memory sample in the background (non-blocking).
+    pub fn request_metrics_refresh(&mut self) {
+        if !self.metrics_config.enabled {
+            self.metrics.clear();
+            return;
+        }
+        if !self.pending_metrics_refresh {
+            self.metrics_poller.request_refresh(self.instances.clone());
+            self.pending_metrics_refresh = true;
+        }
+    }
+
+    /// Apply the latest CPU/memory sample from the background poller.
+    /// Returns true if it changed.
+    pub fn apply_metrics_updates(&mut self) -> bool {
+        let Some(metrics) = self.metrics_poller.try_recv() else {
+            return false;
+        };
+        self.pending_metrics_refresh = false;
+        let changed = metrics != self.metrics;
+        self.metrics = metrics;
+        changed
+    }
+
     pub fn apply_deletion_results(&mut self) -> bool {
         use crate::session::Status;
 
         if let Some(result) = self.deletion_poller.try_recv_result() {
             if result.success {
+                if let Some(inst) = self.get_instance(&result.session_id) {
+                    crate::webhooks::session_deleted(inst);
+                }
                 self.remove_instance(&result.session_id);
                 self.rebuild_group_trees();
 
@@ -751,6 +887,7 @@ impl HomeView {
                 if let Err(e) = self.save() {
                     tracing::error!("Failed to save after creation: {}", e);
                 }
+                crate::webhooks::session_created(&instance);
 
                 if on_launch_hooks_ran {
                     self.on_launch_hooks_ran.insert(session_id.clone());
@@ -917,8 +1054,12 @@ impl HomeView {
             || self.welcome_dialog.is_some()
             || self.changelog_dialog.is_some()
             || self.info_dialog.is_some()
+            || self.session_info_dialog.is_some()
+            || self.compare_dialog.is_some()
             || self.profile_picker_dialog.is_some()
             || self.send_message_dialog.is_some()
+            || self.message_queue_dialog.is_some()
+            || self.snapshot_dialog.is_some()
             || serve_open
             || self.settings_view.is_some()
             || self.diff_view.is_some()
@@ -1098,7 +1239,9 @@ impl HomeView {
         self.mutate_instance(id, |inst| inst.status = status);
     }
 
-    pub fn save(&self) -> anyhow::Result<()> {
+    /// Persist every profile, merging with changes other processes (CLI,
+    /// `aoe serve`) wrote since our last load or save.
+    pub fn save(&mut self) -> anyhow::Result<()> {
         for (profile_name, storage) in &self.storages {
             let profile_instances: Vec<Instance> = self
                 .instances
@@ -1112,7 +1255,8 @@ impl HomeView {
                 .get(profile_name)
                 .cloned()
                 .unwrap_or_else(|| GroupTree::new_with_groups(&profile_instances, &[]));
-            storage.save_with_groups(&profile_instances, &tree)?;
+            let base = self.sync_bases.entry(profile_name.clone()).or_default();
+            *base = storage.save_merged(base, &profile_instances, &tree)?;
         }
         Ok(())
     }
@@ -1266,6 +1410,9 @@ impl HomeView {
 
             // Refresh sound config
             self.sound_config = config.sound.clone();
+            self.desktop_notifications = config.desktop_notifications.clone();
+            self.usage_config = config.usage.clone();
+            self.metrics_config = config.metrics.clone();
         }
     }
 
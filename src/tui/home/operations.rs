@@ -1,9 +1,11 @@
-//! Session operations for HomeView (create, delete, rename)
+//! Session operations for HomeView (create, delete, rename, message queue)
 
 use crate::session::builder::{self, InstanceParams};
-use crate::session::{list_profiles, GroupTree, Status, Storage};
+use crate::session::{list_profiles, message_queue, GroupTree, Status, Storage};
 use crate::tui::deletion_poller::DeletionRequest;
-use crate::tui::dialogs::{DeleteOptions, GroupDeleteOptions, NewSessionData};
+use crate::tui::dialogs::{
+    DeleteOptions, GroupDeleteOptions, InfoDialog, MessageQueueAction, NewSessionData,
+};
 
 use super::HomeView;
 
@@ -34,6 +36,8 @@ impl HomeView {
             extra_args: data.extra_args,
             command_override: data.command_override,
             extra_repo_paths: data.extra_repo_paths,
+            prompt: data.prompt,
+            template: data.template,
         };
 
         let build_result = builder::build_instance(params, &existing_titles, &target_profile)?;
@@ -55,6 +59,7 @@ impl HomeView {
             }
         }
         self.save()?;
+        crate::webhooks::session_created(&instance);
 
         self.reload()?;
         Ok(session_id)
@@ -196,6 +201,12 @@ impl HomeView {
     /// Used for sessions stuck in the Deleting state where the background
     /// deletion thread never returned a result.
     pub(super) fn force_remove_session(&mut self, session_id: &str) -> anyhow::Result<()> {
+        if let Some(inst) = self.get_instance(session_id) {
+            crate::webhooks::session_deleted(inst);
+        }
+        if let Err(e) = crate::session::ports::release(session_id) {
+            tracing::warn!("Failed to release session ports: {}", e);
+        }
         self.remove_instance(session_id);
         self.rebuild_group_trees();
         self.save()?;
@@ -479,4 +490,89 @@ impl HomeView {
         }
         Ok(())
     }
+
+    /// Apply an add/edit/remove from the message queue dialog, then show the
+    /// queue as it is now (the dispatcher may have sent messages meanwhile).
+    pub(super) fn apply_message_queue_action(
+        &mut self,
+        session_id: &str,
+        action: MessageQueueAction,
+    ) {
+        let Some(inst) = self.get_instance(session_id).cloned() else {
+            self.message_queue_dialog = None;
+            return;
+        };
+        let profile = inst.source_profile.as_str();
+        let result = match action {
+            MessageQueueAction::Add(text) => {
+                message_queue::enqueue(profile, &inst, &text).map(|_| true)
+            }
+            MessageQueueAction::Edit { id, text } => {
+                message_queue::edit(profile, &inst.id, &id, &text)
+            }
+            MessageQueueAction::Remove(id) => message_queue::remove(profile, &inst.id, &id),
+        };
+        match result {
+            Ok(found) => {
+                let messages = message_queue::load(profile, &inst.id).unwrap_or_default();
+                if let Some(dialog) = &mut self.message_queue_dialog {
+                    dialog.set_messages(messages);
+                }
+                if !found {
+                    self.info_dialog = Some(InfoDialog::new(
+                        "Already Sent",
+                        "That message was delivered before the change was saved.",
+                    ));
+                }
+            }
+            Err(e) => {
+                self.message_queue_dialog = None;
+                self.info_dialog = Some(InfoDialog::new(
+                    "Queue Failed",
+                    &format!("Failed to update message queue: {}", e),
+                ));
+            }
+        }
+    }
+
+    /// Snapshot the session's container and show it in the snapshot dialog.
+    pub(super) fn take_snapshot(&mut self, session_id: &str) {
+        let result = self
+            .try_mutate_instance(session_id, |inst| inst.snapshot().map(|_| ()))
+            .and_then(|()| self.save());
+        match result {
+            Ok(()) => {
+                let snapshots = self
+                    .get_instance(session_id)
+                    .and_then(|inst| inst.sandbox_info.as_ref())
+                    .map(|sandbox| sandbox.snapshots.clone())
+                    .unwrap_or_default();
+                if let Some(dialog) = &mut self.snapshot_dialog {
+                    dialog.set_snapshots(snapshots);
+                }
+            }
+            Err(e) => {
+                self.snapshot_dialog = None;
+                self.info_dialog = Some(InfoDialog::new(
+                    "Snapshot Failed",
+                    &format!("Failed to snapshot the sandbox: {}", e),
+                ));
+            }
+        }
+    }
+
+    /// Recreate the session's container from the snapshot `image`.
+    pub(super) fn restore_snapshot(&mut self, session_id: &str, image: &str) {
+        let result = self
+            .try_mutate_instance(session_id, |inst| inst.restore_snapshot(image).map(|_| ()))
+            .and_then(|()| self.save());
+        crate::tmux::refresh_session_cache();
+        if let Err(e) = result {
+            tracing::error!("Failed to restore snapshot: {}", e);
+            self.info_dialog = Some(InfoDialog::new(
+                "Roll Back Failed",
+                &format!("Failed to restore the snapshot: {}", e),
+            ));
+        }
+    }
 }
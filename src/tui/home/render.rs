@@ -9,7 +9,7 @@ use rattles::presets::prelude as spinners;
 
 use super::{
     get_indent, HomeView, TerminalMode, ViewMode, ICON_COLLAPSED, ICON_DELETING, ICON_ERROR,
-    ICON_EXPANDED, ICON_IDLE, ICON_STOPPED, ICON_UNKNOWN,
+    ICON_EXPANDED, ICON_IDLE, ICON_STALLED, ICON_STOPPED, ICON_UNKNOWN,
 };
 use crate::session::config::GroupByMode;
 use crate::session::{Item, Status};
@@ -152,10 +152,28 @@ impl HomeView {
             dialog.render(frame, area, theme);
         }
 
+        // Below the info dialog, which reports queue updates that failed
+        if let Some(dialog) = &self.message_queue_dialog {
+            dialog.render(frame, area, theme);
+        }
+
+        // Below the info dialog, which reports snapshots that failed
+        if let Some(dialog) = &self.snapshot_dialog {
+            dialog.render(frame, area, theme);
+        }
+
         if let Some(dialog) = &self.info_dialog {
             dialog.render(frame, area, theme);
         }
 
+        if let Some(dialog) = &self.session_info_dialog {
+            dialog.render(frame, area, theme);
+        }
+
+        if let Some(dialog) = &self.compare_dialog {
+            dialog.render(frame, area, theme);
+        }
+
         if let Some(dialog) = &self.profile_picker_dialog {
             dialog.render(frame, area, theme);
         }
@@ -248,6 +266,15 @@ impl HomeView {
             let is_match =
                 !self.search_matches.is_empty() && self.search_matches.contains(&abs_idx);
             let mut line = self.render_item_line(item, is_selected, is_match, theme);
+            if let Some(label) = self.row_label(item) {
+                // Right-align the metrics and usage, if they fit after the title
+                let pad = (inner.width as usize).saturating_sub(line.width() + label.len());
+                if pad >= 2 {
+                    line.spans.push(Span::raw(" ".repeat(pad)));
+                    line.spans
+                        .push(Span::styled(label, Style::default().fg(theme.dimmed)));
+                }
+            }
             if is_selected {
                 // Pad to full width so the selection background fills the entire row
                 let pad = (inner.width as usize).saturating_sub(line.width());
@@ -316,6 +343,27 @@ impl HomeView {
         }
     }
 
+    /// CPU/memory columns and token usage or cost shown at the end of a
+    /// session's row
+    fn row_label(&self, item: &Item) -> Option<String> {
+        let Item::Session { id, .. } = item else {
+            return None;
+        };
+        if self.view_mode != ViewMode::Agent {
+            return None;
+        }
+        let metrics = self
+            .metrics
+            .get(id)
+            .filter(|_| self.metrics_config.list_columns)
+            .map(|metrics| metrics.short_label());
+        let usage = self.usage.get(id).map(|usage| usage.short_label());
+        match (metrics, usage) {
+            (Some(metrics), Some(usage)) => Some(format!("{}  {}", metrics, usage)),
+            (metrics, usage) => metrics.or(usage),
+        }
+    }
+
     fn render_item_line(
         &self,
         item: &Item,
@@ -354,6 +402,7 @@ impl HomeView {
                                 Status::Unknown => ICON_UNKNOWN,
                                 Status::Stopped => ICON_STOPPED,
                                 Status::Error => ICON_ERROR,
+                                Status::Stalled => ICON_STALLED,
                                 Status::Starting => spinner_starting(&inst.created_at),
                                 Status::Deleting => ICON_DELETING,
                                 Status::Creating => spinner_starting(&inst.created_at),
@@ -365,6 +414,7 @@ impl HomeView {
                                 Status::Unknown => theme.waiting,
                                 Status::Stopped => theme.dimmed,
                                 Status::Error => theme.error,
+                                Status::Stalled => theme.error,
                                 Status::Starting => theme.dimmed,
                                 Status::Deleting => theme.waiting,
                                 Status::Creating => theme.accent,
@@ -586,6 +636,7 @@ impl HomeView {
                                 frame,
                                 inner,
                                 inst,
+                                self.metrics.get(id),
                                 &self.preview_cache.content,
                                 theme,
                             );
@@ -685,6 +685,49 @@ fn test_r_opens_rename_dialog() {
     assert!(env.view.rename_dialog.is_some());
 }
 
+#[test]
+#[serial]
+fn test_i_opens_session_info_dialog() {
+    let mut env = create_test_env_with_sessions(3);
+    env.view.update_selected();
+    env.view.handle_key(key(KeyCode::Char('i')));
+    assert!(env.view.session_info_dialog.is_some());
+    assert!(env.view.has_dialog());
+
+    env.view.handle_key(key(KeyCode::Esc));
+    assert!(env.view.session_info_dialog.is_none());
+}
+
+#[test]
+#[serial]
+fn test_compare_opens_for_group() {
+    let mut env = create_test_env_with_mixed_sessions();
+    env.view.cursor = env
+        .view
+        .flat_items
+        .iter()
+        .position(|item| matches!(item, Item::Group { path, .. } if path == "work"))
+        .unwrap();
+    env.view.update_selected();
+
+    env.view.handle_key(key(KeyCode::Char('C')));
+    assert!(env.view.compare_dialog.is_some());
+    assert!(env.view.has_dialog());
+
+    env.view.handle_key(key(KeyCode::Esc));
+    assert!(env.view.compare_dialog.is_none());
+}
+
+#[test]
+#[serial]
+fn test_compare_requires_a_group() {
+    let mut env = create_test_env_with_sessions(2);
+    env.view.update_selected();
+    env.view.handle_key(key(KeyCode::Char('C')));
+    assert!(env.view.compare_dialog.is_none());
+    assert!(env.view.info_dialog.is_some());
+}
+
 #[test]
 #[serial]
 fn test_rename_dialog_opened_on_group() {
@@ -960,6 +1003,10 @@ fn create_test_env_with_group_sessions() -> TestEnv {
         created_at: None,
         extra_env: None,
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
     instances.push(inst3);
 
@@ -1033,6 +1080,10 @@ fn test_group_has_containers() {
         created_at: None,
         extra_env: None,
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
 
     let mut inst2 = Instance::new("other-session", "/tmp/other");
@@ -1226,6 +1277,10 @@ fn test_delete_group_with_sessions_respects_container_option() {
         created_at: None,
         extra_env: None,
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
 
     storage.save(&[inst1]).unwrap();
@@ -1845,6 +1900,8 @@ fn test_create_session_in_all_mode_is_findable() {
         extra_env: Vec::new(),
         extra_args: String::new(),
         command_override: String::new(),
+        prompt: None,
+        template: None,
     };
 
     let session_id = view.create_session(data).unwrap();
@@ -1938,6 +1995,36 @@ fn test_save_preserves_per_profile_collapsed_state() {
     );
 }
 
+#[test]
+#[serial]
+fn test_save_merges_sessions_changed_by_other_process() {
+    let mut env = create_test_env_with_sessions(2);
+    let storage = Storage::new("test").unwrap();
+
+    // Another process (e.g. `aoe add`) appends a session and renames one
+    let external = Instance::new("external", "/tmp/external");
+    storage
+        .update(|instances| {
+            instances[1].title = "renamed-by-cli".to_string();
+            instances.push(external.clone());
+            Ok(())
+        })
+        .unwrap();
+
+    // The TUI deletes session0 without having reloaded
+    let first_id = env.view.instances[0].id.clone();
+    env.view.remove_instance(&first_id);
+    env.view.save().unwrap();
+
+    let titles: Vec<String> = storage
+        .load()
+        .unwrap()
+        .into_iter()
+        .map(|i| i.title)
+        .collect();
+    assert_eq!(titles, vec!["renamed-by-cli", "external"]);
+}
+
 #[test]
 #[serial]
 fn test_create_profile_rejects_reserved_name_all() {
@@ -2505,6 +2592,8 @@ fn test_apply_creation_results_returns_session_id() {
         extra_env: Vec::new(),
         extra_args: String::new(),
         command_override: String::new(),
+        prompt: None,
+        template: None,
     };
 
     // Use the async CreationPoller path (pass None hooks, non-sandbox,
@@ -2535,7 +2624,7 @@ fn test_apply_creation_results_returns_session_id() {
 fn test_project_group_name_uses_last_path_segment() {
     use super::project_group_name;
 
-    let mut inst = Instance::new("test", "/home/user/my-project");
+    let inst = Instance::new("test", "/home/user/my-project");
     assert_eq!(project_group_name(&inst), "my-project");
 }
 
@@ -0,0 +1,56 @@
+//! Background CPU and memory sampling
+//!
+//! Reading the process table and `docker stats` takes a moment, so it runs
+//! in its own thread. The sampler lives in that thread between requests to
+//! keep each session's previous CPU time and rolling history.
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::process::metrics::{MetricsSampler, SessionMetrics};
+use crate::session::Instance;
+
+pub struct MetricsPoller {
+    request_tx: mpsc::Sender<Vec<Instance>>,
+    result_rx: mpsc::Receiver<HashMap<String, SessionMetrics>>,
+    _handle: thread::JoinHandle<()>,
+}
+
+impl MetricsPoller {
+    pub fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<Vec<Instance>>();
+        let (result_tx, result_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let mut sampler = MetricsSampler::new();
+            while let Ok(instances) = request_rx.recv() {
+                if result_tx.send(sampler.collect(&instances)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            request_tx,
+            result_rx,
+            _handle: handle,
+        }
+    }
+
+    /// Request a sample of all given instances (non-blocking).
+    pub fn request_refresh(&self, instances: Vec<Instance>) {
+        let _ = self.request_tx.send(instances);
+    }
+
+    /// Try to receive sampling results without blocking.
+    pub fn try_recv(&self) -> Option<HashMap<String, SessionMetrics>> {
+        self.result_rx.try_recv().ok()
+    }
+}
+
+impl Default for MetricsPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,15 +1,18 @@
 //! Terminal User Interface module
 
 mod app;
+mod compare_poller;
 mod components;
 mod creation_poller;
 mod deletion_poller;
 pub mod dialogs;
 pub mod diff;
 mod home;
+mod metrics_poller;
 pub mod settings;
 mod status_poller;
 pub(crate) mod styles;
+mod usage_poller;
 
 pub use app::*;
 
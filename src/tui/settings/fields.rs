@@ -69,6 +69,7 @@ pub enum FieldKey {
     PortMappings,
     VolumeIgnores,
     MountSsh,
+    UseDevcontainer,
     CustomInstruction,
     ContainerRuntime,
     // Tmux
@@ -81,6 +82,7 @@ pub enum FieldKey {
     AgentStatusHooks,
     CustomAgents,
     AgentDetectAs,
+    RecordSessions,
     // Sound
     SoundEnabled,
     SoundMode,
@@ -94,11 +96,16 @@ pub enum FieldKey {
     HookOnCreate,
     HookOnLaunch,
     HookOnDestroy,
+    HookOnWaiting,
+    HookOnIdle,
+    HookOnError,
+    HookOnStop,
     // Web
     WebNotificationsEnabled,
     WebNotifyOnWaiting,
     WebNotifyOnIdle,
     WebNotifyOnError,
+    WebMetricsEnabled,
 }
 
 /// Resolve a field value from global config and optional profile override.
@@ -310,6 +317,15 @@ fn build_web_fields(
             has_override: false,
             inherited_display: None,
         },
+        SettingField {
+            key: FieldKey::WebMetricsEnabled,
+            label: "Prometheus metrics",
+            description: "Serve /metrics for Prometheus. Scrapers need web.metrics_token as a bearer token, or must connect over loopback when no token is set. Takes effect on server restart.",
+            value: FieldValue::Bool(global.web.metrics_enabled),
+            category: SettingsCategory::Web,
+            has_override: false,
+            inherited_display: None,
+        },
     ]
 }
 
@@ -567,6 +583,11 @@ fn build_sandbox_fields(
         global.sandbox.mount_ssh,
         sb.and_then(|s| s.mount_ssh),
     );
+    let (use_devcontainer, o_dc) = resolve_value(
+        scope,
+        global.sandbox.use_devcontainer,
+        sb.and_then(|s| s.use_devcontainer),
+    );
     let (custom_instruction, o_ci) = resolve_optional(
         scope,
         global.sandbox.custom_instruction.clone(),
@@ -587,6 +608,7 @@ fn build_sandbox_fields(
     let container_runtime_selected = match container_runtime {
         ContainerRuntimeName::Docker => 0,
         ContainerRuntimeName::AppleContainer => 1,
+        ContainerRuntimeName::Podman => 2,
     };
 
     let global_terminal_mode_selected = match global.sandbox.default_terminal_mode {
@@ -598,8 +620,10 @@ fn build_sandbox_fields(
     let global_container_runtime_selected = match global.sandbox.container_runtime {
         ContainerRuntimeName::Docker => 0,
         ContainerRuntimeName::AppleContainer => 1,
+        ContainerRuntimeName::Podman => 2,
     };
-    let container_runtime_options = vec!["Docker".into(), "Apple Container".into()];
+    let container_runtime_options =
+        vec!["Docker".into(), "Apple Container".into(), "Podman".into()];
 
     vec![
         SettingField {
@@ -734,6 +758,18 @@ fn build_sandbox_fields(
             has_override: o8,
             inherited_display: inherited_if(o8, FieldValue::Bool(global.sandbox.mount_ssh)),
         },
+        SettingField {
+            key: FieldKey::UseDevcontainer,
+            label: "Use Devcontainer",
+            description: "Build sandbox containers from the repo's .devcontainer/devcontainer.json",
+            value: FieldValue::Bool(use_devcontainer),
+            category: SettingsCategory::Sandbox,
+            has_override: o_dc,
+            inherited_display: inherited_if(
+                o_dc,
+                FieldValue::Bool(global.sandbox.use_devcontainer),
+            ),
+        },
         SettingField {
             key: FieldKey::CustomInstruction,
             label: "Custom Instruction",
@@ -749,7 +785,7 @@ fn build_sandbox_fields(
         SettingField {
             key: FieldKey::ContainerRuntime,
             label: "Container Runtime",
-            description: "Container runtime for sandboxing (Docker or Apple Container on macOS)",
+            description: "Container runtime for sandboxing (Docker, Podman, or Apple Container on macOS)",
             value: FieldValue::Select {
                 selected: container_runtime_selected,
                 options: container_runtime_options.clone(),
@@ -878,6 +914,9 @@ fn build_session_fields(
         session.and_then(|s| s.agent_status_hooks),
     );
 
+    let (record, record_override) =
+        resolve_value(scope, global.session.record, session.and_then(|s| s.record));
+
     // Agent extra args: HashMap -> Vec<String> of "key=value" items for List field
     let (extra_args_map, extra_args_override) = resolve_value(
         scope,
@@ -1075,6 +1114,18 @@ fn build_session_fields(
                 FieldValue::Bool(global.session.agent_status_hooks),
             ),
         },
+        SettingField {
+            key: FieldKey::RecordSessions,
+            label: "Record Sessions",
+            description: "Record agent panes to asciicast files (replay with `aoe session replay`)",
+            value: FieldValue::Bool(record),
+            category: SettingsCategory::Session,
+            has_override: record_override,
+            inherited_display: inherited_if(
+                record_override,
+                FieldValue::Bool(global.session.record),
+            ),
+        },
     ]
 }
 
@@ -1270,6 +1321,26 @@ fn build_hooks_fields(
         global.hooks.on_destroy.clone(),
         hooks.and_then(|h| h.on_destroy.clone()),
     );
+    let (on_waiting, o4) = resolve_value(
+        scope,
+        global.hooks.on_waiting.clone(),
+        hooks.and_then(|h| h.on_waiting.clone()),
+    );
+    let (on_idle, o5) = resolve_value(
+        scope,
+        global.hooks.on_idle.clone(),
+        hooks.and_then(|h| h.on_idle.clone()),
+    );
+    let (on_error, o6) = resolve_value(
+        scope,
+        global.hooks.on_error.clone(),
+        hooks.and_then(|h| h.on_error.clone()),
+    );
+    let (on_stop, o7) = resolve_value(
+        scope,
+        global.hooks.on_stop.clone(),
+        hooks.and_then(|h| h.on_stop.clone()),
+    );
 
     vec![
         SettingField {
@@ -1308,6 +1379,54 @@ fn build_hooks_fields(
                 FieldValue::List(global.hooks.on_destroy.clone()),
             ),
         },
+        SettingField {
+            key: FieldKey::HookOnWaiting,
+            label: "On Waiting",
+            description: "Commands run when the agent starts waiting for input. Session details are in $AOE_* variables.",
+            value: FieldValue::List(on_waiting),
+            category: SettingsCategory::Hooks,
+            has_override: o4,
+            inherited_display: inherited_if(
+                o4,
+                FieldValue::List(global.hooks.on_waiting.clone()),
+            ),
+        },
+        SettingField {
+            key: FieldKey::HookOnIdle,
+            label: "On Idle",
+            description: "Commands run when the agent finishes a turn (e.g. run the test suite).",
+            value: FieldValue::List(on_idle),
+            category: SettingsCategory::Hooks,
+            has_override: o5,
+            inherited_display: inherited_if(
+                o5,
+                FieldValue::List(global.hooks.on_idle.clone()),
+            ),
+        },
+        SettingField {
+            key: FieldKey::HookOnError,
+            label: "On Error",
+            description: "Commands run when the session enters the error state.",
+            value: FieldValue::List(on_error),
+            category: SettingsCategory::Hooks,
+            has_override: o6,
+            inherited_display: inherited_if(
+                o6,
+                FieldValue::List(global.hooks.on_error.clone()),
+            ),
+        },
+        SettingField {
+            key: FieldKey::HookOnStop,
+            label: "On Stop",
+            description: "Commands run when the session is stopped, before its sandbox container stops.",
+            value: FieldValue::List(on_stop),
+            category: SettingsCategory::Hooks,
+            has_override: o7,
+            inherited_display: inherited_if(
+                o7,
+                FieldValue::List(global.hooks.on_stop.clone()),
+            ),
+        },
     ]
 }
 
@@ -1359,12 +1478,14 @@ fn apply_field_to_global(field: &SettingField, config: &mut Config) {
         (FieldKey::AgentStatusHooks, FieldValue::Bool(v)) => {
             config.session.agent_status_hooks = *v;
         }
+        (FieldKey::RecordSessions, FieldValue::Bool(v)) => config.session.record = *v,
         (FieldKey::DefaultImage, FieldValue::Text(v)) => config.sandbox.default_image = v.clone(),
         (FieldKey::Environment, FieldValue::List(v)) => config.sandbox.environment = v.clone(),
         (FieldKey::ExtraVolumes, FieldValue::List(v)) => config.sandbox.extra_volumes = v.clone(),
         (FieldKey::PortMappings, FieldValue::List(v)) => config.sandbox.port_mappings = v.clone(),
         (FieldKey::VolumeIgnores, FieldValue::List(v)) => config.sandbox.volume_ignores = v.clone(),
         (FieldKey::MountSsh, FieldValue::Bool(v)) => config.sandbox.mount_ssh = *v,
+        (FieldKey::UseDevcontainer, FieldValue::Bool(v)) => config.sandbox.use_devcontainer = *v,
         (FieldKey::SandboxAutoCleanup, FieldValue::Bool(v)) => config.sandbox.auto_cleanup = *v,
         (FieldKey::CpuLimit, FieldValue::OptionalText(v)) => {
             config.sandbox.cpu_limit = v.clone();
@@ -1384,7 +1505,8 @@ fn apply_field_to_global(field: &SettingField, config: &mut Config) {
         (FieldKey::ContainerRuntime, FieldValue::Select { selected, .. }) => {
             config.sandbox.container_runtime = match selected {
                 0 => ContainerRuntimeName::Docker,
-                _ => ContainerRuntimeName::AppleContainer,
+                1 => ContainerRuntimeName::AppleContainer,
+                _ => ContainerRuntimeName::Podman,
             };
         }
         // Tmux
@@ -1451,6 +1573,10 @@ fn apply_field_to_global(field: &SettingField, config: &mut Config) {
         (FieldKey::HookOnCreate, FieldValue::List(v)) => config.hooks.on_create = v.clone(),
         (FieldKey::HookOnLaunch, FieldValue::List(v)) => config.hooks.on_launch = v.clone(),
         (FieldKey::HookOnDestroy, FieldValue::List(v)) => config.hooks.on_destroy = v.clone(),
+        (FieldKey::HookOnWaiting, FieldValue::List(v)) => config.hooks.on_waiting = v.clone(),
+        (FieldKey::HookOnIdle, FieldValue::List(v)) => config.hooks.on_idle = v.clone(),
+        (FieldKey::HookOnError, FieldValue::List(v)) => config.hooks.on_error = v.clone(),
+        (FieldKey::HookOnStop, FieldValue::List(v)) => config.hooks.on_stop = v.clone(),
         // Web
         (FieldKey::WebNotificationsEnabled, FieldValue::Bool(v)) => {
             config.web.notifications_enabled = *v;
@@ -1464,6 +1590,9 @@ fn apply_field_to_global(field: &SettingField, config: &mut Config) {
         (FieldKey::WebNotifyOnError, FieldValue::Bool(v)) => {
             config.web.notify_on_error = *v;
         }
+        (FieldKey::WebMetricsEnabled, FieldValue::Bool(v)) => {
+            config.web.metrics_enabled = *v;
+        }
         _ => {}
     }
 }
@@ -1547,6 +1676,9 @@ fn apply_field_to_profile(field: &SettingField, _global: &Config, config: &mut P
         (FieldKey::MountSsh, FieldValue::Bool(v)) => {
             set_profile_override(*v, &mut config.sandbox, |s, val| s.mount_ssh = val);
         }
+        (FieldKey::UseDevcontainer, FieldValue::Bool(v)) => {
+            set_profile_override(*v, &mut config.sandbox, |s, val| s.use_devcontainer = val);
+        }
         (FieldKey::SandboxAutoCleanup, FieldValue::Bool(v)) => {
             set_profile_override(*v, &mut config.sandbox, |s, val| s.auto_cleanup = val);
         }
@@ -1583,7 +1715,8 @@ fn apply_field_to_profile(field: &SettingField, _global: &Config, config: &mut P
         (FieldKey::ContainerRuntime, FieldValue::Select { selected, .. }) => {
             let runtime = match selected {
                 0 => ContainerRuntimeName::Docker,
-                _ => ContainerRuntimeName::AppleContainer,
+                1 => ContainerRuntimeName::AppleContainer,
+                _ => ContainerRuntimeName::Podman,
             };
             set_profile_override(runtime, &mut config.sandbox, |s, val| {
                 s.container_runtime = val
@@ -1623,6 +1756,9 @@ fn apply_field_to_profile(field: &SettingField, _global: &Config, config: &mut P
                 s.agent_status_hooks = val;
             });
         }
+        (FieldKey::RecordSessions, FieldValue::Bool(v)) => {
+            set_profile_override(*v, &mut config.session, |s, val| s.record = val);
+        }
         (FieldKey::AgentExtraArgs, FieldValue::List(v)) => {
             let map = parse_key_value_list(v);
             use crate::session::SessionConfigOverride;
@@ -1712,6 +1848,18 @@ fn apply_field_to_profile(field: &SettingField, _global: &Config, config: &mut P
         (FieldKey::HookOnDestroy, FieldValue::List(v)) => {
             set_profile_override(v.clone(), &mut config.hooks, |s, val| s.on_destroy = val);
         }
+        (FieldKey::HookOnWaiting, FieldValue::List(v)) => {
+            set_profile_override(v.clone(), &mut config.hooks, |s, val| s.on_waiting = val);
+        }
+        (FieldKey::HookOnIdle, FieldValue::List(v)) => {
+            set_profile_override(v.clone(), &mut config.hooks, |s, val| s.on_idle = val);
+        }
+        (FieldKey::HookOnError, FieldValue::List(v)) => {
+            set_profile_override(v.clone(), &mut config.hooks, |s, val| s.on_error = val);
+        }
+        (FieldKey::HookOnStop, FieldValue::List(v)) => {
+            set_profile_override(v.clone(), &mut config.hooks, |s, val| s.on_stop = val);
+        }
         _ => {}
     }
 }
@@ -646,6 +646,11 @@ impl SettingsView {
                     s.agent_status_hooks = None;
                 }
             }
+            FieldKey::RecordSessions => {
+                if let Some(ref mut s) = config.session {
+                    s.record = None;
+                }
+            }
             FieldKey::DefaultTerminalMode => {
                 if let Some(ref mut s) = config.sandbox {
                     s.default_terminal_mode = None;
@@ -671,6 +676,11 @@ impl SettingsView {
                     s.mount_ssh = None;
                 }
             }
+            FieldKey::UseDevcontainer => {
+                if let Some(ref mut s) = config.sandbox {
+                    s.use_devcontainer = None;
+                }
+            }
             FieldKey::CpuLimit => {
                 if let Some(ref mut s) = config.sandbox {
                     s.cpu_limit = None;
@@ -748,11 +758,32 @@ impl SettingsView {
                     h.on_destroy = None;
                 }
             }
+            FieldKey::HookOnWaiting => {
+                if let Some(ref mut h) = config.hooks {
+                    h.on_waiting = None;
+                }
+            }
+            FieldKey::HookOnIdle => {
+                if let Some(ref mut h) = config.hooks {
+                    h.on_idle = None;
+                }
+            }
+            FieldKey::HookOnError => {
+                if let Some(ref mut h) = config.hooks {
+                    h.on_error = None;
+                }
+            }
+            FieldKey::HookOnStop => {
+                if let Some(ref mut h) = config.hooks {
+                    h.on_stop = None;
+                }
+            }
             // Web settings are server-global; no per-profile override to clear.
             FieldKey::WebNotificationsEnabled
             | FieldKey::WebNotifyOnWaiting
             | FieldKey::WebNotifyOnIdle
-            | FieldKey::WebNotifyOnError => {}
+            | FieldKey::WebNotifyOnError
+            | FieldKey::WebMetricsEnabled => {}
         }
 
         // Sync repo_config when in Repo scope
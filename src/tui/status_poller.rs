@@ -9,15 +9,20 @@
 //!    O(3N) per-instance `display-message` subprocesses with O(1).
 //!
 //! 2. **Adaptive polling tiers**: Sessions are polled at different frequencies
-//!    based on their status. Hot (Running/Waiting/Starting) every cycle, Warm
-//!    (Idle/Unknown) every 5 cycles, Cold (Error) every 60 cycles, Frozen
-//!    (Stopped/Deleting) never.
+//!    based on their status. Hot (Running/Waiting/Stalled/Starting) every
+//!    cycle, Warm (Idle/Unknown) every 5 cycles, Cold (Error) every 60
+//!    cycles, Frozen (Stopped/Deleting) never.
+//!
+//! Transitions observed here are appended to the profile's status history
+//! and reported to any configured webhooks from the polling thread, keeping
+//! that work off the UI thread.
 
 use std::collections::HashMap;
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::session::history::{self, StatusEvent, StatusSource};
 use crate::session::{Instance, Status};
 
 /// Adaptive polling intervals (in cycles). 0 = never poll.
@@ -27,7 +32,7 @@ const TIER_COLD: u64 = 60;
 
 fn polling_tier(status: Status) -> u64 {
     match status {
-        Status::Running | Status::Waiting | Status::Starting => TIER_HOT,
+        Status::Running | Status::Waiting | Status::Stalled | Status::Starting => TIER_HOT,
         Status::Idle | Status::Unknown => TIER_WARM,
         Status::Error => TIER_COLD,
         Status::Stopped | Status::Deleting | Status::Creating => 0,
@@ -144,6 +149,12 @@ impl StatusPoller {
                         if let Some(sandbox) = &inst.sandbox_info {
                             if let Some(&running) = container_states.get(&sandbox.container_name) {
                                 if !running {
+                                    record_transition(
+                                        &inst,
+                                        inst.status,
+                                        Status::Error,
+                                        StatusSource::Container,
+                                    );
                                     return Some(StatusUpdate {
                                         id: inst.id,
                                         status: Status::Error,
@@ -158,7 +169,21 @@ impl StatusPoller {
                     let session_name = crate::tmux::Session::generate_name(&inst.id, &inst.title);
                     let metadata = pane_metadata.get(&session_name);
 
+                    let old_status = inst.status;
                     inst.update_status_with_metadata(metadata);
+                    record_transition(&inst, old_status, inst.status, inst.status_source);
+                    if inst.status == Status::Stalled && old_status != Status::Stalled {
+                        let mut stalled = inst.clone();
+                        std::thread::spawn(move || {
+                            if let Err(e) = crate::session::watchdog::recover(&mut stalled) {
+                                tracing::warn!(
+                                    "watchdog: recovery for '{}' failed: {}",
+                                    stalled.title,
+                                    e
+                                );
+                            }
+                        });
+                    }
 
                     Some(StatusUpdate {
                         id: inst.id,
@@ -186,6 +211,16 @@ impl StatusPoller {
     }
 }
 
+fn record_transition(inst: &Instance, old: Status, new: Status, source: StatusSource) {
+    crate::webhooks::status_changed(inst, old, new);
+    crate::session::status_hooks::status_changed(inst, old, new);
+    if old == new || inst.source_profile.is_empty() {
+        return;
+    }
+    let event = StatusEvent::new(&inst.id, old, new, source);
+    history::record_best_effort(&inst.source_profile, &event);
+}
+
 impl Default for StatusPoller {
     fn default() -> Self {
         Self::new()
@@ -221,6 +256,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::modulo_one)]
     fn test_tier_cycle_alignment() {
         // Hot sessions are polled every cycle
         for cycle in 1..=10u64 {
@@ -238,6 +274,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::modulo_one)]
     fn test_first_cycle_polls_all_tiers() {
         // cycle_count starts at TIER_COLD - 1, first cycle wraps to TIER_COLD
         let first_cycle = (TIER_COLD - 1).wrapping_add(1);
@@ -0,0 +1,59 @@
+//! Background token usage polling
+//!
+//! Reading agent transcripts can take a while for long sessions, so it runs
+//! in its own thread. The tracker lives in that thread between requests and
+//! only reads what was appended since the previous poll.
+
+use std::collections::HashMap;
+use std::sync::mpsc;
+use std::thread;
+
+use crate::session::Instance;
+use crate::usage::{SessionUsage, Sources, UsageConfig, UsageTracker};
+
+pub struct UsagePoller {
+    request_tx: mpsc::Sender<(Vec<Instance>, UsageConfig)>,
+    result_rx: mpsc::Receiver<HashMap<String, SessionUsage>>,
+    _handle: thread::JoinHandle<()>,
+}
+
+impl UsagePoller {
+    pub fn new() -> Self {
+        let (request_tx, request_rx) = mpsc::channel::<(Vec<Instance>, UsageConfig)>();
+        let (result_tx, result_rx) = mpsc::channel();
+
+        let handle = thread::spawn(move || {
+            let mut tracker = UsageTracker::new(Sources::detect());
+            while let Ok((instances, config)) = request_rx.recv() {
+                if result_tx
+                    .send(tracker.collect(&instances, &config))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            request_tx,
+            result_rx,
+            _handle: handle,
+        }
+    }
+
+    /// Request a usage refresh for all given instances (non-blocking).
+    pub fn request_refresh(&self, instances: Vec<Instance>, config: UsageConfig) {
+        let _ = self.request_tx.send((instances, config));
+    }
+
+    /// Try to receive usage results without blocking.
+    pub fn try_recv(&self) -> Option<HashMap<String, SessionUsage>> {
+        self.result_rx.try_recv().ok()
+    }
+}
+
+impl Default for UsagePoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
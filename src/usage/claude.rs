@@ -0,0 +1,144 @@
+//! Claude Code transcripts: `<config dir>/projects/<encoded cwd>/*.jsonl`,
+//! one JSON entry per line. Assistant entries carry the API usage of the
+//! request; a streamed message is written once per content block with the
+//! same usage, so entries are deduplicated by message and request ID.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use super::{LineParser, ModelTokens, Tokens};
+
+/// Transcript directory for sessions started in `cwd`. Claude Code replaces
+/// every non-alphanumeric character of the path with `-`.
+pub fn project_dir(claude_dir: &Path, cwd: &str) -> PathBuf {
+    let encoded: String = cwd
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    claude_dir.join("projects").join(encoded)
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
+    message: Option<Message>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    id: Option<String>,
+    model: Option<String>,
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+}
+
+#[derive(Default)]
+pub struct Parser {
+    seen: HashSet<String>,
+    usage: ModelTokens,
+}
+
+impl LineParser for Parser {
+    fn feed(&mut self, line: &str, since: DateTime<Utc>) {
+        if !line.contains("\"usage\"") {
+            return;
+        }
+        let Ok(entry) = serde_json::from_str::<Entry>(line) else {
+            return;
+        };
+        let Some(message) = entry.message else {
+            return;
+        };
+        let (Some(usage), Some(model)) = (message.usage, message.model) else {
+            return;
+        };
+        if entry.timestamp.is_some_and(|t| t < since) {
+            return;
+        }
+        if message.id.is_some() || entry.request_id.is_some() {
+            let key = format!(
+                "{}:{}",
+                message.id.unwrap_or_default(),
+                entry.request_id.unwrap_or_default()
+            );
+            if !self.seen.insert(key) {
+                return;
+            }
+        }
+
+        let tokens = Tokens {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            cache_write_tokens: usage.cache_creation_input_tokens,
+        };
+        if !tokens.is_empty() {
+            self.usage.entry(model).or_default().add(&tokens);
+        }
+    }
+
+    fn usage(&self) -> &ModelTokens {
+        &self.usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn since() -> DateTime<Utc> {
+        "2025-06-01T00:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_project_dir_encoding() {
+        assert_eq!(
+            project_dir(Path::new("/home/u/.claude"), "/home/u/src/my_app.v2"),
+            Path::new("/home/u/.claude/projects/-home-u-src-my-app-v2")
+        );
+    }
+
+    #[test]
+    fn test_parser_dedupes_streamed_entries_and_skips_old_ones() {
+        let line = |ts: &str, msg: &str, output: u64| {
+            format!(
+                r#"{{"type":"assistant","timestamp":"{}","requestId":"req_1","message":{{"id":"{}","model":"claude-sonnet-4-5","usage":{{"input_tokens":10,"output_tokens":{},"cache_read_input_tokens":100,"cache_creation_input_tokens":20}}}}}}"#,
+                ts, msg, output
+            )
+        };
+        let mut parser = Parser::default();
+        parser.feed(&line("2025-06-02T10:00:00Z", "msg_a", 5), since());
+        parser.feed(&line("2025-06-02T10:00:01Z", "msg_a", 5), since());
+        parser.feed(&line("2025-06-02T10:01:00Z", "msg_b", 7), since());
+        parser.feed(&line("2025-05-30T10:00:00Z", "msg_old", 1000), since());
+        parser.feed(r#"{"type":"user","message":{"role":"user"}}"#, since());
+        parser.feed("not json", since());
+
+        let tokens = parser.usage()["claude-sonnet-4-5"];
+        assert_eq!(
+            tokens,
+            Tokens {
+                input_tokens: 20,
+                output_tokens: 12,
+                cache_read_tokens: 200,
+                cache_write_tokens: 40,
+            }
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Codex rollouts: `<codex home>/sessions/YYYY/MM/DD/rollout-*.jsonl`. The
+//! first entry (`session_meta`) records the working directory, and
+//! `token_count` events carry the running total for the whole rollout, so
+//! usage is the difference between consecutive totals.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Days, Utc};
+use serde::Deserialize;
+
+use super::{LineParser, ModelTokens, Tokens};
+
+/// Model name used until a `turn_context` entry names one
+const UNKNOWN_MODEL: &str = "codex";
+
+/// Rollout files in the day directories from `since` to today.
+pub fn rollout_files(codex_dir: &Path, since: DateTime<Utc>) -> Vec<PathBuf> {
+    let sessions = codex_dir.join("sessions");
+    // Day directories use local dates; widen by a day on each side
+    let mut day = since.date_naive() - Days::new(1);
+    let last = Utc::now().date_naive() + Days::new(1);
+    let mut files = Vec::new();
+    while day <= last {
+        let dir = sessions.join(day.format("%Y/%m/%d").to_string());
+        if let Ok(entries) = std::fs::read_dir(&dir) {
+            files.extend(
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().is_some_and(|e| e == "jsonl")),
+            );
+        }
+        day = day + Days::new(1);
+    }
+    files
+}
+
+/// Working directory of a rollout, from its `session_meta` entry.
+pub fn rollout_cwd(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(5)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Entry>(&line).ok())
+        .find(|entry| entry.kind == "session_meta")
+        .and_then(|entry| entry.payload.cwd)
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    timestamp: Option<DateTime<Utc>>,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    payload: Payload,
+}
+
+#[derive(Default, Deserialize)]
+struct Payload {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    cwd: Option<String>,
+    model: Option<String>,
+    info: Option<TokenInfo>,
+}
+
+#[derive(Deserialize)]
+struct TokenInfo {
+    total_token_usage: Option<TokenTotals>,
+}
+
+#[derive(Deserialize)]
+struct TokenTotals {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    cached_input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+#[derive(Default)]
+pub struct Parser {
+    model: Option<String>,
+    last_total: Tokens,
+    usage: ModelTokens,
+}
+
+impl LineParser for Parser {
+    fn feed(&mut self, line: &str, since: DateTime<Utc>) {
+        if !line.contains("\"turn_context\"") && !line.contains("\"token_count\"") {
+            return;
+        }
+        let Ok(entry) = serde_json::from_str::<Entry>(line) else {
+            return;
+        };
+        if entry.kind == "turn_context" {
+            if let Some(model) = entry.payload.model {
+                self.model = Some(model);
+            }
+            return;
+        }
+        if entry.payload.kind.as_deref() != Some("token_count") {
+            return;
+        }
+        let Some(totals) = entry.payload.info.and_then(|i| i.total_token_usage) else {
+            return;
+        };
+
+        // Codex counts cached input as part of the input
+        let total = Tokens {
+            input_tokens: totals
+                .input_tokens
+                .saturating_sub(totals.cached_input_tokens),
+            output_tokens: totals.output_tokens,
+            cache_read_tokens: totals.cached_input_tokens,
+            cache_write_tokens: 0,
+        };
+        let delta = total.saturating_sub(&self.last_total);
+        self.last_total = total;
+        if entry.timestamp.is_some_and(|t| t < since) || delta.is_empty() {
+            return;
+        }
+        let model = self.model.as_deref().unwrap_or(UNKNOWN_MODEL);
+        self.usage.entry(model.to_string()).or_default().add(&delta);
+    }
+
+    fn usage(&self) -> &ModelTokens {
+        &self.usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token_count(ts: &str, input: u64, cached: u64, output: u64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","type":"event_msg","payload":{{"type":"token_count","info":{{"total_token_usage":{{"input_tokens":{},"cached_input_tokens":{},"output_tokens":{},"reasoning_output_tokens":0,"total_tokens":0}}}}}}}}"#,
+            ts, input, cached, output
+        )
+    }
+
+    #[test]
+    fn test_parser_counts_deltas_after_since() {
+        let since: DateTime<Utc> = "2025-06-01T12:00:00Z".parse().unwrap();
+        let mut parser = Parser::default();
+        parser.feed(
+            r#"{"timestamp":"2025-06-01T11:00:00Z","type":"session_meta","payload":{"cwd":"/src/app"}}"#,
+            since,
+        );
+        parser.feed(&token_count("2025-06-01T11:30:00Z", 1000, 0, 100), since);
+        parser.feed(
+            r#"{"timestamp":"2025-06-01T12:01:00Z","type":"turn_context","payload":{"cwd":"/src/app","model":"gpt-5-codex"}}"#,
+            since,
+        );
+        parser.feed(&token_count("2025-06-01T12:02:00Z", 3000, 1500, 250), since);
+        parser.feed(
+            r#"{"timestamp":"2025-06-01T12:03:00Z","type":"event_msg","payload":{"type":"token_count","info":null}}"#,
+            since,
+        );
+
+        assert_eq!(parser.usage().len(), 1);
+        assert_eq!(
+            parser.usage()["gpt-5-codex"],
+            Tokens {
+                input_tokens: 500,
+                output_tokens: 150,
+                cache_read_tokens: 1500,
+                cache_write_tokens: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_rollout_files_and_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let since = Utc::now();
+        let day_dir = dir
+            .path()
+            .join("sessions")
+            .join(since.date_naive().format("%Y/%m/%d").to_string());
+        std::fs::create_dir_all(&day_dir).unwrap();
+        let rollout = day_dir.join("rollout-1.jsonl");
+        std::fs::write(
+            &rollout,
+            "{\"timestamp\":\"2025-06-01T11:00:00Z\",\"type\":\"session_meta\",\"payload\":{\"id\":\"x\",\"cwd\":\"/src/app\"}}\n",
+        )
+        .unwrap();
+        std::fs::write(day_dir.join("notes.txt"), "").unwrap();
+
+        assert_eq!(rollout_files(dir.path(), since), vec![rollout.clone()]);
+        assert_eq!(rollout_cwd(&rollout).as_deref(), Some("/src/app"));
+    }
+}
@@ -0,0 +1,134 @@
+//! Gemini CLI chats: `~/.gemini/tmp/<sha256 of cwd>/chats/session-*.json`.
+//! Each file is a single JSON document rewritten as the chat grows, with
+//! per-message token counts.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use super::{ModelTokens, Tokens};
+
+/// Chat directory for sessions started in `cwd`.
+pub fn chats_dir(gemini_dir: &Path, cwd: &str) -> PathBuf {
+    let hash = Sha256::digest(cwd.as_bytes());
+    let hex: String = hash.iter().map(|b| format!("{:02x}", b)).collect();
+    gemini_dir.join("tmp").join(hex).join("chats")
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Chat {
+    start_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    messages: Vec<Message>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    timestamp: Option<DateTime<Utc>>,
+    model: Option<String>,
+    tokens: Option<MessageTokens>,
+}
+
+#[derive(Deserialize)]
+struct MessageTokens {
+    #[serde(default)]
+    input: u64,
+    #[serde(default)]
+    output: u64,
+    #[serde(default)]
+    cached: u64,
+    #[serde(default)]
+    thoughts: u64,
+    #[serde(default)]
+    tool: u64,
+}
+
+/// When the chat started: its `startTime`, else its first message.
+pub fn start_time(content: &str) -> Option<DateTime<Utc>> {
+    let chat = serde_json::from_str::<Chat>(content).ok()?;
+    chat.start_time
+        .or_else(|| chat.messages.iter().find_map(|m| m.timestamp))
+}
+
+/// Usage of the messages in a chat file sent at or after `since`.
+pub fn parse(content: &str, since: DateTime<Utc>) -> ModelTokens {
+    let mut usage = ModelTokens::new();
+    let Ok(chat) = serde_json::from_str::<Chat>(content) else {
+        return usage;
+    };
+    for message in chat.messages {
+        let Some(tokens) = message.tokens else {
+            continue;
+        };
+        if message.timestamp.is_some_and(|t| t < since) {
+            continue;
+        }
+        // The prompt count includes cached tokens; thinking is billed as output
+        let tokens = Tokens {
+            input_tokens: tokens.input.saturating_sub(tokens.cached) + tokens.tool,
+            output_tokens: tokens.output + tokens.thoughts,
+            cache_read_tokens: tokens.cached,
+            cache_write_tokens: 0,
+        };
+        let model = message.model.unwrap_or_else(|| "gemini".to_string());
+        usage.entry(model).or_default().add(&tokens);
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chats_dir_is_keyed_by_path_hash() {
+        let dir = chats_dir(Path::new("/home/u/.gemini"), "/src/app");
+        let hash = dir.parent().unwrap().file_name().unwrap().to_str().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_ne!(chats_dir(Path::new("/home/u/.gemini"), "/src/other"), dir);
+    }
+
+    #[test]
+    fn test_parse_messages_after_since() {
+        let content = r#"{
+            "sessionId": "s1",
+            "messages": [
+                {"type": "user", "timestamp": "2025-06-02T10:00:00Z", "content": "hi"},
+                {"type": "gemini", "timestamp": "2025-06-02T10:00:05Z", "model": "gemini-2.5-pro",
+                 "tokens": {"input": 1000, "output": 50, "cached": 400, "thoughts": 30, "tool": 10, "total": 1090}},
+                {"type": "gemini", "timestamp": "2025-05-01T10:00:05Z", "model": "gemini-2.5-pro",
+                 "tokens": {"input": 99999, "output": 99999}}
+            ]
+        }"#;
+        let usage = parse(content, "2025-06-01T00:00:00Z".parse().unwrap());
+        assert_eq!(
+            usage["gemini-2.5-pro"],
+            Tokens {
+                input_tokens: 610,
+                output_tokens: 80,
+                cache_read_tokens: 400,
+                cache_write_tokens: 0,
+            }
+        );
+        assert!(parse("{", Utc::now()).is_empty());
+    }
+
+    #[test]
+    fn test_start_time() {
+        let with_start = r#"{"startTime": "2025-06-02T09:59:00Z", "messages": [
+            {"type": "user", "timestamp": "2025-06-02T10:00:00Z"}]}"#;
+        assert_eq!(
+            start_time(with_start),
+            Some("2025-06-02T09:59:00Z".parse().unwrap())
+        );
+        let without = r#"{"messages": [{"type": "user", "timestamp": "2025-06-02T10:00:00Z"}]}"#;
+        assert_eq!(
+            start_time(without),
+            Some("2025-06-02T10:00:00Z".parse().unwrap())
+        );
+        assert_eq!(start_time(r#"{"messages": []}"#), None);
+    }
+}
@@ -0,0 +1,641 @@
+//! Token and cost usage per session
+//!
+//! Claude Code, Codex and Gemini CLI keep local transcripts that record the
+//! tokens of every model call. A session's transcripts are found by the
+//! directory the agent runs in and must have started after the session was
+//! created. With several sessions of an agent in one directory, a transcript
+//! belongs to the latest one created before it started, so no transcript is
+//! counted twice. Cost is estimated from a per-model price table
+//! (`[usage.prices]`, on top of built-in list prices).
+//!
+//! Sandboxed sessions keep their transcripts inside the container, so no
+//! usage is reported for them.
+
+mod claude;
+mod codex;
+mod gemini;
+mod pricing;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::session::Instance;
+
+pub use pricing::{ModelPrice, UsageConfig};
+
+/// Tokens per model name
+pub type ModelTokens = BTreeMap<String, Tokens>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tokens {
+    /// Uncached input
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cache_write_tokens: u64,
+}
+
+impl Tokens {
+    pub fn add(&mut self, other: &Tokens) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cache_write_tokens += other.cache_write_tokens;
+    }
+
+    fn saturating_sub(&self, other: &Tokens) -> Tokens {
+        Tokens {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_read_tokens: self
+                .cache_read_tokens
+                .saturating_sub(other.cache_read_tokens),
+            cache_write_tokens: self
+                .cache_write_tokens
+                .saturating_sub(other.cache_write_tokens),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_read_tokens + self.cache_write_tokens
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Token usage and estimated cost of one session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionUsage {
+    #[serde(flatten)]
+    pub tokens: Tokens,
+    /// Estimated cost in USD of the models with a known price; `None` if
+    /// none of them has one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_usd: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub models: Vec<String>,
+}
+
+impl SessionUsage {
+    pub fn from_models(usage: &ModelTokens, config: &UsageConfig) -> Self {
+        let mut tokens = Tokens::default();
+        for model_tokens in usage.values() {
+            tokens.add(model_tokens);
+        }
+        Self {
+            tokens,
+            cost_usd: config.cost(usage),
+            models: usage.keys().cloned().collect(),
+        }
+    }
+
+    /// Compact label for the session list: the cost if known, else the
+    /// token count.
+    pub fn short_label(&self) -> String {
+        match self.cost_usd {
+            Some(cost) => format_cost(cost),
+            None => format!("{} tok", format_tokens(self.tokens.total())),
+        }
+    }
+}
+
+/// `950`, `12.3k`, `4.1M`
+pub fn format_tokens(n: u64) -> String {
+    match n {
+        0..=999 => n.to_string(),
+        1_000..=999_999 => trim_decimal(n as f64 / 1_000.0, "k"),
+        _ => trim_decimal(n as f64 / 1_000_000.0, "M"),
+    }
+}
+
+fn trim_decimal(value: f64, unit: &str) -> String {
+    if value >= 100.0 {
+        format!("{:.0}{}", value, unit)
+    } else {
+        format!("{:.1}{}", value, unit)
+    }
+}
+
+/// `$0.42`, `$12.80`, `$1234`
+pub fn format_cost(usd: f64) -> String {
+    if usd >= 1000.0 {
+        format!("${:.0}", usd)
+    } else {
+        format!("${:.2}", usd)
+    }
+}
+
+/// Where each agent keeps its transcripts. `None` when it can't be located.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    pub claude: Option<PathBuf>,
+    pub codex: Option<PathBuf>,
+    pub gemini: Option<PathBuf>,
+}
+
+impl Sources {
+    /// The default locations, honouring `claude.config_dir`,
+    /// `CLAUDE_CONFIG_DIR` and `CODEX_HOME`.
+    pub fn detect() -> Self {
+        let home = dirs::home_dir();
+        let env_dir = |name: &str| std::env::var_os(name).map(PathBuf::from);
+        Self {
+            claude: crate::session::get_claude_config_dir()
+                .or_else(|| env_dir("CLAUDE_CONFIG_DIR"))
+                .or_else(|| home.as_ref().map(|h| h.join(".claude"))),
+            codex: env_dir("CODEX_HOME").or_else(|| home.as_ref().map(|h| h.join(".codex"))),
+            gemini: home.as_ref().map(|h| h.join(".gemini")),
+        }
+    }
+}
+
+/// A parser fed one transcript line at a time.
+trait LineParser: Default {
+    fn feed(&mut self, line: &str, since: DateTime<Utc>);
+    fn usage(&self) -> &ModelTokens;
+}
+
+/// An append-only JSONL transcript, read from where the last refresh
+/// stopped.
+#[derive(Default)]
+struct JsonlFile<P> {
+    offset: u64,
+    parser: P,
+}
+
+impl<P: LineParser> JsonlFile<P> {
+    fn refresh(&mut self, path: &Path, since: DateTime<Utc>) -> std::io::Result<()> {
+        let mut file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            // Rewritten rather than appended to: start over
+            *self = Self::default();
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+        // Leave a partially written last line for the next refresh
+        let Some(end) = buf.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        for line in String::from_utf8_lossy(&buf[..end]).lines() {
+            self.parser.feed(line, since);
+        }
+        self.offset += end as u64 + 1;
+        Ok(())
+    }
+}
+
+/// A JSON transcript rewritten as a whole, re-read when it changes.
+#[derive(Default)]
+struct JsonFile {
+    stamp: Option<(u64, SystemTime)>,
+    usage: ModelTokens,
+}
+
+impl JsonFile {
+    fn refresh(&mut self, path: &Path, since: DateTime<Utc>) -> std::io::Result<()> {
+        let meta = std::fs::metadata(path)?;
+        let stamp = Some((meta.len(), meta.modified()?));
+        if stamp != self.stamp {
+            self.usage = gemini::parse(&std::fs::read_to_string(path)?, since);
+            self.stamp = stamp;
+        }
+        Ok(())
+    }
+}
+
+enum Transcript {
+    Claude(JsonlFile<claude::Parser>),
+    Codex(JsonlFile<codex::Parser>),
+    Gemini(JsonFile),
+}
+
+impl Transcript {
+    fn refresh(&mut self, path: &Path, since: DateTime<Utc>) -> std::io::Result<()> {
+        match self {
+            Transcript::Claude(file) => file.refresh(path, since),
+            Transcript::Codex(file) => file.refresh(path, since),
+            Transcript::Gemini(file) => file.refresh(path, since),
+        }
+    }
+
+    fn usage(&self) -> &ModelTokens {
+        match self {
+            Transcript::Claude(file) => file.parser.usage(),
+            Transcript::Codex(file) => file.parser.usage(),
+            Transcript::Gemini(file) => &file.usage,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Agent {
+    Claude,
+    Codex,
+    Gemini,
+}
+
+/// Keeps parsed transcripts between refreshes, so polling only reads what
+/// the agents appended since the last call.
+#[derive(Default)]
+pub struct UsageTracker {
+    sources: Sources,
+    /// Keyed by session ID and transcript path
+    transcripts: HashMap<(String, PathBuf), Transcript>,
+    /// Working directory of each Codex rollout seen so far
+    codex_cwds: HashMap<PathBuf, Option<String>>,
+    /// Time of the first entry of each transcript seen so far
+    starts: HashMap<PathBuf, DateTime<Utc>>,
+}
+
+impl UsageTracker {
+    pub fn new(sources: Sources) -> Self {
+        Self {
+            sources,
+            ..Default::default()
+        }
+    }
+
+    /// Usage of every session that has transcripts, by session ID. State
+    /// kept for sessions no longer in `instances` is dropped.
+    pub fn collect(
+        &mut self,
+        instances: &[Instance],
+        config: &UsageConfig,
+    ) -> HashMap<String, SessionUsage> {
+        let ids: HashSet<&str> = instances.iter().map(|i| i.id.as_str()).collect();
+        self.transcripts
+            .retain(|(id, _), _| ids.contains(id.as_str()));
+        instances
+            .iter()
+            .filter_map(|inst| {
+                Some((
+                    inst.id.clone(),
+                    self.session_usage(inst, instances, config)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Usage of one session, or `None` if usage tracking is off, the agent
+    /// isn't supported or no transcript was found. Transcripts that belong
+    /// to one of `sessions` created after it are left out.
+    pub fn session_usage(
+        &mut self,
+        inst: &Instance,
+        sessions: &[Instance],
+        config: &UsageConfig,
+    ) -> Option<SessionUsage> {
+        if !config.enabled || inst.is_sandboxed() {
+            return None;
+        }
+        let agent = agent_of(inst)?;
+        let dirs = working_dirs(&inst.project_path);
+        let since = inst.created_at;
+
+        // Sessions of the same agent in the same directory created after
+        // this one claim the transcripts started after them
+        let later: Vec<DateTime<Utc>> = sessions
+            .iter()
+            .filter(|other| {
+                (other.created_at, other.id.as_str()) > (since, inst.id.as_str())
+                    && !other.is_sandboxed()
+                    && agent_of(other) == Some(agent)
+                    && working_dirs(&other.project_path)
+                        .iter()
+                        .any(|dir| dirs.contains(dir))
+            })
+            .map(|other| other.created_at)
+            .collect();
+        let files: Vec<PathBuf> = self
+            .transcript_files(agent, &dirs, since)
+            .into_iter()
+            .filter(|path| {
+                self.transcript_start(agent, path).is_some_and(|start| {
+                    start >= since && !later.iter().any(|created| *created <= start)
+                })
+            })
+            .collect();
+        if files.is_empty() {
+            return None;
+        }
+
+        let mut usage = ModelTokens::new();
+        for path in files {
+            let transcript = self
+                .transcripts
+                .entry((inst.id.clone(), path.clone()))
+                .or_insert_with(|| match agent {
+                    Agent::Claude => Transcript::Claude(JsonlFile::default()),
+                    Agent::Codex => Transcript::Codex(JsonlFile::default()),
+                    Agent::Gemini => Transcript::Gemini(JsonFile::default()),
+                });
+            if let Err(e) = transcript.refresh(&path, since) {
+                tracing::debug!("Failed to read transcript {}: {}", path.display(), e);
+                continue;
+            }
+            for (model, tokens) in transcript.usage() {
+                usage.entry(model.clone()).or_default().add(tokens);
+            }
+        }
+        Some(SessionUsage::from_models(&usage, config))
+    }
+
+    /// Transcripts written in one of `dirs` and modified since `since`.
+    fn transcript_files(
+        &mut self,
+        agent: Agent,
+        dirs: &[String],
+        since: DateTime<Utc>,
+    ) -> Vec<PathBuf> {
+        let files: Vec<PathBuf> = match agent {
+            Agent::Claude => {
+                let Some(root) = &self.sources.claude else {
+                    return Vec::new();
+                };
+                dirs.iter()
+                    .flat_map(|dir| files_with_extension(&claude::project_dir(root, dir), "jsonl"))
+                    .collect()
+            }
+            Agent::Gemini => {
+                let Some(root) = &self.sources.gemini else {
+                    return Vec::new();
+                };
+                dirs.iter()
+                    .flat_map(|dir| files_with_extension(&gemini::chats_dir(root, dir), "json"))
+                    .collect()
+            }
+            Agent::Codex => {
+                let Some(root) = &self.sources.codex else {
+                    return Vec::new();
+                };
+                codex::rollout_files(root, since)
+                    .into_iter()
+                    .filter(|path| {
+                        let cwd = self
+                            .codex_cwds
+                            .entry(path.clone())
+                            .or_insert_with(|| codex::rollout_cwd(path));
+                        cwd.as_ref().is_some_and(|cwd| dirs.contains(cwd))
+                    })
+                    .collect()
+            }
+        };
+        let since = SystemTime::from(since);
+        files
+            .into_iter()
+            .filter(|path| {
+                std::fs::metadata(path)
+                    .and_then(|m| m.modified())
+                    .is_ok_and(|modified| modified >= since)
+            })
+            .collect()
+    }
+
+    /// Time of the transcript's first entry, `None` until it has one.
+    fn transcript_start(&mut self, agent: Agent, path: &Path) -> Option<DateTime<Utc>> {
+        if let Some(start) = self.starts.get(path) {
+            return Some(*start);
+        }
+        let start = match agent {
+            Agent::Claude | Agent::Codex => first_timestamp(path),
+            Agent::Gemini => gemini::start_time(&std::fs::read_to_string(path).ok()?),
+        }?;
+        self.starts.insert(path.to_path_buf(), start);
+        Some(start)
+    }
+}
+
+/// One-off usage of a session, for CLI commands. `sessions` are the other
+/// sessions that may have claimed its transcripts.
+pub fn session_usage(
+    inst: &Instance,
+    sessions: &[Instance],
+    config: &UsageConfig,
+) -> Option<SessionUsage> {
+    UsageTracker::new(Sources::detect()).session_usage(inst, sessions, config)
+}
+
+fn agent_of(inst: &Instance) -> Option<Agent> {
+    let agent_name = if inst.detect_as.is_empty() {
+        &inst.tool
+    } else {
+        &inst.detect_as
+    };
+    match agent_name.as_str() {
+        "claude" => Some(Agent::Claude),
+        "codex" => Some(Agent::Codex),
+        "gemini" => Some(Agent::Gemini),
+        _ => None,
+    }
+}
+
+/// The first `timestamp` in a JSONL transcript. Leading entries such as
+/// summaries may not have one.
+fn first_timestamp(path: &Path) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    struct Entry {
+        timestamp: Option<DateTime<Utc>>,
+    }
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(50)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Entry>(&line).ok())
+        .find_map(|entry| entry.timestamp)
+}
+
+/// The directory as stored and as the agent sees it (symlinks and `..`
+/// resolved).
+fn working_dirs(project_path: &str) -> Vec<String> {
+    let mut dirs = vec![project_path.to_string()];
+    if let Ok(canonical) = Path::new(project_path).canonicalize() {
+        let canonical = canonical.to_string_lossy().to_string();
+        if canonical != project_path {
+            dirs.push(canonical);
+        }
+    }
+    dirs
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|e| e == extension))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn claude_line(msg: &str, output: u64) -> String {
+        claude_line_at(Utc::now(), msg, output)
+    }
+
+    fn claude_line_at(time: DateTime<Utc>, msg: &str, output: u64) -> String {
+        format!(
+            "{{\"type\":\"assistant\",\"timestamp\":\"{}\",\"requestId\":\"r-{}\",\"message\":{{\"id\":\"{}\",\"model\":\"claude-sonnet-4-5\",\"usage\":{{\"input_tokens\":1000,\"output_tokens\":{}}}}}}}\n",
+            time.to_rfc3339(),
+            msg,
+            msg,
+            output
+        )
+    }
+
+    #[test]
+    fn test_format_tokens_and_cost() {
+        assert_eq!(format_tokens(950), "950");
+        assert_eq!(format_tokens(12_345), "12.3k");
+        assert_eq!(format_tokens(4_100_000), "4.1M");
+        assert_eq!(format_tokens(250_000_000), "250M");
+        assert_eq!(format_cost(0.4249), "$0.42");
+        assert_eq!(format_cost(1234.5), "$1234");
+    }
+
+    #[test]
+    fn test_tracker_reads_claude_transcripts_incrementally() {
+        let claude_dir = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        let project_path = project_path.to_string_lossy().to_string();
+
+        let mut inst = Instance::new("task", &project_path);
+        inst.created_at = Utc::now() - chrono::Duration::minutes(1);
+
+        let mut tracker = UsageTracker::new(Sources {
+            claude: Some(claude_dir.path().to_path_buf()),
+            ..Default::default()
+        });
+        let config = UsageConfig::default();
+        assert_eq!(tracker.session_usage(&inst, &[], &config), None);
+
+        let transcript_dir = claude::project_dir(claude_dir.path(), &project_path);
+        std::fs::create_dir_all(&transcript_dir).unwrap();
+        let transcript = transcript_dir.join("session.jsonl");
+        std::fs::write(&transcript, claude_line("a", 100)).unwrap();
+
+        let usage = tracker.session_usage(&inst, &[], &config).unwrap();
+        assert_eq!(usage.tokens.input_tokens, 1000);
+        assert_eq!(usage.tokens.output_tokens, 100);
+        assert_eq!(usage.models, vec!["claude-sonnet-4-5".to_string()]);
+        assert!(usage.cost_usd.unwrap() > 0.0);
+
+        // Appended lines are picked up; a partial line waits for its newline
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&transcript)
+            .unwrap();
+        file.write_all(claude_line("b", 50).as_bytes()).unwrap();
+        file.write_all(b"{\"type\":\"assist").unwrap();
+        let usage = tracker.session_usage(&inst, &[], &config).unwrap();
+        assert_eq!(usage.tokens.output_tokens, 150);
+
+        // Other agents and disabled tracking report nothing
+        inst.tool = "opencode".to_string();
+        assert_eq!(tracker.session_usage(&inst, &[], &config), None);
+        inst.tool = "claude".to_string();
+        let disabled = UsageConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        assert_eq!(tracker.session_usage(&inst, &[], &disabled), None);
+
+        // Sessions that are gone are forgotten
+        assert_eq!(tracker.collect(&[inst.clone()], &config).len(), 1);
+        tracker.collect(&[], &config);
+        assert!(tracker.transcripts.is_empty());
+    }
+
+    #[test]
+    fn test_sessions_in_one_directory_split_transcripts() {
+        let claude_dir = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let project_path = project.path().canonicalize().unwrap();
+        let project_path = project_path.to_string_lossy().to_string();
+        let now = Utc::now();
+
+        let mut first = Instance::new("first", &project_path);
+        first.created_at = now - chrono::Duration::minutes(10);
+        let mut second = Instance::new("second", &project_path);
+        second.created_at = now - chrono::Duration::minutes(5);
+
+        let transcript_dir = claude::project_dir(claude_dir.path(), &project_path);
+        std::fs::create_dir_all(&transcript_dir).unwrap();
+        // Started between the two sessions, and still written to
+        std::fs::write(
+            transcript_dir.join("first.jsonl"),
+            claude_line_at(now - chrono::Duration::minutes(8), "a", 100) + &claude_line("b", 10),
+        )
+        .unwrap();
+        std::fs::write(
+            transcript_dir.join("second.jsonl"),
+            claude_line_at(now - chrono::Duration::minutes(2), "c", 7),
+        )
+        .unwrap();
+        // Started before either session
+        std::fs::write(
+            transcript_dir.join("older.jsonl"),
+            claude_line_at(now - chrono::Duration::minutes(30), "d", 1000)
+                + &claude_line("e", 1000),
+        )
+        .unwrap();
+
+        let mut tracker = UsageTracker::new(Sources {
+            claude: Some(claude_dir.path().to_path_buf()),
+            ..Default::default()
+        });
+        let usage = tracker.collect(&[first.clone(), second.clone()], &UsageConfig::default());
+        assert_eq!(usage[&first.id].tokens.output_tokens, 110);
+        assert_eq!(usage[&second.id].tokens.output_tokens, 7);
+
+        // Alone, the first session has both
+        let alone = tracker
+            .session_usage(&first, &[first.clone()], &UsageConfig::default())
+            .unwrap();
+        assert_eq!(alone.tokens.output_tokens, 117);
+    }
+
+    #[test]
+    fn test_session_usage_serializes_flat() {
+        let usage = SessionUsage {
+            tokens: Tokens {
+                input_tokens: 1,
+                output_tokens: 2,
+                cache_read_tokens: 3,
+                cache_write_tokens: 4,
+            },
+            cost_usd: Some(0.5),
+            models: vec!["gpt-5".to_string()],
+        };
+        let json = serde_json::to_value(&usage).unwrap();
+        assert_eq!(json["output_tokens"], 2);
+        assert_eq!(json["cost_usd"], 0.5);
+        assert_eq!(usage.short_label(), "$0.50");
+        let unpriced = SessionUsage {
+            cost_usd: None,
+            ..usage
+        };
+        assert_eq!(unpriced.short_label(), "10 tok");
+    }
+}
@@ -0,0 +1,184 @@
+//! Model price table for cost estimates
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ModelTokens, Tokens};
+
+/// Price of a model in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    /// Defaults to the input price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+    /// Defaults to the input price
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: Some(cache_read),
+            cache_write: Some(cache_write),
+        }
+    }
+
+    pub fn cost(&self, tokens: &Tokens) -> f64 {
+        let per_token = |price: f64, count: u64| price * count as f64 / 1_000_000.0;
+        per_token(self.input, tokens.input_tokens)
+            + per_token(self.output, tokens.output_tokens)
+            + per_token(
+                self.cache_read.unwrap_or(self.input),
+                tokens.cache_read_tokens,
+            )
+            + per_token(
+                self.cache_write.unwrap_or(self.input),
+                tokens.cache_write_tokens,
+            )
+    }
+}
+
+/// Built-in list prices, matched by model-name prefix. Estimates only:
+/// override or extend them with `[usage.prices."<prefix>"]`.
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    ("claude-opus-4", ModelPrice::new(15.0, 75.0, 1.5, 18.75)),
+    ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 0.5, 6.25)),
+    ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 0.3, 3.75)),
+    ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0, 0.1, 1.25)),
+    ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 0.08, 1.0)),
+    ("gpt-5", ModelPrice::new(1.25, 10.0, 0.125, 1.25)),
+    ("gpt-5-mini", ModelPrice::new(0.25, 2.0, 0.025, 0.25)),
+    ("gpt-5-nano", ModelPrice::new(0.05, 0.4, 0.005, 0.05)),
+    ("gemini-2.5-pro", ModelPrice::new(1.25, 10.0, 0.31, 1.25)),
+    ("gemini-2.5-flash", ModelPrice::new(0.3, 2.5, 0.075, 0.3)),
+    (
+        "gemini-2.5-flash-lite",
+        ModelPrice::new(0.1, 0.4, 0.025, 0.1),
+    ),
+];
+
+/// Token usage tracking configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Read agent transcripts to show token usage and estimated cost
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Prices in USD per million tokens, keyed by model-name prefix. They
+    /// are merged with the built-in table; the longest matching prefix wins.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prices: BTreeMap<String, ModelPrice>,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            prices: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl UsageConfig {
+    /// Price of the longest prefix of `model` in the configured and built-in
+    /// tables, preferring the configured one for the same prefix.
+    pub fn price_for(&self, model: &str) -> Option<ModelPrice> {
+        BUILTIN_PRICES
+            .iter()
+            .map(|(prefix, price)| (*prefix, price))
+            .chain(
+                self.prices
+                    .iter()
+                    .map(|(prefix, price)| (prefix.as_str(), price)),
+            )
+            .filter(|(prefix, _)| model.starts_with(prefix))
+            // max_by_key keeps the last of equal keys: configured over built-in
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| *price)
+    }
+
+    /// Estimated cost of `usage`, or `None` if no model in it has a price.
+    pub fn cost(&self, usage: &ModelTokens) -> Option<f64> {
+        usage
+            .iter()
+            .filter_map(|(model, tokens)| Some(self.price_for(model)?.cost(tokens)))
+            .reduce(|a, b| a + b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let config = UsageConfig::default();
+        assert_eq!(
+            config.price_for("claude-opus-4-1-20250805").unwrap().input,
+            15.0
+        );
+        assert_eq!(
+            config.price_for("claude-opus-4-5-20251101").unwrap().input,
+            5.0
+        );
+        assert_eq!(config.price_for("gpt-5-codex").unwrap().output, 10.0);
+        assert!(config.price_for("llama-3").is_none());
+    }
+
+    #[test]
+    fn test_configured_prices_override_builtin() {
+        let config: UsageConfig = toml::from_str(
+            r#"
+            [prices."gpt-5"]
+            input = 2.0
+            output = 20.0
+            "#,
+        )
+        .unwrap();
+        assert!(config.enabled);
+        let price = config.price_for("gpt-5-codex").unwrap();
+        assert_eq!(price.input, 2.0);
+        assert_eq!(price.cache_read, None);
+        // A longer built-in prefix still wins over a shorter configured one
+        assert_eq!(config.price_for("gpt-5-mini").unwrap().input, 0.25);
+    }
+
+    #[test]
+    fn test_cost_sums_priced_models() {
+        let config = UsageConfig::default();
+        let usage = ModelTokens::from([
+            (
+                "claude-sonnet-4-5".to_string(),
+                Tokens {
+                    input_tokens: 1_000_000,
+                    output_tokens: 100_000,
+                    cache_read_tokens: 2_000_000,
+                    cache_write_tokens: 0,
+                },
+            ),
+            (
+                "local-model".to_string(),
+                Tokens {
+                    input_tokens: 5_000_000,
+                    ..Default::default()
+                },
+            ),
+        ]);
+        let cost = config.cost(&usage).unwrap();
+        assert!((cost - (3.0 + 1.5 + 0.6)).abs() < 1e-9, "{}", cost);
+
+        let unpriced = ModelTokens::from([("local-model".to_string(), Tokens::default())]);
+        assert_eq!(config.cost(&unpriced), None);
+    }
+}
@@ -0,0 +1,546 @@
+//! Outgoing webhooks for session lifecycle events
+//!
+//! Each `[[webhooks]]` entry in the config is an HTTP endpoint that gets a
+//! POST when a session starts waiting, goes idle, errors, stalls, or is
+//! created or deleted. Bodies come in plain JSON or Slack/Discord-compatible
+//! presets and can be HMAC-signed. Failed deliveries are retried with
+//! exponential backoff.
+//!
+//! Events come from whichever of the TUI and `aoe serve` is running, so
+//! webhooks work without the server. Status events wait out a short dwell
+//! like Web Push does, so a brief flicker into Waiting doesn't post. When
+//! both processes see the same event only the first delivers it: a status
+//! event is claimed by the transition it came from and when that was seen.
+
+pub mod payload;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{mpsc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use self::payload::Delivery;
+use crate::session::{Instance, Status};
+
+/// How long the resolved webhook list is reused before the config is read
+/// from disk again.
+const CONFIG_TTL: Duration = Duration::from_secs(10);
+
+/// Continuous time in Waiting before it is posted; Claude often pauses
+/// there briefly.
+const DWELL_WAITING: Duration = Duration::from_secs(5);
+
+/// Continuous time in Idle, Error or Stalled before it is posted.
+const DWELL_TERMINAL: Duration = Duration::from_secs(2);
+
+const MARKER_PREFIX: &str = "webhook_";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest wait between retries.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// One webhook endpoint (`[[webhooks]]`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+
+    /// Events to send; defaults to the status events
+    #[serde(default = "default_events")]
+    pub events: Vec<WebhookEvent>,
+
+    /// Request body shape
+    #[serde(default)]
+    pub format: PayloadFormat,
+
+    /// Key for the `X-Aoe-Signature-256` HMAC of the body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+
+    /// Attempts after the first when the endpoint is unreachable or
+    /// answers 429 or 5xx
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+}
+
+fn default_events() -> Vec<WebhookEvent> {
+    vec![
+        WebhookEvent::Waiting,
+        WebhookEvent::Idle,
+        WebhookEvent::Error,
+        WebhookEvent::Stalled,
+    ]
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookEvent {
+    Waiting,
+    Idle,
+    Error,
+    Stalled,
+    Created,
+    Deleted,
+}
+
+impl WebhookEvent {
+    /// The event a transition into `status` fires, if any.
+    fn for_status(status: Status) -> Option<Self> {
+        match status {
+            Status::Waiting => Some(Self::Waiting),
+            Status::Idle => Some(Self::Idle),
+            Status::Error => Some(Self::Error),
+            Status::Stalled => Some(Self::Stalled),
+            _ => None,
+        }
+    }
+
+    fn dwell(self) -> Duration {
+        match self {
+            Self::Waiting => DWELL_WAITING,
+            Self::Idle | Self::Error | Self::Stalled => DWELL_TERMINAL,
+            Self::Created | Self::Deleted => Duration::ZERO,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Waiting => "waiting",
+            Self::Idle => "idle",
+            Self::Error => "error",
+            Self::Stalled => "stalled",
+            Self::Created => "created",
+            Self::Deleted => "deleted",
+        }
+    }
+}
+
+/// Request body preset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    /// The full event as JSON
+    #[default]
+    Json,
+    /// `{"text": ...}` for Slack incoming webhooks
+    Slack,
+    /// `{"content": ...}` for Discord webhooks
+    Discord,
+}
+
+/// The session an event is about, as sent in JSON payloads.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionInfo {
+    pub id: String,
+    pub title: String,
+    pub profile: String,
+    pub project_path: String,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    #[serde(skip)]
+    overrides: Overrides,
+}
+
+/// The session's `webhook_on_*` settings.
+#[derive(Debug, Clone, Copy, Default)]
+struct Overrides {
+    waiting: Option<bool>,
+    idle: Option<bool>,
+    error: Option<bool>,
+}
+
+impl From<&Instance> for SessionInfo {
+    fn from(inst: &Instance) -> Self {
+        Self {
+            id: inst.id.clone(),
+            title: inst.title.clone(),
+            profile: inst.source_profile.clone(),
+            project_path: inst.project_path.clone(),
+            tool: inst.tool.clone(),
+            branch: inst.worktree_info.as_ref().map(|w| w.branch.clone()),
+            overrides: Overrides {
+                waiting: inst.webhook_on_waiting,
+                idle: inst.webhook_on_idle,
+                error: inst.webhook_on_error,
+            },
+        }
+    }
+}
+
+/// Whether `endpoint` should get `event` for a session. A session's
+/// `webhook_on_*` setting replaces every endpoint's filter for that event;
+/// Error's setting also covers Stalled.
+fn wants(endpoint: &WebhookConfig, event: WebhookEvent, session: &SessionInfo) -> bool {
+    let overridden = match event {
+        WebhookEvent::Waiting => session.overrides.waiting,
+        WebhookEvent::Idle => session.overrides.idle,
+        WebhookEvent::Error | WebhookEvent::Stalled => session.overrides.error,
+        WebhookEvent::Created | WebhookEvent::Deleted => None,
+    };
+    overridden.unwrap_or_else(|| endpoint.events.contains(&event))
+}
+
+// ── Entry points ────────────────────────────────────────────────────────────
+
+/// Report a status transition seen by a status poller.
+pub fn status_changed(inst: &Instance, old: Status, new: Status) {
+    if old == new || endpoints().is_empty() {
+        return;
+    }
+    send(Trigger::Status {
+        session: SessionInfo::from(inst),
+        old,
+        new,
+    });
+}
+
+pub fn session_created(inst: &Instance) {
+    lifecycle(inst, WebhookEvent::Created);
+}
+
+pub fn session_deleted(inst: &Instance) {
+    lifecycle(inst, WebhookEvent::Deleted);
+}
+
+fn lifecycle(inst: &Instance, event: WebhookEvent) {
+    if endpoints().is_empty() {
+        return;
+    }
+    send(Trigger::Lifecycle {
+        session: SessionInfo::from(inst),
+        event,
+    });
+}
+
+enum Trigger {
+    Status {
+        session: SessionInfo,
+        old: Status,
+        new: Status,
+    },
+    Lifecycle {
+        session: SessionInfo,
+        event: WebhookEvent,
+    },
+}
+
+static DISPATCHER: OnceLock<Option<mpsc::Sender<Trigger>>> = OnceLock::new();
+
+fn send(trigger: Trigger) {
+    let sender = DISPATCHER.get_or_init(|| match spawn_dispatcher() {
+        Ok(tx) => Some(tx),
+        Err(e) => {
+            tracing::error!("webhooks: failed to start dispatcher: {}", e);
+            None
+        }
+    });
+    if let Some(tx) = sender {
+        let _ = tx.send(trigger);
+    }
+}
+
+// ── Dispatcher ──────────────────────────────────────────────────────────────
+
+/// A status event waiting out its dwell.
+struct Pending {
+    since: Instant,
+    delivery: Delivery,
+}
+
+/// Start the thread that applies dwell and hands deliveries to a small
+/// runtime of its own, so callers never block on HTTP and the TUI needs
+/// no async context.
+fn spawn_dispatcher() -> Result<mpsc::Sender<Trigger>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("aoe-webhooks")
+        .enable_all()
+        .build()?;
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(concat!("agent-of-empires/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let (tx, rx) = mpsc::channel::<Trigger>();
+
+    std::thread::spawn(move || {
+        let mut pending: HashMap<String, Pending> = HashMap::new();
+        loop {
+            match rx.recv_timeout(Duration::from_millis(500)) {
+                Ok(Trigger::Status { session, old, new }) => {
+                    // Any transition restarts the clock
+                    pending.remove(&session.id);
+                    if let Some(event) = WebhookEvent::for_status(new) {
+                        let id = session.id.clone();
+                        let delivery = Delivery {
+                            event,
+                            session,
+                            previous_status: Some(old),
+                            status: Some(new),
+                            at: Utc::now(),
+                        };
+                        pending.insert(
+                            id,
+                            Pending {
+                                since: Instant::now(),
+                                delivery,
+                            },
+                        );
+                    }
+                }
+                Ok(Trigger::Lifecycle { session, event }) => {
+                    pending.remove(&session.id);
+                    let delivery = Delivery {
+                        event,
+                        session,
+                        previous_status: None,
+                        status: None,
+                        at: Utc::now(),
+                    };
+                    dispatch(&runtime, &client, delivery);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+
+            let due: Vec<String> = pending
+                .iter()
+                .filter(|(_, p)| p.since.elapsed() >= p.delivery.event.dwell())
+                .map(|(id, _)| id.clone())
+                .collect();
+            for id in due {
+                if let Some(p) = pending.remove(&id) {
+                    dispatch(&runtime, &client, p.delivery);
+                }
+            }
+        }
+    });
+
+    Ok(tx)
+}
+
+/// Send a delivery to every endpoint that wants it, unless it was already
+/// sent recently.
+fn dispatch(runtime: &tokio::runtime::Runtime, client: &reqwest::Client, delivery: Delivery) {
+    let targets: Vec<WebhookConfig> = endpoints()
+        .into_iter()
+        .filter(|e| wants(e, delivery.event, &delivery.session))
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    match claim(&delivery) {
+        Ok(true) => {}
+        Ok(false) => return,
+        Err(e) => tracing::warn!("webhooks: claim failed, sending anyway: {}", e),
+    }
+
+    let delivery_id = uuid::Uuid::new_v4().to_string();
+    for endpoint in targets {
+        let body = payload::render(endpoint.format, &delivery);
+        let client = client.clone();
+        let delivery_id = delivery_id.clone();
+        let event = delivery.event;
+        runtime.spawn(async move {
+            deliver(&client, &endpoint, event, &delivery_id, body).await;
+        });
+    }
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    endpoint: &WebhookConfig,
+    event: WebhookEvent,
+    delivery_id: &str,
+    body: String,
+) {
+    let signature = endpoint
+        .secret
+        .as_deref()
+        .filter(|s| !s.is_empty())
+        .map(|secret| payload::sign(secret, &body));
+
+    for attempt in 0..=endpoint.max_retries {
+        if attempt > 0 {
+            tokio::time::sleep(backoff(attempt)).await;
+        }
+        let mut request = client
+            .post(&endpoint.url)
+            .header("Content-Type", "application/json")
+            .header("X-Aoe-Event", event.label())
+            .header("X-Aoe-Delivery", delivery_id)
+            .body(body.clone());
+        if let Some(signature) = &signature {
+            request = request.header("X-Aoe-Signature-256", signature);
+        }
+
+        let status = match request.send().await {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => Some(response.status().as_u16()),
+            Err(e) => {
+                tracing::debug!("webhooks: POST {} failed: {}", endpoint.url, e);
+                None
+            }
+        };
+        if !should_retry(status) {
+            tracing::warn!(
+                "webhooks: {} rejected {} event with HTTP {}",
+                endpoint.url,
+                event.label(),
+                status.unwrap_or_default()
+            );
+            return;
+        }
+    }
+    tracing::warn!(
+        "webhooks: giving up on {} event for {} after {} attempts",
+        event.label(),
+        endpoint.url,
+        endpoint.max_retries + 1
+    );
+}
+
+/// Whether a failed attempt is worth repeating: no response at all, rate
+/// limiting, or a server error.
+fn should_retry(status: Option<u16>) -> bool {
+    match status {
+        None => true,
+        Some(code) => code == 429 || code >= 500,
+    }
+}
+
+/// Wait before retry `attempt` (1-based): 1s, 2s, 4s, ... up to a minute.
+fn backoff(attempt: u32) -> Duration {
+    let secs = 1u64
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u64::MAX);
+    Duration::from_secs(secs).min(MAX_BACKOFF)
+}
+
+// ── Config ──────────────────────────────────────────────────────────────────
+
+static CONFIG_CACHE: RwLock<Option<(Instant, Vec<WebhookConfig>)>> = RwLock::new(None);
+
+/// Configured endpoints, cached for a few seconds.
+fn endpoints() -> Vec<WebhookConfig> {
+    if let Ok(cache) = CONFIG_CACHE.read() {
+        if let Some((loaded, endpoints)) = cache.as_ref() {
+            if loaded.elapsed() < CONFIG_TTL {
+                return endpoints.clone();
+            }
+        }
+    }
+    let endpoints = crate::session::Config::load()
+        .map(|c| c.webhooks)
+        .unwrap_or_default();
+    if let Ok(mut cache) = CONFIG_CACHE.write() {
+        *cache = Some((Instant::now(), endpoints.clone()));
+    }
+    endpoints
+}
+
+// ── Claims ──────────────────────────────────────────────────────────────────
+
+/// Record that a delivery is being made. `false` if this or another
+/// process already made it.
+fn claim(delivery: &Delivery) -> Result<bool> {
+    let path = crate::hooks::claim_marker_path(
+        &delivery.session.id,
+        &format!("{}{}", MARKER_PREFIX, delivery.event.label()),
+    )?;
+    claim_in(
+        &path,
+        delivery.event,
+        delivery.previous_status,
+        delivery.at.timestamp(),
+    )
+}
+
+/// Claim the transition from `previous` seen at `seen`. Pollers see the
+/// same transition within a moment of each other, while a second real one
+/// comes at least a dwell later. Lifecycle events happen once per session.
+fn claim_in(path: &Path, event: WebhookEvent, previous: Option<Status>, seen: i64) -> Result<bool> {
+    let from = previous.map_or_else(|| "-".to_string(), |s| format!("{:?}", s).to_lowercase());
+    let window = event.dwell().as_secs() as i64;
+    crate::hooks::claim_event(path, &format!("{} {}", from, seen), |last| {
+        previous.is_none()
+            || last.split_once(' ').is_some_and(|(last_from, at)| {
+                last_from == from && at.parse::<i64>().is_ok_and(|at| (seen - at).abs() < window)
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(events: &str) -> WebhookConfig {
+        toml::from_str(&format!("url = \"http://x\"\n{}", events)).unwrap()
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let config = endpoint("");
+        assert_eq!(config.events, default_events());
+        assert_eq!(config.format, PayloadFormat::Json);
+        assert_eq!(config.max_retries, 3);
+        assert!(config.secret.is_none());
+    }
+
+    #[test]
+    fn test_session_override_replaces_filter() {
+        let errors_only = endpoint("events = [\"error\", \"created\"]");
+        let mut session = SessionInfo::default();
+        assert!(!wants(&errors_only, WebhookEvent::Waiting, &session));
+        assert!(wants(&errors_only, WebhookEvent::Error, &session));
+        assert!(wants(&errors_only, WebhookEvent::Created, &session));
+
+        session.overrides.waiting = Some(true);
+        session.overrides.error = Some(false);
+        assert!(wants(&errors_only, WebhookEvent::Waiting, &session));
+        assert!(!wants(&errors_only, WebhookEvent::Error, &session));
+        assert!(!wants(&errors_only, WebhookEvent::Stalled, &session));
+        assert!(wants(&errors_only, WebhookEvent::Created, &session));
+    }
+
+    #[test]
+    fn test_retry_policy() {
+        assert!(should_retry(None));
+        assert!(should_retry(Some(429)));
+        assert!(should_retry(Some(503)));
+        assert!(!should_retry(Some(404)));
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_BACKOFF);
+        assert_eq!(backoff(100), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_claim_once_per_transition() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("webhook_waiting");
+        let waiting = WebhookEvent::Waiting;
+        let running = Some(Status::Running);
+
+        assert!(claim_in(&path, waiting, running, 1_000)?);
+        // The other process seeing the same transition
+        assert!(!claim_in(&path, waiting, running, 1_002)?);
+        // A second real Waiting within a minute
+        assert!(claim_in(&path, waiting, running, 1_020)?);
+        // Coming from elsewhere is another transition
+        assert!(claim_in(&path, waiting, Some(Status::Idle), 1_021)?);
+
+        let path = dir.path().join("webhook_created");
+        assert!(claim_in(&path, WebhookEvent::Created, None, 1_000)?);
+        assert!(!claim_in(&path, WebhookEvent::Created, None, 1_300)?);
+        Ok(())
+    }
+}
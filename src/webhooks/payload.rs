@@ -0,0 +1,121 @@
+//! Request bodies and signatures for webhook deliveries
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+
+use super::{PayloadFormat, SessionInfo, WebhookEvent};
+use crate::session::Status;
+
+/// One occurrence of an event, before it is shaped for an endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct Delivery {
+    pub event: WebhookEvent,
+    pub session: SessionInfo,
+    /// Status before and after, for status events
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<Status>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    pub at: DateTime<Utc>,
+}
+
+impl Delivery {
+    /// One-line summary for chat presets, with the title wrapped in
+    /// `emphasis` (`*` for Slack, `**` for Discord).
+    pub fn summary(&self, emphasis: &str) -> String {
+        let title = format!("{}{}{}", emphasis, self.session.title, emphasis);
+        let what = match self.event {
+            WebhookEvent::Waiting => format!("{} is waiting for input", title),
+            WebhookEvent::Idle => format!("{} finished and is idle", title),
+            WebhookEvent::Error => format!("{} hit an error", title),
+            WebhookEvent::Stalled => format!("{} stalled with no output", title),
+            WebhookEvent::Created => format!("Session {} was created", title),
+            WebhookEvent::Deleted => format!("Session {} was deleted", title),
+        };
+        format!("{} ({})", what, self.session.project_path)
+    }
+}
+
+/// The request body for an endpoint's format.
+pub fn render(format: PayloadFormat, delivery: &Delivery) -> String {
+    let value = match format {
+        PayloadFormat::Json => json!(delivery),
+        PayloadFormat::Slack => json!({ "text": delivery.summary("*") }),
+        PayloadFormat::Discord => json!({
+            "username": "Agent of Empires",
+            "content": delivery.summary("**"),
+        }),
+    };
+    value.to_string()
+}
+
+/// `sha256=<hex>` HMAC of the body, sent as `X-Aoe-Signature-256`.
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body.as_bytes());
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delivery(event: WebhookEvent) -> Delivery {
+        Delivery {
+            event,
+            session: SessionInfo {
+                id: "abc123".to_string(),
+                title: "fix-login".to_string(),
+                profile: "default".to_string(),
+                project_path: "/src/app".to_string(),
+                tool: "claude".to_string(),
+                branch: Some("fix-login".to_string()),
+                ..Default::default()
+            },
+            previous_status: Some(Status::Running),
+            status: Some(Status::Waiting),
+            at: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+                .unwrap()
+                .with_timezone(&Utc),
+        }
+    }
+
+    #[test]
+    fn test_presets() {
+        let d = delivery(WebhookEvent::Waiting);
+
+        let body: serde_json::Value =
+            serde_json::from_str(&render(PayloadFormat::Json, &d)).unwrap();
+        assert_eq!(body["event"], "waiting");
+        assert_eq!(body["status"], "waiting");
+        assert_eq!(body["previous_status"], "running");
+        assert_eq!(body["session"]["title"], "fix-login");
+        assert!(body["session"].get("overrides").is_none());
+
+        let body: serde_json::Value =
+            serde_json::from_str(&render(PayloadFormat::Slack, &d)).unwrap();
+        assert_eq!(body["text"], "*fix-login* is waiting for input (/src/app)");
+
+        let body: serde_json::Value =
+            serde_json::from_str(&render(PayloadFormat::Discord, &d)).unwrap();
+        assert_eq!(
+            body["content"],
+            "**fix-login** is waiting for input (/src/app)"
+        );
+    }
+
+    #[test]
+    fn test_sign_matches_known_hmac() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", "what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+}
@@ -4,15 +4,19 @@ use std::process::Command;
 
 use crate::harness::{require_tmux, TuiTestHarness};
 
-/// Helper to read a session field from the sessions.json in the harness's isolated home.
-fn read_sessions_json(h: &TuiTestHarness) -> serde_json::Value {
-    let sessions_path = if cfg!(target_os = "linux") {
+fn sessions_json_path(h: &TuiTestHarness) -> std::path::PathBuf {
+    if cfg!(target_os = "linux") {
         h.home_path()
             .join(".config/agent-of-empires/profiles/default/sessions.json")
     } else {
         h.home_path()
             .join(".agent-of-empires/profiles/default/sessions.json")
-    };
+    }
+}
+
+/// Helper to read a session field from the sessions.json in the harness's isolated home.
+fn read_sessions_json(h: &TuiTestHarness) -> serde_json::Value {
+    let sessions_path = sessions_json_path(h);
     let content = std::fs::read_to_string(&sessions_path)
         .unwrap_or_else(|e| panic!("failed to read {}: {}", sessions_path.display(), e));
     serde_json::from_str(&content).expect("invalid sessions JSON")
@@ -381,6 +385,54 @@ fn test_cli_add_yolo_flag_without_config() {
     );
 }
 
+#[test]
+#[serial]
+fn test_cli_add_prompt_file_stores_initial_prompt() {
+    let h = TuiTestHarness::new("cli_add_prompt_file");
+    let project = h.project_path();
+    let prompt_path = h.home_path().join("task.md");
+    std::fs::write(&prompt_path, "Fix the flaky test\nThen run the suite\n").unwrap();
+
+    let add_output = h.run_cli(&[
+        "add",
+        project.to_str().unwrap(),
+        "-t",
+        "Prompted",
+        "--prompt-file",
+        prompt_path.to_str().unwrap(),
+    ]);
+    assert!(
+        add_output.status.success(),
+        "aoe add failed: {}",
+        String::from_utf8_lossy(&add_output.stderr)
+    );
+
+    let sessions = read_sessions_json(&h);
+    assert_eq!(
+        sessions[0]["initial_prompt"].as_str(),
+        Some("Fix the flaky test\nThen run the suite"),
+        "--prompt-file should be stored as the initial prompt"
+    );
+}
+
+#[test]
+#[serial]
+fn test_cli_add_rejects_empty_prompt() {
+    let h = TuiTestHarness::new("cli_add_empty_prompt");
+    let project = h.project_path();
+
+    let output = h.run_cli(&["add", project.to_str().unwrap(), "--prompt", "  "]);
+    assert!(
+        !output.status.success(),
+        "empty --prompt should be rejected"
+    );
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("Prompt is empty"),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
 #[test]
 #[serial]
 fn test_cli_add_default_tool_no_config() {
@@ -407,6 +459,80 @@ fn test_cli_add_default_tool_no_config() {
     );
 }
 
+/// Add a session and mark it stopped in storage, returning its ID.
+fn add_stopped_session(h: &TuiTestHarness, title: &str) -> String {
+    let project = h.project_path();
+    let add_output = h.run_cli(&["add", project.to_str().unwrap(), "-t", title]);
+    assert!(
+        add_output.status.success(),
+        "aoe add failed: {}",
+        String::from_utf8_lossy(&add_output.stderr)
+    );
+
+    let mut sessions = read_sessions_json(h);
+    sessions[0]["status"] = "stopped".into();
+    std::fs::write(sessions_json_path(h), sessions.to_string()).expect("write sessions.json");
+    sessions[0]["id"].as_str().unwrap().to_string()
+}
+
+#[test]
+#[serial]
+fn test_cli_wait_reached() {
+    let h = TuiTestHarness::new("cli_wait_reached");
+    let session_id = add_stopped_session(&h, "WaitTest");
+
+    let output = h.run_cli(&["wait", "WaitTest", "--for", "idle,stopped", "--json"]);
+    assert_eq!(
+        output.status.code(),
+        Some(0),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("should be valid JSON");
+    assert_eq!(json["outcome"], "reached");
+    assert_eq!(json["sessions"][0]["id"], session_id.as_str());
+    assert_eq!(json["sessions"][0]["status"], "stopped");
+    assert_eq!(json["sessions"][0]["reached"], true);
+}
+
+#[test]
+#[serial]
+fn test_cli_wait_timeout() {
+    let h = TuiTestHarness::new("cli_wait_timeout");
+    add_stopped_session(&h, "WaitTest");
+
+    let output = h.run_cli(&[
+        "wait",
+        "--all",
+        "--for",
+        "idle",
+        "--timeout",
+        "1s",
+        "--interval",
+        "1s",
+        "--json",
+    ]);
+    assert_eq!(output.status.code(), Some(124), "timeout should exit 124");
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("should be valid JSON");
+    assert_eq!(json["outcome"], "timeout");
+    assert_eq!(json["sessions"][0]["reached"], false);
+}
+
+#[test]
+#[serial]
+fn test_cli_wait_unknown_session() {
+    let h = TuiTestHarness::new("cli_wait_unknown");
+    add_stopped_session(&h, "WaitTest");
+
+    let output = h.run_cli(&["wait", "nope"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Session not found"));
+}
+
 /// `aoe session capture` should return pane content or empty output for a stopped session.
 #[test]
 #[serial]
@@ -692,3 +818,300 @@ on_create = ["touch {}"]
         "global hook marker file should exist, proving global on_create hooks ran as fallback"
     );
 }
+
+/// `aoe add --fanout` creates one worktree session per agent, each on its own
+/// suffixed branch, in a shared group and with the same initial prompt.
+#[test]
+#[serial]
+fn test_cli_add_fanout_creates_session_per_agent() {
+    let h = TuiTestHarness::new("cli_add_fanout");
+    let project = h.home_path().join("fanout-project");
+    init_git_repo(&project);
+
+    let add_output = h.run_cli(&[
+        "add",
+        project.to_str().unwrap(),
+        "--fanout",
+        "claude,codex",
+        "-w",
+        "fix-login",
+        "--prompt",
+        "Fix the login bug",
+    ]);
+    assert!(
+        add_output.status.success(),
+        "aoe add --fanout failed:\nstdout: {}\nstderr: {}",
+        String::from_utf8_lossy(&add_output.stdout),
+        String::from_utf8_lossy(&add_output.stderr)
+    );
+
+    let sessions = read_sessions_json(&h);
+    let sessions = sessions.as_array().expect("sessions array");
+    assert_eq!(sessions.len(), 2);
+
+    let mut branches = Vec::new();
+    for (session, tool) in sessions.iter().zip(["claude", "codex"]) {
+        assert_eq!(session["tool"], tool);
+        assert_eq!(session["group_path"], "fanout/fix-login");
+        assert_eq!(session["initial_prompt"], "Fix the login bug");
+        let branch = session["worktree_info"]["branch"].as_str().unwrap();
+        let path = session["project_path"].as_str().unwrap();
+        assert!(Path::new(path).is_dir(), "worktree missing: {}", path);
+        branches.push(branch.to_string());
+    }
+    assert_eq!(branches, ["fix-login-claude", "fix-login-codex"]);
+}
+
+#[test]
+#[serial]
+fn test_cli_add_count_requires_git_repo() {
+    let h = TuiTestHarness::new("cli_add_count_no_git");
+    let project = h.project_path();
+
+    let output = h.run_cli(&["add", project.to_str().unwrap(), "--count", "2"]);
+    assert!(!output.status.success(), "--count outside git should fail");
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("not in a git repository"),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+#[serial]
+fn test_cli_add_template_applies_preset() {
+    let h = TuiTestHarness::new("cli_add_template");
+    let project = h.project_path();
+
+    // A global template, refined by the repo's config
+    let config_dir = if cfg!(target_os = "linux") {
+        h.home_path().join(".config/agent-of-empires")
+    } else {
+        h.home_path().join(".agent-of-empires")
+    };
+    let config_content = format!(
+        r#"[updates]
+check_enabled = false
+
+[app_state]
+has_seen_welcome = true
+last_seen_version = "{}"
+
+[templates.review]
+agent = "claude"
+group = "reviews"
+extra_args = "--verbose"
+"#,
+        env!("CARGO_PKG_VERSION")
+    );
+    std::fs::write(config_dir.join("config.toml"), config_content).expect("write config.toml");
+    let repo_config_dir = project.join(".agent-of-empires");
+    std::fs::create_dir_all(&repo_config_dir).expect("create .agent-of-empires dir");
+    std::fs::write(
+        repo_config_dir.join("config.toml"),
+        "[templates.review]\nyolo = true\n",
+    )
+    .expect("write repo config");
+
+    let output = h.run_cli(&[
+        "add",
+        project.to_str().unwrap(),
+        "-t",
+        "FromTemplate",
+        "--template",
+        "review",
+    ]);
+    assert!(
+        output.status.success(),
+        "aoe add --template failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    // Flags take priority over the template
+    let output = h.run_cli(&[
+        "add",
+        project.to_str().unwrap(),
+        "-t",
+        "Overridden",
+        "--template",
+        "review",
+        "-g",
+        "mine",
+        "--extra-args=--debug",
+    ]);
+    assert!(
+        output.status.success(),
+        "aoe add --template with flags failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let sessions = read_sessions_json(&h);
+    let from_template = &sessions[0];
+    assert_eq!(from_template["tool"], "claude");
+    assert_eq!(from_template["group_path"], "reviews");
+    assert_eq!(from_template["extra_args"], "--verbose");
+    assert_eq!(from_template["yolo_mode"], true);
+    let overridden = &sessions[1];
+    assert_eq!(overridden["group_path"], "mine");
+    assert_eq!(overridden["extra_args"], "--debug");
+    assert_eq!(overridden["yolo_mode"], true);
+
+    let output = h.run_cli(&["add", project.to_str().unwrap(), "--template", "nope"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Available: review"), "stderr: {}", stderr);
+}
+
+#[test]
+#[serial]
+fn test_cli_send_queue_keeps_messages_for_stopped_session() {
+    let h = TuiTestHarness::new("cli_send_queue");
+    let project = h.project_path();
+
+    let output = h.run_cli(&["add", project.to_str().unwrap(), "-t", "Queued"]);
+    assert!(output.status.success());
+
+    // Without --queue a stopped session can't take input
+    let output = h.run_cli(&["send", "Queued", "now please"]);
+    assert!(!output.status.success());
+
+    for (message, expected) in [("first", "(1 in queue)"), ("second", "(2 in queue)")] {
+        let output = h.run_cli(&["send", "Queued", "--queue", message]);
+        assert!(
+            output.status.success(),
+            "aoe send --queue failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains(expected));
+    }
+
+    let sessions = read_sessions_json(&h);
+    let id = sessions[0]["id"].as_str().unwrap();
+    let queue_path = sessions_json_path(&h)
+        .with_file_name("queue")
+        .join(format!("{}.json", id));
+    let queue: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&queue_path).unwrap()).unwrap();
+    let texts: Vec<&str> = queue
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, ["first", "second"]);
+
+    let output = h.run_cli(&["remove", "Queued"]);
+    assert!(output.status.success());
+    assert!(
+        !queue_path.exists(),
+        "queue should be removed with the session"
+    );
+}
+
+#[test]
+#[serial]
+fn test_cli_session_export_import_round_trip() {
+    let h = TuiTestHarness::new("cli_session_export_import");
+    let repo = h.home_path().join("repo");
+    init_git_repo(&repo);
+
+    let output = h.run_cli(&[
+        "add",
+        repo.to_str().unwrap(),
+        "-w",
+        "feat/export",
+        "-b",
+        "-t",
+        "Exported",
+    ]);
+    assert!(
+        output.status.success(),
+        "aoe add failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let sessions = read_sessions_json(&h);
+    let worktree = Path::new(sessions[0]["project_path"].as_str().unwrap()).to_path_buf();
+
+    // One commit on the branch, one uncommitted edit and an untracked file
+    let git = |dir: &Path, args: &[&str]| {
+        let out = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_AUTHOR_NAME", "test")
+            .env("GIT_AUTHOR_EMAIL", "test@test.com")
+            .env("GIT_COMMITTER_NAME", "test")
+            .env("GIT_COMMITTER_EMAIL", "test@test.com")
+            .output()
+            .expect("run git");
+        assert!(out.status.success(), "git {:?} failed", args);
+    };
+    std::fs::write(worktree.join("committed.txt"), "committed\n").unwrap();
+    git(&worktree, &["add", "committed.txt"]);
+    git(&worktree, &["commit", "-m", "work"]);
+    std::fs::write(worktree.join("committed.txt"), "committed\nedited\n").unwrap();
+    std::fs::write(worktree.join("untracked.txt"), "untracked\n").unwrap();
+
+    let bundle = h.home_path().join("exported.tar.gz");
+    let output = h.run_cli(&[
+        "session",
+        "export",
+        "Exported",
+        "-o",
+        bundle.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "export failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(
+        stdout.contains("feat/export (1 commit, uncommitted changes)"),
+        "stdout: {}",
+        stdout
+    );
+
+    // Import into a clone that has never seen the branch, in another profile
+    let clone = h.home_path().join("clone");
+    git(
+        h.home_path(),
+        &[
+            "clone",
+            "-q",
+            "--single-branch",
+            repo.to_str().unwrap(),
+            clone.to_str().unwrap(),
+        ],
+    );
+    let output = h.run_cli(&[
+        "-p",
+        "laptop",
+        "session",
+        "import",
+        bundle.to_str().unwrap(),
+        "--path",
+        clone.to_str().unwrap(),
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "import failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Profile: laptop"), "stdout: {}", stdout);
+
+    let output = h.run_cli(&["-p", "laptop", "list", "--json"]);
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout).expect("list json");
+    let imported = &listed.as_array().unwrap()[0];
+    assert_eq!(imported["title"], "Exported");
+    assert_ne!(imported["id"], sessions[0]["id"]);
+    let imported_path = Path::new(imported["path"].as_str().unwrap());
+    assert_eq!(
+        std::fs::read_to_string(imported_path.join("committed.txt")).unwrap(),
+        "committed\nedited\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(imported_path.join("untracked.txt")).unwrap(),
+        "untracked\n"
+    );
+}
@@ -23,6 +23,10 @@ fn test_sandbox_info_serialization() {
         created_at: Some(chrono::Utc::now()),
         extra_env: Some(vec!["MY_VAR".to_string()]),
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     };
 
     let json = serde_json::to_string(&sandbox_info).unwrap();
@@ -48,6 +52,10 @@ fn test_instance_is_sandboxed() {
         created_at: None,
         extra_env: None,
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
     assert!(inst.is_sandboxed());
 
@@ -59,6 +67,10 @@ fn test_instance_is_sandboxed() {
         created_at: None,
         extra_env: None,
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
     assert!(!inst.is_sandboxed());
 }
@@ -79,6 +91,10 @@ fn test_sandbox_info_persists_across_save_load() {
         created_at: Some(chrono::Utc::now()),
         extra_env: Some(vec!["API_KEY".to_string(), "SECRET=my_secret".to_string()]),
         custom_instruction: None,
+        remote_user: None,
+        egress_proxy: None,
+        snapshots: Vec::new(),
+        compose_project: None,
     });
 
     storage.save(&[inst.clone()]).unwrap();
@@ -137,6 +153,7 @@ fn test_container_lifecycle() {
         cpu_limit: None,
         memory_limit: None,
         port_mappings: vec![],
+        network: None,
     };
 
     let container_id = container.create(&config).unwrap();
@@ -179,6 +196,7 @@ fn test_container_force_remove() {
         cpu_limit: None,
         memory_limit: None,
         port_mappings: vec![],
+        network: None,
     };
 
     container.create(&config).unwrap();
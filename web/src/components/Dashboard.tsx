@@ -27,7 +27,7 @@ export function Dashboard({
       projects.add(s.main_repo_path || s.project_path);
       if (isSessionActive(s.status)) active++;
       if (s.status === "Waiting") waiting++;
-      if (s.status === "Error") errors++;
+      if (s.status === "Error" || s.status === "Stalled") errors++;
     }
     return { active, waiting, errors, projects: projects.size };
   }, [sessions]);
@@ -21,6 +21,7 @@ const STATIC_GLYPH: Record<SessionStatus, string> = {
   Running: "⠋",
   Waiting: "⠃",
   Idle: "⠒",
+  Stalled: "⠿",
   Error: "✕",
   Starting: "⠀",
   Stopped: "⠒",
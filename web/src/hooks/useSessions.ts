@@ -5,6 +5,16 @@ import { setServerDown } from "../lib/connectionState";
 
 const POLL_INTERVAL = 3000;
 
+// Events on /api/events that change what the session list shows. Polling
+// stays on as a fallback for when the stream can't connect.
+const SESSION_EVENTS = [
+  "session_created",
+  "session_renamed",
+  "session_deleted",
+  "status_changed",
+  "resync",
+];
+
 export function useSessions() {
   const [sessions, setSessions] = useState<SessionResponse[]>([]);
   const [error, setError] = useState(false);
@@ -38,8 +48,23 @@ export function useSessions() {
       () => void fetchSessions().then(applyResult),
       POLL_INTERVAL,
     );
+
+    let pending = false;
+    const onEvent = () => {
+      if (pending) return;
+      pending = true;
+      void fetchSessions()
+        .then(applyResult)
+        .finally(() => {
+          pending = false;
+        });
+    };
+    const events = typeof EventSource === "undefined" ? null : new EventSource("/api/events");
+    for (const name of SESSION_EVENTS) events?.addEventListener(name, onEvent);
+
     return () => {
       if (intervalRef.current) clearInterval(intervalRef.current);
+      events?.close();
     };
   }, [applyResult]);
 
@@ -3,11 +3,13 @@ import type {
   RichDiffFilesResponse,
   RichFileDiffResponse,
   AgentInfo,
+  TemplateInfo,
   ProfileInfo,
   BrowseResponse,
   GroupInfo,
   DockerStatusResponse,
   CreateSessionRequest,
+  QueuedMessage,
 } from "./types";
 
 // GET a JSON endpoint; returns null on non-2xx or network/parse errors.
@@ -156,6 +158,19 @@ export async function fetchAgents(): Promise<AgentInfo[]> {
   return (await fetchJson<AgentInfo[]>("/api/agents")) ?? [];
 }
 
+export async function fetchTemplates(
+  profile?: string,
+  path?: string,
+): Promise<TemplateInfo[]> {
+  const params = new URLSearchParams();
+  if (profile) params.set("profile", profile);
+  if (path) params.set("path", path);
+  const query = params.toString();
+  return (
+    (await fetchJson<TemplateInfo[]>(`/api/templates${query ? `?${query}` : ""}`)) ?? []
+  );
+}
+
 export async function fetchProfiles(): Promise<ProfileInfo[]> {
   return (await fetchJson<ProfileInfo[]>("/api/profiles")) ?? [];
 }
@@ -375,3 +390,46 @@ export async function deleteSession(
     };
   }
 }
+
+// --- Message queue ---
+
+export function fetchQueue(id: string): Promise<QueuedMessage[] | null> {
+  return fetchJson<QueuedMessage[]>(`/api/sessions/${id}/queue`);
+}
+
+export function enqueueMessage(
+  id: string,
+  text: string,
+): Promise<QueuedMessage | null> {
+  return fetchJson<QueuedMessage>(`/api/sessions/${id}/queue`, {
+    method: "POST",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ text }),
+  });
+}
+
+export function updateQueuedMessage(
+  id: string,
+  messageId: string,
+  text: string,
+): Promise<QueuedMessage | null> {
+  return fetchJson<QueuedMessage>(`/api/sessions/${id}/queue/${messageId}`, {
+    method: "PATCH",
+    headers: { "Content-Type": "application/json" },
+    body: JSON.stringify({ text }),
+  });
+}
+
+export async function deleteQueuedMessage(
+  id: string,
+  messageId: string,
+): Promise<boolean> {
+  try {
+    const res = await fetch(`/api/sessions/${id}/queue/${messageId}`, {
+      method: "DELETE",
+    });
+    return res.ok;
+  } catch {
+    return false;
+  }
+}
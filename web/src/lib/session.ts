@@ -5,6 +5,7 @@ export const STATUS_DOT_CLASS: Record<SessionStatus, string> = {
   Running: "bg-status-running",
   Waiting: "bg-status-waiting",
   Idle: "bg-status-idle",
+  Stalled: "bg-status-error",
   Error: "bg-status-error",
   Starting: "bg-status-starting",
   Stopped: "bg-status-stopped",
@@ -18,6 +19,7 @@ export const STATUS_TEXT_CLASS: Record<SessionStatus, string> = {
   Running: "text-status-running",
   Waiting: "text-status-waiting",
   Idle: "text-status-idle",
+  Stalled: "text-status-error",
   Error: "text-status-error",
   Starting: "text-status-starting",
   Stopped: "text-status-stopped",
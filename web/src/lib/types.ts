@@ -23,6 +23,51 @@ export interface SessionResponse {
   notify_on_waiting: boolean | null;
   notify_on_idle: boolean | null;
   notify_on_error: boolean | null;
+  webhook_on_waiting: boolean | null;
+  webhook_on_idle: boolean | null;
+  webhook_on_error: boolean | null;
+  /** Host ports reserved for the session, from `session.ports`. */
+  ports: PortAllocation[];
+  /** Token usage read from the agent's transcripts. null until the
+   *  server's first usage poll, or when the transcripts can't be found. */
+  usage: SessionUsage | null;
+  /** CPU and memory of the session's processes (or container). null
+   *  until it has been sampled twice, or while it isn't running. */
+  metrics: SessionMetrics | null;
+}
+
+export interface PortAllocation {
+  /** Environment variable the port is set in, e.g. `AOE_PORT_0`. */
+  name: string;
+  port: number;
+}
+
+export interface MetricsSample {
+  /** Percent of one core; can exceed 100 for parallel work. */
+  cpu_percent: number;
+  rss_bytes: number;
+}
+
+export interface SessionMetrics extends MetricsSample {
+  /** Recent samples, oldest first, ending with the current one. */
+  history: MetricsSample[];
+}
+
+export interface SessionUsage {
+  input_tokens: number;
+  output_tokens: number;
+  cache_read_tokens: number;
+  cache_write_tokens: number;
+  /** Estimate from the model price table; absent for unpriced models. */
+  cost_usd?: number;
+  models?: string[];
+}
+
+/** A message waiting to be typed into a session when its agent goes idle. */
+export interface QueuedMessage {
+  id: string;
+  text: string;
+  queued_at: string;
 }
 
 export interface CleanupDefaults {
@@ -35,6 +80,7 @@ export type SessionStatus =
   | "Running"
   | "Waiting"
   | "Idle"
+  | "Stalled"
   | "Error"
   | "Starting"
   | "Stopped"
@@ -125,6 +171,20 @@ export interface AgentInfo {
   installed: boolean;
 }
 
+/** Session template returned by /api/templates */
+export interface TemplateInfo {
+  name: string;
+  agent?: string;
+  yolo?: boolean;
+  sandbox?: boolean;
+  sandbox_image?: string;
+  extra_env?: string[];
+  custom_instruction?: string;
+  group?: string;
+  worktree_template?: string;
+  extra_args?: string;
+}
+
 /** Profile info returned by /api/profiles */
 export interface ProfileInfo {
   name: string;
@@ -161,7 +221,8 @@ export interface DockerStatusResponse {
 export interface CreateSessionRequest {
   title?: string;
   path: string;
-  tool: string;
+  /** Defaults to the template's agent, then the configured default */
+  tool?: string;
   group?: string;
   yolo_mode?: boolean;
   worktree_branch?: string;
@@ -174,4 +235,8 @@ export interface CreateSessionRequest {
   command_override?: string;
   custom_instruction?: string;
   profile?: string;
+  /** Initial prompt, sent once the agent is first ready */
+  prompt?: string;
+  /** Session template to start from; fields set here override it */
+  template?: string;
 }